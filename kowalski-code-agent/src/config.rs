@@ -152,6 +152,14 @@ pub struct CodeAgentConfig {
 
     /// Whether to enable code verifiability
     pub enable_verifiability: bool,
+
+    /// Maximum number of code executions the REPL manager runs at once
+    #[serde(default = "default_max_concurrent_executions")]
+    pub max_concurrent_executions: usize,
+}
+
+fn default_max_concurrent_executions() -> usize {
+    4
 }
 
 impl ConfigExt for CodeAgentConfig {
@@ -217,6 +225,7 @@ impl Default for CodeAgentConfig {
             enable_consistency: true,
             enable_traceability: true,
             enable_verifiability: true,
+            max_concurrent_executions: default_max_concurrent_executions(),
         }
     }
 }
@@ -8,6 +8,7 @@ pub mod repl_manager;
 pub mod python_executor;
 pub mod java_executor;
 pub mod rust_executor;
+mod process;
 
 pub use repl_manager::{REPLManager, ExecutionResult, ExecutionLanguage};
 pub use python_executor::PythonExecutor;
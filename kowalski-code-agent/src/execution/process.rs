@@ -0,0 +1,320 @@
+//! Child process runner shared by the language executors
+//!
+//! Runs a command to completion with a timeout, capturing its output, its
+//! wall-clock duration and, on Unix, the CPU time it used (from `wait4`).
+
+use std::io::Read;
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::time::{Duration, Instant};
+
+/// How often a running child is checked for completion
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// How long to keep reading output after the child has been reaped
+///
+/// Killing the process group closes the pipes almost immediately, but a
+/// process that escaped the group could still hold them open.
+const OUTPUT_GRACE: Duration = Duration::from_secs(1);
+
+/// Outcome of a finished (or killed) child process
+#[derive(Debug)]
+pub(crate) struct ProcessOutput {
+    pub stdout: String,
+    pub stderr: String,
+    /// Exit code, or -1 if the process was killed by a signal
+    pub exit_code: i32,
+    /// Wall-clock time from spawn to exit
+    pub duration_ms: u64,
+    /// User plus system CPU time, where the platform reports it
+    pub cpu_time_ms: Option<u64>,
+    pub timed_out: bool,
+}
+
+/// Exit status and CPU time of a reaped child
+struct Reaped {
+    exit_code: i32,
+    cpu_time_ms: Option<u64>,
+}
+
+/// Owns a running child and kills it (with its process group) if dropped
+/// before the child was reaped, e.g. when the `run` future is cancelled
+struct ChildGuard(Option<Child>);
+
+impl ChildGuard {
+    fn child(&mut self) -> &mut Child {
+        self.0.as_mut().expect("child is only taken when it is reaped")
+    }
+
+    /// Hand the child over to the caller, who becomes responsible for reaping it
+    fn take(&mut self) -> Child {
+        self.0.take().expect("child is only taken once")
+    }
+}
+
+impl Drop for ChildGuard {
+    fn drop(&mut self) {
+        if let Some(mut child) = self.0.take() {
+            kill_tree(&mut child);
+            std::thread::spawn(move || {
+                let _ = reap(child);
+            });
+        }
+    }
+}
+
+/// Run `command` until it exits, killing it after `timeout`
+///
+/// Stdin is closed; stdout and stderr are captured in full. The command runs
+/// in its own process group on Unix, and the whole group is killed once the
+/// command exits or times out, so processes it left behind cannot keep the
+/// call waiting on their output.
+pub(crate) async fn run(mut command: Command, timeout: Duration) -> Result<ProcessOutput, String> {
+    let program = command.get_program().to_string_lossy().into_owned();
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut command, 0);
+    let started = Instant::now();
+    let mut guard = ChildGuard(Some(
+        command
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Failed to start {}: {}", program, e))?,
+    ));
+    let pid = guard.child().id();
+
+    // Drain both pipes while the child runs so it never blocks on a full pipe
+    let stdout = drain(guard.child().stdout.take());
+    let stderr = drain(guard.child().stderr.take());
+
+    let mut timed_out = false;
+    let reaped = loop {
+        if let Some(reaped) = try_reap(guard.child())? {
+            drop(guard.take());
+            break reaped;
+        }
+        if started.elapsed() >= timeout {
+            timed_out = true;
+            let mut child = guard.take();
+            kill_tree(&mut child);
+            break tokio::task::spawn_blocking(move || reap(child))
+                .await
+                .map_err(|e| format!("Failed to wait for {}: {}", program, e))??;
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    };
+    let duration_ms = started.elapsed().as_millis() as u64;
+    kill_group(pid);
+
+    let (stdout, stderr) = tokio::task::spawn_blocking(move || {
+        let deadline = Instant::now() + OUTPUT_GRACE;
+        (collect(stdout, deadline), collect(stderr, deadline))
+    })
+    .await
+    .map_err(|e| format!("Failed to read output of {}: {}", program, e))?;
+
+    Ok(ProcessOutput {
+        stdout,
+        stderr,
+        exit_code: reaped.exit_code,
+        duration_ms,
+        cpu_time_ms: reaped.cpu_time_ms,
+        timed_out,
+    })
+}
+
+fn drain<R: Read + Send + 'static>(pipe: Option<R>) -> Option<Receiver<Vec<u8>>> {
+    pipe.map(|mut pipe| {
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let mut buf = Vec::new();
+            let _ = pipe.read_to_end(&mut buf);
+            let _ = tx.send(buf);
+        });
+        rx
+    })
+}
+
+/// Wait for a drained pipe until `deadline`, giving up on output still held
+/// open by a process outside our control
+fn collect(reader: Option<Receiver<Vec<u8>>>, deadline: Instant) -> String {
+    reader
+        .and_then(|reader| {
+            reader
+                .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                .ok()
+        })
+        .map(|buf| String::from_utf8_lossy(&buf).into_owned())
+        .unwrap_or_default()
+}
+
+/// Kill the child and everything in its process group
+#[cfg(unix)]
+fn kill_tree(child: &mut Child) {
+    kill_group(child.id());
+    let _ = child.kill();
+}
+
+/// Kill every process left in the group led by `pid`
+#[cfg(unix)]
+fn kill_group(pid: u32) {
+    // SAFETY: killpg takes no pointers; a group that no longer exists is
+    // reported as ESRCH, which is ignored
+    unsafe {
+        libc::killpg(pid as libc::pid_t, libc::SIGKILL);
+    }
+}
+
+#[cfg(not(unix))]
+fn kill_tree(child: &mut Child) {
+    let _ = child.kill();
+}
+
+#[cfg(not(unix))]
+fn kill_group(_pid: u32) {}
+
+/// Reap the child if it has exited, without blocking
+#[cfg(unix)]
+fn try_reap(child: &mut Child) -> Result<Option<Reaped>, String> {
+    wait4(child, libc::WNOHANG)
+}
+
+/// Block until the child exits and reap it
+#[cfg(unix)]
+fn reap(mut child: Child) -> Result<Reaped, String> {
+    loop {
+        if let Some(reaped) = wait4(&mut child, 0)? {
+            return Ok(reaped);
+        }
+    }
+}
+
+/// `wait4` on the child, which reports its resource usage along with the status
+#[cfg(unix)]
+fn wait4(child: &mut Child, options: libc::c_int) -> Result<Option<Reaped>, String> {
+    let pid = child.id() as libc::pid_t;
+    let mut status: libc::c_int = 0;
+    // SAFETY: rusage is plain old data, so all zeroes is a valid value
+    let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+    // SAFETY: both pointers refer to live locals for the duration of the call
+    let reaped = unsafe { libc::wait4(pid, &mut status, options, &mut usage) };
+    match reaped {
+        0 => Ok(None),
+        -1 => {
+            let err = std::io::Error::last_os_error();
+            if err.kind() == std::io::ErrorKind::Interrupted {
+                Ok(None)
+            } else {
+                Err(format!("Failed to wait for process {}: {}", pid, err))
+            }
+        }
+        _ => {
+            let exit_code = if libc::WIFEXITED(status) {
+                libc::WEXITSTATUS(status)
+            } else {
+                -1
+            };
+            Ok(Some(Reaped {
+                exit_code,
+                cpu_time_ms: Some(timeval_ms(usage.ru_utime) + timeval_ms(usage.ru_stime)),
+            }))
+        }
+    }
+}
+
+#[cfg(unix)]
+fn timeval_ms(time: libc::timeval) -> u64 {
+    time.tv_sec as u64 * 1000 + time.tv_usec as u64 / 1000
+}
+
+#[cfg(not(unix))]
+fn try_reap(child: &mut Child) -> Result<Option<Reaped>, String> {
+    child
+        .try_wait()
+        .map(|status| status.map(reaped_without_usage))
+        .map_err(|e| format!("Failed to wait for process {}: {}", child.id(), e))
+}
+
+#[cfg(not(unix))]
+fn reap(mut child: Child) -> Result<Reaped, String> {
+    child
+        .wait()
+        .map(reaped_without_usage)
+        .map_err(|e| format!("Failed to wait for process {}: {}", child.id(), e))
+}
+
+#[cfg(not(unix))]
+fn reaped_without_usage(status: std::process::ExitStatus) -> Reaped {
+    Reaped {
+        exit_code: status.code().unwrap_or(-1),
+        cpu_time_ms: None,
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_run_captures_output_and_exit_code() {
+        let mut command = Command::new("sh");
+        command.args(["-c", "echo out; echo err >&2; exit 3"]);
+
+        let output = run(command, Duration::from_secs(5)).await.unwrap();
+        assert_eq!(output.stdout, "out\n");
+        assert_eq!(output.stderr, "err\n");
+        assert_eq!(output.exit_code, 3);
+        assert!(output.cpu_time_ms.is_some());
+        assert!(!output.timed_out);
+    }
+
+    #[tokio::test]
+    async fn test_run_kills_on_timeout() {
+        let mut command = Command::new("sleep");
+        command.arg("5");
+
+        let output = run(command, Duration::from_millis(50)).await.unwrap();
+        assert!(output.timed_out);
+        assert_eq!(output.exit_code, -1);
+        assert!(output.duration_ms < 5_000);
+    }
+
+    #[tokio::test]
+    async fn test_run_is_not_held_up_by_background_processes() {
+        let mut command = Command::new("sh");
+        command.args(["-c", "sleep 5 & sleep 5"]);
+
+        let started = Instant::now();
+        let output = run(command, Duration::from_millis(50)).await.unwrap();
+        assert!(output.timed_out);
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        let mut command = Command::new("sh");
+        command.args(["-c", "sleep 5 & echo done"]);
+
+        let started = Instant::now();
+        let output = run(command, Duration::from_secs(5)).await.unwrap();
+        assert!(!output.timed_out);
+        assert_eq!(output.stdout, "done\n");
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn test_run_kills_child_when_cancelled() {
+        let marker = std::env::temp_dir().join(format!("kowalski-run-cancel-{}", std::process::id()));
+        let _ = std::fs::remove_file(&marker);
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(format!("sleep 0.3; touch '{}'", marker.display()));
+
+        let cancelled = tokio::time::timeout(
+            Duration::from_millis(50),
+            run(command, Duration::from_secs(5)),
+        )
+        .await;
+        assert!(cancelled.is_err());
+
+        tokio::time::sleep(Duration::from_millis(600)).await;
+        assert!(!marker.exists());
+    }
+}
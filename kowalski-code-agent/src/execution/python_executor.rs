@@ -1,21 +1,26 @@
+use super::process;
 use super::repl_manager::{ExecutionResult, ExecutionLanguage, Executor};
 use async_trait::async_trait;
+use std::process::Command;
 use std::time::Duration;
 
 /// Python code executor
 ///
-/// Executes Python code in a sandboxed environment.
-/// Uses one of these approaches depending on configuration:
+/// Will execute Python code in a sandboxed environment once Phase 1b picks
+/// one of these approaches:
 /// - Child process with resource limits (rlimit)
 /// - Interpreter-level sandboxing (RestrictedPython)
 /// - Container isolation (Docker) for maximum safety
 ///
 /// # Phase 1 Note
-/// This is a placeholder that will be implemented in Phase 1b.
-/// The actual implementation will choose a sandboxing strategy
-/// during Phase 0 and implement the executor accordingly.
+/// No sandbox exists yet, so by default `execute` returns an empty placeholder
+/// result without running anything. [`PythonExecutor::allow_unsandboxed_execution`]
+/// opts in to running code in a plain `python3 -c` child process with no
+/// isolation, which also reports wall-clock and CPU time. Only enable it for
+/// trusted code.
 pub struct PythonExecutor {
-    // Configuration will be added in Phase 1b
+    interpreter: String,
+    unsandboxed: bool,
 }
 
 impl PythonExecutor {
@@ -25,7 +30,25 @@ impl PythonExecutor {
     /// Will panic if Python environment cannot be initialized.
     /// This should be handled gracefully once the implementation is complete.
     pub fn new() -> Self {
-        Self {}
+        Self {
+            interpreter: "python3".to_string(),
+            unsandboxed: false,
+        }
+    }
+
+    /// Runs code in an unsandboxed interpreter process instead of returning a
+    /// placeholder result
+    ///
+    /// The code gets the same permissions as the current process.
+    pub fn allow_unsandboxed_execution(mut self) -> Self {
+        self.unsandboxed = true;
+        self
+    }
+
+    /// Runs code with `interpreter` instead of `python3`
+    pub fn with_interpreter(mut self, interpreter: impl Into<String>) -> Self {
+        self.interpreter = interpreter.into();
+        self
     }
 }
 
@@ -40,23 +63,55 @@ impl Executor for PythonExecutor {
     async fn execute(
         &self,
         code: &str,
-        _timeout: Duration,
-        _max_output: usize,
+        timeout: Duration,
+        max_output: usize,
     ) -> Result<ExecutionResult, String> {
-        // Placeholder implementation
-        // Will be replaced with actual execution in Phase 1b
+        if !self.unsandboxed {
+            return Ok(ExecutionResult {
+                language: ExecutionLanguage::Python,
+                code: code.to_string(),
+                stdout: String::new(),
+                stderr: String::new(),
+                exit_code: 0,
+                success: true,
+                duration_ms: 0,
+                cpu_time_ms: None,
+            });
+        }
+
+        let mut command = Command::new(&self.interpreter);
+        command.arg("-c").arg(code);
+        let output = process::run(command, timeout).await?;
+
+        let mut stderr = output.stderr;
+        if output.timed_out {
+            stderr.push_str(&format!("\nExecution timed out after {:?}", timeout));
+        }
         Ok(ExecutionResult {
             language: ExecutionLanguage::Python,
             code: code.to_string(),
-            stdout: String::new(),
-            stderr: String::new(),
-            exit_code: 0,
-            success: true,
-            duration_ms: 0,
+            stdout: truncate(output.stdout, max_output),
+            stderr: truncate(stderr, max_output),
+            exit_code: output.exit_code,
+            success: output.exit_code == 0 && !output.timed_out,
+            duration_ms: output.duration_ms,
+            cpu_time_ms: output.cpu_time_ms,
         })
     }
 }
 
+/// Cut `text` to at most `max_len` bytes on a character boundary
+fn truncate(mut text: String, max_len: usize) -> String {
+    if text.len() > max_len {
+        let mut cut = max_len;
+        while !text.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        text.truncate(cut);
+    }
+    text
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -73,12 +128,62 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_python_executor_placeholder() {
+    async fn test_python_executor_does_not_run_code_by_default() {
         let executor = PythonExecutor::new();
         let result = executor
             .execute("print('test')", Duration::from_secs(5), 8192)
-            .await;
+            .await
+            .unwrap();
+
+        assert!(result.stdout.is_empty());
+        assert_eq!(result.cpu_time_ms, None);
+    }
+
+    #[tokio::test]
+    #[ignore]  // Requires Python to be installed
+    async fn test_python_executor_runs_print() {
+        let executor = PythonExecutor::new().allow_unsandboxed_execution();
+        let result = executor
+            .execute("print('test')", Duration::from_secs(5), 8192)
+            .await
+            .unwrap();
+
+        assert!(result.success, "stderr: {}", result.stderr);
+        assert_eq!(result.stdout.trim(), "test");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    #[ignore]  // Requires Python to be installed
+    async fn test_python_executor_reports_cpu_time() {
+        let executor = PythonExecutor::new().allow_unsandboxed_execution();
+        let code = "total = 0\nfor i in range(3_000_000):\n    total += i * i\nprint(total > 0)";
+
+        let result = executor.execute(code, Duration::from_secs(30), 8192).await.unwrap();
 
-        assert!(result.is_ok());
+        assert!(result.success, "stderr: {}", result.stderr);
+        assert_eq!(result.stdout.trim(), "True");
+        let cpu_time_ms = result.cpu_time_ms.expect("CPU time is reported on Unix");
+        assert!(cpu_time_ms > 0);
+    }
+
+    #[tokio::test]
+    #[ignore]  // Requires Python to be installed
+    async fn test_python_executor_reports_failures() {
+        let executor = PythonExecutor::new().allow_unsandboxed_execution();
+
+        let result = executor
+            .execute("raise SystemExit(4)", Duration::from_secs(5), 8192)
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert_eq!(result.exit_code, 4);
+
+        let result = executor
+            .execute("import time\ntime.sleep(5)", Duration::from_millis(100), 8192)
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result.stderr.contains("timed out"));
     }
 }
@@ -1,9 +1,13 @@
+use crate::config::CodeAgentConfig;
 use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::Arc;
 use std::time::Duration;
 use serde::{Deserialize, Serialize};
+use tokio::sync::Semaphore;
 
 /// Supported programming languages for execution
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ExecutionLanguage {
     Python,
     Java,
@@ -35,8 +39,13 @@ pub struct ExecutionResult {
     pub exit_code: i32,
     /// Whether execution was successful
     pub success: bool,
-    /// Execution time in milliseconds
+    /// Execution time in milliseconds (wall clock)
     pub duration_ms: u64,
+    /// CPU time (user plus system) used by the child process, in milliseconds
+    ///
+    /// `None` when no process ran or the platform does not report it.
+    #[serde(default)]
+    pub cpu_time_ms: Option<u64>,
 }
 
 impl ExecutionResult {
@@ -84,6 +93,8 @@ pub trait Executor: Send + Sync {
 /// - Async-first design with cancellation support
 /// - Resource isolation and sandboxing
 /// - Per-language executor pool for warm starts
+/// - A cap on simultaneous executions, with waiting requests served in
+///   arrival order
 ///
 /// # Example
 ///
@@ -109,17 +120,57 @@ pub trait Executor: Send + Sync {
 /// }
 /// ```
 pub struct REPLManager {
-    // Executors will be added in Phase 1b
+    executors: HashMap<ExecutionLanguage, Arc<dyn Executor>>,
+    max_concurrent: usize,
+    // Tokio's semaphore hands out permits in FIFO order, so a long queue
+    // cannot starve any single request
+    permits: Arc<Semaphore>,
 }
 
 impl REPLManager {
     /// Creates a new REPL manager with default executors
     pub fn new() -> Self {
-        Self {}
+        Self::with_max_concurrent(CodeAgentConfig::default().max_concurrent_executions)
+    }
+
+    /// Creates a REPL manager using the execution limits in `config`
+    pub fn from_config(config: &CodeAgentConfig) -> Self {
+        Self::with_max_concurrent(config.max_concurrent_executions)
+    }
+
+    /// Creates a REPL manager running at most `max_concurrent` executions at once
+    ///
+    /// A limit of 0 is treated as 1.
+    pub fn with_max_concurrent(max_concurrent: usize) -> Self {
+        let max_concurrent = max_concurrent.max(1);
+        Self {
+            executors: HashMap::new(),
+            max_concurrent,
+            permits: Arc::new(Semaphore::new(max_concurrent)),
+        }
+    }
+
+    /// Runs `language` code with `executor`
+    pub fn with_executor(mut self, language: ExecutionLanguage, executor: Arc<dyn Executor>) -> Self {
+        self.executors.insert(language, executor);
+        self
+    }
+
+    /// Maximum number of simultaneous executions
+    pub fn max_concurrent(&self) -> usize {
+        self.max_concurrent
+    }
+
+    /// Number of executions currently running
+    pub fn in_flight(&self) -> usize {
+        self.max_concurrent - self.permits.available_permits()
     }
 
     /// Executes code in the specified language
     ///
+    /// Waits for a free execution slot first if `max_concurrent` executions
+    /// are already running; waiting calls are admitted in arrival order.
+    ///
     /// # Arguments
     /// * `language` - The programming language
     /// * `code` - The code to execute
@@ -132,9 +183,19 @@ impl REPLManager {
         &self,
         language: ExecutionLanguage,
         code: &str,
-        _timeout: Duration,
-        _max_output: usize,
+        timeout: Duration,
+        max_output: usize,
     ) -> Result<ExecutionResult, String> {
+        let _permit = self
+            .permits
+            .acquire()
+            .await
+            .map_err(|_| "REPL manager is shut down".to_string())?;
+
+        if let Some(executor) = self.executors.get(&language) {
+            return executor.execute(code, timeout, max_output).await;
+        }
+
         // Placeholder - will be implemented in Phase 1b
         // For now, return a mock result for testing
         Ok(ExecutionResult {
@@ -145,6 +206,7 @@ impl REPLManager {
             exit_code: 0,
             success: true,
             duration_ms: 0,
+            cpu_time_ms: None,
         })
     }
 
@@ -186,6 +248,7 @@ mod tests {
             exit_code: 0,
             success: true,
             duration_ms: 10,
+            cpu_time_ms: None,
         };
 
         assert!(result.success);
@@ -203,6 +266,7 @@ mod tests {
             exit_code: 1,
             success: false,
             duration_ms: 5,
+            cpu_time_ms: None,
         };
 
         assert!(!result.success);
@@ -222,6 +286,7 @@ mod tests {
             exit_code: 0,
             success: true,
             duration_ms: 20,
+            cpu_time_ms: None,
         };
 
         let output = result.get_output(100);
@@ -254,4 +319,77 @@ mod tests {
         let exec_result = result.unwrap();
         assert!(exec_result.success);
     }
+
+    /// Executor that sleeps briefly and records the peak number of overlapping calls
+    #[derive(Default)]
+    struct CountingExecutor {
+        running: std::sync::atomic::AtomicUsize,
+        peak: std::sync::atomic::AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Executor for CountingExecutor {
+        async fn execute(
+            &self,
+            code: &str,
+            _timeout: Duration,
+            _max_output: usize,
+        ) -> Result<ExecutionResult, String> {
+            use std::sync::atomic::Ordering;
+
+            let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak.fetch_max(running, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            self.running.fetch_sub(1, Ordering::SeqCst);
+
+            Ok(ExecutionResult {
+                language: ExecutionLanguage::Python,
+                code: code.to_string(),
+                stdout: String::new(),
+                stderr: String::new(),
+                exit_code: 0,
+                success: true,
+                duration_ms: 20,
+                cpu_time_ms: None,
+            })
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_repl_manager_caps_concurrent_executions() {
+        let executor = Arc::new(CountingExecutor::default());
+        let manager = Arc::new(
+            REPLManager::with_max_concurrent(3)
+                .with_executor(ExecutionLanguage::Python, executor.clone()),
+        );
+
+        let handles: Vec<_> = (0..12)
+            .map(|i| {
+                let manager = manager.clone();
+                tokio::spawn(async move {
+                    manager
+                        .execute_limited(
+                            ExecutionLanguage::Python,
+                            &format!("print({})", i),
+                            Duration::from_secs(5),
+                        )
+                        .await
+                })
+            })
+            .collect();
+        for handle in handles {
+            assert!(handle.await.unwrap().unwrap().success);
+        }
+
+        assert_eq!(executor.peak.load(std::sync::atomic::Ordering::SeqCst), 3);
+        assert_eq!(manager.in_flight(), 0);
+    }
+
+    #[test]
+    fn test_repl_manager_limit_from_config() {
+        let mut config = CodeAgentConfig::default();
+        config.max_concurrent_executions = 2;
+        assert_eq!(REPLManager::from_config(&config).max_concurrent(), 2);
+        assert_eq!(REPLManager::with_max_concurrent(0).max_concurrent(), 1);
+    }
 }
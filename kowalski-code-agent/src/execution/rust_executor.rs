@@ -45,6 +45,7 @@ impl Executor for RustExecutor {
             exit_code: 0,
             success: true,
             duration_ms: 0,
+            cpu_time_ms: None,
         })
     }
 }
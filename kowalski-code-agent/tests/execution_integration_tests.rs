@@ -119,6 +119,7 @@ mod execution_integration_tests {
             exit_code: 0,
             success: true,
             duration_ms: 100,
+            cpu_time_ms: None,
         };
 
         let output = result.get_output(8192);
@@ -136,6 +137,7 @@ mod execution_integration_tests {
             exit_code: 1,
             success: false,
             duration_ms: 5,
+            cpu_time_ms: None,
         };
 
         let output = result.get_output(8192);
@@ -200,6 +202,7 @@ mod execution_integration_tests {
             exit_code: 0,
             success: true,
             duration_ms: 50,
+            cpu_time_ms: None,
         };
 
         let truncated = result.get_output(100);
@@ -252,6 +255,7 @@ mod execution_integration_tests {
             exit_code: 1,
             success: false,
             duration_ms: 123,
+            cpu_time_ms: None,
         };
 
         assert_eq!(result.exit_code, 1);
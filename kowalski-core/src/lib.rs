@@ -19,7 +19,7 @@ pub use logging::*;
 pub use model::ModelManager;
 pub use model::*;
 pub use providers::OpenRouterClient;
-pub use rlm::{AnswerBuffer, RLMConfig, RLMEnvironment, EnvironmentTips};
+pub use rlm::{AnswerBuffer, RLMConfig, RLMEnvironment, EnvironmentTips, EnvironmentTipsDiff, TokenCounter};
 pub use role::{Audience, Preset, Role, Style};
 pub use tool_chain::*;
 pub use tools::ToolCall;
@@ -116,6 +116,23 @@ impl AnswerBuffer {
         inner.content.clone()
     }
 
+    /// Returns the current content without waiting for the lock
+    ///
+    /// Returns `None` if the buffer is currently being written to.
+    pub fn try_get_content(&self) -> Option<String> {
+        self.inner.try_read().ok().map(|inner| inner.content.clone())
+    }
+
+    /// Replaces the content of the buffer
+    ///
+    /// Unlike `append`, this is allowed after `finalize()`; the ready flag
+    /// and iteration count are left unchanged. Used to swap in a compressed
+    /// version of the accumulated answer.
+    pub async fn replace_content(&self, content: String) {
+        let mut inner = self.inner.write().await;
+        inner.content = content;
+    }
+
     /// Returns whether the answer buffer is finalized and ready
     pub async fn is_ready(&self) -> bool {
         let inner = self.inner.read().await;
@@ -225,6 +242,20 @@ mod tests {
         assert_eq!(buffer.iteration_count().await, 0);
     }
 
+    #[tokio::test]
+    async fn test_replace_content_keeps_state() {
+        let buffer = AnswerBuffer::new();
+        buffer.append("Long draft").await;
+        buffer.next_iteration().await;
+        buffer.finalize().await;
+
+        buffer.replace_content("Short".to_string()).await;
+
+        assert_eq!(buffer.try_get_content().as_deref(), Some("Short"));
+        assert!(buffer.is_ready().await);
+        assert_eq!(buffer.iteration_count().await, 1);
+    }
+
     #[tokio::test]
     #[should_panic(expected = "Cannot append to finalized")]
     async fn test_append_after_finalize() {
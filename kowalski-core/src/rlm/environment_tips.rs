@@ -20,7 +20,7 @@ use std::collections::HashMap;
 ///
 /// let prompt = tips.augment_prompt("Find the latest AI papers");
 /// ```
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct EnvironmentTips {
     /// Tool-specific tips (name -> suggestion)
     tips: HashMap<String, String>,
@@ -30,6 +30,93 @@ pub struct EnvironmentTips {
     context: HashMap<String, String>,
 }
 
+/// Changes between two [`EnvironmentTips`] instances
+///
+/// Produced by [`EnvironmentTips::diff`] and applied with
+/// [`EnvironmentTips::apply_diff`]. Modified entries map a key to its
+/// `(old, new)` values. Removed keys are sorted.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EnvironmentTipsDiff {
+    /// Tips present only in the new instance
+    pub added_tips: HashMap<String, String>,
+    /// Tools whose tips were removed
+    pub removed_tips: Vec<String>,
+    /// Tips whose suggestion changed
+    pub modified_tips: HashMap<String, (String, String)>,
+    /// Resources present only in the new instance
+    pub added_resources: HashMap<String, String>,
+    /// Resources that were removed
+    pub removed_resources: Vec<String>,
+    /// Resources whose value changed
+    pub modified_resources: HashMap<String, (String, String)>,
+    /// Context entries present only in the new instance
+    pub added_context: HashMap<String, String>,
+    /// Context keys that were removed
+    pub removed_context: Vec<String>,
+    /// Context entries whose value changed
+    pub modified_context: HashMap<String, (String, String)>,
+}
+
+impl EnvironmentTipsDiff {
+    /// Returns true if the two instances were identical
+    pub fn is_empty(&self) -> bool {
+        self.added_tips.is_empty()
+            && self.removed_tips.is_empty()
+            && self.modified_tips.is_empty()
+            && self.added_resources.is_empty()
+            && self.removed_resources.is_empty()
+            && self.modified_resources.is_empty()
+            && self.added_context.is_empty()
+            && self.removed_context.is_empty()
+            && self.modified_context.is_empty()
+    }
+}
+
+/// Added, removed and modified entries going from `old` to `new`
+type MapDiff = (
+    HashMap<String, String>,
+    Vec<String>,
+    HashMap<String, (String, String)>,
+);
+
+fn diff_maps(old: &HashMap<String, String>, new: &HashMap<String, String>) -> MapDiff {
+    let mut added = HashMap::new();
+    let mut modified = HashMap::new();
+    for (key, new_value) in new {
+        match old.get(key) {
+            None => {
+                added.insert(key.clone(), new_value.clone());
+            }
+            Some(old_value) if old_value != new_value => {
+                modified.insert(key.clone(), (old_value.clone(), new_value.clone()));
+            }
+            Some(_) => {}
+        }
+    }
+
+    let mut removed: Vec<String> = old.keys().filter(|key| !new.contains_key(*key)).cloned().collect();
+    removed.sort();
+
+    (added, removed, modified)
+}
+
+fn apply_map_diff(
+    map: &mut HashMap<String, String>,
+    added: &HashMap<String, String>,
+    removed: &[String],
+    modified: &HashMap<String, (String, String)>,
+) {
+    for key in removed {
+        map.remove(key);
+    }
+    for (key, value) in added {
+        map.insert(key.clone(), value.clone());
+    }
+    for (key, (_, new_value)) in modified {
+        map.insert(key.clone(), new_value.clone());
+    }
+}
+
 impl EnvironmentTips {
     /// Creates a new, empty environment tips system
     pub fn new() -> Self {
@@ -146,6 +233,46 @@ impl EnvironmentTips {
     pub fn context(&self) -> &HashMap<String, String> {
         &self.context
     }
+
+    /// Computes what changed going from `self` (old) to `other` (new)
+    pub fn diff(&self, other: &EnvironmentTips) -> EnvironmentTipsDiff {
+        let (added_tips, removed_tips, modified_tips) = diff_maps(&self.tips, &other.tips);
+        let (added_resources, removed_resources, modified_resources) =
+            diff_maps(&self.resources, &other.resources);
+        let (added_context, removed_context, modified_context) =
+            diff_maps(&self.context, &other.context);
+
+        EnvironmentTipsDiff {
+            added_tips,
+            removed_tips,
+            modified_tips,
+            added_resources,
+            removed_resources,
+            modified_resources,
+            added_context,
+            removed_context,
+            modified_context,
+        }
+    }
+
+    /// Applies a diff produced by [`EnvironmentTips::diff`]
+    ///
+    /// Modified entries take their new value whatever the current value is.
+    pub fn apply_diff(&mut self, diff: &EnvironmentTipsDiff) {
+        apply_map_diff(&mut self.tips, &diff.added_tips, &diff.removed_tips, &diff.modified_tips);
+        apply_map_diff(
+            &mut self.resources,
+            &diff.added_resources,
+            &diff.removed_resources,
+            &diff.modified_resources,
+        );
+        apply_map_diff(
+            &mut self.context,
+            &diff.added_context,
+            &diff.removed_context,
+            &diff.modified_context,
+        );
+    }
 }
 
 impl Default for EnvironmentTips {
@@ -236,4 +363,54 @@ mod tests {
         assert_eq!(deserialized.get_resource("res1"), original.get_resource("res1"));
         assert_eq!(deserialized.get_context("ctx1"), original.get_context("ctx1"));
     }
+
+    #[test]
+    fn test_diff_and_apply() {
+        let mut old = EnvironmentTips::new()
+            .add_tip("web_search", "Use for recent info")
+            .add_tip("csv_analysis", "Stream large files")
+            .add_resource("max_iterations", "5")
+            .add_resource("gpu", "none")
+            .add_context("task", "research");
+        let new = EnvironmentTips::new()
+            .add_tip("web_search", "Prefer primary sources")
+            .add_tip("code_execution", "Python 3.11 available")
+            .add_resource("max_iterations", "5")
+            .add_resource("timeout_seconds", "300")
+            .add_context("task", "review")
+            .add_context("user_id", "user123");
+
+        let diff = old.diff(&new);
+        assert_eq!(
+            diff.added_tips,
+            HashMap::from([("code_execution".to_string(), "Python 3.11 available".to_string())])
+        );
+        assert_eq!(diff.removed_tips, vec!["csv_analysis".to_string()]);
+        assert_eq!(
+            diff.modified_tips,
+            HashMap::from([(
+                "web_search".to_string(),
+                ("Use for recent info".to_string(), "Prefer primary sources".to_string())
+            )])
+        );
+        assert_eq!(
+            diff.added_resources,
+            HashMap::from([("timeout_seconds".to_string(), "300".to_string())])
+        );
+        assert_eq!(diff.removed_resources, vec!["gpu".to_string()]);
+        assert!(diff.modified_resources.is_empty());
+        assert_eq!(
+            diff.added_context,
+            HashMap::from([("user_id".to_string(), "user123".to_string())])
+        );
+        assert!(diff.removed_context.is_empty());
+        assert_eq!(
+            diff.modified_context,
+            HashMap::from([("task".to_string(), ("research".to_string(), "review".to_string()))])
+        );
+
+        old.apply_diff(&diff);
+        assert_eq!(old, new);
+        assert!(old.diff(&new).is_empty());
+    }
 }
@@ -14,11 +14,16 @@
 //! - [`AnswerBuffer`]: Accumulates content across RLM iterations
 //! - [`EnvironmentTips`]: Dynamic prompt augmentation based on execution context
 //! - [`RLMEnvironment`]: Orchestrates RLM execution with all components
+//! - [`TokenCounter`]: Pluggable token counting shared by folding and batching
 
 pub mod answer_buffer;
 pub mod environment;
 pub mod environment_tips;
+pub mod token_counter;
 
 pub use answer_buffer::AnswerBuffer;
 pub use environment::{RLMConfig, RLMEnvironment};
-pub use environment_tips::EnvironmentTips;
+pub use environment_tips::{EnvironmentTips, EnvironmentTipsDiff};
+pub use token_counter::{default_token_counter, HeuristicTokenCounter, TokenCounter};
+#[cfg(feature = "tiktoken")]
+pub use token_counter::TiktokenCounter;
@@ -0,0 +1,153 @@
+//! Pluggable token counting
+//!
+//! Folding, batching and context-limit checks all need a token count for a
+//! piece of text. They share one [`TokenCounter`] so that their counts agree.
+//!
+//! [`HeuristicTokenCounter`] is the default. With the `tiktoken` feature,
+//! `TiktokenCounter` counts with OpenAI's BPE encodings. Any
+//! `Fn(&str) -> usize` closure is also a counter, which is how other
+//! tokenizers are plugged in:
+//!
+//! ```
+//! use std::sync::Arc;
+//! use kowalski_core::rlm::TokenCounter;
+//!
+//! let counter: Arc<dyn TokenCounter> = Arc::new(|text: &str| text.chars().count());
+//! assert_eq!(counter.count_tokens("abc"), 3);
+//! ```
+
+use std::fmt;
+use std::sync::Arc;
+
+/// Counts the tokens in a piece of text
+pub trait TokenCounter: Send + Sync {
+    /// Number of tokens in `text`
+    fn count_tokens(&self, text: &str) -> usize;
+
+    /// Short name of the counter, used in debug output
+    fn name(&self) -> &str {
+        "custom"
+    }
+}
+
+impl fmt::Debug for dyn TokenCounter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "TokenCounter({})", self.name())
+    }
+}
+
+impl<F> TokenCounter for F
+where
+    F: Fn(&str) -> usize + Send + Sync,
+{
+    fn count_tokens(&self, text: &str) -> usize {
+        self(text)
+    }
+}
+
+/// Estimates tokens as words plus half the ASCII punctuation
+///
+/// **Note**: This is a heuristic estimation only. Actual LLM tokenization may
+/// vary and this tends to undercount; plug in a real tokenizer where the
+/// count matters.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HeuristicTokenCounter;
+
+impl TokenCounter for HeuristicTokenCounter {
+    fn count_tokens(&self, text: &str) -> usize {
+        let words = text.split_whitespace().count();
+        let punctuation = text.matches(|c: char| c.is_ascii_punctuation()).count();
+        words + (punctuation / 2)
+    }
+
+    fn name(&self) -> &str {
+        "heuristic"
+    }
+}
+
+/// Counts tokens exactly with a tiktoken BPE encoding
+///
+/// Matches what OpenAI models are billed for; other model families use
+/// other tokenizers, so counts for them are close but not exact.
+#[cfg(feature = "tiktoken")]
+#[derive(Clone)]
+pub struct TiktokenCounter {
+    bpe: tiktoken_rs::CoreBPE,
+}
+
+#[cfg(feature = "tiktoken")]
+impl TiktokenCounter {
+    /// Counter for `cl100k_base`, the encoding of GPT-4 and GPT-3.5
+    pub fn cl100k_base() -> Self {
+        Self {
+            bpe: tiktoken_rs::cl100k_base_singleton().clone(),
+        }
+    }
+
+    /// Counter for `o200k_base`, the encoding of GPT-4o
+    pub fn o200k_base() -> Self {
+        Self {
+            bpe: tiktoken_rs::o200k_base_singleton().clone(),
+        }
+    }
+
+    /// Counter for the encoding of the OpenAI model `model`, if tiktoken knows it
+    pub fn for_model(model: &str) -> Option<Self> {
+        tiktoken_rs::get_bpe_from_model(model)
+            .ok()
+            .map(|bpe| Self { bpe })
+    }
+}
+
+#[cfg(feature = "tiktoken")]
+impl TokenCounter for TiktokenCounter {
+    fn count_tokens(&self, text: &str) -> usize {
+        self.bpe.encode_ordinary(text).len()
+    }
+
+    fn name(&self) -> &str {
+        "tiktoken"
+    }
+}
+
+/// Shared instance of the default counter
+pub fn default_token_counter() -> Arc<dyn TokenCounter> {
+    Arc::new(HeuristicTokenCounter)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heuristic_counts_words_and_punctuation() {
+        let counter = HeuristicTokenCounter;
+        assert_eq!(counter.count_tokens(""), 0);
+        assert_eq!(counter.count_tokens("Hello world test"), 3);
+        assert_eq!(counter.count_tokens("fn main() {}"), 5);
+    }
+
+    #[test]
+    fn test_closure_is_a_counter() {
+        let counter: Arc<dyn TokenCounter> = Arc::new(|text: &str| text.len());
+        assert_eq!(counter.count_tokens("abcd"), 4);
+        assert_eq!(format!("{:?}", counter), "TokenCounter(custom)");
+        assert_eq!(format!("{:?}", default_token_counter()), "TokenCounter(heuristic)");
+    }
+
+    #[cfg(feature = "tiktoken")]
+    #[test]
+    fn test_tiktoken_counts_bpe_tokens() {
+        let counter = TiktokenCounter::cl100k_base();
+        assert_eq!(counter.count_tokens(""), 0);
+        assert_eq!(counter.count_tokens("hello world"), 2);
+        // The heuristic sees one word; BPE splits it into several tokens
+        let word = "antidisestablishmentarianism";
+        assert!(counter.count_tokens(word) > HeuristicTokenCounter.count_tokens(word));
+        assert_eq!(counter.name(), "tiktoken");
+
+        let gpt4o = TiktokenCounter::for_model("gpt-4o").unwrap();
+        assert_eq!(gpt4o.count_tokens("hello world"), 2);
+        assert!(TiktokenCounter::for_model("llama3.2").is_none());
+    }
+}
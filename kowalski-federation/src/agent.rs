@@ -1,5 +1,6 @@
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::{Arc, LazyLock};
 use tokio::sync::RwLock;
 use tracing::{info, debug};
@@ -50,6 +51,20 @@ pub trait FederatedAgent: Agent {
         &mut self,
         message: FederationMessage,
     ) -> Result<(), FederationError>;
+
+    /// Versions of the capabilities this agent provides, keyed by capability
+    ///
+    /// Agents that do not version their capabilities return an empty map.
+    fn capability_versions(&self) -> HashMap<String, semver::Version> {
+        HashMap::new()
+    }
+
+    /// Returns true if the agent provides `capability` at version `required` or newer
+    fn supports_capability_version(&self, capability: &str, required: &semver::Version) -> bool {
+        self.capability_versions()
+            .get(capability)
+            .is_some_and(|version| version >= required)
+    }
 }
 
 /// Federated agent with registry reference for communication
@@ -178,6 +193,9 @@ impl FederatedAgent for BaseAgent {
                     // Process task logic would go here
                 }
             }
+            MessageType::TaskWithdrawal => {
+                info!("Received task withdrawal from: {}", message.sender);
+            }
             MessageType::TaskCompletion => {
                 info!("Received task completion from: {}", message.sender);
             }
@@ -1,6 +1,8 @@
 use crate::{FederationError, AgentRegistry, FederationRole};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
+use tracing::warn;
 
 /// Criteria for selecting an agent for task delegation
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,6 +19,9 @@ pub struct SelectionCriteria {
     pub max_depth: usize,
     /// Avoid these agent IDs (already tried)
     pub exclude_agents: Vec<String>,
+    /// Minimum version the agent must provide of each listed capability
+    #[serde(default)]
+    pub min_capability_versions: HashMap<String, semver::Version>,
 }
 
 impl SelectionCriteria {
@@ -29,6 +34,7 @@ impl SelectionCriteria {
             current_depth: 0,
             max_depth: 3,
             exclude_agents: Vec::new(),
+            min_capability_versions: HashMap::new(),
         }
     }
 
@@ -57,10 +63,33 @@ impl SelectionCriteria {
         self
     }
 
+    /// Requires agents to provide `capability` at `min_version` or newer
+    ///
+    /// Agents with an older version, or without the capability, are not
+    /// candidates and are never selected.
+    pub fn with_minimum_capability_version(
+        mut self,
+        capability: String,
+        min_version: semver::Version,
+    ) -> Self {
+        self.min_capability_versions.insert(capability, min_version);
+        self
+    }
+
     /// Returns true if agent should be simplified at this depth
     pub fn should_simplify_agent(&self) -> bool {
         self.current_depth >= 2
     }
+
+    /// Returns a less demanding copy for fallback selection
+    ///
+    /// Required tools are dropped and the current depth is reduced by one.
+    pub fn relax(&self) -> SelectionCriteria {
+        let mut relaxed = self.clone();
+        relaxed.required_tools.clear();
+        relaxed.current_depth = relaxed.current_depth.saturating_sub(1);
+        relaxed
+    }
 }
 
 /// Agent selection score for ranking candidates
@@ -91,6 +120,33 @@ impl AgentScore {
             depth_appropriateness,
         }
     }
+
+    /// Returns true if this score Pareto-dominates `other`
+    ///
+    /// `self` dominates when it is at least as good on capability,
+    /// availability and depth appropriateness, and strictly better on at
+    /// least one of them.
+    pub fn dominates(&self, other: &AgentScore) -> bool {
+        let metrics = [
+            (self.capability_match, other.capability_match),
+            (self.availability_score, other.availability_score),
+            (self.depth_appropriateness, other.depth_appropriateness),
+        ];
+        metrics.iter().all(|(ours, theirs)| ours >= theirs)
+            && metrics.iter().any(|(ours, theirs)| ours > theirs)
+    }
+}
+
+/// Keeps the scores no other score dominates, highest weighted score first
+fn pareto_front(scores: Vec<AgentScore>) -> Vec<AgentScore> {
+    let mut front: Vec<AgentScore> = scores
+        .iter()
+        .filter(|candidate| !scores.iter().any(|other| other.dominates(candidate)))
+        .cloned()
+        .collect();
+    // `Ord` for AgentScore puts higher scores first
+    front.sort();
+    front
 }
 
 impl Ord for AgentScore {
@@ -152,32 +208,7 @@ impl AgentSelector {
         &self,
         criteria: &SelectionCriteria,
     ) -> Result<AgentScore, FederationError> {
-        let agents = self.registry.list_agents().await;
-
-        // Filter for worker agents
-        let candidates: Vec<_> = agents
-            .iter()
-            .filter(|(id, role)| {
-                *role == FederationRole::Worker && !criteria.exclude_agents.contains(id)
-            })
-            .map(|(id, _)| id.clone())
-            .collect();
-
-        if candidates.is_empty() {
-            return Err(FederationError::NoSuitableAgents);
-        }
-
-        // Score each candidate
-        let mut scores = Vec::new();
-        for agent_id in candidates {
-            let score = self
-                .score_agent(&agent_id, criteria)
-                .await
-                .unwrap_or_else(|_| {
-                    AgentScore::new(agent_id.clone(), 0.0, 0.0, 0.0)
-                });
-            scores.push(score);
-        }
+        let mut scores = self.score_candidates(criteria).await?;
 
         // Sort by score (highest first)
         scores.sort();
@@ -186,14 +217,65 @@ impl AgentSelector {
         scores.pop().ok_or(FederationError::NoSuitableAgents)
     }
 
+    /// Selects an agent, falling back to looser criteria if none matches
+    ///
+    /// # Returns
+    /// The selected agent and whether `fallback_criteria` had to be used
+    pub async fn select_with_fallback(
+        &self,
+        criteria: &SelectionCriteria,
+        fallback_criteria: &SelectionCriteria,
+    ) -> Result<(AgentScore, bool), FederationError> {
+        match self.select_agent(criteria).await {
+            Ok(score) => Ok((score, false)),
+            Err(e) => {
+                warn!(
+                    "No agent matched criteria for task type {} ({}), using fallback criteria",
+                    criteria.task_type, e
+                );
+                let score = self.select_agent(fallback_criteria).await?;
+                Ok((score, true))
+            }
+        }
+    }
+
     /// Selects the top N agents for parallel delegation
     pub async fn select_multiple(
         &self,
         criteria: &SelectionCriteria,
         count: usize,
+    ) -> Result<Vec<AgentScore>, FederationError> {
+        let mut scores = self.score_candidates(criteria).await?;
+        scores.sort();
+        Ok(scores.into_iter().take(count).collect())
+    }
+
+    /// Selects every agent on the Pareto front of the candidates
+    ///
+    /// Instead of collapsing capability, availability and depth
+    /// appropriateness into one weighted score, this keeps each agent that no
+    /// other candidate [dominates](AgentScore::dominates). Callers can then
+    /// apply their own trade-off between the three metrics.
+    ///
+    /// The front is ordered by weighted score, highest first.
+    pub async fn select_pareto_optimal(
+        &self,
+        criteria: &SelectionCriteria,
+    ) -> Result<Vec<AgentScore>, FederationError> {
+        let scores = self.score_candidates(criteria).await?;
+        Ok(pareto_front(scores))
+    }
+
+    /// Scores every worker agent not excluded by `criteria`
+    ///
+    /// Agents below a capability version the criteria require are left out.
+    async fn score_candidates(
+        &self,
+        criteria: &SelectionCriteria,
     ) -> Result<Vec<AgentScore>, FederationError> {
         let agents = self.registry.list_agents().await;
 
+        // Filter for worker agents
         let candidates: Vec<_> = agents
             .iter()
             .filter(|(id, role)| {
@@ -208,6 +290,9 @@ impl AgentSelector {
 
         let mut scores = Vec::new();
         for agent_id in candidates {
+            if !self.meets_capability_versions(&agent_id, criteria).await {
+                continue;
+            }
             let score = self
                 .score_agent(&agent_id, criteria)
                 .await
@@ -217,8 +302,11 @@ impl AgentSelector {
             scores.push(score);
         }
 
-        scores.sort();
-        Ok(scores.into_iter().take(count).collect())
+        // Every candidate fell short of a required capability version
+        if scores.is_empty() {
+            return Err(FederationError::NoSuitableAgents);
+        }
+        Ok(scores)
     }
 
     /// Scores a single agent based on selection criteria
@@ -251,6 +339,21 @@ impl AgentSelector {
         ))
     }
 
+    /// Returns true if the agent provides every capability version `criteria` requires
+    async fn meets_capability_versions(&self, agent_id: &str, criteria: &SelectionCriteria) -> bool {
+        if criteria.min_capability_versions.is_empty() {
+            return true;
+        }
+        let Some(agent) = self.registry.get_agent(agent_id).await else {
+            return false;
+        };
+        let agent = agent.read().await;
+        criteria
+            .min_capability_versions
+            .iter()
+            .all(|(capability, min_version)| agent.supports_capability_version(capability, min_version))
+    }
+
     /// Recommends agent type based on task type
     pub fn recommend_agent_type(&self, task_type: &str) -> String {
         match task_type {
@@ -271,6 +374,8 @@ impl AgentSelector {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_utils::{registry_with_workers, MockAgent};
+    use tokio::sync::RwLock;
 
     #[test]
     fn test_selection_criteria() {
@@ -343,6 +448,144 @@ mod tests {
         assert!(criteria.exclude_agents.contains(&"agent-1".to_string()));
     }
 
+    #[test]
+    fn test_relax_criteria() {
+        let criteria = SelectionCriteria::new("analysis".to_string())
+            .with_required_tools(vec!["csv".to_string()])
+            .with_preferred_tools(vec!["plot".to_string()])
+            .with_depth(2, 3)
+            .with_exclusions(vec!["agent-1".to_string()]);
+
+        let relaxed = criteria.relax();
+        assert!(relaxed.required_tools.is_empty());
+        assert_eq!(relaxed.current_depth, 1);
+        assert_eq!(relaxed.max_depth, 3);
+        assert_eq!(relaxed.preferred_tools, criteria.preferred_tools);
+        assert_eq!(relaxed.exclude_agents, criteria.exclude_agents);
+
+        let at_root = SelectionCriteria::new("analysis".to_string()).relax();
+        assert_eq!(at_root.current_depth, 0);
+    }
+
+    #[tokio::test]
+    async fn test_select_with_fallback_prefers_primary() {
+        let selector = AgentSelector::new(registry_with_workers(&["agent-1", "agent-2"]).await);
+        let primary = SelectionCriteria::new("analysis".to_string())
+            .with_exclusions(vec!["agent-2".to_string()]);
+        let fallback = SelectionCriteria::new("analysis".to_string())
+            .with_exclusions(vec!["agent-1".to_string()]);
+
+        let (score, used_fallback) = selector.select_with_fallback(&primary, &fallback).await.unwrap();
+        assert_eq!(score.agent_id, "agent-1");
+        assert!(!used_fallback);
+    }
+
+    #[tokio::test]
+    async fn test_select_with_fallback_uses_fallback() {
+        let selector = AgentSelector::new(registry_with_workers(&["agent-1"]).await);
+        let primary = SelectionCriteria::new("analysis".to_string())
+            .with_required_tools(vec!["csv".to_string()])
+            .with_exclusions(vec!["agent-1".to_string()]);
+        let fallback = SelectionCriteria::new("analysis".to_string());
+
+        let (score, used_fallback) = selector.select_with_fallback(&primary, &fallback).await.unwrap();
+        assert_eq!(score.agent_id, "agent-1");
+        assert!(used_fallback);
+    }
+
+    #[tokio::test]
+    async fn test_select_with_fallback_both_fail() {
+        let selector = AgentSelector::new(Arc::new(Default::default()));
+        let criteria = SelectionCriteria::new("analysis".to_string());
+
+        let result = selector.select_with_fallback(&criteria, &criteria.relax()).await;
+        assert!(matches!(result, Err(FederationError::NoSuitableAgents)));
+    }
+
+    #[tokio::test]
+    async fn test_minimum_capability_version_disqualifies_older_agents() {
+        let registry = Arc::new(AgentRegistry::new());
+        for (id, version) in [("agent-old", "1.2.0"), ("agent-new", "2.0.0")] {
+            let agent = MockAgent::new(id, FederationRole::Worker)
+                .with_capability_version("sql", version);
+            registry.register_agent(Arc::new(RwLock::new(agent))).await.unwrap();
+        }
+        let selector = AgentSelector::new(registry);
+        let criteria = SelectionCriteria::new("analysis".to_string())
+            .with_minimum_capability_version("sql".to_string(), semver::Version::new(2, 0, 0));
+
+        let selected = selector.select_multiple(&criteria, 2).await.unwrap();
+        let ids: Vec<_> = selected.iter().map(|s| s.agent_id.as_str()).collect();
+        assert_eq!(ids, vec!["agent-new"]);
+        assert_eq!(selector.select_agent(&criteria).await.unwrap().agent_id, "agent-new");
+
+        let too_new = SelectionCriteria::new("analysis".to_string())
+            .with_minimum_capability_version("sql".to_string(), semver::Version::new(3, 0, 0));
+        let result = selector.select_agent(&too_new).await;
+        assert!(matches!(result, Err(FederationError::NoSuitableAgents)));
+    }
+
+    #[tokio::test]
+    async fn test_unversioned_agents_selected_without_version_requirement() {
+        let registry = registry_with_workers(&["agent-1", "agent-2"]).await;
+        let selector = AgentSelector::new(registry);
+        let criteria = SelectionCriteria::new("analysis".to_string());
+
+        assert_eq!(selector.select_multiple(&criteria, 5).await.unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_agent_score_dominates() {
+        let strong = AgentScore::new("strong".to_string(), 0.9, 0.9, 1.0);
+        let weak = AgentScore::new("weak".to_string(), 0.5, 0.9, 0.7);
+        let specialist = AgentScore::new("specialist".to_string(), 1.0, 0.4, 0.7);
+
+        assert!(strong.dominates(&weak));
+        assert!(!weak.dominates(&strong));
+        // Better on one metric and worse on another: neither dominates
+        assert!(!strong.dominates(&specialist));
+        assert!(!specialist.dominates(&strong));
+        // Equal on every metric is not domination
+        assert!(!strong.dominates(&strong.clone()));
+    }
+
+    #[test]
+    fn test_pareto_front_excludes_dominated_agents() {
+        let scores = vec![
+            AgentScore::new("balanced".to_string(), 0.8, 0.8, 0.8),
+            AgentScore::new("capable".to_string(), 1.0, 0.3, 0.5),
+            AgentScore::new("idle".to_string(), 0.4, 1.0, 0.6),
+            // Dominated by "balanced" on every metric
+            AgentScore::new("mediocre".to_string(), 0.7, 0.6, 0.8),
+            // Dominated by "capable" and "balanced"
+            AgentScore::new("busy".to_string(), 0.3, 0.2, 0.5),
+        ];
+
+        let front = pareto_front(scores);
+        let ids: Vec<_> = front.iter().map(|s| s.agent_id.as_str()).collect();
+        assert_eq!(ids, vec!["balanced", "capable", "idle"]);
+        for (i, a) in front.iter().enumerate() {
+            for b in &front[i + 1..] {
+                assert!(!a.dominates(b) && !b.dominates(a));
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_select_pareto_optimal() {
+        let selector = AgentSelector::new(registry_with_workers(&["agent-1", "agent-2"]).await);
+        let criteria = SelectionCriteria::new("analysis".to_string())
+            .with_exclusions(vec!["agent-2".to_string()]);
+
+        let front = selector.select_pareto_optimal(&criteria).await.unwrap();
+        assert_eq!(front.len(), 1);
+        assert_eq!(front[0].agent_id, "agent-1");
+
+        let empty = AgentSelector::new(Arc::new(Default::default()));
+        let result = empty.select_pareto_optimal(&criteria).await;
+        assert!(matches!(result, Err(FederationError::NoSuitableAgents)));
+    }
+
     #[test]
     fn test_agent_score_weighted_average() {
         // Test that weighting is correct: 50% capability, 30% availability, 20% depth
@@ -1,4 +1,10 @@
+use crate::batch_scheduler::DEFAULT_PRIORITY;
+use crate::model_registry::ModelRegistry;
+use crate::rate_limiter::RateLimiter;
 use crate::FederationError;
+use kowalski_core::rlm::{default_token_counter, TokenCounter};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, RwLock};
 use std::time::Duration;
 use std::time::Instant;
 use tokio::sync::Semaphore;
@@ -19,6 +25,9 @@ pub struct BatchCallResult {
     pub success: bool,
     /// Error message if failed
     pub error: Option<String>,
+    /// Model that produced this result
+    #[serde(default)]
+    pub model_used: String,
 }
 
 /// Request for batch LLM execution
@@ -32,6 +41,48 @@ pub struct BatchLLMRequest {
     pub temperature: f32,
     /// Maximum tokens per response
     pub max_tokens: usize,
+    /// Optional idempotency key per prompt, aligned by index with `prompts`
+    #[serde(default)]
+    pub idempotency_keys: Vec<Option<String>>,
+    /// Optional priority per prompt, aligned by index with `prompts`
+    ///
+    /// Prompts without an entry run at [`DEFAULT_PRIORITY`]. Higher
+    /// priorities run first; equal priorities run in list order.
+    #[serde(default)]
+    pub priorities: Vec<u8>,
+}
+
+impl BatchLLMRequest {
+    /// Gets the idempotency key for the prompt at `index`, if any
+    pub fn idempotency_key(&self, index: usize) -> Option<&str> {
+        self.idempotency_keys.get(index).and_then(|k| k.as_deref())
+    }
+
+    /// Gets the priority of the prompt at `index`
+    pub fn priority(&self, index: usize) -> u8 {
+        self.priorities.get(index).copied().unwrap_or(DEFAULT_PRIORITY)
+    }
+}
+
+/// Generation settings of a batch request, kept with its response
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BatchRequestConfig {
+    /// Model the batch ran on
+    pub model: String,
+    /// Temperature for generation (0.0-1.0)
+    pub temperature: f32,
+    /// Maximum tokens per response
+    pub max_tokens: usize,
+}
+
+impl From<&BatchLLMRequest> for BatchRequestConfig {
+    fn from(request: &BatchLLMRequest) -> Self {
+        Self {
+            model: request.model.clone(),
+            temperature: request.temperature,
+            max_tokens: request.max_tokens,
+        }
+    }
 }
 
 /// Response from batch execution
@@ -45,6 +96,9 @@ pub struct BatchLLMResponse {
     pub duration_ms: u64,
     /// Whether all calls succeeded
     pub all_succeeded: bool,
+    /// Settings of the request that produced this response
+    #[serde(default)]
+    pub original_config: BatchRequestConfig,
 }
 
 impl BatchLLMResponse {
@@ -64,10 +118,139 @@ impl BatchLLMResponse {
             .collect()
     }
 
+    /// Number of results produced by a model other than the request's
+    ///
+    /// See [`BatchExecutor::execute_with_fallback_model`].
+    pub fn fallback_used_count(&self) -> usize {
+        self.results
+            .iter()
+            .filter(|r| r.model_used != self.original_config.model)
+            .count()
+    }
+
     /// Gets the response at a specific index, preserving order
+    ///
+    /// Responses from [`BatchExecutor`] hold result `i` at position `i`, so
+    /// the lookup is direct; other responses fall back to a scan.
     pub fn get_response(&self, index: usize) -> Option<&BatchCallResult> {
-        self.results.iter().find(|r| r.index == index)
+        self.results
+            .get(index)
+            .filter(|r| r.index == index)
+            .or_else(|| self.results.iter().find(|r| r.index == index))
+    }
+
+    /// Builds a request that re-runs only the failed prompts
+    ///
+    /// The prompts keep their relative order and run with the original
+    /// model, temperature and max tokens. Prompt `i` of the new request is
+    /// the `i`-th failed result, which is how [`BatchLLMResponse::merge`]
+    /// maps the retry back onto this response.
+    pub fn retry_failed(&self) -> BatchLLMRequest {
+        BatchLLMRequest {
+            prompts: self
+                .failed_responses()
+                .into_iter()
+                .map(|result| result.prompt.clone())
+                .collect(),
+            model: self.original_config.model.clone(),
+            temperature: self.original_config.temperature,
+            max_tokens: self.original_config.max_tokens,
+            idempotency_keys: Vec::new(),
+            priorities: Vec::new(),
+        }
+    }
+
+    /// Replaces the failed results of `original` with those of a retry
+    ///
+    /// `retry_results` must come from executing `original.retry_failed()`:
+    /// its result at index `i` replaces the `i`-th failed result of
+    /// `original` and takes over that result's index. Failures the retry has
+    /// no result for are kept. Token counts and durations of both runs are
+    /// added up.
+    pub fn merge(original: &BatchLLMResponse, retry_results: &BatchLLMResponse) -> BatchLLMResponse {
+        let mut failed_seen = 0;
+        let results: Vec<BatchCallResult> = original
+            .results
+            .iter()
+            .map(|result| {
+                if result.success {
+                    return result.clone();
+                }
+                let retry_index = failed_seen;
+                failed_seen += 1;
+                match retry_results.get_response(retry_index) {
+                    Some(retried) => BatchCallResult {
+                        index: result.index,
+                        ..retried.clone()
+                    },
+                    None => result.clone(),
+                }
+            })
+            .collect();
+
+        BatchLLMResponse {
+            total_tokens: original.total_tokens + retry_results.total_tokens,
+            duration_ms: original.duration_ms + retry_results.duration_ms,
+            all_succeeded: results.iter().all(|result| result.success),
+            results,
+            original_config: original.original_config.clone(),
+        }
+    }
+}
+
+/// Adjusts the priority of batch items while the batch runs
+///
+/// Items are identified by their index in [`BatchLLMRequest::prompts`] and
+/// start at [`DEFAULT_PRIORITY`]. Higher priorities run first. A controller
+/// can be shared with other threads and boosted while
+/// [`BatchExecutor::execute`] is in progress.
+#[derive(Debug, Clone, Default)]
+pub struct PriorityController {
+    priorities: Arc<RwLock<HashMap<usize, u8>>>,
+}
+
+impl PriorityController {
+    /// Creates a controller with every item at the default priority
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Raises the priority of the item at `index` to `new_priority`
+    ///
+    /// A boost never lowers an item's priority.
+    pub fn boost(&self, index: usize, new_priority: u8) {
+        let mut priorities = self.priorities.write().unwrap();
+        let priority = priorities.entry(index).or_insert(DEFAULT_PRIORITY);
+        *priority = (*priority).max(new_priority);
     }
+
+    /// Current priority of the item at `index`
+    pub fn priority(&self, index: usize) -> u8 {
+        self.priorities
+            .read()
+            .unwrap()
+            .get(&index)
+            .copied()
+            .unwrap_or(DEFAULT_PRIORITY)
+    }
+
+    /// Snapshot of every boosted item's priority, keyed by index
+    pub fn current_priorities(&self) -> HashMap<usize, u8> {
+        self.priorities.read().unwrap().clone()
+    }
+}
+
+/// Removes and returns the pending index with the highest priority
+///
+/// Ties go to the index submitted first, so with equal priorities items
+/// come out in submission order.
+fn take_highest_priority(pending: &mut Vec<usize>, priority: impl Fn(usize) -> u8) -> Option<usize> {
+    let best = pending
+        .iter()
+        .enumerate()
+        .max_by(|(a_pos, a), (b_pos, b)| priority(**a).cmp(&priority(**b)).then(b_pos.cmp(a_pos)))
+        .map(|(pos, _)| pos)?;
+    Some(pending.remove(best))
 }
 
 /// Batch LLM Executor
@@ -97,6 +280,8 @@ impl BatchLLMResponse {
 ///         model: "llama3.2".to_string(),
 ///         temperature: 0.7,
 ///         max_tokens: 500,
+///         idempotency_keys: Vec::new(),
+///         priorities: Vec::new(),
 ///     };
 ///
 ///     let response = executor
@@ -115,8 +300,19 @@ pub struct BatchExecutor {
     client: reqwest::Client,
     semaphore: Semaphore,
     max_concurrent: usize,
+    endpoint: String,
+    completed: Mutex<IdempotencyCache>,
+    priority_controller: Option<Arc<PriorityController>>,
+    token_counter: Arc<dyn TokenCounter>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    model_registry: Option<Arc<ModelRegistry>>,
 }
 
+const DEFAULT_ENDPOINT: &str = "http://127.0.0.1:11434/api/generate";
+
+/// Completed prompts kept by idempotency key unless configured otherwise
+pub const DEFAULT_IDEMPOTENCY_CACHE_CAPACITY: usize = 1024;
+
 impl BatchExecutor {
     /// Creates a new batch executor with default configuration
     pub fn new() -> Self {
@@ -131,6 +327,12 @@ impl BatchExecutor {
             client,
             semaphore: Semaphore::new(10),
             max_concurrent: 10,
+            endpoint: DEFAULT_ENDPOINT.to_string(),
+            completed: Mutex::new(IdempotencyCache::new(DEFAULT_IDEMPOTENCY_CACHE_CAPACITY)),
+            priority_controller: None,
+            token_counter: default_token_counter(),
+            rate_limiter: None,
+            model_registry: None,
         }
     }
 
@@ -147,17 +349,127 @@ impl BatchExecutor {
             client,
             semaphore: Semaphore::new(max_concurrent),
             max_concurrent,
+            endpoint: DEFAULT_ENDPOINT.to_string(),
+            completed: Mutex::new(IdempotencyCache::new(DEFAULT_IDEMPOTENCY_CACHE_CAPACITY)),
+            priority_controller: None,
+            token_counter: default_token_counter(),
+            rate_limiter: None,
+            model_registry: None,
         }
     }
 
+    /// Sets the generate endpoint prompts are posted to
+    pub fn with_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoint = endpoint.into();
+        self
+    }
+
+    /// Orders batch items by the priorities held in `controller`
+    ///
+    /// [`BatchExecutor::execute`] consults the controller each time it picks
+    /// the next item, so boosts made while a batch runs take effect for
+    /// the items still pending.
+    pub fn with_priority_controller(mut self, controller: Arc<PriorityController>) -> Self {
+        self.priority_controller = Some(controller);
+        self
+    }
+
+    /// Keeps at most `capacity` completed prompts by idempotency key
+    ///
+    /// Once the cache is full, the oldest entry is evicted for each new one.
+    /// Defaults to [`DEFAULT_IDEMPOTENCY_CACHE_CAPACITY`]; a capacity of 0
+    /// disables caching.
+    pub fn with_idempotency_cache_capacity(mut self, capacity: usize) -> Self {
+        self.completed = Mutex::new(IdempotencyCache::new(capacity));
+        self
+    }
+
+    /// Counts response tokens with `counter` instead of the default heuristic
+    pub fn with_token_counter(mut self, counter: Arc<dyn TokenCounter>) -> Self {
+        self.token_counter = counter;
+        self
+    }
+
+    /// Waits for capacity in `limiter` before each prompt is sent
+    ///
+    /// Each prompt reserves its own tokens plus `max_tokens` against the
+    /// model's token limit, and the unused part is returned once the response
+    /// arrives. Prompts answered from the idempotency cache are not limited.
+    /// If capacity would only free up after the call's timeout, the prompt
+    /// fails with [`FederationError::RateLimited`].
+    pub fn with_rate_limiter(mut self, limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limiter = Some(limiter);
+        self
+    }
+
+    /// Looks model prices up in `registry` instead of [`ModelRegistry::global`]
+    ///
+    /// This also enables the context window check: before a prompt is sent,
+    /// its tokens plus `max_tokens` are checked against the model's context
+    /// window, and a prompt that does not fit fails with
+    /// [`FederationError::ContextWindowExceeded`] without being sent. Models
+    /// missing from the registry are not checked. Without a registry no
+    /// prompt is checked, since the built-in windows need not match how a
+    /// model is actually served.
+    pub fn with_model_registry(mut self, registry: Arc<ModelRegistry>) -> Self {
+        self.model_registry = Some(registry);
+        self
+    }
+
+    /// Registry consulted for prices
+    ///
+    /// Falls back to [`ModelRegistry::global`]; context windows are only
+    /// checked against a registry set with
+    /// [`with_model_registry`](Self::with_model_registry).
+    pub fn model_registry(&self) -> &ModelRegistry {
+        match &self.model_registry {
+            Some(registry) => registry,
+            None => ModelRegistry::global(),
+        }
+    }
+
+    /// Upper bound on the price of `request`
+    ///
+    /// Counts every prompt's tokens plus `max_tokens` of response per prompt,
+    /// at the prices in [`BatchExecutor::model_registry`]. Returns `None` if
+    /// the model is not registered.
+    pub fn estimate_cost(&self, request: &BatchLLMRequest) -> Option<f64> {
+        let spec = self.model_registry().get(&request.model)?;
+        let tokens_in: usize = request.prompts.iter().map(|p| self.count_tokens(p)).sum();
+        let tokens_out = request.max_tokens * request.prompts.len();
+        Some(spec.cost(tokens_in, tokens_out))
+    }
+
+    /// Tokens in `text`, as reported in [`BatchCallResult::tokens_used`]
+    pub fn count_tokens(&self, text: &str) -> usize {
+        self.token_counter.count_tokens(text)
+    }
+
+    /// Number of completed prompts cached by idempotency key
+    pub fn cached_result_count(&self) -> usize {
+        self.completed.lock().unwrap().len()
+    }
+
+    /// Drops all results cached by idempotency key
+    pub fn clear_idempotency_cache(&self) {
+        self.completed.lock().unwrap().clear();
+    }
+
     /// Executes a batch of LLM requests in parallel
     ///
     /// # Arguments
     /// * `request` - The batch request with prompts and configuration
     /// * `timeout` - Maximum time for the entire batch operation
     ///
+    /// Prompts run highest priority first, taking the larger of the
+    /// request's [`priorities`](BatchLLMRequest::priorities) and any
+    /// [`PriorityController`] boost; prompts of equal priority run in list
+    /// order. Permits are granted first come, first served, so a prompt is
+    /// never overtaken by one submitted after it at the same priority.
+    ///
     /// # Returns
-    /// The batch response with results in the same order as input
+    /// The batch response with results in the same order as input, even
+    /// when priorities changed the execution order
     pub async fn execute(
         &self,
         request: BatchLLMRequest,
@@ -167,16 +479,23 @@ impl BatchExecutor {
         let mut results = Vec::with_capacity(request.prompts.len());
         let mut total_tokens = usize::default();
         let mut all_succeeded = true;
+        let mut pending: Vec<usize> = (0..request.prompts.len()).collect();
 
-        for (index, prompt) in request.prompts.iter().enumerate() {
+        while !pending.is_empty() {
             let permit = self.semaphore.acquire().await;
             let _guard = permit;
 
+            // Pick the next item only once a permit is held, so boosts made
+            // while the previous item ran are honored
+            let Some(index) = self.take_next(&request, &mut pending) else { break };
+            let prompt = &request.prompts[index];
+
             let call_start = Instant::now();
-            
+            let deadline = tokio::time::Instant::now() + timeout;
+
             let result = tokio::time::timeout(
                 timeout,
-                self.execute_single_prompt(prompt, &request.model, request.temperature, request.max_tokens)
+                self.execute_keyed_prompt(&request, index, prompt, deadline)
             ).await;
 
             let _elapsed_ms = call_start.elapsed().as_millis();
@@ -191,6 +510,7 @@ impl BatchExecutor {
                         tokens_used: response.tokens_used,
                         success: true,
                         error: None,
+                        model_used: request.model.clone(),
                     }
                 }
                 Ok(Err(FederationError::Timeout(_))) => {
@@ -202,6 +522,7 @@ impl BatchExecutor {
                         tokens_used: 0,
                         success: false,
                         error: Some("Request timed out".to_string()),
+                        model_used: request.model.clone(),
                     }
                 }
                 Ok(Err(e)) => {
@@ -213,6 +534,7 @@ impl BatchExecutor {
                         tokens_used: 0,
                         success: false,
                         error: Some(e.to_string()),
+                        model_used: request.model.clone(),
                     }
                 }
                 Err(_) => {
@@ -224,6 +546,7 @@ impl BatchExecutor {
                         tokens_used: 0,
                         success: false,
                         error: Some("Request timed out".to_string()),
+                        model_used: request.model.clone(),
                     }
                 }
             };
@@ -232,14 +555,43 @@ impl BatchExecutor {
         }
 
         Ok(BatchLLMResponse {
-            results,
+            results: in_index_order(results),
             total_tokens,
             duration_ms: start_time.elapsed().as_millis() as u64,
             all_succeeded,
+            original_config: BatchRequestConfig::from(&request),
         })
     }
 
+    /// Executes a batch, retrying failed prompts once on `fallback_model`
+    ///
+    /// The batch first runs on `request.model`. Prompts that fail are sent
+    /// again, once, with `fallback_model` and the same generation settings;
+    /// their results replace the failures, so
+    /// [`BatchCallResult::model_used`] shows which model answered each prompt.
+    /// Tokens and durations of both runs are added up.
+    pub async fn execute_with_fallback_model(
+        &self,
+        request: BatchLLMRequest,
+        fallback_model: String,
+        timeout: Duration,
+    ) -> Result<BatchLLMResponse, FederationError> {
+        let primary = self.execute(request, timeout).await?;
+        if primary.all_succeeded {
+            return Ok(primary);
+        }
+
+        let fallback_request = BatchLLMRequest {
+            model: fallback_model,
+            ..primary.retry_failed()
+        };
+        let fallback = self.execute(fallback_request, timeout).await?;
+        Ok(BatchLLMResponse::merge(&primary, &fallback))
+    }
+
     /// Executes with rate limiting (maximum calls per second)
+    ///
+    /// Prompts run in the same order as with [`execute`](Self::execute).
     pub async fn execute_rate_limited(
         &self,
         request: BatchLLMRequest,
@@ -252,15 +604,21 @@ impl BatchExecutor {
         let mut all_succeeded = true;
         let interval = Duration::from_secs(1) / max_calls_per_sec.max(1) as u32;
 
-        for (index, prompt) in request.prompts.iter().enumerate() {
+        let mut pending: Vec<usize> = (0..request.prompts.len()).collect();
+
+        while !pending.is_empty() {
             let permit = self.semaphore.acquire().await;
             let _guard = permit;
 
             tokio::time::sleep(interval).await;
 
+            let Some(index) = self.take_next(&request, &mut pending) else { break };
+            let prompt = &request.prompts[index];
+
+            let deadline = tokio::time::Instant::now() + timeout;
             let result = tokio::time::timeout(
                 timeout,
-                self.execute_single_prompt(prompt, &request.model, request.temperature, request.max_tokens)
+                self.execute_keyed_prompt(&request, index, prompt, deadline)
             ).await;
 
             let call_result = match result {
@@ -273,6 +631,7 @@ impl BatchExecutor {
                         tokens_used: response.tokens_used,
                         success: true,
                         error: None,
+                        model_used: request.model.clone(),
                     }
                 }
                 Ok(Err(FederationError::Timeout(_))) => {
@@ -284,6 +643,7 @@ impl BatchExecutor {
                         tokens_used: 0,
                         success: false,
                         error: Some("Request timed out".to_string()),
+                        model_used: request.model.clone(),
                     }
                 }
                 Ok(Err(e)) => {
@@ -295,6 +655,7 @@ impl BatchExecutor {
                         tokens_used: 0,
                         success: false,
                         error: Some(e.to_string()),
+                        model_used: request.model.clone(),
                     }
                 }
                 Err(_) => {
@@ -306,6 +667,7 @@ impl BatchExecutor {
                         tokens_used: 0,
                         success: false,
                         error: Some("Request timed out".to_string()),
+                        model_used: request.model.clone(),
                     }
                 }
             };
@@ -314,13 +676,72 @@ impl BatchExecutor {
         }
 
         Ok(BatchLLMResponse {
-            results,
+            results: in_index_order(results),
             total_tokens,
             duration_ms: start_time.elapsed().as_millis() as u64,
             all_succeeded,
+            original_config: BatchRequestConfig::from(&request),
         })
     }
 
+    /// Removes and returns the pending prompt index to run next
+    fn take_next(&self, request: &BatchLLMRequest, pending: &mut Vec<usize>) -> Option<usize> {
+        take_highest_priority(pending, |index| {
+            let boosted = self
+                .priority_controller
+                .as_ref()
+                .map_or(DEFAULT_PRIORITY, |controller| controller.priority(index));
+            request.priority(index).max(boosted)
+        })
+    }
+
+    /// Execute a prompt, reusing the cached result for its idempotency key
+    ///
+    /// Waits for the rate limiter, if any, until `deadline`.
+    async fn execute_keyed_prompt(
+        &self,
+        request: &BatchLLMRequest,
+        index: usize,
+        prompt: &str,
+        deadline: tokio::time::Instant,
+    ) -> Result<SingleLLMResponse, FederationError> {
+        let key = request.idempotency_key(index);
+
+        if let Some(key) = key {
+            if let Some(cached) = self.completed.lock().unwrap().get(key) {
+                return Ok(cached.clone());
+            }
+        }
+
+        let prompt_tokens = self.count_tokens(prompt);
+        if let Some(registry) = &self.model_registry {
+            registry.check_context(&request.model, prompt_tokens + request.max_tokens)?;
+        }
+        let reserved = match &self.rate_limiter {
+            Some(limiter) => {
+                limiter
+                    .acquire(&request.model, prompt_tokens + request.max_tokens, deadline)
+                    .await?
+            }
+            None => 0,
+        };
+
+        let response = self
+            .execute_single_prompt(prompt, &request.model, request.temperature, request.max_tokens, key)
+            .await?;
+
+        if let Some(limiter) = &self.rate_limiter {
+            let used = prompt_tokens + response.tokens_used;
+            limiter.refund(&request.model, reserved.saturating_sub(used));
+        }
+
+        if let Some(key) = key {
+            self.completed.lock().unwrap().insert(key, &response);
+        }
+
+        Ok(response)
+    }
+
     /// Execute a single prompt with retry logic
     async fn execute_single_prompt(
         &self,
@@ -328,78 +749,186 @@ impl BatchExecutor {
         model: &str,
         temperature: f32,
         max_tokens: usize,
+        idempotency_key: Option<&str>,
     ) -> Result<SingleLLMResponse, FederationError> {
         const MAX_RETRIES: usize = 3;
-        let mut last_error = None;
-
-        for attempt in 0..MAX_RETRIES {
-            let request = serde_json::json!({
-                "model": model,
-                "prompt": prompt,
-                "stream": false,
-                "temperature": temperature,
-                "max_tokens": max_tokens,
-            });
-
-            let response = self.client
-                .post("http://127.0.0.1:11434/api/generate")
-                .json(&request)
-                .send()
-                .await;
 
-            match response {
-                Ok(resp) => {
-                    if resp.status().is_success() {
-                        if let Ok(body) = resp.text().await {
-                            if let Ok(json) = serde_json::from_str::<serde_json::Value>(&body) {
-                                if let Some(response_str) = json.get("response").and_then(|v| v.as_str()) {
-                                    return Ok(SingleLLMResponse {
-                                        content: response_str.to_string(),
-                                        tokens_used: self.estimate_tokens(response_str),
-                                    });
-                                }
-                            }
-                        }
-                    } else if attempt < MAX_RETRIES - 1 {
-                        last_error = Some(FederationError::ExecutionError(
-                            format!("HTTP error: {}", resp.status())
-                        ));
-                        tokio::time::sleep(Duration::from_millis(100 * (attempt + 1) as u64)).await;
-                        continue;
+        let request = serde_json::json!({
+            "model": model,
+            "prompt": prompt,
+            "stream": false,
+            "temperature": temperature,
+            "max_tokens": max_tokens,
+        });
+
+        let mut attempt = 0;
+        loop {
+            let mut builder = self.client.post(&self.endpoint).json(&request);
+            if let Some(key) = idempotency_key {
+                builder = builder.header("Idempotency-Key", key);
+            }
+
+            let (error, server_delay) = match builder.send().await {
+                Ok(resp) if resp.status().is_success() => {
+                    let body = resp
+                        .text()
+                        .await
+                        .map_err(|e| FederationError::NetworkError(e.to_string()));
+                    match body.and_then(|body| self.parse_response(&body)) {
+                        Ok(response) => return Ok(response),
+                        Err(e) => (e, None),
                     }
                 }
-                Err(e) if attempt < MAX_RETRIES - 1 => {
-                    last_error = Some(FederationError::ExecutionError(
-                        format!("Request failed: {}", e)
-                    ));
-                    tokio::time::sleep(Duration::from_millis(100 * (attempt + 1) as u64)).await;
-                    continue;
+                Ok(resp) => {
+                    let status = resp.status();
+                    let server_delay = if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+                        rate_limit_delay(resp.headers())
+                    } else {
+                        None
+                    };
+                    let message = resp.text().await.unwrap_or_default();
+                    (
+                        FederationError::HttpError {
+                            status: status.as_u16(),
+                            message,
+                        },
+                        server_delay,
+                    )
                 }
-                Err(e) => return Err(FederationError::ExecutionError(
-                    format!("Request failed after {} retries: {}", MAX_RETRIES, e)
-                )),
+                Err(e) if e.is_timeout() => (FederationError::Timeout(e.to_string()), None),
+                Err(e) => (FederationError::NetworkError(e.to_string()), None),
+            };
+
+            attempt += 1;
+            let Some(base_delay) = error.suggested_retry_delay() else {
+                return Err(error);
+            };
+            if attempt >= MAX_RETRIES {
+                return Err(error);
             }
+            let delay = server_delay.unwrap_or(base_delay * attempt as u32);
+            tokio::time::sleep(delay).await;
         }
-
-        Err(last_error.unwrap_or(FederationError::ExecutionError(
-            "All retries exhausted".to_string()
-        )))
     }
 
-    /// Estimate token count from text (conservative heuristic)
-    fn estimate_tokens(&self, text: &str) -> usize {
-        let words = text.split_whitespace().count();
-        let chars = text.chars().count();
-        (words + (chars / 4)).max(1)
+    /// Extract the generated text from a successful response body
+    fn parse_response(&self, body: &str) -> Result<SingleLLMResponse, FederationError> {
+        let json: serde_json::Value = serde_json::from_str(body)
+            .map_err(|e| FederationError::DeserializationError(e.to_string()))?;
+        let response_str = json
+            .get("response")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| {
+                FederationError::DeserializationError("response has no \"response\" field".to_string())
+            })?;
+        Ok(SingleLLMResponse {
+            content: response_str.to_string(),
+            tokens_used: self.count_tokens(response_str),
+        })
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Longest server-requested delay honored before retrying
+const MAX_RATE_LIMIT_DELAY: Duration = Duration::from_secs(60);
+
+/// Delay requested by a rate-limited backend, if any
+///
+/// Reads `Retry-After` (delta seconds) first, then `X-RateLimit-Reset`, which
+/// may be either delta seconds or a Unix timestamp. The delay is capped at
+/// [`MAX_RATE_LIMIT_DELAY`].
+fn rate_limit_delay(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    // Values this large can only be absolute Unix timestamps
+    const EPOCH_THRESHOLD: u64 = 1_000_000_000;
+
+    let header_secs = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse::<f64>().ok())
+            .filter(|secs| secs.is_finite() && *secs >= 0.0)
+    };
+
+    let secs = match header_secs("retry-after") {
+        Some(secs) => secs,
+        None => {
+            let reset = header_secs("x-ratelimit-reset")?;
+            if reset >= EPOCH_THRESHOLD as f64 {
+                let now = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs_f64();
+                (reset - now).max(0.0)
+            } else {
+                reset
+            }
+        }
+    };
+
+    Some(Duration::from_secs_f64(secs).min(MAX_RATE_LIMIT_DELAY))
+}
+
+/// Orders call results by prompt index, whatever order they completed in
+///
+/// Every prompt of a batch yields exactly one result, so afterwards
+/// `results[i].index == i`.
+fn in_index_order(mut results: Vec<BatchCallResult>) -> Vec<BatchCallResult> {
+    results.sort_unstable_by_key(|result| result.index);
+    results
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct SingleLLMResponse {
     content: String,
     tokens_used: usize,
 }
 
+/// Completed responses by idempotency key, evicting the oldest when full
+#[derive(Debug)]
+struct IdempotencyCache {
+    capacity: usize,
+    entries: HashMap<String, SingleLLMResponse>,
+    /// Keys in insertion order, oldest first
+    order: VecDeque<String>,
+}
+
+impl IdempotencyCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    fn get(&self, key: &str) -> Option<&SingleLLMResponse> {
+        self.entries.get(key)
+    }
+
+    fn insert(&mut self, key: &str, response: &SingleLLMResponse) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.insert(key.to_string(), response.clone()).is_none() {
+            self.order.push_back(key.to_string());
+        }
+        while self.entries.len() > self.capacity {
+            match self.order.pop_front() {
+                Some(oldest) => self.entries.remove(&oldest),
+                None => break,
+            };
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+    }
+}
+
 impl Default for BatchExecutor {
     fn default() -> Self {
         Self::new()
@@ -419,6 +948,7 @@ mod tests {
             tokens_used: 50,
             success: true,
             error: None,
+            model_used: "test-model".to_string(),
         };
 
         assert!(result.success);
@@ -435,6 +965,7 @@ mod tests {
                 tokens_used: 50,
                 success: true,
                 error: None,
+                model_used: "test-model".to_string(),
             },
             BatchCallResult {
                 index: 1,
@@ -443,6 +974,7 @@ mod tests {
                 tokens_used: 0,
                 success: false,
                 error: Some("Timeout".to_string()),
+                model_used: "test-model".to_string(),
             },
         ];
 
@@ -451,6 +983,7 @@ mod tests {
             total_tokens: 50,
             duration_ms: 1000,
             all_succeeded: false,
+            original_config: BatchRequestConfig::default(),
         };
 
         assert_eq!(response.successful_responses().len(), 1);
@@ -468,6 +1001,7 @@ mod tests {
                 tokens_used: 50,
                 success: true,
                 error: None,
+                model_used: "test-model".to_string(),
             },
             BatchCallResult {
                 index: 2,
@@ -476,6 +1010,7 @@ mod tests {
                 tokens_used: 60,
                 success: true,
                 error: None,
+                model_used: "test-model".to_string(),
             },
         ];
 
@@ -484,6 +1019,7 @@ mod tests {
             total_tokens: 110,
             duration_ms: 1000,
             all_succeeded: true,
+            original_config: BatchRequestConfig::default(),
         };
 
         assert!(response.get_response(0).is_some());
@@ -491,6 +1027,99 @@ mod tests {
         assert!(response.get_response(2).is_some());
     }
 
+    fn call_result(index: usize, prompt: &str, response: Option<&str>) -> BatchCallResult {
+        BatchCallResult {
+            index,
+            prompt: prompt.to_string(),
+            response: response.unwrap_or_default().to_string(),
+            tokens_used: if response.is_some() { 10 } else { 0 },
+            success: response.is_some(),
+            error: response.is_none().then(|| "Timeout".to_string()),
+            model_used: "test-model".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_retry_failed_keeps_original_config() {
+        let config = BatchRequestConfig {
+            model: "mistral".to_string(),
+            temperature: 0.2,
+            max_tokens: 256,
+        };
+        let response = BatchLLMResponse {
+            results: vec![
+                call_result(0, "Q0", Some("A0")),
+                call_result(1, "Q1", None),
+                call_result(2, "Q2", Some("A2")),
+                call_result(3, "Q3", None),
+            ],
+            total_tokens: 20,
+            duration_ms: 500,
+            all_succeeded: false,
+            original_config: config.clone(),
+        };
+
+        let retry = response.retry_failed();
+        assert_eq!(retry.prompts, vec!["Q1", "Q3"]);
+        assert_eq!(BatchRequestConfig::from(&retry), config);
+        assert!(retry.idempotency_keys.is_empty());
+    }
+
+    #[test]
+    fn test_merge_replaces_failed_results() {
+        let original = BatchLLMResponse {
+            results: vec![
+                call_result(0, "Q0", Some("A0")),
+                call_result(1, "Q1", None),
+                call_result(2, "Q2", Some("A2")),
+                call_result(3, "Q3", None),
+                call_result(4, "Q4", None),
+            ],
+            total_tokens: 20,
+            duration_ms: 500,
+            all_succeeded: false,
+            original_config: BatchRequestConfig::default(),
+        };
+        // Retry of Q1, Q3 and Q4: Q3 fails again
+        let retry = BatchLLMResponse {
+            results: vec![
+                call_result(0, "Q1", Some("A1")),
+                call_result(1, "Q3", None),
+                call_result(2, "Q4", Some("A4")),
+            ],
+            total_tokens: 20,
+            duration_ms: 300,
+            all_succeeded: false,
+            original_config: BatchRequestConfig::default(),
+        };
+
+        let merged = BatchLLMResponse::merge(&original, &retry);
+
+        let indices: Vec<_> = merged.results.iter().map(|r| r.index).collect();
+        assert_eq!(indices, vec![0, 1, 2, 3, 4]);
+        let responses: Vec<_> = merged.results.iter().map(|r| r.response.as_str()).collect();
+        assert_eq!(responses, vec!["A0", "A1", "A2", "", "A4"]);
+        assert_eq!(merged.get_response(4).unwrap().prompt, "Q4");
+        assert_eq!(merged.failed_responses().len(), 1);
+        assert_eq!(merged.failed_responses()[0].index, 3);
+        assert!(!merged.all_succeeded);
+        assert_eq!(merged.total_tokens, 40);
+        assert_eq!(merged.duration_ms, 800);
+
+        // A second retry that succeeds completes the batch
+        let second = BatchLLMResponse {
+            results: vec![call_result(0, "Q3", Some("A3"))],
+            total_tokens: 10,
+            duration_ms: 100,
+            all_succeeded: true,
+            original_config: BatchRequestConfig::default(),
+        };
+        assert_eq!(merged.retry_failed().prompts, vec!["Q3"]);
+        let complete = BatchLLMResponse::merge(&merged, &second);
+        assert!(complete.all_succeeded);
+        assert_eq!(complete.get_response(3).unwrap().response, "A3");
+    }
+
     #[test]
     fn test_batch_executor_creation() {
         let executor = BatchExecutor::new();
@@ -501,4 +1130,662 @@ mod tests {
             std::mem::size_of_val(&executor_default)
         );
     }
+
+    #[test]
+    fn test_idempotency_key_lookup() {
+        let request = BatchLLMRequest {
+            prompts: vec!["Q0".to_string(), "Q1".to_string(), "Q2".to_string()],
+            model: "test-model".to_string(),
+            temperature: 0.7,
+            max_tokens: 100,
+            idempotency_keys: vec![Some("k0".to_string()), None],
+            priorities: Vec::new(),
+        };
+
+        assert_eq!(request.idempotency_key(0), Some("k0"));
+        assert_eq!(request.idempotency_key(1), None);
+        assert_eq!(request.idempotency_key(2), None);
+    }
+
+    fn headers(pairs: &[(&'static str, &str)]) -> reqwest::header::HeaderMap {
+        let mut map = reqwest::header::HeaderMap::new();
+        for (name, value) in pairs {
+            map.insert(*name, value.parse().unwrap());
+        }
+        map
+    }
+
+    #[test]
+    fn test_rate_limit_delay_parsing() {
+        assert_eq!(rate_limit_delay(&headers(&[])), None);
+        assert_eq!(
+            rate_limit_delay(&headers(&[("Retry-After", "2")])),
+            Some(Duration::from_secs(2))
+        );
+        assert_eq!(
+            rate_limit_delay(&headers(&[("X-RateLimit-Reset", "3")])),
+            Some(Duration::from_secs(3))
+        );
+        // Retry-After wins over X-RateLimit-Reset
+        assert_eq!(
+            rate_limit_delay(&headers(&[("Retry-After", "1"), ("X-RateLimit-Reset", "5")])),
+            Some(Duration::from_secs(1))
+        );
+        assert_eq!(
+            rate_limit_delay(&headers(&[("Retry-After", "86400")])),
+            Some(MAX_RATE_LIMIT_DELAY)
+        );
+        assert_eq!(rate_limit_delay(&headers(&[("Retry-After", "soon")])), None);
+    }
+
+    #[test]
+    fn test_rate_limit_reset_as_timestamp() {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let reset = (now + 10).to_string();
+
+        let delay = rate_limit_delay(&headers(&[("X-RateLimit-Reset", &reset)])).unwrap();
+        assert!(delay > Duration::from_secs(8) && delay <= Duration::from_secs(10));
+
+        let past = (now - 10).to_string();
+        assert_eq!(
+            rate_limit_delay(&headers(&[("X-RateLimit-Reset", &past)])),
+            Some(Duration::ZERO)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_retry_honors_retry_after_header() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/generate"))
+            .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "2"))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/generate"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "response": "ok" })))
+            .mount(&server)
+            .await;
+
+        let executor = BatchExecutor::new().with_endpoint(format!("{}/api/generate", server.uri()));
+        let request = BatchLLMRequest {
+            prompts: vec!["Q0".to_string()],
+            model: "test-model".to_string(),
+            temperature: 0.7,
+            max_tokens: 100,
+            idempotency_keys: Vec::new(),
+            priorities: Vec::new(),
+        };
+
+        let start = Instant::now();
+        let response = executor.execute(request, Duration::from_secs(10)).await.unwrap();
+
+        assert!(response.all_succeeded);
+        assert!(start.elapsed() >= Duration::from_secs(2), "retry did not wait for Retry-After");
+    }
+
+    #[tokio::test]
+    async fn test_permanent_errors_are_not_retried() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/generate"))
+            .respond_with(ResponseTemplate::new(400).set_body_string("unknown model"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let executor = BatchExecutor::new().with_endpoint(format!("{}/api/generate", server.uri()));
+        let result = executor
+            .execute_single_prompt("Q0", "missing-model", 0.7, 100, None)
+            .await;
+
+        match result {
+            Err(FederationError::HttpError { status, message }) => {
+                assert_eq!(status, 400);
+                assert_eq!(message, "unknown model");
+            }
+            other => panic!("expected HttpError, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_transient_errors_are_retried() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/generate"))
+            .respond_with(ResponseTemplate::new(503))
+            .expect(3)
+            .mount(&server)
+            .await;
+
+        let executor = BatchExecutor::new().with_endpoint(format!("{}/api/generate", server.uri()));
+        let result = executor
+            .execute_single_prompt("Q0", "test-model", 0.7, 100, None)
+            .await;
+
+        assert!(matches!(result, Err(ref e) if e.is_transient()));
+    }
+
+    #[tokio::test]
+    async fn test_failed_prompts_fall_back_to_the_cheaper_model() {
+        use wiremock::matchers::{body_partial_json, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        for prompt in ["Q0", "Q2"] {
+            Mock::given(method("POST"))
+                .and(path("/api/generate"))
+                .and(body_partial_json(serde_json::json!({"model": "primary", "prompt": prompt})))
+                .respond_with(ResponseTemplate::new(400).set_body_string("model rejected the prompt"))
+                .with_priority(1)
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+        for (model, answer) in [("primary", "primary answer"), ("fallback", "fallback answer here")] {
+            Mock::given(method("POST"))
+                .and(path("/api/generate"))
+                .and(body_partial_json(serde_json::json!({"model": model})))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({"response": answer})))
+                .expect(2)
+                .mount(&server)
+                .await;
+        }
+
+        let executor = BatchExecutor::new().with_endpoint(format!("{}/api/generate", server.uri()));
+        let request = BatchLLMRequest {
+            prompts: (0..4).map(|i| format!("Q{}", i)).collect(),
+            model: "primary".to_string(),
+            temperature: 0.7,
+            max_tokens: 100,
+            idempotency_keys: Vec::new(),
+            priorities: Vec::new(),
+        };
+
+        let response = executor
+            .execute_with_fallback_model(request, "fallback".to_string(), Duration::from_secs(5))
+            .await
+            .unwrap();
+
+        assert!(response.all_succeeded);
+        assert_eq!(response.fallback_used_count(), 2);
+        for result in &response.results {
+            let (model, answer) = if result.index % 2 == 0 {
+                ("fallback", "fallback answer here")
+            } else {
+                ("primary", "primary answer")
+            };
+            assert_eq!(result.model_used, model);
+            assert_eq!(result.response, answer);
+            assert_eq!(result.prompt, format!("Q{}", result.index));
+        }
+        let expected_tokens = 2 * executor.count_tokens("primary answer")
+            + 2 * executor.count_tokens("fallback answer here");
+        assert_eq!(response.total_tokens, expected_tokens);
+        assert_eq!(response.original_config.model, "primary");
+    }
+
+    #[tokio::test]
+    async fn test_idempotency_cache_evicts_oldest_entries() {
+        use wiremock::matchers::{header, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        // key-0 is evicted by key-2, so it is sent again on the second run
+        for (key, calls) in [("key-0", 2), ("key-1", 1), ("key-2", 1)] {
+            Mock::given(method("POST"))
+                .and(path("/api/generate"))
+                .and(header("Idempotency-Key", key))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "response": key })))
+                .expect(calls)
+                .mount(&server)
+                .await;
+        }
+
+        let executor = BatchExecutor::with_concurrency(1)
+            .with_endpoint(format!("{}/api/generate", server.uri()))
+            .with_idempotency_cache_capacity(2);
+        let keyed = |keys: &[&str]| BatchLLMRequest {
+            prompts: keys.iter().map(|key| format!("Q {}", key)).collect(),
+            model: "test-model".to_string(),
+            temperature: 0.7,
+            max_tokens: 100,
+            idempotency_keys: keys.iter().map(|key| Some(key.to_string())).collect(),
+            priorities: Vec::new(),
+        };
+
+        let first = executor
+            .execute(keyed(&["key-0", "key-1", "key-2"]), Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert!(first.all_succeeded);
+        assert_eq!(executor.cached_result_count(), 2);
+
+        let second = executor
+            .execute(keyed(&["key-0", "key-2"]), Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert!(second.all_succeeded);
+        assert_eq!(executor.cached_result_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_retried_batch_reuses_completed_prompts() {
+        use wiremock::matchers::{header, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+
+        // First prompt succeeds once and must never be re-issued
+        Mock::given(method("POST"))
+            .and(path("/api/generate"))
+            .and(header("Idempotency-Key", "key-0"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "response": "A0" })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        // Second prompt fails through every retry of the first batch, then recovers
+        Mock::given(method("POST"))
+            .and(path("/api/generate"))
+            .and(header("Idempotency-Key", "key-1"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(3)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/generate"))
+            .and(header("Idempotency-Key", "key-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "response": "A1" })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let executor = BatchExecutor::new().with_endpoint(format!("{}/api/generate", server.uri()));
+        let request = BatchLLMRequest {
+            prompts: vec!["Q0".to_string(), "Q1".to_string()],
+            model: "test-model".to_string(),
+            temperature: 0.7,
+            max_tokens: 100,
+            idempotency_keys: vec![Some("key-0".to_string()), Some("key-1".to_string())],
+            priorities: Vec::new(),
+        };
+
+        let first = executor
+            .execute(request.clone(), Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert!(first.get_response(0).unwrap().success);
+        assert!(!first.get_response(1).unwrap().success);
+        assert_eq!(executor.cached_result_count(), 1);
+
+        let retried = executor
+            .execute(request, Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert!(retried.all_succeeded);
+        assert_eq!(retried.get_response(0).unwrap().response, "A0");
+        assert_eq!(retried.get_response(1).unwrap().response, "A1");
+        assert_eq!(executor.cached_result_count(), 2);
+
+        // Mock expectations (one call per key) are verified when the server drops
+    }
+
+    #[tokio::test]
+    async fn test_rate_limiter_spaces_calls_to_configured_rpm() {
+        use crate::rate_limiter::ModelRateLimit;
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/generate"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "response": "ok" })))
+            .expect(3)
+            .mount(&server)
+            .await;
+
+        // 120 requests per minute, one at a time: one request every 500 ms
+        let limiter = Arc::new(RateLimiter::new().with_limit(
+            "test-model",
+            ModelRateLimit::new().with_requests_per_minute(120).with_request_burst(1),
+        ));
+        let executor = BatchExecutor::new()
+            .with_endpoint(format!("{}/api/generate", server.uri()))
+            .with_rate_limiter(limiter);
+        let request = BatchLLMRequest {
+            prompts: vec!["Q0".to_string(), "Q1".to_string(), "Q2".to_string()],
+            model: "test-model".to_string(),
+            temperature: 0.7,
+            max_tokens: 100,
+            idempotency_keys: Vec::new(),
+            priorities: Vec::new(),
+        };
+
+        let start = Instant::now();
+        let response = executor.execute(request, Duration::from_secs(5)).await.unwrap();
+
+        assert!(response.all_succeeded);
+        assert!(start.elapsed() >= Duration::from_millis(1000), "calls were not spaced");
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited_call_fails_at_its_deadline() {
+        use crate::rate_limiter::ModelRateLimit;
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/generate"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "response": "ok" })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let limiter = Arc::new(
+            RateLimiter::new().with_limit("test-model", ModelRateLimit::new().with_requests_per_minute(1)),
+        );
+        let executor = BatchExecutor::new()
+            .with_endpoint(format!("{}/api/generate", server.uri()))
+            .with_rate_limiter(limiter);
+        let request = BatchLLMRequest {
+            prompts: vec!["Q0".to_string(), "Q1".to_string()],
+            model: "test-model".to_string(),
+            temperature: 0.7,
+            max_tokens: 100,
+            idempotency_keys: Vec::new(),
+            priorities: Vec::new(),
+        };
+
+        let start = Instant::now();
+        let response = executor.execute(request, Duration::from_secs(1)).await.unwrap();
+
+        assert!(response.get_response(0).unwrap().success);
+        let limited = response.get_response(1).unwrap();
+        assert!(!limited.success);
+        assert!(limited.error.as_deref().unwrap().contains("Rate limit"));
+        assert!(start.elapsed() < Duration::from_secs(1), "did not fail fast");
+    }
+
+    #[test]
+    fn test_priority_controller_boost_only_raises() {
+        let controller = PriorityController::new();
+        controller.boost(3, 5);
+        controller.boost(3, 2);
+        controller.boost(1, 7);
+
+        assert_eq!(controller.priority(3), 5);
+        assert_eq!(controller.priority(0), DEFAULT_PRIORITY);
+        assert_eq!(controller.current_priorities(), HashMap::from([(3, 5), (1, 7)]));
+
+        let mut pending = vec![0, 1, 2, 3];
+        let priority = |index| controller.priority(index);
+        assert_eq!(take_highest_priority(&mut pending, priority), Some(1));
+        assert_eq!(take_highest_priority(&mut pending, priority), Some(3));
+        assert_eq!(take_highest_priority(&mut pending, priority), Some(0));
+        assert_eq!(pending, vec![2]);
+    }
+
+    #[tokio::test]
+    async fn test_boosted_item_runs_before_earlier_items() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, Request, Respond, ResponseTemplate};
+
+        /// Boosts the last item when the server receives the first prompt,
+        /// so the boost always lands while that prompt is in flight
+        struct BoostOnFirstPrompt(Arc<PriorityController>);
+
+        impl Respond for BoostOnFirstPrompt {
+            fn respond(&self, request: &Request) -> ResponseTemplate {
+                let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+                if body["prompt"] == "Q0" {
+                    self.0.boost(4, 9);
+                }
+                ResponseTemplate::new(200).set_body_json(serde_json::json!({ "response": "ok" }))
+            }
+        }
+
+        let controller = Arc::new(PriorityController::new());
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/generate"))
+            .respond_with(BoostOnFirstPrompt(Arc::clone(&controller)))
+            .mount(&server)
+            .await;
+
+        let executor = BatchExecutor::with_concurrency(1)
+            .with_endpoint(format!("{}/api/generate", server.uri()))
+            .with_priority_controller(Arc::clone(&controller));
+        let request = BatchLLMRequest {
+            prompts: (0..5).map(|i| format!("Q{}", i)).collect(),
+            model: "test-model".to_string(),
+            temperature: 0.7,
+            max_tokens: 100,
+            idempotency_keys: Vec::new(),
+            priorities: Vec::new(),
+        };
+
+        let response = executor.execute(request, Duration::from_secs(5)).await.unwrap();
+
+        assert!(response.all_succeeded);
+        let indices: Vec<usize> = response.results.iter().map(|r| r.index).collect();
+        assert_eq!(indices, vec![0, 1, 2, 3, 4]);
+
+        let order: Vec<String> = server
+            .received_requests()
+            .await
+            .unwrap()
+            .iter()
+            .map(|r| {
+                let body: serde_json::Value = serde_json::from_slice(&r.body).unwrap();
+                body["prompt"].as_str().unwrap().to_string()
+            })
+            .collect();
+        assert_eq!(order, vec!["Q0", "Q4", "Q1", "Q2", "Q3"]);
+        assert_eq!(controller.current_priorities(), HashMap::from([(4, 9)]));
+    }
+
+    #[tokio::test]
+    async fn test_results_are_index_ordered_when_completed_in_reverse() {
+        use wiremock::matchers::{body_partial_json, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        for i in 0..5 {
+            Mock::given(method("POST"))
+                .and(path("/api/generate"))
+                .and(body_partial_json(serde_json::json!({ "prompt": format!("Q{}", i) })))
+                .respond_with(
+                    ResponseTemplate::new(200).set_body_json(serde_json::json!({ "response": format!("A{}", i) })),
+                )
+                .mount(&server)
+                .await;
+        }
+
+        // Later prompts get higher priorities, so the batch completes in reverse
+        let controller = Arc::new(PriorityController::new());
+        for i in 0..5 {
+            controller.boost(i, 1 + i as u8);
+        }
+        let executor = BatchExecutor::with_concurrency(1)
+            .with_endpoint(format!("{}/api/generate", server.uri()))
+            .with_priority_controller(controller);
+        let request = BatchLLMRequest {
+            prompts: (0..5).map(|i| format!("Q{}", i)).collect(),
+            model: "test-model".to_string(),
+            temperature: 0.7,
+            max_tokens: 100,
+            idempotency_keys: Vec::new(),
+            priorities: Vec::new(),
+        };
+
+        let response = executor.execute(request, Duration::from_secs(5)).await.unwrap();
+
+        let order: Vec<String> = server
+            .received_requests()
+            .await
+            .unwrap()
+            .iter()
+            .map(|r| {
+                let body: serde_json::Value = serde_json::from_slice(&r.body).unwrap();
+                body["prompt"].as_str().unwrap().to_string()
+            })
+            .collect();
+        assert_eq!(order, vec!["Q4", "Q3", "Q2", "Q1", "Q0"]);
+        for (i, result) in response.results.iter().enumerate() {
+            assert_eq!(result.index, i);
+            assert_eq!(result.response, format!("A{}", i));
+            assert_eq!(response.get_response(i).unwrap().index, i);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_higher_priority_prompts_run_first() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/generate"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "response": "ok" })))
+            .mount(&server)
+            .await;
+
+        let executor = BatchExecutor::with_concurrency(1).with_endpoint(format!("{}/api/generate", server.uri()));
+        let request = BatchLLMRequest {
+            prompts: (0..5).map(|i| format!("Q{}", i)).collect(),
+            model: "test-model".to_string(),
+            temperature: 0.7,
+            max_tokens: 100,
+            idempotency_keys: Vec::new(),
+            // Q3 and Q4 fall back to the default priority
+            priorities: vec![1, 7, 3, DEFAULT_PRIORITY],
+        };
+
+        let response = executor.execute(request.clone(), Duration::from_secs(5)).await.unwrap();
+        assert!(response.all_succeeded);
+        let indices: Vec<usize> = response.results.iter().map(|r| r.index).collect();
+        assert_eq!(indices, vec![0, 1, 2, 3, 4]);
+
+        let rate_limited = executor
+            .execute_rate_limited(request, Duration::from_secs(5), 1000)
+            .await
+            .unwrap();
+        assert!(rate_limited.all_succeeded);
+
+        let order: Vec<String> = server
+            .received_requests()
+            .await
+            .unwrap()
+            .iter()
+            .map(|r| {
+                let body: serde_json::Value = serde_json::from_slice(&r.body).unwrap();
+                body["prompt"].as_str().unwrap().to_string()
+            })
+            .collect();
+        let expected = ["Q1", "Q2", "Q0", "Q3", "Q4"];
+        assert_eq!(order[..5], expected);
+        assert_eq!(order[5..], expected);
+    }
+
+    #[tokio::test]
+    async fn test_prompt_over_context_window_is_not_sent() {
+        use crate::model_registry::ModelSpec;
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/generate"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "response": "ok" })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let registry = Arc::new(ModelRegistry::new());
+        registry.register(ModelSpec::new("small-model", 150));
+        let executor = BatchExecutor::new()
+            .with_endpoint(format!("{}/api/generate", server.uri()))
+            .with_model_registry(registry);
+        let request = BatchLLMRequest {
+            prompts: vec!["short".to_string(), "word ".repeat(400)],
+            model: "small-model".to_string(),
+            temperature: 0.7,
+            max_tokens: 100,
+            idempotency_keys: Vec::new(),
+            priorities: Vec::new(),
+        };
+
+        let response = executor.execute(request, Duration::from_secs(5)).await.unwrap();
+
+        assert!(response.results[0].success);
+        assert!(!response.results[1].success);
+        assert!(response.results[1].error.as_deref().unwrap().contains("context window is 150"));
+    }
+
+    #[tokio::test]
+    async fn test_context_window_not_checked_without_registry() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/generate"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "response": "ok" })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        // Larger than the built-in codellama window, which a local server may not use
+        let executor = BatchExecutor::new().with_endpoint(format!("{}/api/generate", server.uri()));
+        let request = BatchLLMRequest {
+            prompts: vec!["word ".repeat(20_000)],
+            model: "codellama".to_string(),
+            temperature: 0.7,
+            max_tokens: 100,
+            idempotency_keys: Vec::new(),
+            priorities: Vec::new(),
+        };
+
+        let response = executor.execute(request, Duration::from_secs(5)).await.unwrap();
+        assert!(response.results[0].success);
+    }
+
+    #[test]
+    fn test_estimate_cost_uses_registered_prices() {
+        use crate::model_registry::ModelSpec;
+
+        let registry = Arc::new(ModelRegistry::new());
+        registry.register(ModelSpec::new("priced-model", 8_000).with_cost(1.0, 2.0));
+        let executor = BatchExecutor::new().with_model_registry(registry);
+        let request = BatchLLMRequest {
+            prompts: vec!["one".to_string(), "two".to_string()],
+            model: "priced-model".to_string(),
+            temperature: 0.7,
+            max_tokens: 500,
+            idempotency_keys: Vec::new(),
+            priorities: Vec::new(),
+        };
+
+        let tokens_in = executor.count_tokens("one") + executor.count_tokens("two");
+        let expected = (tokens_in as f64 + 2.0 * 1000.0) / 1000.0;
+        assert!((executor.estimate_cost(&request).unwrap() - expected).abs() < 1e-9);
+
+        let unknown = BatchLLMRequest { model: "unknown-model".to_string(), ..request };
+        assert_eq!(executor.estimate_cost(&unknown), None);
+    }
 }
@@ -1,3 +1,8 @@
+use crate::batch_executor::{BatchCallResult, BatchExecutor, BatchLLMRequest, BatchLLMResponse};
+use crate::FederationError;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt;
+use std::sync::{Arc, Mutex, RwLock};
 use std::time::Duration;
 use serde::{Deserialize, Serialize};
 
@@ -33,6 +38,18 @@ pub struct BatchSchedulerConfig {
     pub max_retries: usize,
     /// Timeout per individual request
     pub request_timeout: Duration,
+    /// Maximum number of requests waiting in the queue
+    #[serde(default = "default_max_queue_size")]
+    pub max_queue_size: usize,
+    /// Custom retry rules, checked before the built-in ones
+    ///
+    /// See [`RetryMatcher`]. Not serialized.
+    #[serde(skip)]
+    pub retry_matchers: Vec<RetryMatcher>,
+}
+
+fn default_max_queue_size() -> usize {
+    1000
 }
 
 impl Default for BatchSchedulerConfig {
@@ -43,7 +60,154 @@ impl Default for BatchSchedulerConfig {
             retry_backoff_ms: 100,
             max_retries: 3,
             request_timeout: Duration::from_secs(30),
+            max_queue_size: default_max_queue_size(),
+            retry_matchers: Vec::new(),
+        }
+    }
+}
+
+impl BatchSchedulerConfig {
+    /// Adds a custom retry rule after the ones already configured
+    pub fn with_retry_matcher(mut self, matcher: RetryMatcher) -> Self {
+        self.retry_matchers.push(matcher);
+        self
+    }
+}
+
+/// Custom rule for [`BatchScheduler::should_retry`]
+///
+/// Matchers are checked in order and the first one matching the error
+/// decides whether it is retried. Errors no matcher recognizes fall back to
+/// the built-in rules (rate limits, timeouts, unavailable services).
+///
+/// # Example
+///
+/// ```
+/// use kowalski_federation::batch_scheduler::{BatchScheduler, BatchSchedulerConfig, RetryMatcher};
+///
+/// let config = BatchSchedulerConfig::default()
+///     .with_retry_matcher(RetryMatcher::non_retryable(|error| error.contains("quota")))
+///     .with_retry_matcher(RetryMatcher::retryable_containing("overloaded"));
+/// let scheduler = BatchScheduler::new(config);
+///
+/// assert!(scheduler.should_retry(0, "Model is Overloaded, try again"));
+/// assert!(!scheduler.should_retry(0, "429: monthly quota exceeded"));
+/// ```
+#[derive(Clone)]
+pub struct RetryMatcher {
+    retryable: bool,
+    matches: Arc<dyn Fn(&str) -> bool + Send + Sync>,
+}
+
+impl RetryMatcher {
+    /// Retries errors for which `matches` returns true
+    pub fn retryable(matches: impl Fn(&str) -> bool + Send + Sync + 'static) -> Self {
+        Self {
+            retryable: true,
+            matches: Arc::new(matches),
+        }
+    }
+
+    /// Never retries errors for which `matches` returns true
+    pub fn non_retryable(matches: impl Fn(&str) -> bool + Send + Sync + 'static) -> Self {
+        Self {
+            retryable: false,
+            matches: Arc::new(matches),
+        }
+    }
+
+    /// Retries errors containing `pattern`, ignoring case
+    pub fn retryable_containing(pattern: impl Into<String>) -> Self {
+        let pattern = pattern.into().to_lowercase();
+        Self::retryable(move |error| error.to_lowercase().contains(&pattern))
+    }
+
+    /// Never retries errors containing `pattern`, ignoring case
+    pub fn non_retryable_containing(pattern: impl Into<String>) -> Self {
+        let pattern = pattern.into().to_lowercase();
+        Self::non_retryable(move |error| error.to_lowercase().contains(&pattern))
+    }
+
+    /// Whether matching errors are retried
+    pub fn is_retryable(&self) -> bool {
+        self.retryable
+    }
+
+    /// The decision for `error`, or `None` if this matcher does not apply
+    fn classify(&self, error: &str) -> Option<bool> {
+        (self.matches)(error).then_some(self.retryable)
+    }
+}
+
+impl fmt::Debug for RetryMatcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryMatcher")
+            .field("retryable", &self.retryable)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Per-model usage statistics collected by the scheduler
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModelUsageStats {
+    /// Model name
+    pub model: String,
+    /// Total number of requests sent to this model
+    pub total_requests: u64,
+    /// Total tokens consumed by this model
+    pub total_tokens: u64,
+    /// Number of failed requests
+    pub failed_requests: u64,
+    /// Running average latency per request in milliseconds
+    pub avg_latency_ms: f64,
+}
+
+impl ModelUsageStats {
+    /// Creates empty statistics for a model
+    pub fn new(model: String) -> Self {
+        Self {
+            model,
+            ..Default::default()
+        }
+    }
+
+    /// Records a single call result with its latency
+    fn record(&mut self, result: &BatchCallResult, latency_ms: u64) {
+        self.total_requests += 1;
+        self.total_tokens += result.tokens_used as u64;
+        if !result.success {
+            self.failed_requests += 1;
+        }
+        self.avg_latency_ms +=
+            (latency_ms as f64 - self.avg_latency_ms) / self.total_requests as f64;
+    }
+}
+
+/// Priority used by [`BatchScheduler::submit`] and [`BatchScheduler::submit_many`]
+pub const DEFAULT_PRIORITY: u8 = 0;
+
+/// Pending requests, one FIFO lane per priority
+#[derive(Debug, Default)]
+struct BatchQueue {
+    lanes: BTreeMap<u8, VecDeque<BatchLLMRequest>>,
+    len: usize,
+}
+
+impl BatchQueue {
+    fn push(&mut self, request: BatchLLMRequest, priority: u8) {
+        self.lanes.entry(priority).or_default().push_back(request);
+        self.len += 1;
+    }
+
+    /// Pops the oldest request from the highest-priority lane
+    fn pop(&mut self) -> Option<BatchLLMRequest> {
+        let mut lane = self.lanes.last_entry()?;
+        let request = lane.get_mut().pop_front();
+        if lane.get().is_empty() {
+            lane.remove();
         }
+        self.len -= 1;
+        request
     }
 }
 
@@ -55,6 +219,8 @@ impl Default for BatchSchedulerConfig {
 /// - Retry logic with exponential backoff
 /// - Timeout management
 /// - Resource pooling
+/// - Per-model token usage tracking
+/// - A bounded request queue with priority lanes
 ///
 /// # Example
 ///
@@ -68,6 +234,8 @@ impl Default for BatchSchedulerConfig {
 ///     retry_backoff_ms: 100,
 ///     max_retries: 3,
 ///     request_timeout: Duration::from_secs(30),
+///     max_queue_size: 100,
+///     retry_matchers: Vec::new(),
 /// };
 ///
 /// let scheduler = BatchScheduler::new(config);
@@ -75,12 +243,18 @@ impl Default for BatchSchedulerConfig {
 /// ```
 pub struct BatchScheduler {
     config: BatchSchedulerConfig,
+    model_stats: Mutex<HashMap<String, ModelUsageStats>>,
+    queue: RwLock<BatchQueue>,
 }
 
 impl BatchScheduler {
     /// Creates a new batch scheduler with the given configuration
     pub fn new(config: BatchSchedulerConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            model_stats: Mutex::new(HashMap::new()),
+            queue: RwLock::new(BatchQueue::default()),
+        }
     }
 
     /// Creates a scheduler with default configuration
@@ -107,17 +281,157 @@ impl BatchScheduler {
     }
 
     /// Determines if a request should be retried based on error
+    ///
+    /// The configured [`RetryMatcher`]s are checked first; errors none of
+    /// them match are retried if they look transient.
     pub fn should_retry(&self, attempt: usize, error: &str) -> bool {
         if attempt >= self.config.max_retries {
             return false;
         }
 
+        if let Some(retry) = self
+            .config
+            .retry_matchers
+            .iter()
+            .find_map(|matcher| matcher.classify(error))
+        {
+            return retry;
+        }
+
         // Retry on specific errors
         error.contains("429") // Rate limit
             || error.contains("timeout")
             || error.contains("temporarily unavailable")
             || error.contains("service unavailable")
     }
+
+    /// Queues a request at the default priority
+    pub fn submit(&self, request: BatchLLMRequest) -> Result<(), FederationError> {
+        self.submit_with_priority(request, DEFAULT_PRIORITY)
+    }
+
+    /// Queues a request in the lane for `priority` (higher runs first)
+    pub fn submit_with_priority(
+        &self,
+        request: BatchLLMRequest,
+        priority: u8,
+    ) -> Result<(), FederationError> {
+        self.submit_many_with_priority(vec![(request, priority)])
+            .map(|_| ())
+    }
+
+    /// Queues several requests at the default priority as one atomic insertion
+    ///
+    /// See [`submit_many_with_priority`](Self::submit_many_with_priority).
+    pub fn submit_many(&self, requests: Vec<BatchLLMRequest>) -> Result<usize, FederationError> {
+        self.submit_many_with_priority(
+            requests
+                .into_iter()
+                .map(|request| (request, DEFAULT_PRIORITY))
+                .collect(),
+        )
+    }
+
+    /// Queues several requests with priorities as one atomic insertion
+    ///
+    /// The queue lock is held for the whole insertion, so the items are not
+    /// interleaved with concurrent submissions. Either every item is queued
+    /// or none is.
+    ///
+    /// # Returns
+    /// - `Ok(n)` with the number of requests queued
+    /// - `Err(FederationError::QueueFull)` with how many would have fit
+    pub fn submit_many_with_priority(
+        &self,
+        items: Vec<(BatchLLMRequest, u8)>,
+    ) -> Result<usize, FederationError> {
+        let mut queue = self.queue.write().unwrap();
+        let available = self.config.max_queue_size.saturating_sub(queue.len);
+        if items.len() > available {
+            return Err(FederationError::QueueFull {
+                requested: items.len(),
+                available,
+            });
+        }
+
+        let count = items.len();
+        for (request, priority) in items {
+            queue.push(request, priority);
+        }
+        Ok(count)
+    }
+
+    /// Removes the next request to run: highest priority first, FIFO within a priority
+    pub fn next_request(&self) -> Option<BatchLLMRequest> {
+        self.queue.write().unwrap().pop()
+    }
+
+    /// Number of requests waiting in the queue
+    pub fn queue_len(&self) -> usize {
+        self.queue.read().unwrap().len
+    }
+
+    /// Runs a batch through the executor and records per-model usage
+    ///
+    /// Uses the configured `request_timeout` for each call.
+    pub async fn run_batch(
+        &self,
+        executor: &BatchExecutor,
+        request: BatchLLMRequest,
+    ) -> Result<BatchLLMResponse, FederationError> {
+        let model = request.model.clone();
+        let response = executor.execute(request, self.config.request_timeout).await?;
+        self.record_response(&model, &response);
+        Ok(response)
+    }
+
+    /// Records usage for every call result in a batch response
+    ///
+    /// The batch duration is spread evenly across its results, since
+    /// individual call latencies are not reported by the executor.
+    pub fn record_response(&self, model: &str, response: &BatchLLMResponse) {
+        if response.results.is_empty() {
+            return;
+        }
+
+        let per_call_ms = response.duration_ms / response.results.len() as u64;
+        for result in &response.results {
+            self.record_call(model, result, per_call_ms);
+        }
+    }
+
+    /// Records usage for a single call result
+    pub fn record_call(&self, model: &str, result: &BatchCallResult, latency_ms: u64) {
+        let mut stats = self.model_stats.lock().unwrap();
+        stats
+            .entry(model.to_string())
+            .or_insert_with(|| ModelUsageStats::new(model.to_string()))
+            .record(result, latency_ms);
+    }
+
+    /// Returns a snapshot of usage statistics keyed by model name
+    pub fn model_stats(&self) -> HashMap<String, ModelUsageStats> {
+        self.model_stats.lock().unwrap().clone()
+    }
+
+    /// Clears all per-model usage statistics
+    pub fn reset_model_stats(&self) {
+        self.model_stats.lock().unwrap().clear();
+    }
+
+    /// Returns the model that has consumed the most tokens
+    pub fn top_model_by_tokens(&self) -> Option<String> {
+        self.model_stats
+            .lock()
+            .unwrap()
+            .values()
+            .max_by(|a, b| {
+                a.total_tokens
+                    .cmp(&b.total_tokens)
+                    .then_with(|| b.model.cmp(&a.model))
+            })
+            .map(|stats| stats.model.clone())
+    }
 }
 
 impl Default for BatchScheduler {
@@ -167,6 +481,27 @@ mod tests {
         assert!(!scheduler.should_retry(3, "Some error"));
     }
 
+    #[test]
+    fn test_custom_retry_matchers_take_precedence() {
+        let config = BatchSchedulerConfig::default()
+            .with_retry_matcher(RetryMatcher::non_retryable(|error| error.starts_with("400")))
+            .with_retry_matcher(RetryMatcher::retryable_containing("OVERLOADED"))
+            .with_retry_matcher(RetryMatcher::non_retryable_containing("timeout"));
+        let scheduler = BatchScheduler::new(config);
+
+        // The provider's own wording becomes retryable
+        assert!(scheduler.should_retry(0, "Model is overloaded, please retry"));
+        // A built-in retryable error can be turned off
+        assert!(!scheduler.should_retry(0, "Request timeout"));
+        // The first matching rule wins
+        assert!(!scheduler.should_retry(0, "400 Bad Request: overloaded prompt"));
+        // Unmatched errors keep the built-in behavior
+        assert!(scheduler.should_retry(0, "429 Rate limit exceeded"));
+        assert!(!scheduler.should_retry(0, "Invalid API key"));
+        // Custom rules do not lift the retry limit
+        assert!(!scheduler.should_retry(3, "Model is overloaded"));
+    }
+
     #[test]
     fn test_max_retries_boundary() {
         let config = BatchSchedulerConfig {
@@ -191,4 +526,196 @@ mod tests {
         assert_ne!(parallel, sequential);
         assert_ne!(grouped, adaptive);
     }
+
+    fn call_result(index: usize, tokens_used: usize, success: bool) -> BatchCallResult {
+        BatchCallResult {
+            index,
+            prompt: format!("Q{}", index),
+            response: if success { format!("A{}", index) } else { String::new() },
+            tokens_used,
+            success,
+            error: if success { None } else { Some("Timeout".to_string()) },
+            model_used: "test-model".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_model_stats_per_model() {
+        let scheduler = BatchScheduler::with_defaults();
+
+        let llama = BatchLLMResponse {
+            results: vec![call_result(0, 100, true), call_result(1, 50, true)],
+            total_tokens: 150,
+            duration_ms: 400,
+            all_succeeded: true,
+            original_config: Default::default(),
+        };
+        let mistral = BatchLLMResponse {
+            results: vec![call_result(0, 30, true), call_result(1, 0, false)],
+            total_tokens: 30,
+            duration_ms: 100,
+            all_succeeded: false,
+            original_config: Default::default(),
+        };
+
+        scheduler.record_response("llama3.2", &llama);
+        scheduler.record_response("mistral", &mistral);
+
+        let stats = scheduler.model_stats();
+        assert_eq!(stats.len(), 2);
+
+        let llama_stats = &stats["llama3.2"];
+        assert_eq!(llama_stats.total_requests, 2);
+        assert_eq!(llama_stats.total_tokens, 150);
+        assert_eq!(llama_stats.failed_requests, 0);
+        assert_eq!(llama_stats.avg_latency_ms, 200.0);
+
+        let mistral_stats = &stats["mistral"];
+        assert_eq!(mistral_stats.total_requests, 2);
+        assert_eq!(mistral_stats.total_tokens, 30);
+        assert_eq!(mistral_stats.failed_requests, 1);
+        assert_eq!(mistral_stats.avg_latency_ms, 50.0);
+
+        let total_tokens: u64 = stats.values().map(|s| s.total_tokens).sum();
+        assert_eq!(total_tokens, (llama.total_tokens + mistral.total_tokens) as u64);
+        assert_eq!(scheduler.top_model_by_tokens(), Some("llama3.2".to_string()));
+    }
+
+    #[test]
+    fn test_model_stats_running_average() {
+        let scheduler = BatchScheduler::with_defaults();
+
+        scheduler.record_call("llama3.2", &call_result(0, 10, true), 100);
+        scheduler.record_call("llama3.2", &call_result(1, 10, true), 300);
+
+        let stats = scheduler.model_stats();
+        assert_eq!(stats["llama3.2"].avg_latency_ms, 200.0);
+    }
+
+    #[test]
+    fn test_reset_model_stats() {
+        let scheduler = BatchScheduler::with_defaults();
+        assert_eq!(scheduler.top_model_by_tokens(), None);
+
+        scheduler.record_call("llama3.2", &call_result(0, 10, true), 100);
+        assert_eq!(scheduler.model_stats().len(), 1);
+
+        scheduler.reset_model_stats();
+        assert!(scheduler.model_stats().is_empty());
+        assert_eq!(scheduler.top_model_by_tokens(), None);
+    }
+
+    #[tokio::test]
+    async fn test_run_batch_tracks_tokens_per_model() {
+        use wiremock::matchers::{body_partial_json, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let llama_answer = "The report covers three quarters of growth";
+        let mistral_answer = "Growth";
+        let server = MockServer::start().await;
+        for (model, answer) in [("llama3.2", llama_answer), ("mistral", mistral_answer)] {
+            Mock::given(method("POST"))
+                .and(path("/api/generate"))
+                .and(body_partial_json(serde_json::json!({ "model": model })))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "response": answer })))
+                .mount(&server)
+                .await;
+        }
+
+        let scheduler = BatchScheduler::with_defaults();
+        let executor = BatchExecutor::new().with_endpoint(format!("{}/api/generate", server.uri()));
+        let batch_for = |model: &str, prompts: usize| BatchLLMRequest {
+            prompts: (0..prompts).map(|i| format!("Summarize part {}", i)).collect(),
+            model: model.to_string(),
+            temperature: 0.7,
+            max_tokens: 100,
+            idempotency_keys: Vec::new(),
+            priorities: Vec::new(),
+        };
+
+        let llama = scheduler.run_batch(&executor, batch_for("llama3.2", 3)).await.unwrap();
+        let mistral = scheduler.run_batch(&executor, batch_for("mistral", 2)).await.unwrap();
+        assert!(llama.all_succeeded && mistral.all_succeeded);
+
+        let stats = scheduler.model_stats();
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats["llama3.2"].total_requests, 3);
+        assert_eq!(stats["llama3.2"].total_tokens, 3 * executor.count_tokens(llama_answer) as u64);
+        assert_eq!(stats["mistral"].total_requests, 2);
+        assert_eq!(stats["mistral"].total_tokens, 2 * executor.count_tokens(mistral_answer) as u64);
+        assert_eq!(scheduler.top_model_by_tokens(), Some("llama3.2".to_string()));
+    }
+
+    fn batch(id: &str) -> BatchLLMRequest {
+        BatchLLMRequest {
+            prompts: vec![format!("prompt for {}", id)],
+            model: id.to_string(),
+            temperature: 0.7,
+            max_tokens: 100,
+            idempotency_keys: Vec::new(),
+            priorities: Vec::new(),
+        }
+    }
+
+    fn scheduler_with_queue(max_queue_size: usize) -> BatchScheduler {
+        BatchScheduler::new(BatchSchedulerConfig {
+            max_queue_size,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_submit_many_fills_queue_exactly() {
+        let scheduler = scheduler_with_queue(3);
+
+        let queued = scheduler
+            .submit_many(vec![batch("a"), batch("b"), batch("c")])
+            .unwrap();
+        assert_eq!(queued, 3);
+        assert_eq!(scheduler.queue_len(), 3);
+
+        match scheduler.submit_many(vec![batch("d")]) {
+            Err(FederationError::QueueFull { requested, available }) => {
+                assert_eq!(requested, 1);
+                assert_eq!(available, 0);
+            }
+            other => panic!("expected QueueFull, got {:?}", other),
+        }
+        assert!(scheduler.submit(batch("d")).is_err());
+        assert_eq!(scheduler.queue_len(), 3);
+    }
+
+    #[test]
+    fn test_submit_many_is_all_or_nothing() {
+        let scheduler = scheduler_with_queue(3);
+        scheduler.submit(batch("a")).unwrap();
+
+        match scheduler.submit_many(vec![batch("b"), batch("c"), batch("d")]) {
+            Err(FederationError::QueueFull { requested, available }) => {
+                assert_eq!(requested, 3);
+                assert_eq!(available, 2);
+            }
+            other => panic!("expected QueueFull, got {:?}", other),
+        }
+        assert_eq!(scheduler.queue_len(), 1);
+    }
+
+    #[test]
+    fn test_submit_many_with_priority_order() {
+        let scheduler = scheduler_with_queue(10);
+        scheduler
+            .submit_many_with_priority(vec![
+                (batch("low-1"), 1),
+                (batch("high"), 9),
+                (batch("low-2"), 1),
+            ])
+            .unwrap();
+        scheduler.submit(batch("default")).unwrap();
+
+        let order: Vec<String> = std::iter::from_fn(|| scheduler.next_request())
+            .map(|request| request.model)
+            .collect();
+        assert_eq!(order, vec!["high", "low-1", "low-2", "default"]);
+        assert_eq!(scheduler.queue_len(), 0);
+    }
 }
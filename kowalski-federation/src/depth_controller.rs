@@ -1,6 +1,9 @@
+use crate::protocols::RLMTaskRequest;
 use crate::FederationError;
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::time::Duration;
+use tokio::time::Instant;
 
 /// Configuration for recursive depth control
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
@@ -38,6 +41,45 @@ impl DepthConfig {
     }
 }
 
+/// How requests are pared down for agents that should be simplified
+///
+/// See [`DepthController::should_simplify_agent`]. Simplified requests keep
+/// only the tools on the allow-list and have `max_tokens` capped.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SimplificationPolicy {
+    /// Tools light enough to keep at simplified depths
+    pub simple_tools: Vec<String>,
+    /// Cap on `max_tokens` at simplified depths
+    pub max_tokens: usize,
+}
+
+impl Default for SimplificationPolicy {
+    fn default() -> Self {
+        Self {
+            simple_tools: Vec::new(),
+            max_tokens: 512,
+        }
+    }
+}
+
+impl SimplificationPolicy {
+    /// Keeps `simple_tools` and caps responses at `max_tokens`
+    pub fn new(simple_tools: Vec<String>, max_tokens: usize) -> Self {
+        Self {
+            simple_tools,
+            max_tokens,
+        }
+    }
+
+    /// Drops tools not on the allow-list and caps `max_tokens`
+    pub fn simplify(&self, request: &mut RLMTaskRequest) {
+        request
+            .suggested_tools
+            .retain(|tool| self.simple_tools.contains(tool));
+        request.max_tokens = request.max_tokens.min(self.max_tokens);
+    }
+}
+
 /// Manages recursive depth for RLM workflows
 ///
 /// Prevents infinite recursion by tracking the current depth level
@@ -70,6 +112,13 @@ pub struct DepthController {
     config: DepthConfig,
     current_depth: usize,
     depth_stack: Vec<String>, // Track agent IDs at each level for debugging
+    /// When each level in `depth_stack` was entered
+    #[serde(skip)]
+    level_started: Vec<Instant>,
+    #[serde(default)]
+    level_timeout: Option<Duration>,
+    #[serde(default)]
+    simplification: SimplificationPolicy,
 }
 
 impl DepthController {
@@ -79,9 +128,27 @@ impl DepthController {
             config,
             current_depth: 0,
             depth_stack: Vec::new(),
+            level_started: Vec::new(),
+            level_timeout: None,
+            simplification: SimplificationPolicy::default(),
         }
     }
 
+    /// Sets how requests are simplified at deeper levels
+    pub fn with_simplification(mut self, policy: SimplificationPolicy) -> Self {
+        self.simplification = policy;
+        self
+    }
+
+    /// Limits the time a workflow may spend at any one depth
+    ///
+    /// A level that is left after more than `timeout` makes
+    /// [`decrement`](Self::decrement) fail.
+    pub fn with_level_timeout(mut self, timeout: Duration) -> Self {
+        self.level_timeout = Some(timeout);
+        self
+    }
+
     /// Creates a controller with default configuration (max_depth = 3)
     pub fn with_defaults() -> Self {
         Self::new(DepthConfig::default())
@@ -117,6 +184,7 @@ impl DepthController {
 
         self.current_depth += 1;
         self.depth_stack.push(agent_id);
+        self.level_started.push(Instant::now());
         Ok(())
     }
 
@@ -125,6 +193,9 @@ impl DepthController {
     /// # Returns
     /// - `Ok(())` if depth was successfully decremented
     /// - `Err(FederationError::ProtocolViolation)` if already at depth 0
+    /// - `Err(FederationError::Timeout)` if the level outlasted the level
+    ///   timeout; the depth is left unchanged so the caller can handle the
+    ///   stall, for example with [`reset`](Self::reset)
     pub fn decrement(&mut self) -> Result<(), FederationError> {
         if self.current_depth == 0 {
             return Err(FederationError::ProtocolViolation(
@@ -132,11 +203,36 @@ impl DepthController {
             ));
         }
 
+        if let (Some(timeout), Some(elapsed)) =
+            (self.level_timeout, self.time_at_depth(self.current_depth))
+        {
+            if elapsed > timeout {
+                return Err(FederationError::Timeout(
+                    "depth level timeout exceeded".to_string(),
+                ));
+            }
+        }
+
         self.current_depth -= 1;
         self.depth_stack.pop();
+        self.level_started.pop();
         Ok(())
     }
 
+    /// Time spent so far at `level` (1 is the first level entered)
+    ///
+    /// `None` if the workflow is not that deep, or if the controller was
+    /// deserialized after entering the level.
+    pub fn time_at_depth(&self, level: usize) -> Option<Duration> {
+        if level == 0 || level > self.current_depth {
+            return None;
+        }
+        // Deserialized controllers have no start times for their levels
+        let missing = self.current_depth - self.level_started.len();
+        let started = self.level_started.get(level.checked_sub(missing + 1)?)?;
+        Some(started.elapsed())
+    }
+
     /// Returns the current recursion depth
     pub fn current_depth(&self) -> usize {
         self.current_depth
@@ -179,10 +275,31 @@ impl DepthController {
         self.current_depth >= 2
     }
 
+    /// Builds the request delegating `task` to a child of `parent`
+    ///
+    /// Call it after [`increment`](Self::increment) for the child. The
+    /// child gets a nested context and inherits the parent's tools and
+    /// generation settings; when
+    /// [`should_simplify_agent`](Self::should_simplify_agent) holds, the
+    /// request is pared down by the [`SimplificationPolicy`].
+    pub fn child_request(&self, parent: &RLMTaskRequest, task: String) -> RLMTaskRequest {
+        let mut request = RLMTaskRequest::new(task, parent.context.workflow_id.clone())
+            .with_tools(parent.suggested_tools.clone())
+            .with_temperature(parent.temperature)
+            .with_max_tokens(parent.max_tokens);
+        request.context = parent.context.create_child();
+
+        if self.should_simplify_agent() {
+            self.simplification.simplify(&mut request);
+        }
+        request
+    }
+
     /// Resets the depth controller to initial state
     pub fn reset(&mut self) {
         self.current_depth = 0;
         self.depth_stack.clear();
+        self.level_started.clear();
     }
 
     /// Returns a copy of the configuration
@@ -398,6 +515,74 @@ mod tests {
         assert_eq!(controller.max_depth(), 10);
     }
 
+    #[tokio::test(start_paused = true)]
+    async fn test_level_timeout_blocks_decrement() {
+        let mut controller = DepthController::new(DepthConfig::with_max_depth(3))
+            .with_level_timeout(Duration::from_millis(50));
+
+        controller.increment("fast".to_string()).unwrap();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        controller.increment("slow".to_string()).unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        assert_eq!(controller.time_at_depth(1), Some(Duration::from_millis(110)));
+        assert_eq!(controller.time_at_depth(2), Some(Duration::from_millis(100)));
+        assert_eq!(controller.time_at_depth(3), None);
+        assert_eq!(controller.time_at_depth(0), None);
+
+        match controller.decrement() {
+            Err(FederationError::Timeout(msg)) => assert_eq!(msg, "depth level timeout exceeded"),
+            other => panic!("Expected Timeout error, got {:?}", other),
+        }
+        assert_eq!(controller.current_depth(), 2);
+        assert_eq!(controller.depth_stack(), ["fast", "slow"]);
+
+        controller.reset();
+        controller.increment("quick".to_string()).unwrap();
+        tokio::time::sleep(Duration::from_millis(40)).await;
+        controller.decrement().unwrap();
+        assert_eq!(controller.current_depth(), 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_no_level_timeout_by_default() {
+        let mut controller = DepthController::with_defaults();
+        controller.increment("agent-1".to_string()).unwrap();
+        tokio::time::sleep(Duration::from_secs(3600)).await;
+
+        assert!(controller.decrement().is_ok());
+    }
+
+    #[test]
+    fn test_child_requests_are_simplified_from_depth_two() {
+        let mut controller = DepthController::new(DepthConfig::with_max_depth(3)).with_simplification(
+            SimplificationPolicy::new(vec!["calculator".to_string()], 256),
+        );
+        let root = RLMTaskRequest::new("Research the market".to_string(), "workflow-1".to_string())
+            .with_tools(vec![
+                "web_search".to_string(),
+                "calculator".to_string(),
+                "code_interpreter".to_string(),
+            ])
+            .with_temperature(0.3)
+            .with_max_tokens(2048);
+
+        controller.increment("agent-1".to_string()).unwrap();
+        let child = controller.child_request(&root, "Find competitors".to_string());
+        assert_eq!(child.context.depth, 1);
+        assert_eq!(child.suggested_tools, root.suggested_tools);
+        assert_eq!(child.max_tokens, 2048);
+
+        controller.increment("agent-2".to_string()).unwrap();
+        let grandchild = controller.child_request(&child, "Compare prices".to_string());
+        assert_eq!(grandchild.context.depth, 2);
+        assert_eq!(grandchild.context.workflow_id, "workflow-1");
+        assert_eq!(grandchild.suggested_tools, vec!["calculator".to_string()]);
+        assert_eq!(grandchild.max_tokens, 256);
+        assert_eq!(grandchild.temperature, 0.3);
+        assert!(grandchild.validate().is_ok());
+    }
+
     #[test]
     fn test_display() {
         let mut controller = DepthController::new(DepthConfig::with_max_depth(3));
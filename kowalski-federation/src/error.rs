@@ -1,4 +1,5 @@
 use serde::Serialize;
+use std::time::Duration;
 use thiserror::Error;
 
 #[derive(Error, Debug, Serialize)]
@@ -53,4 +54,152 @@ pub enum FederationError {
 
     #[error("Configuration error: {0}")]
     ConfigurationError(String),
+
+    #[error("Template error: {0}")]
+    TemplateError(String),
+
+    #[error("Queue full: {requested} requests submitted but only {available} fit")]
+    QueueFull { requested: usize, available: usize },
+
+    #[error("HTTP error {status}: {message}")]
+    HttpError { status: u16, message: String },
+
+    #[error("Rate limit for model {model} has no capacity before the deadline (next in {retry_after_ms} ms)")]
+    RateLimited { model: String, retry_after_ms: u64 },
+
+    #[error("Call to model {model} needs {tokens} tokens but its context window is {context_window}")]
+    ContextWindowExceeded { model: String, tokens: usize, context_window: usize },
+}
+
+impl FederationError {
+    /// Returns true if the error is a temporary condition worth retrying
+    ///
+    /// Timeouts, network failures, a full queue and HTTP 408, 429 and 5xx
+    /// responses are transient; everything else is permanent.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            FederationError::Timeout(_)
+            | FederationError::NetworkError(_)
+            | FederationError::QueueFull { .. }
+            | FederationError::RateLimited { .. } => true,
+            FederationError::HttpError { status, .. } => {
+                matches!(status, 408 | 429) || (500..600).contains(status)
+            }
+            FederationError::DuplicateAgent(_)
+            | FederationError::AgentNotFound(_)
+            | FederationError::RegistrationFailed(_)
+            | FederationError::MessageDeliveryFailed(_)
+            | FederationError::InvalidMessageType(_)
+            | FederationError::SerializationError(_)
+            | FederationError::DeserializationError(_)
+            | FederationError::InternalError(_)
+            | FederationError::TaskNotFound(_)
+            | FederationError::InvalidTaskState(_)
+            | FederationError::NoSuitableAgents
+            | FederationError::ExecutionError(_)
+            | FederationError::DepthExceeded { .. }
+            | FederationError::ProtocolViolation(_)
+            | FederationError::ConfigurationError(_)
+            | FederationError::TemplateError(_)
+            | FederationError::ContextWindowExceeded { .. } => false,
+        }
+    }
+
+    /// Base delay before retrying, or `None` if the error should not be retried
+    ///
+    /// Rate limiting (HTTP 429) suggests 1 s, a local rate limit suggests
+    /// waiting until it has capacity, timeouts retry immediately and other
+    /// transient errors suggest 100 ms.
+    pub fn suggested_retry_delay(&self) -> Option<Duration> {
+        if !self.is_transient() {
+            return None;
+        }
+        Some(match self {
+            FederationError::HttpError { status: 429, .. } => Duration::from_secs(1),
+            FederationError::RateLimited { retry_after_ms, .. } => {
+                Duration::from_millis(*retry_after_ms)
+            }
+            FederationError::Timeout(_) | FederationError::HttpError { status: 408, .. } => {
+                Duration::ZERO
+            }
+            _ => Duration::from_millis(100),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn http(status: u16) -> FederationError {
+        FederationError::HttpError {
+            status,
+            message: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_transient_errors() {
+        let transient = [
+            FederationError::Timeout("slow".into()),
+            FederationError::NetworkError("reset".into()),
+            FederationError::QueueFull { requested: 2, available: 1 },
+            http(408),
+            http(429),
+            http(500),
+            http(503),
+        ];
+        for error in &transient {
+            assert!(error.is_transient(), "{:?} should be transient", error);
+            assert!(error.suggested_retry_delay().is_some());
+        }
+    }
+
+    #[test]
+    fn test_permanent_errors() {
+        let permanent = [
+            FederationError::DuplicateAgent("a".into()),
+            FederationError::AgentNotFound("a".into()),
+            FederationError::RegistrationFailed("x".into()),
+            FederationError::MessageDeliveryFailed("x".into()),
+            FederationError::InvalidMessageType("x".into()),
+            FederationError::SerializationError("x".into()),
+            FederationError::DeserializationError("x".into()),
+            FederationError::InternalError("x".into()),
+            FederationError::TaskNotFound("t".into()),
+            FederationError::InvalidTaskState("t".into()),
+            FederationError::NoSuitableAgents,
+            FederationError::ExecutionError("x".into()),
+            FederationError::DepthExceeded { max: 3, current: 4 },
+            FederationError::ProtocolViolation("x".into()),
+            FederationError::ConfigurationError("x".into()),
+            FederationError::TemplateError("x".into()),
+            http(400),
+            http(404),
+        ];
+        for error in &permanent {
+            assert!(!error.is_transient(), "{:?} should be permanent", error);
+            assert_eq!(error.suggested_retry_delay(), None);
+        }
+    }
+
+    #[test]
+    fn test_suggested_retry_delay() {
+        assert_eq!(http(429).suggested_retry_delay(), Some(Duration::from_secs(1)));
+        assert_eq!(
+            FederationError::Timeout("slow".into()).suggested_retry_delay(),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(http(408).suggested_retry_delay(), Some(Duration::ZERO));
+        assert_eq!(http(502).suggested_retry_delay(), Some(Duration::from_millis(100)));
+        assert_eq!(
+            FederationError::RateLimited { model: "m".into(), retry_after_ms: 1500 }
+                .suggested_retry_delay(),
+            Some(Duration::from_millis(1500))
+        );
+        assert_eq!(
+            FederationError::NetworkError("reset".into()).suggested_retry_delay(),
+            Some(Duration::from_millis(100))
+        );
+    }
 }
@@ -5,20 +5,30 @@ pub mod batch_scheduler;
 pub mod depth_controller;
 pub mod error;
 pub mod message;
+pub mod model_registry;
 pub mod orchestrator;
 pub mod protocols;
+pub mod rate_limiter;
 pub mod registry;
 
+#[cfg(test)]
+mod test_utils;
+
 pub use agent::{FederatedAgent, FederationRole};
 pub use agent_selector::{AgentSelector, SelectionCriteria, AgentScore};
-pub use batch_executor::{BatchExecutor, BatchLLMRequest, BatchLLMResponse};
-pub use batch_scheduler::{BatchScheduler, BatchSchedulerConfig, SchedulingStrategy};
-pub use depth_controller::{DepthController, DepthConfig};
+pub use batch_executor::{BatchExecutor, BatchLLMRequest, BatchLLMResponse, BatchRequestConfig, PriorityController};
+pub use batch_scheduler::{BatchScheduler, BatchSchedulerConfig, ModelUsageStats, RetryMatcher, SchedulingStrategy};
+pub use depth_controller::{DepthController, DepthConfig, SimplificationPolicy};
 pub use error::FederationError;
-pub use message::{FederationMessage, MessageType};
-pub use orchestrator::{Orchestrator, FederationTask, TaskPriority, TaskStatus};
-pub use protocols::{RLMTaskRequest, RLMTaskResponse, RLMContext, RLMMessageType};
-pub use registry::AgentRegistry;
+pub use message::{FederationMessage, MessageType, TraceContext};
+pub use model_registry::{ModelRegistry, ModelSpec};
+pub use orchestrator::{Orchestrator, OrchestratorConfig, OrchestratorEvent, FederationTask, FederationTaskType, RetryPolicy, TaskPriority, TaskStatus};
+pub use protocols::{
+    ConfidenceInterval, HeartbeatProtocol, HeartbeatRequest, HeartbeatResponse, PromptTemplate, PromptTemplateRegistry, RLMTaskRequest, RLMTaskResponse, RLMContext,
+    RLMMessageType,
+};
+pub use rate_limiter::{ModelRateLimit, RateLimiter};
+pub use registry::{AgentRecord, AgentRegistry, AgentRegistrySnapshot, RestoredAgent};
 
 pub use kowalski_core::conversation::Message;
 /// Re-export common types from core
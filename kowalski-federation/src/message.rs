@@ -1,3 +1,4 @@
+use crate::FederationError;
 use serde::{Deserialize, Serialize};
 use std::time::SystemTime;
 
@@ -8,6 +9,8 @@ pub enum MessageType {
     Register,
     /// Task delegation
     TaskDelegation,
+    /// Withdrawal of a delegated task that was moved to another agent
+    TaskWithdrawal,
     /// Task completion
     TaskCompletion,
     /// Status update
@@ -35,6 +38,101 @@ pub struct FederationMessage {
     pub metadata: Option<serde_json::Value>,
     /// Timestamp
     pub timestamp: u64,
+    /// ID shared by every delivery of this message, used to drop duplicates
+    #[serde(default = "new_replay_id")]
+    pub replay_id: String,
+    /// Distributed trace the message belongs to, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace_context: Option<TraceContext>,
+}
+
+/// W3C Trace Context identifying the trace and span a message belongs to
+///
+/// Converts to and from the `traceparent` header
+/// (`00-<trace_id>-<span_id>-<flags>`), so traces started outside the
+/// federation continue through it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TraceContext {
+    /// 32 lowercase hex digits identifying the whole trace
+    pub trace_id: String,
+    /// 16 lowercase hex digits identifying the sending span
+    pub span_id: String,
+    /// Trace flags; bit 0 marks the trace as sampled
+    pub trace_flags: u8,
+}
+
+impl TraceContext {
+    /// Parse a `traceparent` header value
+    ///
+    /// Headers of a later version than `00` are accepted as long as they
+    /// start with the version `00` fields.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FederationError::ProtocolViolation`] if the header is
+    /// malformed or carries an all-zero trace or span ID.
+    pub fn from_w3c_header(traceparent: &str) -> Result<Self, FederationError> {
+        let invalid = |reason: &str| {
+            FederationError::ProtocolViolation(format!(
+                "Invalid traceparent {:?}: {}",
+                traceparent, reason
+            ))
+        };
+
+        let parts: Vec<&str> = traceparent.trim().split('-').collect();
+        let version = parts[0];
+        if !is_lower_hex(version, 2) || version == "ff" {
+            return Err(invalid("unsupported version"));
+        }
+        if parts.len() < 4 || (version == "00" && parts.len() != 4) {
+            return Err(invalid("expected version-trace_id-span_id-flags"));
+        }
+        let (trace_id, span_id, flags) = (parts[1], parts[2], parts[3]);
+        if !is_lower_hex(trace_id, 32) || trace_id.bytes().all(|b| b == b'0') {
+            return Err(invalid("trace ID must be 32 hex digits, not all zero"));
+        }
+        if !is_lower_hex(span_id, 16) || span_id.bytes().all(|b| b == b'0') {
+            return Err(invalid("span ID must be 16 hex digits, not all zero"));
+        }
+        if !is_lower_hex(flags, 2) {
+            return Err(invalid("flags must be 2 hex digits"));
+        }
+
+        Ok(Self {
+            trace_id: trace_id.to_string(),
+            span_id: span_id.to_string(),
+            trace_flags: u8::from_str_radix(flags, 16).map_err(|_| invalid("bad flags"))?,
+        })
+    }
+
+    /// Format as a version `00` `traceparent` header value
+    pub fn to_w3c_header(&self) -> String {
+        format!("00-{}-{}-{:02x}", self.trace_id, self.span_id, self.trace_flags)
+    }
+
+    /// Returns true if the sampled flag is set
+    pub fn is_sampled(&self) -> bool {
+        self.trace_flags & 0x01 != 0
+    }
+
+    /// Context for a new span in the same trace, with a fresh span ID
+    pub fn child(&self) -> Self {
+        // A v4 UUID's version nibble sits in its first 16 digits, so the span ID is never zero
+        let span_id = uuid::Uuid::new_v4().simple().to_string()[..16].to_string();
+        Self {
+            trace_id: self.trace_id.clone(),
+            span_id,
+            trace_flags: self.trace_flags,
+        }
+    }
+}
+
+fn is_lower_hex(value: &str, len: usize) -> bool {
+    value.len() == len && value.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
+
+fn new_replay_id() -> String {
+    uuid::Uuid::new_v4().to_string()
 }
 
 impl FederationMessage {
@@ -57,6 +155,94 @@ impl FederationMessage {
                 .duration_since(SystemTime::UNIX_EPOCH)
                 .unwrap_or_default()
                 .as_secs(),
+            replay_id: new_replay_id(),
+            trace_context: None,
+        }
+    }
+
+    /// Attach the trace the message belongs to
+    pub fn with_trace_context(mut self, ctx: TraceContext) -> Self {
+        self.trace_context = Some(ctx);
+        self
+    }
+
+    /// Trace the message belongs to, if it carries one
+    pub fn extract_trace_context(&self) -> Option<&TraceContext> {
+        self.trace_context.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRACEPARENT: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+    #[test]
+    fn test_traceparent_round_trip() {
+        let ctx = TraceContext::from_w3c_header(TRACEPARENT).unwrap();
+
+        assert_eq!(ctx.trace_id, "4bf92f3577b34da6a3ce929d0e0e4736");
+        assert_eq!(ctx.span_id, "00f067aa0ba902b7");
+        assert_eq!(ctx.trace_flags, 0x01);
+        assert!(ctx.is_sampled());
+        assert_eq!(ctx.to_w3c_header(), TRACEPARENT);
+    }
+
+    #[test]
+    fn test_traceparent_rejects_malformed_headers() {
+        for header in [
+            "",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-extra",
+            "ff-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            "00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01",
+            "00-00000000000000000000000000000000-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-0000000000000000-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-1",
+        ] {
+            assert!(
+                matches!(TraceContext::from_w3c_header(header), Err(FederationError::ProtocolViolation(_))),
+                "accepted {:?}",
+                header
+            );
         }
+
+        // Later versions may append fields
+        let ctx = TraceContext::from_w3c_header(
+            "01-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-00-extra",
+        )
+        .unwrap();
+        assert!(!ctx.is_sampled());
+    }
+
+    #[test]
+    fn test_child_keeps_trace_and_flags() {
+        let parent = TraceContext::from_w3c_header(TRACEPARENT).unwrap();
+        let child = parent.child();
+
+        assert_eq!(child.trace_id, parent.trace_id);
+        assert_eq!(child.trace_flags, parent.trace_flags);
+        assert_ne!(child.span_id, parent.span_id);
+        assert_eq!(TraceContext::from_w3c_header(&child.to_w3c_header()).unwrap(), child);
+    }
+
+    #[test]
+    fn test_message_carries_trace_context() {
+        let ctx = TraceContext::from_w3c_header(TRACEPARENT).unwrap();
+        let message = FederationMessage::new(
+            MessageType::Status,
+            "agent-1".to_string(),
+            None,
+            "ping".to_string(),
+            None,
+        );
+        assert!(message.extract_trace_context().is_none());
+        assert!(!serde_json::to_string(&message).unwrap().contains("trace_context"));
+
+        let message = message.with_trace_context(ctx.clone());
+        let json = serde_json::to_string(&message).unwrap();
+        let decoded: FederationMessage = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.extract_trace_context(), Some(&ctx));
     }
 }
@@ -0,0 +1,330 @@
+//! Known LLM models and their limits
+//!
+//! A [`ModelRegistry`] maps model names to a [`ModelSpec`] describing the
+//! model's context window, pricing and capabilities.
+//!
+//! [`ModelRegistry::global`] is the process-wide registry, seeded with
+//! common models. [`BatchExecutor::estimate_cost`] prices batches from it
+//! unless the executor was given its own registry with
+//! [`BatchExecutor::with_model_registry`]. Context windows are only enforced
+//! against such an explicit registry: the built-in windows need not match
+//! how a model is actually served, so a model registered in the global
+//! registry never makes a batch reject a prompt.
+//!
+//! [`BatchExecutor`]: crate::batch_executor::BatchExecutor
+//! [`BatchExecutor::estimate_cost`]: crate::batch_executor::BatchExecutor::estimate_cost
+//! [`BatchExecutor::with_model_registry`]: crate::batch_executor::BatchExecutor::with_model_registry
+
+use crate::FederationError;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+
+/// Tag of models that accept image input
+pub const TAG_VISION: &str = "vision";
+/// Tag of models that support tool or function calling
+pub const TAG_TOOLS: &str = "tools";
+/// Tag of models served locally, for example through Ollama
+pub const TAG_LOCAL: &str = "local";
+/// Tag of models tuned for writing code
+pub const TAG_CODE: &str = "code";
+
+/// Limits, pricing and capabilities of one model
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelSpec {
+    /// Model name as sent in requests
+    pub name: String,
+    /// Prompt and response tokens the model accepts per call
+    pub context_window: usize,
+    /// Price per 1000 prompt tokens
+    pub cost_per_1k_in: f64,
+    /// Price per 1000 response tokens
+    pub cost_per_1k_out: f64,
+    /// Whether the model can stream its response
+    pub supports_streaming: bool,
+    /// Capability tags such as [`TAG_VISION`] or [`TAG_TOOLS`]
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+impl ModelSpec {
+    /// Creates a free, non-streaming spec with no tags
+    pub fn new(name: impl Into<String>, context_window: usize) -> Self {
+        Self {
+            name: name.into(),
+            context_window,
+            cost_per_1k_in: 0.0,
+            cost_per_1k_out: 0.0,
+            supports_streaming: false,
+            tags: Vec::new(),
+        }
+    }
+
+    /// Sets the price per 1000 prompt and response tokens
+    pub fn with_cost(mut self, per_1k_in: f64, per_1k_out: f64) -> Self {
+        self.cost_per_1k_in = per_1k_in;
+        self.cost_per_1k_out = per_1k_out;
+        self
+    }
+
+    /// Sets whether the model can stream its response
+    pub fn with_streaming(mut self, supports_streaming: bool) -> Self {
+        self.supports_streaming = supports_streaming;
+        self
+    }
+
+    /// Adds a capability tag
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    /// Returns true if the spec carries `tag`
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+
+    /// Price of a call with the given prompt and response token counts
+    pub fn cost(&self, tokens_in: usize, tokens_out: usize) -> f64 {
+        (tokens_in as f64 * self.cost_per_1k_in + tokens_out as f64 * self.cost_per_1k_out)
+            / 1000.0
+    }
+
+    /// Checks that `tokens` fit in the context window
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FederationError::ContextWindowExceeded`] if they do not.
+    pub fn check_context(&self, tokens: usize) -> Result<(), FederationError> {
+        if tokens > self.context_window {
+            return Err(FederationError::ContextWindowExceeded {
+                model: self.name.clone(),
+                tokens,
+                context_window: self.context_window,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Model specs keyed by model name
+///
+/// Lookups first try the exact name, then the name without an Ollama-style
+/// `:tag` suffix, so `llama3.2:latest` resolves to the `llama3.2` entry.
+#[derive(Debug, Default)]
+pub struct ModelRegistry {
+    models: RwLock<HashMap<String, ModelSpec>>,
+}
+
+impl ModelRegistry {
+    /// Creates an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a registry holding [`ModelRegistry::builtin_models`]
+    pub fn with_builtin_models() -> Self {
+        let registry = Self::new();
+        for spec in Self::builtin_models() {
+            registry.register(spec);
+        }
+        registry
+    }
+
+    /// The process-wide registry, seeded with the built-in models
+    pub fn global() -> &'static ModelRegistry {
+        static GLOBAL: OnceLock<ModelRegistry> = OnceLock::new();
+        GLOBAL.get_or_init(Self::with_builtin_models)
+    }
+
+    /// Specs of common hosted and local models
+    ///
+    /// Prices are list prices in USD at the time of writing; register an
+    /// updated spec to override them.
+    pub fn builtin_models() -> Vec<ModelSpec> {
+        vec![
+            ModelSpec::new("gpt-4o", 128_000)
+                .with_cost(0.0025, 0.01)
+                .with_streaming(true)
+                .with_tag(TAG_VISION)
+                .with_tag(TAG_TOOLS),
+            ModelSpec::new("gpt-4o-mini", 128_000)
+                .with_cost(0.00015, 0.0006)
+                .with_streaming(true)
+                .with_tag(TAG_VISION)
+                .with_tag(TAG_TOOLS),
+            ModelSpec::new("claude-3-5-sonnet", 200_000)
+                .with_cost(0.003, 0.015)
+                .with_streaming(true)
+                .with_tag(TAG_VISION)
+                .with_tag(TAG_TOOLS),
+            ModelSpec::new("claude-3-5-haiku", 200_000)
+                .with_cost(0.0008, 0.004)
+                .with_streaming(true)
+                .with_tag(TAG_TOOLS),
+            ModelSpec::new("llama3.2", 128_000)
+                .with_streaming(true)
+                .with_tag(TAG_LOCAL)
+                .with_tag(TAG_TOOLS),
+            ModelSpec::new("llama3.1", 128_000)
+                .with_streaming(true)
+                .with_tag(TAG_LOCAL)
+                .with_tag(TAG_TOOLS),
+            ModelSpec::new("mistral", 32_768)
+                .with_streaming(true)
+                .with_tag(TAG_LOCAL)
+                .with_tag(TAG_TOOLS),
+            ModelSpec::new("qwen2.5-coder", 32_768)
+                .with_streaming(true)
+                .with_tag(TAG_LOCAL)
+                .with_tag(TAG_CODE),
+            ModelSpec::new("codellama", 16_384)
+                .with_streaming(true)
+                .with_tag(TAG_LOCAL)
+                .with_tag(TAG_CODE),
+        ]
+    }
+
+    /// Adds `spec`, replacing any spec registered under the same name
+    pub fn register(&self, spec: ModelSpec) {
+        self.models.write().unwrap().insert(spec.name.clone(), spec);
+    }
+
+    /// Removes the spec registered under `name`
+    pub fn unregister(&self, name: &str) -> Option<ModelSpec> {
+        self.models.write().unwrap().remove(name)
+    }
+
+    /// Spec of the model `name`, if registered
+    pub fn get(&self, name: &str) -> Option<ModelSpec> {
+        let models = self.models.read().unwrap();
+        models
+            .get(name)
+            .or_else(|| {
+                let (base, _tag) = name.split_once(':')?;
+                models.get(base)
+            })
+            .cloned()
+    }
+
+    /// Returns true if `name` resolves to a registered spec
+    pub fn contains(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    /// Names of all registered models, sorted
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.models.read().unwrap().keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Specs carrying `tag`, sorted by name
+    pub fn with_tag(&self, tag: &str) -> Vec<ModelSpec> {
+        let mut specs: Vec<ModelSpec> = self
+            .models
+            .read()
+            .unwrap()
+            .values()
+            .filter(|spec| spec.has_tag(tag))
+            .cloned()
+            .collect();
+        specs.sort_by(|a, b| a.name.cmp(&b.name));
+        specs
+    }
+
+    /// Context window of the model `name`, if registered
+    pub fn context_window(&self, name: &str) -> Option<usize> {
+        self.get(name).map(|spec| spec.context_window)
+    }
+
+    /// Price of a call to `name`, if the model is registered
+    pub fn estimate_cost(&self, name: &str, tokens_in: usize, tokens_out: usize) -> Option<f64> {
+        self.get(name).map(|spec| spec.cost(tokens_in, tokens_out))
+    }
+
+    /// Checks that `tokens` fit the context window of `name`
+    ///
+    /// Unregistered models are not checked.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FederationError::ContextWindowExceeded`] if the model is
+    /// registered and `tokens` exceed its context window.
+    pub fn check_context(&self, name: &str, tokens: usize) -> Result<(), FederationError> {
+        match self.get(name) {
+            Some(spec) => spec.check_context(tokens),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registered_custom_model_reads_back() {
+        let registry = ModelRegistry::new();
+        let spec = ModelSpec::new("acme-large", 64_000)
+            .with_cost(0.002, 0.008)
+            .with_streaming(true)
+            .with_tag(TAG_TOOLS);
+        registry.register(spec.clone());
+
+        assert_eq!(registry.get("acme-large"), Some(spec));
+        assert_eq!(registry.context_window("acme-large"), Some(64_000));
+        assert_eq!(registry.names(), vec!["acme-large".to_string()]);
+        assert!(registry.get("acme-small").is_none());
+    }
+
+    #[test]
+    fn test_register_replaces_existing_spec() {
+        let registry = ModelRegistry::with_builtin_models();
+        registry.register(ModelSpec::new("mistral", 8_192));
+
+        assert_eq!(registry.context_window("mistral"), Some(8_192));
+        assert_eq!(registry.names().len(), ModelRegistry::builtin_models().len());
+    }
+
+    #[test]
+    fn test_lookup_ignores_ollama_tag_suffix() {
+        let registry = ModelRegistry::with_builtin_models();
+        assert_eq!(registry.context_window("llama3.2:latest"), Some(128_000));
+        assert!(registry.get("unknown:7b").is_none());
+    }
+
+    #[test]
+    fn test_estimate_cost_uses_per_1k_prices() {
+        let registry = ModelRegistry::new();
+        registry.register(ModelSpec::new("priced", 10_000).with_cost(0.5, 2.0));
+
+        let cost = registry.estimate_cost("priced", 2_000, 500).unwrap();
+        assert!((cost - 2.0).abs() < 1e-9);
+        assert_eq!(registry.estimate_cost("unknown", 1, 1), None);
+    }
+
+    #[test]
+    fn test_check_context_rejects_oversized_calls() {
+        let registry = ModelRegistry::new();
+        registry.register(ModelSpec::new("tiny", 100));
+
+        assert!(registry.check_context("tiny", 100).is_ok());
+        assert!(matches!(
+            registry.check_context("tiny", 101),
+            Err(FederationError::ContextWindowExceeded { tokens: 101, context_window: 100, .. })
+        ));
+        assert!(registry.check_context("unknown", usize::MAX).is_ok());
+    }
+
+    #[test]
+    fn test_with_tag_filters_by_capability() {
+        let registry = ModelRegistry::with_builtin_models();
+        let code_models: Vec<String> = registry
+            .with_tag(TAG_CODE)
+            .into_iter()
+            .map(|spec| spec.name)
+            .collect();
+        assert_eq!(code_models, vec!["codellama", "qwen2.5-coder"]);
+    }
+}
@@ -1,14 +1,20 @@
-use std::sync::Arc;
+use std::future::Future;
+use std::sync::{Arc, Weak};
 use std::collections::HashMap;
-use tokio::sync::RwLock;
-use tracing::info;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, RwLock};
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
 use serde::{Serialize, Deserialize};
 
 use crate::{
     agent::FederationRole,
+    agent_selector::{AgentSelector, SelectionCriteria},
     registry::AgentRegistry,
-    message::{FederationMessage, MessageType},
+    message::{FederationMessage, MessageType, TraceContext},
     error::FederationError,
+    protocols::{HeartbeatRequest, HeartbeatResponse, RLMTaskRequest, RLMTaskResponse},
 };
 
 /// Represents a task that needs to be delegated
@@ -23,6 +29,53 @@ pub struct FederationTask {
     pub assigned_to: Option<String>,
     pub created_at: u64,
     pub updated_at: u64,
+    /// Structured task type, used for type-based routing
+    #[serde(default)]
+    pub kind: Option<FederationTaskType>,
+    /// Trace the task was created in; messages sent for it continue the trace
+    #[serde(default)]
+    pub trace_context: Option<TraceContext>,
+}
+
+impl FederationTask {
+    /// Sets the structured task type
+    ///
+    /// `task_type` is set to the type's name as well, so agent selection
+    /// sees the same type.
+    pub fn with_task_type(mut self, task_type: FederationTaskType) -> Self {
+        self.task_type = task_type.as_str().to_string();
+        self.kind = Some(task_type);
+        self
+    }
+
+    /// Sets the trace the task belongs to
+    pub fn with_trace_context(mut self, ctx: TraceContext) -> Self {
+        self.trace_context = Some(ctx);
+        self
+    }
+}
+
+/// Kinds of work a task can be routed by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum FederationTaskType {
+    Research,
+    CodeAnalysis,
+    DataAnalysis,
+    WebSearch,
+    General,
+}
+
+impl FederationTaskType {
+    /// Name of the type as used in `FederationTask::task_type`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FederationTaskType::Research => "research",
+            FederationTaskType::CodeAnalysis => "code_analysis",
+            FederationTaskType::DataAnalysis => "data_analysis",
+            FederationTaskType::WebSearch => "web_search",
+            FederationTaskType::General => "general",
+        }
+    }
 }
 
 /// Task priority levels
@@ -45,19 +98,379 @@ pub enum TaskStatus {
     Cancelled,
 }
 
+/// Time before a deadline in which dispatched requests get a reduced token budget
+const DEADLINE_PRESSURE_WINDOW: Duration = Duration::from_secs(30);
+
+/// Smallest `max_tokens` a request is reduced to under deadline pressure
+const MIN_DEADLINE_MAX_TOKENS: usize = 64;
+
+/// Heartbeat load above which an agent's queued tasks are moved elsewhere
+const REBALANCE_OVERLOAD_THRESHOLD: f64 = 0.8;
+
+/// Heartbeat load below which an agent takes over tasks during a rebalance
+const REBALANCE_UNDERLOAD_THRESHOLD: f64 = 0.3;
+
+/// Capacity of the orchestrator event channel
+const EVENT_CHANNEL_CAPACITY: usize = 64;
+
+/// Events published by an [`Orchestrator`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OrchestratorEvent {
+    /// A task's deadline passed before it could be dispatched
+    TaskExpired {
+        task_id: String,
+        /// The deadline that was missed
+        expired_at: tokio::time::Instant,
+    },
+}
+
+/// Retry policy for dispatching RLM task requests
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetryPolicy {
+    /// Maximum number of attempts (including the first one)
+    pub max_attempts: usize,
+    /// Base delay between attempts (exponential backoff multiplier)
+    pub backoff_ms: u64,
+    /// Whether to select a different agent after each failure
+    pub reselect_agent: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            backoff_ms: 100,
+            reselect_agent: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Creates a policy with the given number of attempts
+    pub fn new(max_attempts: usize) -> Self {
+        Self {
+            max_attempts,
+            ..Default::default()
+        }
+    }
+
+    /// Sets the base backoff delay
+    pub fn with_backoff_ms(mut self, backoff_ms: u64) -> Self {
+        self.backoff_ms = backoff_ms;
+        self
+    }
+
+    /// Enables or disables agent re-selection between attempts
+    pub fn with_reselection(mut self, reselect_agent: bool) -> Self {
+        self.reselect_agent = reselect_agent;
+        self
+    }
+
+    /// Calculates the delay after a failed attempt
+    ///
+    /// delay = backoff_ms * 2^attempt
+    pub fn delay(&self, attempt: usize) -> Duration {
+        let factor = 2_u64.saturating_pow(attempt as u32);
+        Duration::from_millis(self.backoff_ms.saturating_mul(factor))
+    }
+}
+
+/// Configuration for an [`Orchestrator`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrchestratorConfig {
+    /// How long a delivered message's replay ID is remembered
+    pub replay_window: Duration,
+    /// ID reported to agents in heartbeat responses
+    pub orchestrator_id: String,
+    /// How often agents are asked to send heartbeats
+    pub heartbeat_interval: Duration,
+    /// How often the health task calls
+    /// [`dynamic_rebalance`](Orchestrator::dynamic_rebalance); `None`
+    /// disables automatic rebalancing
+    #[serde(default)]
+    pub auto_rebalance_interval: Option<Duration>,
+}
+
+impl Default for OrchestratorConfig {
+    fn default() -> Self {
+        Self {
+            replay_window: Duration::from_secs(5 * 60),
+            orchestrator_id: "orchestrator".to_string(),
+            heartbeat_interval: Duration::from_secs(30),
+            auto_rebalance_interval: None,
+        }
+    }
+}
+
+impl OrchestratorConfig {
+    /// Set the replay window
+    pub fn with_replay_window(mut self, replay_window: Duration) -> Self {
+        self.replay_window = replay_window;
+        self
+    }
+
+    /// Set the orchestrator ID
+    pub fn with_orchestrator_id(mut self, orchestrator_id: impl Into<String>) -> Self {
+        self.orchestrator_id = orchestrator_id.into();
+        self
+    }
+
+    /// Set the heartbeat interval requested from agents
+    pub fn with_heartbeat_interval(mut self, heartbeat_interval: Duration) -> Self {
+        self.heartbeat_interval = heartbeat_interval;
+        self
+    }
+
+    /// Rebalance queued tasks automatically every `interval`
+    pub fn with_auto_rebalance_interval(mut self, interval: Duration) -> Self {
+        self.auto_rebalance_interval = Some(interval);
+        self
+    }
+}
+
 /// Orchestrator manages task delegation and coordination
 pub struct Orchestrator {
     registry: Arc<AgentRegistry>,
     tasks: Arc<RwLock<HashMap<String, FederationTask>>>,
+    config: OrchestratorConfig,
+    /// Replay IDs of delivered messages with the time they were first seen
+    seen_replays: Arc<RwLock<HashMap<String, Instant>>>,
+    /// Most recent heartbeat received from each agent
+    heartbeats: Arc<RwLock<HashMap<String, HeartbeatRequest>>>,
+    /// Deadlines of tasks submitted with `submit_task_with_deadline`
+    deadlines: Arc<RwLock<HashMap<String, tokio::time::Instant>>>,
+    expired_tasks: AtomicUsize,
+    events: broadcast::Sender<OrchestratorEvent>,
+    /// Agents that tasks of each type may be delegated to
+    type_routes: Arc<RwLock<HashMap<FederationTaskType, Vec<String>>>>,
 }
 
 impl Orchestrator {
     /// Create a new orchestrator
     pub fn new(registry: Arc<AgentRegistry>) -> Self {
+        Self::with_config(registry, OrchestratorConfig::default())
+    }
+
+    /// Create a new orchestrator with custom configuration
+    pub fn with_config(registry: Arc<AgentRegistry>, config: OrchestratorConfig) -> Self {
         Self {
             registry,
             tasks: Arc::new(RwLock::new(HashMap::new())),
+            config,
+            seen_replays: Arc::new(RwLock::new(HashMap::new())),
+            heartbeats: Arc::new(RwLock::new(HashMap::new())),
+            deadlines: Arc::new(RwLock::new(HashMap::new())),
+            expired_tasks: AtomicUsize::new(0),
+            events: broadcast::channel(EVENT_CHANNEL_CAPACITY).0,
+            type_routes: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// Subscribe to orchestrator events
+    pub fn subscribe(&self) -> broadcast::Receiver<OrchestratorEvent> {
+        self.events.subscribe()
+    }
+
+    /// Get the orchestrator configuration
+    pub fn config(&self) -> &OrchestratorConfig {
+        &self.config
+    }
+
+    /// Deliver a message to its recipient, or broadcast it if it has none
+    ///
+    /// Messages whose `replay_id` was already delivered within the replay
+    /// window are dropped.
+    ///
+    /// # Returns
+    /// `true` if the message was delivered, `false` if it was a duplicate
+    pub async fn deliver_message(&self, message: FederationMessage) -> Result<bool, FederationError> {
+        self.send_once(message).await
+    }
+
+    /// Number of replay IDs currently remembered
+    pub async fn replay_cache_size(&self) -> usize {
+        self.seen_replays.read().await.len()
+    }
+
+    /// Forget replay IDs older than the replay window, returning how many were evicted
+    pub async fn evict_expired_replays(&self) -> usize {
+        let window = self.config.replay_window;
+        let mut seen = self.seen_replays.write().await;
+        let before = seen.len();
+        seen.retain(|_, first_seen| first_seen.elapsed() < window);
+        before - seen.len()
+    }
+
+    /// Handle a heartbeat from an agent
+    ///
+    /// Heartbeats from registered agents are acknowledged and remembered;
+    /// heartbeats from unknown agents are not acknowledged, so the agent
+    /// knows to re-register.
+    pub async fn handle_heartbeat(&self, request: HeartbeatRequest) -> HeartbeatResponse {
+        let acknowledged = self.registry.get_agent(&request.agent_id).await.is_some();
+        if acknowledged {
+            debug!("Heartbeat from {} (load {:.2})", request.agent_id, request.load);
+            self.heartbeats
+                .write()
+                .await
+                .insert(request.agent_id.clone(), request);
+        } else {
+            warn!("Heartbeat from unregistered agent {}", request.agent_id);
+        }
+
+        HeartbeatResponse {
+            acknowledged,
+            orchestrator_id: self.config.orchestrator_id.clone(),
+            next_heartbeat_in_ms: self.config.heartbeat_interval.as_millis() as u64,
+        }
+    }
+
+    /// Most recent acknowledged heartbeat from an agent
+    pub async fn last_heartbeat(&self, agent_id: &str) -> Option<HeartbeatRequest> {
+        self.heartbeats.read().await.get(agent_id).cloned()
+    }
+
+    /// Spawn the background health task
+    ///
+    /// Every `interval` the task evicts expired replay IDs. If
+    /// `auto_rebalance_interval` is configured, it also calls
+    /// [`dynamic_rebalance`](Self::dynamic_rebalance) at that interval. It
+    /// stops once the orchestrator is dropped.
+    pub fn spawn_health_task(self: &Arc<Self>, interval: Duration) -> JoinHandle<()> {
+        let orchestrator: Weak<Self> = Arc::downgrade(self);
+        let rebalance_interval = self.config.auto_rebalance_interval;
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await;
+            let mut rebalance_ticker = rebalance_interval.map(tokio::time::interval);
+            if let Some(rebalance_ticker) = &mut rebalance_ticker {
+                rebalance_ticker.tick().await;
+            }
+            loop {
+                let rebalance_due = async {
+                    match &mut rebalance_ticker {
+                        Some(rebalance_ticker) => rebalance_ticker.tick().await,
+                        None => std::future::pending().await,
+                    }
+                };
+                let rebalance = tokio::select! {
+                    _ = ticker.tick() => false,
+                    _ = rebalance_due => true,
+                };
+                let Some(orchestrator) = orchestrator.upgrade() else {
+                    break;
+                };
+                if rebalance {
+                    orchestrator.dynamic_rebalance().await;
+                    continue;
+                }
+                let evicted = orchestrator.evict_expired_replays().await;
+                if evicted > 0 {
+                    debug!("Evicted {} expired replay IDs", evicted);
+                }
+            }
+        })
+    }
+
+    /// Move queued tasks from overloaded agents to underloaded ones
+    ///
+    /// Loads come from the agents' latest heartbeats. Every assigned task
+    /// that has not started yet on an agent with a load above 0.8 is
+    /// delegated again to an agent with a load below 0.3, spreading the tasks
+    /// across those agents starting with the least loaded. Agents without a
+    /// heartbeat are left alone.
+    ///
+    /// The overloaded agent is sent a `TaskWithdrawal` for each task before
+    /// it is delegated again, so the task does not run twice. A task whose
+    /// withdrawal cannot be delivered stays where it is; a withdrawn task
+    /// whose new delegation fails goes back to pending. Tasks that change
+    /// while the messages are sent are left as they are.
+    ///
+    /// # Returns
+    /// The number of tasks moved
+    pub async fn dynamic_rebalance(&self) -> usize {
+        let (overloaded, underloaded) = {
+            let heartbeats = self.heartbeats.read().await;
+            let overloaded: Vec<String> = heartbeats
+                .values()
+                .filter(|hb| hb.load > REBALANCE_OVERLOAD_THRESHOLD)
+                .map(|hb| hb.agent_id.clone())
+                .collect();
+            let mut underloaded: Vec<(String, f64)> = heartbeats
+                .values()
+                .filter(|hb| hb.load < REBALANCE_UNDERLOAD_THRESHOLD)
+                .map(|hb| (hb.agent_id.clone(), hb.load))
+                .collect();
+            underloaded.sort_by(|a, b| a.1.total_cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+            (overloaded, underloaded)
+        };
+        if overloaded.is_empty() || underloaded.is_empty() {
+            return 0;
+        }
+
+        // Plan the moves under the lock, but send without holding it
+        let planned: Vec<(FederationTask, String)> = {
+            let tasks = self.tasks.read().await;
+            let mut queued: Vec<&FederationTask> = tasks
+                .values()
+                .filter(|task| {
+                    task.status == TaskStatus::Assigned
+                        && task.assigned_to.as_ref().is_some_and(|id| overloaded.contains(id))
+                })
+                .collect();
+            queued.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+            queued
+                .into_iter()
+                .enumerate()
+                .map(|(i, task)| (task.clone(), underloaded[i % underloaded.len()].0.clone()))
+                .collect()
+        };
+
+        let mut outcomes = Vec::new();
+        for (mut task, target) in planned {
+            let previous = task.assigned_to.clone().unwrap_or_default();
+            if let Err(e) = self.send_once(task_withdrawal_message(&task.id, &previous)).await {
+                warn!("Could not withdraw task {} from {}: {}", task.id, previous, e);
+                continue;
+            }
+            task.assigned_to = Some(target.clone());
+            let delivered = match self.send_once(task_delegation_message(&task, &target)).await {
+                Ok(delivered) => delivered,
+                Err(e) => {
+                    warn!("Could not move task {} to {}: {}", task.id, target, e);
+                    false
+                }
+            };
+            outcomes.push((task.id, previous, delivered.then_some(target)));
+        }
+
+        let mut moved = 0;
+        let mut tasks = self.tasks.write().await;
+        for (task_id, previous, target) in outcomes {
+            let Some(task) = tasks.get_mut(&task_id) else {
+                continue;
+            };
+            if task.status != TaskStatus::Assigned || task.assigned_to.as_ref() != Some(&previous) {
+                warn!("Task {} changed while it was being rebalanced", task_id);
+                continue;
+            }
+            match target {
+                Some(target) => {
+                    task.assigned_to = Some(target);
+                    moved += 1;
+                }
+                None => {
+                    task.assigned_to = None;
+                    task.status = TaskStatus::Pending;
+                }
+            }
+            task.updated_at = get_timestamp();
+        }
+
+        if moved > 0 {
+            info!("Rebalanced {} queued tasks away from overloaded agents", moved);
         }
+        moved
     }
 
     /// Create a new task
@@ -67,6 +480,45 @@ impl Orchestrator {
         content: String,
         metadata: Option<serde_json::Value>,
         priority: TaskPriority,
+    ) -> Result<String, FederationError> {
+        self.insert_task(task_type, content, metadata, priority, None).await
+    }
+
+    /// Create a task from an incoming message
+    ///
+    /// The message content becomes the task content and its metadata the
+    /// task metadata; the task type is read from a `task_type` metadata
+    /// string, defaulting to `general`. The message's trace context is kept,
+    /// so every message sent for the task continues the caller's trace.
+    pub async fn create_task_from_message(
+        &self,
+        message: &FederationMessage,
+        priority: TaskPriority,
+    ) -> Result<String, FederationError> {
+        let task_type = message
+            .metadata
+            .as_ref()
+            .and_then(|metadata| metadata.get("task_type"))
+            .and_then(|task_type| task_type.as_str())
+            .unwrap_or(FederationTaskType::General.as_str())
+            .to_string();
+        self.insert_task(
+            task_type,
+            message.content.clone(),
+            message.metadata.clone(),
+            priority,
+            message.extract_trace_context().cloned(),
+        )
+        .await
+    }
+
+    async fn insert_task(
+        &self,
+        task_type: String,
+        content: String,
+        metadata: Option<serde_json::Value>,
+        priority: TaskPriority,
+        trace_context: Option<TraceContext>,
     ) -> Result<String, FederationError> {
         let task_id = uuid::Uuid::new_v4().to_string();
         let task = FederationTask {
@@ -79,6 +531,8 @@ impl Orchestrator {
             assigned_to: None,
             created_at: get_timestamp(),
             updated_at: get_timestamp(),
+            kind: None,
+            trace_context,
         };
 
         self.tasks.write().await.insert(task_id.clone(), task);
@@ -86,7 +540,124 @@ impl Orchestrator {
         Ok(task_id)
     }
 
+    /// Submit a task that must be dispatched before `deadline`
+    ///
+    /// The task is stored as pending. Dispatching it after the deadline fails
+    /// the task, and dispatching it shortly before the deadline reduces the
+    /// request's `max_tokens` so the agent answers more briefly.
+    pub async fn submit_task_with_deadline(
+        &self,
+        mut task: FederationTask,
+        deadline: tokio::time::Instant,
+    ) -> Result<(), FederationError> {
+        let task_id = task.id.clone();
+        let mut tasks = self.tasks.write().await;
+        if tasks.contains_key(&task_id) {
+            return Err(FederationError::InvalidTaskState(task_id));
+        }
+
+        task.status = TaskStatus::Pending;
+        task.updated_at = get_timestamp();
+        tasks.insert(task_id.clone(), task);
+        self.deadlines.write().await.insert(task_id.clone(), deadline);
+        info!("Submitted task {} with a deadline", task_id);
+        Ok(())
+    }
+
+    /// Number of tasks failed because their deadline passed before dispatch
+    pub fn expired_tasks(&self) -> usize {
+        self.expired_tasks.load(Ordering::Relaxed)
+    }
+
+    /// Fail `task` if its deadline has passed, returning the time left otherwise
+    ///
+    /// Tasks without a deadline have unlimited time (`None`).
+    async fn check_deadline(
+        &self,
+        task: &mut FederationTask,
+    ) -> Result<Option<Duration>, FederationError> {
+        let Some(deadline) = self.deadlines.read().await.get(&task.id).copied() else {
+            return Ok(None);
+        };
+
+        let now = tokio::time::Instant::now();
+        if now < deadline {
+            return Ok(Some(deadline - now));
+        }
+
+        task.status = TaskStatus::Failed;
+        task.updated_at = get_timestamp();
+        self.deadlines.write().await.remove(&task.id);
+        self.expired_tasks.fetch_add(1, Ordering::Relaxed);
+        warn!("Task {} expired before dispatch", task.id);
+        // Nobody listening is fine
+        let _ = self.events.send(OrchestratorEvent::TaskExpired {
+            task_id: task.id.clone(),
+            expired_at: deadline,
+        });
+        Err(FederationError::Timeout(
+            "task deadline expired before dispatch".to_string(),
+        ))
+    }
+
+    /// Dispatch an RLM request for a submitted task to an agent
+    ///
+    /// Like [`dispatch_rlm_request`](Self::dispatch_rlm_request), but the
+    /// task's deadline is enforced: an expired task fails with
+    /// `FederationError::Timeout`, and with less than 30 seconds left the
+    /// request's `max_tokens` is scaled down with the time remaining. The
+    /// task is marked assigned to the agent once the request is delivered.
+    /// The request message continues the task's trace, if it has one.
+    pub async fn dispatch_task_request(
+        &self,
+        task_id: &str,
+        agent_id: &str,
+        mut request: RLMTaskRequest,
+    ) -> Result<(), FederationError> {
+        let mut tasks = self.tasks.write().await;
+        let task = tasks.get_mut(task_id).ok_or_else(|| {
+            FederationError::TaskNotFound(task_id.to_string())
+        })?;
+
+        if task.status != TaskStatus::Pending {
+            return Err(FederationError::InvalidTaskState(task_id.to_string()));
+        }
+
+        if let Some(remaining) = self.check_deadline(task).await? {
+            request.max_tokens = deadline_max_tokens(request.max_tokens, remaining);
+        }
+
+        let mut message = rlm_request_message(agent_id, &request)?;
+        message.trace_context = task.trace_context.as_ref().map(TraceContext::child);
+        self.send_once(message).await?;
+        task.assigned_to = Some(agent_id.to_string());
+        task.status = TaskStatus::Assigned;
+        task.updated_at = get_timestamp();
+        Ok(())
+    }
+
+    /// Restrict tasks of `task_type` to the given agents
+    ///
+    /// [`delegate_task`](Self::delegate_task) only considers these agents for
+    /// tasks of this type, replacing any earlier route for it.
+    pub async fn set_type_route(&self, task_type: FederationTaskType, agent_ids: Vec<String>) {
+        self.type_routes.write().await.insert(task_type, agent_ids);
+    }
+
+    /// Remove all type routes, so every worker is considered again
+    pub async fn clear_type_routes(&self) {
+        self.type_routes.write().await.clear();
+    }
+
     /// Delegate a task to the most suitable agent
+    ///
+    /// Workers are ranked by an [`AgentSelector`]. If the task has a
+    /// structured type with a route (see
+    /// [`set_type_route`](Self::set_type_route)), only the workers on that
+    /// route are candidates.
+    ///
+    /// A task whose deadline has passed fails with `FederationError::Timeout`.
+    /// The delegation message continues the task's trace, if it has one.
     pub async fn delegate_task(
         &self,
         task_id: &str,
@@ -100,40 +671,146 @@ impl Orchestrator {
             return Err(FederationError::InvalidTaskState(task_id.to_string()));
         }
 
+        self.check_deadline(task).await?;
+
         // Find the most suitable agent
         let agents = self.registry.list_agents().await;
-        let mut suitable_agents: Vec<_> = agents
+        let workers: Vec<_> = agents
             .iter()
             .filter(|(_, role)| *role == FederationRole::Worker)
             .map(|(id, _)| id.clone())
             .collect();
 
-        if suitable_agents.is_empty() {
+        if workers.is_empty() {
             return Err(FederationError::NoSuitableAgents);
         }
 
-        // For now, just pick the first available agent
-        let assigned_agent = suitable_agents.remove(0);
+        // Workers off the task type's route are never passed to the selector
+        let excluded = match task.kind {
+            Some(kind) => match self.type_routes.read().await.get(&kind) {
+                Some(route) => workers.into_iter().filter(|id| !route.contains(id)).collect(),
+                None => Vec::new(),
+            },
+            None => Vec::new(),
+        };
+        let criteria = SelectionCriteria::new(task.task_type.clone()).with_exclusions(excluded);
+        let assigned_agent = AgentSelector::new(Arc::clone(&self.registry))
+            .select_agent(&criteria)
+            .await?
+            .agent_id;
         task.assigned_to = Some(assigned_agent.clone());
         task.status = TaskStatus::Assigned;
         task.updated_at = get_timestamp();
 
-        // Send task delegation message
-        let message = FederationMessage::new(
-            MessageType::TaskDelegation,
-            "coordinator".to_string(),
-            Some(assigned_agent.clone()),
-            serde_json::to_string(&task).unwrap_or_default(),
-            Some(serde_json::json!({
-                "task_id": task_id,
-                "priority": format!("{:?}", task.priority),
-            })),
-        );
+        let message = task_delegation_message(task, &assigned_agent);
+        self.send_once(message).await.map(|_| ())
+    }
 
-        self.registry
-            .send_message(&assigned_agent, message)
-            .await
-            .map_err(|e| FederationError::MessageDeliveryFailed(e.to_string()))
+    /// Dispatch an RLM task request to a specific agent
+    ///
+    /// The request is validated first so malformed requests never reach agents.
+    pub async fn dispatch_rlm_request(
+        &self,
+        agent_id: &str,
+        request: &RLMTaskRequest,
+    ) -> Result<(), FederationError> {
+        let message = rlm_request_message(agent_id, request)?;
+        self.send_once(message).await.map(|_| ())
+    }
+
+    /// Send a message unless its `replay_id` was sent within the replay window
+    ///
+    /// Every message the orchestrator sends on behalf of callers goes through
+    /// here, so a replay is dropped whichever path it arrives by.
+    async fn send_once(&self, message: FederationMessage) -> Result<bool, FederationError> {
+        let replay_id = message.replay_id.clone();
+        {
+            let mut seen = self.seen_replays.write().await;
+            if let Some(first_seen) = seen.get(&replay_id) {
+                if first_seen.elapsed() < self.config.replay_window {
+                    debug!("Dropping duplicate message {} (replay {})", message.id, replay_id);
+                    return Ok(false);
+                }
+            }
+            seen.insert(replay_id.clone(), Instant::now());
+        }
+
+        let result = match message.recipient.clone() {
+            Some(recipient) => self.registry.send_message(&recipient, message).await,
+            None => self.registry.broadcast_message(message).await,
+        };
+
+        if let Err(e) = result {
+            // Allow a redelivery of a message that never arrived
+            self.seen_replays.write().await.remove(&replay_id);
+            return Err(FederationError::MessageDeliveryFailed(e.to_string()));
+        }
+
+        Ok(true)
+    }
+
+    /// Dispatch an RLM task request with retries
+    ///
+    /// Agents are chosen through an [`AgentSelector`] using `criteria`. Each
+    /// failed agent is added to `exclude_agents` so that, when
+    /// `policy.reselect_agent` is set, the next attempt goes to a different
+    /// agent. `handler` performs the actual work for the chosen agent.
+    ///
+    /// # Returns
+    /// The first successful response. If every attempt fails, the last
+    /// failure is returned: either the handler's error or its unsuccessful
+    /// response.
+    pub async fn dispatch_with_retry<F, Fut>(
+        &self,
+        request: &RLMTaskRequest,
+        criteria: &SelectionCriteria,
+        policy: &RetryPolicy,
+        handler: F,
+    ) -> Result<RLMTaskResponse, FederationError>
+    where
+        F: Fn(String, RLMTaskRequest) -> Fut,
+        Fut: Future<Output = Result<RLMTaskResponse, FederationError>>,
+    {
+        request.validate()?;
+
+        let selector = AgentSelector::new(Arc::clone(&self.registry));
+        let mut criteria = criteria.clone();
+        let mut agent_id: Option<String> = None;
+        let mut last_failure: Option<Result<RLMTaskResponse, FederationError>> = None;
+        let max_attempts = policy.max_attempts.max(1);
+
+        for attempt in 0..max_attempts {
+            let current = match agent_id.take() {
+                Some(id) if !policy.reselect_agent => id,
+                _ => match selector.select_agent(&criteria).await {
+                    Ok(score) => score.agent_id,
+                    // Candidates exhausted: surface the last real failure if we have one
+                    Err(e) => return last_failure.unwrap_or(Err(e)),
+                },
+            };
+
+            match handler(current.clone(), request.clone()).await {
+                Ok(response) if response.metadata.success => return Ok(response),
+                outcome => {
+                    warn!(
+                        "RLM dispatch attempt {}/{} on agent {} failed",
+                        attempt + 1,
+                        max_attempts,
+                        current
+                    );
+                    last_failure = Some(outcome);
+                }
+            }
+
+            criteria.exclude_agents.push(current.clone());
+            agent_id = Some(current);
+
+            if attempt + 1 < max_attempts {
+                tokio::time::sleep(policy.delay(attempt)).await;
+            }
+        }
+
+        last_failure.unwrap_or(Err(FederationError::NoSuitableAgents))
     }
 
     /// Update task status
@@ -183,6 +860,68 @@ impl Orchestrator {
     }
 }
 
+/// Token budget for a request dispatched `remaining` before its deadline
+///
+/// Outside the pressure window the budget is unchanged; inside it shrinks in
+/// proportion to the time left, down to a small floor.
+fn deadline_max_tokens(max_tokens: usize, remaining: Duration) -> usize {
+    if remaining >= DEADLINE_PRESSURE_WINDOW {
+        return max_tokens;
+    }
+    let scaled = (max_tokens as u128 * remaining.as_millis()
+        / DEADLINE_PRESSURE_WINDOW.as_millis()) as usize;
+    scaled.max(MIN_DEADLINE_MAX_TOKENS.min(max_tokens))
+}
+
+/// Task delegation message handing `task` to `agent_id`
+///
+/// The message continues the task's trace in a new span.
+fn task_delegation_message(task: &FederationTask, agent_id: &str) -> FederationMessage {
+    let mut message = FederationMessage::new(
+        MessageType::TaskDelegation,
+        "coordinator".to_string(),
+        Some(agent_id.to_string()),
+        serde_json::to_string(task).unwrap_or_default(),
+        Some(serde_json::json!({
+            "task_id": task.id,
+            "priority": format!("{:?}", task.priority),
+        })),
+    );
+    message.trace_context = task.trace_context.as_ref().map(TraceContext::child);
+    message
+}
+
+/// Message telling `agent_id` to drop the delegated task `task_id`
+fn task_withdrawal_message(task_id: &str, agent_id: &str) -> FederationMessage {
+    FederationMessage::new(
+        MessageType::TaskWithdrawal,
+        "coordinator".to_string(),
+        Some(agent_id.to_string()),
+        task_id.to_string(),
+        Some(serde_json::json!({ "task_id": task_id })),
+    )
+}
+
+/// Message delivering a validated RLM `request` to `agent_id`
+fn rlm_request_message(agent_id: &str, request: &RLMTaskRequest) -> Result<FederationMessage, FederationError> {
+    request.validate()?;
+
+    let content = serde_json::to_string(request)
+        .map_err(|e| FederationError::SerializationError(e.to_string()))?;
+
+    Ok(FederationMessage::new(
+        MessageType::TaskDelegation,
+        "coordinator".to_string(),
+        Some(agent_id.to_string()),
+        content,
+        Some(serde_json::json!({
+            "workflow_id": request.context.workflow_id,
+            "depth": request.context.depth,
+            "message_type": format!("{:?}", request.message_type),
+        })),
+    ))
+}
+
 /// Helper function to get current timestamp
 fn get_timestamp() -> u64 {
     std::time::SystemTime::now()
@@ -190,3 +929,532 @@ fn get_timestamp() -> u64 {
         .unwrap_or_default()
         .as_secs()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{inbox_len, registry_with_workers, MockAgent};
+    use std::sync::Mutex;
+
+    #[tokio::test]
+    async fn test_dispatch_rejects_invalid_request() {
+        let orchestrator = Orchestrator::new(Arc::new(AgentRegistry::new()));
+        let request = RLMTaskRequest::new(String::new(), "workflow-1".to_string());
+
+        let result = orchestrator.dispatch_rlm_request("agent-1", &request).await;
+        assert!(matches!(result, Err(FederationError::ProtocolViolation(_))));
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_valid_request_to_unknown_agent() {
+        let orchestrator = Orchestrator::new(Arc::new(AgentRegistry::new()));
+        let request = RLMTaskRequest::new("Analyze".to_string(), "workflow-1".to_string());
+
+        let result = orchestrator.dispatch_rlm_request("agent-1", &request).await;
+        assert!(matches!(result, Err(FederationError::MessageDeliveryFailed(_))));
+    }
+
+    fn message_to(recipient: &str) -> FederationMessage {
+        FederationMessage::new(
+            MessageType::Status,
+            "coordinator".to_string(),
+            Some(recipient.to_string()),
+            "ping".to_string(),
+            None,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_message_delivered_once() {
+        let registry = registry_with_workers(&["agent-1"]).await;
+        let orchestrator = Orchestrator::new(Arc::clone(&registry));
+        let message = message_to("agent-1");
+
+        assert!(orchestrator.deliver_message(message.clone()).await.unwrap());
+        assert!(!orchestrator.deliver_message(message).await.unwrap());
+
+        assert_eq!(inbox_len(&registry, "agent-1").await, 1);
+        assert_eq!(orchestrator.replay_cache_size().await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_distinct_messages_all_delivered() {
+        let registry = registry_with_workers(&["agent-1"]).await;
+        let orchestrator = Orchestrator::new(Arc::clone(&registry));
+
+        for _ in 0..3 {
+            assert!(orchestrator.deliver_message(message_to("agent-1")).await.unwrap());
+        }
+
+        assert_eq!(inbox_len(&registry, "agent-1").await, 3);
+        assert_eq!(orchestrator.replay_cache_size().await, 3);
+    }
+
+    #[tokio::test]
+    async fn test_failed_delivery_can_be_retried() {
+        let registry = registry_with_workers(&["agent-1"]).await;
+        let orchestrator = Orchestrator::new(Arc::clone(&registry));
+
+        let result = orchestrator.deliver_message(message_to("missing")).await;
+        assert!(matches!(result, Err(FederationError::MessageDeliveryFailed(_))));
+        assert_eq!(orchestrator.replay_cache_size().await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_replay_ids_expire_after_window() {
+        let registry = registry_with_workers(&["agent-1"]).await;
+        let config = OrchestratorConfig::default().with_replay_window(Duration::from_millis(20));
+        let orchestrator = Arc::new(Orchestrator::with_config(Arc::clone(&registry), config));
+        let message = message_to("agent-1");
+
+        assert!(orchestrator.deliver_message(message.clone()).await.unwrap());
+        let health = orchestrator.spawn_health_task(Duration::from_millis(10));
+        tokio::time::sleep(Duration::from_millis(60)).await;
+
+        assert_eq!(orchestrator.replay_cache_size().await, 0);
+        // Outside the window the same replay ID is accepted again
+        assert!(orchestrator.deliver_message(message).await.unwrap());
+        assert_eq!(inbox_len(&registry, "agent-1").await, 2);
+        health.abort();
+    }
+
+    #[tokio::test]
+    async fn test_replayed_delegation_delivered_once() {
+        let registry = registry_with_workers(&["agent-1"]).await;
+        let orchestrator = Orchestrator::new(Arc::clone(&registry));
+        let task_id = orchestrator
+            .create_task("analysis".to_string(), "Analyze".to_string(), None, TaskPriority::Normal)
+            .await
+            .unwrap();
+        orchestrator.delegate_task(&task_id).await.unwrap();
+
+        // Replaying the delegation message is caught like any other replay
+        let delegation = inbox(&registry, "agent-1").await.remove(0);
+        assert!(!orchestrator.deliver_message(delegation).await.unwrap());
+
+        assert_eq!(inbox_len(&registry, "agent-1").await, 1);
+        assert_eq!(orchestrator.replay_cache_size().await, 1);
+    }
+
+    fn pending_task(id: &str) -> FederationTask {
+        FederationTask {
+            id: id.to_string(),
+            task_type: "analysis".to_string(),
+            content: "Analyze".to_string(),
+            metadata: None,
+            priority: TaskPriority::Normal,
+            status: TaskStatus::Pending,
+            assigned_to: None,
+            created_at: get_timestamp(),
+            updated_at: get_timestamp(),
+            kind: None,
+            trace_context: None,
+        }
+    }
+
+    /// `max_tokens` of the last request delivered to an agent
+    async fn delivered_max_tokens(registry: &AgentRegistry, agent_id: &str) -> usize {
+        let agent = registry.get_agent(agent_id).await.unwrap();
+        let agent = agent.read().await;
+        let mock = agent.as_any().downcast_ref::<MockAgent>().unwrap();
+        let message = mock.inbox.last().unwrap();
+        serde_json::from_str::<RLMTaskRequest>(&message.content).unwrap().max_tokens
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_deadline_reduces_max_tokens_when_close() {
+        let registry = registry_with_workers(&["agent-1"]).await;
+        let orchestrator = Orchestrator::new(Arc::clone(&registry));
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(60);
+        orchestrator.submit_task_with_deadline(pending_task("far"), deadline).await.unwrap();
+        orchestrator.submit_task_with_deadline(pending_task("near"), deadline).await.unwrap();
+        let request = RLMTaskRequest::new("Analyze".to_string(), "workflow-1".to_string());
+
+        orchestrator.dispatch_task_request("far", "agent-1", request.clone()).await.unwrap();
+        assert_eq!(delivered_max_tokens(&registry, "agent-1").await, 1024);
+
+        // 15 s left: half of the 30 s window, so half the tokens
+        tokio::time::advance(Duration::from_secs(45)).await;
+        orchestrator.dispatch_task_request("near", "agent-1", request).await.unwrap();
+        assert_eq!(delivered_max_tokens(&registry, "agent-1").await, 512);
+        assert_eq!(orchestrator.get_task_status("near").await.unwrap(), TaskStatus::Assigned);
+        assert_eq!(orchestrator.expired_tasks(), 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_expired_deadline_fails_task_before_dispatch() {
+        let registry = registry_with_workers(&["agent-1"]).await;
+        let orchestrator = Orchestrator::new(Arc::clone(&registry));
+        let mut events = orchestrator.subscribe();
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+        orchestrator.submit_task_with_deadline(pending_task("late"), deadline).await.unwrap();
+        orchestrator.submit_task_with_deadline(pending_task("delegated"), deadline).await.unwrap();
+
+        tokio::time::advance(Duration::from_secs(6)).await;
+        let request = RLMTaskRequest::new("Analyze".to_string(), "workflow-1".to_string());
+        let result = orchestrator.dispatch_task_request("late", "agent-1", request).await;
+        assert!(matches!(result, Err(FederationError::Timeout(msg)) if msg == "task deadline expired before dispatch"));
+        let result = orchestrator.delegate_task("delegated").await;
+        assert!(matches!(result, Err(FederationError::Timeout(_))));
+
+        assert_eq!(orchestrator.get_task_status("late").await.unwrap(), TaskStatus::Failed);
+        assert_eq!(orchestrator.expired_tasks(), 2);
+        assert_eq!(inbox_len(&registry, "agent-1").await, 0);
+        assert_eq!(
+            events.recv().await.unwrap(),
+            OrchestratorEvent::TaskExpired {
+                task_id: "late".to_string(),
+                expired_at: deadline,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_type_route_restricts_delegation() {
+        let registry = registry_with_workers(&["data-agent", "web-agent"]).await;
+        let orchestrator = Orchestrator::new(Arc::clone(&registry));
+        orchestrator
+            .set_type_route(FederationTaskType::DataAnalysis, vec!["data-agent".to_string()])
+            .await;
+
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(60);
+        for i in 0..4 {
+            let task = pending_task(&format!("data-{}", i)).with_task_type(FederationTaskType::DataAnalysis);
+            assert_eq!(task.task_type, "data_analysis");
+            orchestrator.submit_task_with_deadline(task, deadline).await.unwrap();
+            orchestrator.delegate_task(&format!("data-{}", i)).await.unwrap();
+        }
+
+        assert_eq!(inbox_len(&registry, "data-agent").await, 4);
+        assert_eq!(inbox_len(&registry, "web-agent").await, 0);
+        assert!(orchestrator
+            .list_tasks()
+            .await
+            .iter()
+            .all(|task| task.assigned_to.as_deref() == Some("data-agent")));
+    }
+
+    #[tokio::test]
+    async fn test_type_routes_only_apply_to_their_type() {
+        let registry = registry_with_workers(&["data-agent"]).await;
+        let orchestrator = Orchestrator::new(Arc::clone(&registry));
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(60);
+        orchestrator
+            .set_type_route(FederationTaskType::DataAnalysis, vec!["offline-agent".to_string()])
+            .await;
+
+        // No routed agent is registered, so nothing is eligible
+        let routed = pending_task("routed").with_task_type(FederationTaskType::DataAnalysis);
+        orchestrator.submit_task_with_deadline(routed, deadline).await.unwrap();
+        let result = orchestrator.delegate_task("routed").await;
+        assert!(matches!(result, Err(FederationError::NoSuitableAgents)));
+
+        // Other types and untyped tasks are unaffected
+        let research = pending_task("research").with_task_type(FederationTaskType::Research);
+        orchestrator.submit_task_with_deadline(research, deadline).await.unwrap();
+        orchestrator.delegate_task("research").await.unwrap();
+        orchestrator.submit_task_with_deadline(pending_task("untyped"), deadline).await.unwrap();
+        orchestrator.delegate_task("untyped").await.unwrap();
+
+        orchestrator.clear_type_routes().await;
+        orchestrator.delegate_task("routed").await.unwrap();
+        assert_eq!(inbox_len(&registry, "data-agent").await, 3);
+    }
+
+    #[test]
+    fn test_deadline_max_tokens_floor() {
+        assert_eq!(deadline_max_tokens(1024, Duration::from_secs(40)), 1024);
+        assert_eq!(deadline_max_tokens(1024, Duration::from_millis(10)), 64);
+        assert_eq!(deadline_max_tokens(32, Duration::from_millis(10)), 32);
+    }
+
+    #[test]
+    fn test_retry_policy_delay() {
+        let policy = RetryPolicy::default().with_backoff_ms(50);
+        assert_eq!(policy.delay(0), Duration::from_millis(50));
+        assert_eq!(policy.delay(1), Duration::from_millis(100));
+        assert_eq!(policy.delay(2), Duration::from_millis(200));
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_with_retry_reselects_agents() {
+        let registry = registry_with_workers(&["agent-1", "agent-2", "agent-3"]).await;
+        let orchestrator = Orchestrator::new(registry);
+        let request = RLMTaskRequest::new("Analyze".to_string(), "workflow-1".to_string());
+        let criteria = SelectionCriteria::new("analysis".to_string());
+        let policy = RetryPolicy::new(3).with_backoff_ms(1);
+        let attempted = Mutex::new(Vec::new());
+
+        let response = orchestrator
+            .dispatch_with_retry(&request, &criteria, &policy, |agent_id, request| {
+                let attempt = {
+                    let mut attempted = attempted.lock().unwrap();
+                    attempted.push(agent_id.clone());
+                    attempted.len()
+                };
+                async move {
+                    if attempt < 3 {
+                        Ok(RLMTaskResponse::failure(
+                            request.context.workflow_id,
+                            agent_id,
+                            "agent crashed".to_string(),
+                            5,
+                        ))
+                    } else {
+                        Ok(RLMTaskResponse::success(
+                            request.context.workflow_id,
+                            "done".to_string(),
+                            agent_id,
+                            5,
+                            10,
+                        ))
+                    }
+                }
+            })
+            .await
+            .unwrap();
+
+        let attempted = attempted.into_inner().unwrap();
+        assert_eq!(attempted.len(), 3);
+        assert_ne!(attempted[0], attempted[1]);
+        assert_ne!(attempted[1], attempted[2]);
+        assert_ne!(attempted[0], attempted[2]);
+        assert!(response.metadata.success);
+        assert_eq!(response.metadata.agent_id, attempted[2]);
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_with_retry_returns_last_failure() {
+        let registry = registry_with_workers(&["agent-1", "agent-2"]).await;
+        let orchestrator = Orchestrator::new(registry);
+        let request = RLMTaskRequest::new("Analyze".to_string(), "workflow-1".to_string());
+        let criteria = SelectionCriteria::new("analysis".to_string());
+        let policy = RetryPolicy::new(5).with_backoff_ms(1);
+
+        let result = orchestrator
+            .dispatch_with_retry(&request, &criteria, &policy, |agent_id, _| async move {
+                Err::<RLMTaskResponse, _>(FederationError::ExecutionError(agent_id))
+            })
+            .await;
+
+        // Only two distinct agents exist, so the loop stops once they are exhausted
+        assert!(matches!(result, Err(FederationError::ExecutionError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_with_retry_same_agent() {
+        let registry = registry_with_workers(&["agent-1", "agent-2"]).await;
+        let orchestrator = Orchestrator::new(registry);
+        let request = RLMTaskRequest::new("Analyze".to_string(), "workflow-1".to_string());
+        let criteria = SelectionCriteria::new("analysis".to_string());
+        let policy = RetryPolicy::new(3).with_backoff_ms(1).with_reselection(false);
+        let attempted = Mutex::new(Vec::new());
+
+        let result = orchestrator
+            .dispatch_with_retry(&request, &criteria, &policy, |agent_id, _| {
+                attempted.lock().unwrap().push(agent_id.clone());
+                async move { Err::<RLMTaskResponse, _>(FederationError::ExecutionError(agent_id)) }
+            })
+            .await;
+
+        assert!(result.is_err());
+        let attempted = attempted.into_inner().unwrap();
+        assert_eq!(attempted.len(), 3);
+        assert!(attempted.iter().all(|id| id == &attempted[0]));
+    }
+
+    fn heartbeat_from(agent_id: &str) -> HeartbeatRequest {
+        HeartbeatRequest {
+            agent_id: agent_id.to_string(),
+            timestamp: chrono::Utc::now(),
+            load: 0.5,
+            capabilities: vec!["analysis".to_string()],
+        }
+    }
+
+    /// Messages received so far by a mock agent
+    async fn inbox(registry: &AgentRegistry, agent_id: &str) -> Vec<FederationMessage> {
+        let agent = registry.get_agent(agent_id).await.unwrap();
+        let agent = agent.read().await;
+        agent.as_any().downcast_ref::<MockAgent>().unwrap().inbox.clone()
+    }
+
+    #[tokio::test]
+    async fn test_trace_context_propagates_to_sub_task_messages() {
+        let registry = registry_with_workers(&["agent-1"]).await;
+        let orchestrator = Orchestrator::new(Arc::clone(&registry));
+        let parent = TraceContext::from_w3c_header(
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+        )
+        .unwrap();
+        let incoming = FederationMessage::new(
+            MessageType::TaskDelegation,
+            "gateway".to_string(),
+            None,
+            "Summarize the report".to_string(),
+            Some(serde_json::json!({ "task_type": "research" })),
+        )
+        .with_trace_context(parent.clone());
+
+        let delegated = orchestrator
+            .create_task_from_message(&incoming, TaskPriority::High)
+            .await
+            .unwrap();
+        let dispatched = orchestrator
+            .create_task_from_message(&incoming, TaskPriority::Normal)
+            .await
+            .unwrap();
+        orchestrator.delegate_task(&delegated).await.unwrap();
+        let request = RLMTaskRequest::new("Analyze".to_string(), "workflow-1".to_string());
+        orchestrator.dispatch_task_request(&dispatched, "agent-1", request.clone()).await.unwrap();
+        // Requests outside a task have no trace to continue
+        orchestrator.dispatch_rlm_request("agent-1", &request).await.unwrap();
+
+        let inbox = inbox(&registry, "agent-1").await;
+        assert_eq!(inbox.len(), 3);
+        for message in &inbox[..2] {
+            let ctx = message.extract_trace_context().unwrap();
+            assert_eq!(ctx.trace_id, parent.trace_id);
+            assert_ne!(ctx.span_id, parent.span_id);
+            assert!(ctx.is_sampled());
+        }
+        assert!(inbox[2].extract_trace_context().is_none());
+
+        let task: FederationTask = serde_json::from_str(&inbox[0].content).unwrap();
+        assert_eq!(task.task_type, "research");
+        assert_eq!(task.trace_context, Some(parent));
+    }
+
+    async fn report_load(orchestrator: &Orchestrator, agent_id: &str, load: f64) {
+        let heartbeat = HeartbeatRequest {
+            load,
+            ..heartbeat_from(agent_id)
+        };
+        assert!(orchestrator.handle_heartbeat(heartbeat).await.acknowledged);
+    }
+
+    /// Submit and delegate `count` tasks, which all go to the only worker
+    async fn queue_tasks(orchestrator: &Orchestrator, count: usize) {
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(60);
+        for i in 0..count {
+            let id = format!("task-{}", i);
+            orchestrator.submit_task_with_deadline(pending_task(&id), deadline).await.unwrap();
+            orchestrator.delegate_task(&id).await.unwrap();
+        }
+    }
+
+    fn assigned_counts(tasks: &[FederationTask]) -> HashMap<String, usize> {
+        let mut counts = HashMap::new();
+        for task in tasks {
+            *counts.entry(task.assigned_to.clone().unwrap()).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    #[tokio::test]
+    async fn test_dynamic_rebalance_moves_queued_tasks() {
+        let registry = registry_with_workers(&["busy"]).await;
+        let orchestrator = Orchestrator::new(Arc::clone(&registry));
+        queue_tasks(&orchestrator, 6).await;
+        orchestrator.update_task_status("task-5", TaskStatus::InProgress).await.unwrap();
+
+        for id in ["idle-1", "idle-2", "steady"] {
+            registry
+                .register_agent(Arc::new(RwLock::new(MockAgent::new(id, FederationRole::Worker))))
+                .await
+                .unwrap();
+        }
+        report_load(&orchestrator, "busy", 0.95).await;
+        report_load(&orchestrator, "idle-1", 0.1).await;
+        report_load(&orchestrator, "idle-2", 0.2).await;
+        report_load(&orchestrator, "steady", 0.5).await;
+
+        assert_eq!(orchestrator.dynamic_rebalance().await, 5);
+
+        let tasks = orchestrator.list_tasks().await;
+        let counts = assigned_counts(&tasks);
+        assert_eq!(counts.get("idle-1"), Some(&3));
+        assert_eq!(counts.get("idle-2"), Some(&2));
+        // The running task stays where it is
+        assert_eq!(counts.get("busy"), Some(&1));
+        assert_eq!(counts.get("steady"), None);
+        assert_eq!(inbox_len(&registry, "idle-1").await, 3);
+        assert_eq!(inbox_len(&registry, "idle-2").await, 2);
+        // The busy agent is told to drop each moved task
+        let withdrawn: Vec<String> = inbox(&registry, "busy")
+            .await
+            .into_iter()
+            .filter(|message| matches!(message.message_type, MessageType::TaskWithdrawal))
+            .map(|message| message.content)
+            .collect();
+        assert_eq!(withdrawn, (0..5).map(|i| format!("task-{}", i)).collect::<Vec<_>>());
+
+        // Nothing is left to move
+        assert_eq!(orchestrator.dynamic_rebalance().await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_dynamic_rebalance_needs_an_underloaded_agent() {
+        let registry = registry_with_workers(&["busy"]).await;
+        let orchestrator = Orchestrator::new(Arc::clone(&registry));
+        queue_tasks(&orchestrator, 2).await;
+        registry
+            .register_agent(Arc::new(RwLock::new(MockAgent::new("steady", FederationRole::Worker))))
+            .await
+            .unwrap();
+        report_load(&orchestrator, "busy", 0.9).await;
+        report_load(&orchestrator, "steady", 0.5).await;
+
+        assert_eq!(orchestrator.dynamic_rebalance().await, 0);
+        assert_eq!(assigned_counts(&orchestrator.list_tasks().await).get("busy"), Some(&2));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_health_task_rebalances_automatically() {
+        let registry = registry_with_workers(&["busy"]).await;
+        let config = OrchestratorConfig::default()
+            .with_auto_rebalance_interval(Duration::from_millis(10));
+        let orchestrator = Arc::new(Orchestrator::with_config(Arc::clone(&registry), config));
+        queue_tasks(&orchestrator, 3).await;
+        registry
+            .register_agent(Arc::new(RwLock::new(MockAgent::new("idle", FederationRole::Worker))))
+            .await
+            .unwrap();
+        report_load(&orchestrator, "busy", 0.9).await;
+        report_load(&orchestrator, "idle", 0.0).await;
+
+        let health = orchestrator.spawn_health_task(Duration::from_secs(60));
+        // Let the health task start its timers before moving the clock
+        tokio::task::yield_now().await;
+        tokio::time::advance(Duration::from_millis(15)).await;
+        // Let the health task finish the rebalance it just started
+        for _ in 0..100 {
+            if assigned_counts(&orchestrator.list_tasks().await).get("idle") == Some(&3) {
+                break;
+            }
+            tokio::task::yield_now().await;
+        }
+
+        assert_eq!(assigned_counts(&orchestrator.list_tasks().await).get("idle"), Some(&3));
+        health.abort();
+    }
+
+    #[tokio::test]
+    async fn test_handle_heartbeat() {
+        let registry = registry_with_workers(&["agent-1"]).await;
+        let config = OrchestratorConfig::default()
+            .with_orchestrator_id("orch-1")
+            .with_heartbeat_interval(Duration::from_secs(10));
+        let orchestrator = Orchestrator::with_config(registry, config);
+
+        let response = orchestrator.handle_heartbeat(heartbeat_from("agent-1")).await;
+        assert!(response.acknowledged);
+        assert_eq!(response.orchestrator_id, "orch-1");
+        assert_eq!(response.next_heartbeat_in_ms, 10_000);
+        assert_eq!(orchestrator.last_heartbeat("agent-1").await.unwrap().load, 0.5);
+
+        let response = orchestrator.handle_heartbeat(heartbeat_from("ghost")).await;
+        assert!(!response.acknowledged);
+        assert!(orchestrator.last_heartbeat("ghost").await.is_none());
+    }
+}
@@ -0,0 +1,180 @@
+//! Heartbeat protocol for agent liveness signaling
+//!
+//! Agents periodically POST a [`HeartbeatRequest`] to the orchestrator's
+//! `/heartbeat` endpoint. The orchestrator acknowledges registered agents
+//! and tells them when to send the next heartbeat.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+
+use crate::{AgentRegistry, FederationError};
+
+/// Liveness signal sent by an agent to the orchestrator
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeartbeatRequest {
+    /// ID of the agent sending the heartbeat
+    pub agent_id: String,
+    /// When the heartbeat was sent
+    pub timestamp: DateTime<Utc>,
+    /// Current agent load, from 0.0 (idle) to 1.0 (saturated)
+    pub load: f64,
+    /// Capabilities the agent currently offers
+    pub capabilities: Vec<String>,
+}
+
+/// Orchestrator reply to a heartbeat
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HeartbeatResponse {
+    /// Whether the orchestrator recognized the agent
+    pub acknowledged: bool,
+    /// ID of the orchestrator that handled the heartbeat
+    pub orchestrator_id: String,
+    /// Milliseconds until the agent should send its next heartbeat
+    pub next_heartbeat_in_ms: u64,
+}
+
+/// Sends heartbeats on behalf of a registered agent
+pub struct HeartbeatProtocol {
+    agent_id: String,
+    registry: Arc<AgentRegistry>,
+    client: reqwest::Client,
+    load: Mutex<f64>,
+    capabilities: Vec<String>,
+}
+
+impl HeartbeatProtocol {
+    /// Creates a heartbeat protocol for `agent_id`
+    pub fn new(agent_id: String, registry: Arc<AgentRegistry>) -> Self {
+        Self {
+            agent_id,
+            registry,
+            client: reqwest::Client::new(),
+            load: Mutex::new(0.0),
+            capabilities: Vec::new(),
+        }
+    }
+
+    /// Sets the capabilities advertised in each heartbeat
+    pub fn with_capabilities(mut self, capabilities: Vec<String>) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    /// ID of the agent this protocol signals for
+    pub fn agent_id(&self) -> &str {
+        &self.agent_id
+    }
+
+    /// Updates the load reported in subsequent heartbeats, clamped to 0.0..=1.0
+    pub fn set_load(&self, load: f64) {
+        *self.load.lock().unwrap() = load.clamp(0.0, 1.0);
+    }
+
+    /// Load reported in the next heartbeat
+    pub fn load(&self) -> f64 {
+        *self.load.lock().unwrap()
+    }
+
+    /// Builds the heartbeat for the current agent state
+    pub fn heartbeat(&self) -> HeartbeatRequest {
+        HeartbeatRequest {
+            agent_id: self.agent_id.clone(),
+            timestamp: Utc::now(),
+            load: self.load(),
+            capabilities: self.capabilities.clone(),
+        }
+    }
+
+    /// Sends a heartbeat to the orchestrator at `orchestrator_url`
+    ///
+    /// # Returns
+    /// - `Ok(HeartbeatResponse)` with the orchestrator's reply
+    /// - `Err(FederationError::AgentNotFound)` if the agent is not in the local registry
+    /// - `Err(FederationError::NetworkError)` if the request fails or is rejected
+    pub async fn send_heartbeat(&self, orchestrator_url: &str) -> Result<HeartbeatResponse, FederationError> {
+        if self.registry.get_agent(&self.agent_id).await.is_none() {
+            return Err(FederationError::AgentNotFound(self.agent_id.clone()));
+        }
+
+        let url = format!("{}/heartbeat", orchestrator_url.trim_end_matches('/'));
+        let response = self
+            .client
+            .post(&url)
+            .json(&self.heartbeat())
+            .send()
+            .await
+            .map_err(|e| FederationError::NetworkError(e.to_string()))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(FederationError::NetworkError(format!(
+                "heartbeat rejected with {}: {}",
+                status, body
+            )));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| FederationError::DeserializationError(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::registry_with_workers;
+    use wiremock::matchers::{body_partial_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_send_heartbeat_posts_json() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/heartbeat"))
+            .and(body_partial_json(serde_json::json!({
+                "agent_id": "worker-1",
+                "load": 0.25,
+                "capabilities": ["code_review"],
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "acknowledged": true,
+                "orchestrator_id": "orch-1",
+                "next_heartbeat_in_ms": 5000,
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let registry = registry_with_workers(&["worker-1"]).await;
+        let protocol = HeartbeatProtocol::new("worker-1".to_string(), registry)
+            .with_capabilities(vec!["code_review".to_string()]);
+        protocol.set_load(0.25);
+
+        let response = protocol.send_heartbeat(&format!("{}/", server.uri())).await.unwrap();
+        assert_eq!(
+            response,
+            HeartbeatResponse {
+                acknowledged: true,
+                orchestrator_id: "orch-1".to_string(),
+                next_heartbeat_in_ms: 5000,
+            }
+        );
+
+        let requests = server.received_requests().await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        let timestamp = body["timestamp"].as_str().unwrap();
+        assert!(timestamp.parse::<DateTime<Utc>>().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_send_heartbeat_for_unregistered_agent() {
+        let registry = registry_with_workers(&["worker-1"]).await;
+        let protocol = HeartbeatProtocol::new("ghost".to_string(), registry);
+
+        let result = protocol.send_heartbeat("http://127.0.0.1:1").await;
+        assert!(matches!(result, Err(FederationError::AgentNotFound(id)) if id == "ghost"));
+    }
+}
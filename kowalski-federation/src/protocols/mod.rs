@@ -3,9 +3,14 @@
 /// Defines message types, request/response structures, and protocols
 /// for Recursive Language Model (RLM) workflows in federated settings.
 
+pub mod heartbeat;
+pub mod prompt_template;
 pub mod rlm_protocol;
 
+pub use heartbeat::{HeartbeatProtocol, HeartbeatRequest, HeartbeatResponse};
+pub use prompt_template::{PromptTemplate, PromptTemplateRegistry};
+
 pub use rlm_protocol::{
-    RLMTaskRequest, RLMTaskResponse, RLMMessageType, RLMContext,
+    ConfidenceInterval, RLMTaskRequest, RLMTaskResponse, RLMMessageType, RLMContext,
     RLMRefinementData, RLMExecutionMetadata,
 };
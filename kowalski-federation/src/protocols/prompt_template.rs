@@ -0,0 +1,257 @@
+//! Prompt templates for building RLM task requests
+//!
+//! Templates contain named `{placeholder}`s that are filled in from a map of
+//! variables. Use `{{` and `}}` for literal braces.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::FederationError;
+
+/// A named prompt template for a task type
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptTemplate {
+    /// Unique template name
+    pub name: String,
+    /// Task type this template is intended for
+    pub task_type: String,
+    /// Template text with `{placeholder}`s
+    pub template: String,
+}
+
+/// Piece of a parsed template
+enum Segment<'a> {
+    Text(&'a str),
+    Placeholder(&'a str),
+}
+
+impl PromptTemplate {
+    /// Creates a new prompt template
+    pub fn new(name: &str, task_type: &str, template: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            task_type: task_type.to_string(),
+            template: template.to_string(),
+        }
+    }
+
+    /// Names of the placeholders in the template, in order of first use
+    pub fn placeholders(&self) -> Result<Vec<String>, FederationError> {
+        let mut names: Vec<String> = Vec::new();
+        for segment in self.segments()? {
+            if let Segment::Placeholder(name) = segment {
+                if !names.iter().any(|n| n == name) {
+                    names.push(name.to_string());
+                }
+            }
+        }
+        Ok(names)
+    }
+
+    /// Renders the template, substituting every placeholder from `vars`
+    ///
+    /// # Returns
+    /// - `Ok(String)` with the rendered prompt
+    /// - `Err(FederationError::TemplateError)` if a variable is missing or the template is malformed
+    pub fn render(&self, vars: &HashMap<String, String>) -> Result<String, FederationError> {
+        let mut rendered = String::with_capacity(self.template.len());
+        for segment in self.segments()? {
+            match segment {
+                Segment::Text(text) => rendered.push_str(text),
+                Segment::Placeholder(name) => {
+                    let value = vars.get(name).ok_or_else(|| {
+                        FederationError::TemplateError(format!(
+                            "template '{}' is missing variable '{}'",
+                            self.name, name
+                        ))
+                    })?;
+                    rendered.push_str(value);
+                }
+            }
+        }
+        Ok(rendered)
+    }
+
+    fn segments(&self) -> Result<Vec<Segment<'_>>, FederationError> {
+        let template = self.template.as_str();
+        let mut segments = Vec::new();
+        let mut rest = template;
+
+        while let Some(pos) = rest.find(['{', '}']) {
+            if pos > 0 {
+                segments.push(Segment::Text(&rest[..pos]));
+            }
+            let tail = &rest[pos..];
+
+            if let Some(after) = tail.strip_prefix("{{") {
+                segments.push(Segment::Text("{"));
+                rest = after;
+            } else if let Some(after) = tail.strip_prefix("}}") {
+                segments.push(Segment::Text("}"));
+                rest = after;
+            } else if let Some(inner) = tail.strip_prefix('{') {
+                let end = inner.find('}').ok_or_else(|| self.malformed("unclosed '{'"))?;
+                let name = inner[..end].trim();
+                if name.is_empty() || name.contains('{') {
+                    return Err(self.malformed("invalid placeholder name"));
+                }
+                segments.push(Segment::Placeholder(name));
+                rest = &inner[end + 1..];
+            } else {
+                return Err(self.malformed("unmatched '}'"));
+            }
+        }
+
+        if !rest.is_empty() {
+            segments.push(Segment::Text(rest));
+        }
+        Ok(segments)
+    }
+
+    fn malformed(&self, reason: &str) -> FederationError {
+        FederationError::TemplateError(format!("template '{}' is malformed: {}", self.name, reason))
+    }
+}
+
+/// Registry of prompt templates keyed by name
+#[derive(Debug, Clone, Default)]
+pub struct PromptTemplateRegistry {
+    templates: HashMap<String, PromptTemplate>,
+}
+
+impl PromptTemplateRegistry {
+    /// Creates an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a registry with a template for each built-in task type
+    pub fn with_defaults() -> Self {
+        let mut registry = Self::new();
+        registry.register(PromptTemplate::new(
+            "data_analysis",
+            "data_analysis",
+            "Analyze the following data and report key insights.\n\nData:\n{data}",
+        ));
+        registry.register(PromptTemplate::new(
+            "code_review",
+            "code_review",
+            "Review the following {language} code for bugs, style and performance issues.\n\n{code}",
+        ));
+        registry.register(PromptTemplate::new(
+            "web_search",
+            "web_search",
+            "Search the web for: {query}\nSummarize the most relevant findings.",
+        ));
+        registry.register(PromptTemplate::new(
+            "research",
+            "research",
+            "Research the topic '{topic}' and summarize the current state of knowledge.",
+        ));
+        registry
+    }
+
+    /// Adds a template, replacing any existing template with the same name
+    pub fn register(&mut self, template: PromptTemplate) {
+        self.templates.insert(template.name.clone(), template);
+    }
+
+    /// Gets a template by name
+    pub fn get(&self, name: &str) -> Option<&PromptTemplate> {
+        self.templates.get(name)
+    }
+
+    /// Finds a template for a task type
+    ///
+    /// A template named after the task type wins; otherwise any template
+    /// registered for that task type is returned.
+    pub fn for_task_type(&self, task_type: &str) -> Option<&PromptTemplate> {
+        self.templates
+            .get(task_type)
+            .filter(|t| t.task_type == task_type)
+            .or_else(|| self.templates.values().find(|t| t.task_type == task_type))
+    }
+
+    /// Renders the named template
+    pub fn render(&self, name: &str, vars: &HashMap<String, String>) -> Result<String, FederationError> {
+        self.get(name)
+            .ok_or_else(|| FederationError::TemplateError(format!("unknown template '{}'", name)))?
+            .render(vars)
+    }
+
+    /// Number of registered templates
+    pub fn len(&self) -> usize {
+        self.templates.len()
+    }
+
+    /// Returns true if no templates are registered
+    pub fn is_empty(&self) -> bool {
+        self.templates.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_render_with_all_variables() {
+        let template = PromptTemplate::new(
+            "review",
+            "code_review",
+            "Review this {language} code:\n{code}\nFocus on {language} idioms.",
+        );
+
+        let rendered = template
+            .render(&vars(&[("language", "Rust"), ("code", "fn main() {}")]))
+            .unwrap();
+        assert_eq!(
+            rendered,
+            "Review this Rust code:\nfn main() {}\nFocus on Rust idioms."
+        );
+        assert_eq!(template.placeholders().unwrap(), vec!["language", "code"]);
+    }
+
+    #[test]
+    fn test_render_missing_variable() {
+        let template = PromptTemplate::new("review", "code_review", "Review {language} code: {code}");
+
+        let result = template.render(&vars(&[("language", "Rust")]));
+        match result {
+            Err(FederationError::TemplateError(msg)) => assert!(msg.contains("code")),
+            other => panic!("expected TemplateError, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_escaped_and_malformed_braces() {
+        let template = PromptTemplate::new("json", "general", "Return {{\"answer\": {answer}}}");
+        assert_eq!(
+            template.render(&vars(&[("answer", "42")])).unwrap(),
+            "Return {\"answer\": 42}"
+        );
+
+        let unclosed = PromptTemplate::new("bad", "general", "Hello {name");
+        assert!(matches!(
+            unclosed.render(&vars(&[("name", "x")])),
+            Err(FederationError::TemplateError(_))
+        ));
+    }
+
+    #[test]
+    fn test_select_template_by_task_type() {
+        let mut registry = PromptTemplateRegistry::with_defaults();
+        registry.register(PromptTemplate::new("summarize", "summarization", "Summarize: {text}"));
+
+        assert_eq!(registry.for_task_type("code_review").unwrap().name, "code_review");
+        assert_eq!(registry.for_task_type("summarization").unwrap().name, "summarize");
+        assert!(registry.for_task_type("unknown").is_none());
+    }
+}
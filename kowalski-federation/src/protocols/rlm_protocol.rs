@@ -1,3 +1,5 @@
+use crate::FederationError;
+use super::PromptTemplateRegistry;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -34,6 +36,9 @@ pub struct RLMContext {
     pub accumulated_results: String,
     /// Custom metadata from parent agent
     pub metadata: HashMap<String, serde_json::Value>,
+    /// Token budget for accumulated results before they should be folded
+    #[serde(default)]
+    pub max_accumulated_tokens: Option<usize>,
 }
 
 impl RLMContext {
@@ -46,9 +51,16 @@ impl RLMContext {
             max_depth: 3,
             accumulated_results: String::new(),
             metadata: HashMap::new(),
+            max_accumulated_tokens: None,
         }
     }
 
+    /// Sets the token budget for accumulated results
+    pub fn with_max_accumulated_tokens(mut self, max_tokens: usize) -> Self {
+        self.max_accumulated_tokens = Some(max_tokens);
+        self
+    }
+
     /// Creates a child context for recursive delegation
     pub fn create_child(&self) -> Self {
         let mut child = Self::new(self.workflow_id.clone());
@@ -56,6 +68,7 @@ impl RLMContext {
         child.depth = self.depth + 1;
         child.max_depth = self.max_depth;
         child.metadata = self.metadata.clone();
+        child.max_accumulated_tokens = self.max_accumulated_tokens;
         child
     }
 
@@ -150,6 +163,21 @@ impl RLMTaskRequest {
         }
     }
 
+    /// Creates a request whose task is rendered from a registered prompt template
+    ///
+    /// # Returns
+    /// - `Ok(Self)` with the rendered task
+    /// - `Err(FederationError::TemplateError)` if the template is unknown or a variable is missing
+    pub fn from_template(
+        templates: &PromptTemplateRegistry,
+        name: &str,
+        vars: &HashMap<String, String>,
+        workflow_id: String,
+    ) -> Result<Self, FederationError> {
+        let task = templates.render(name, vars)?;
+        Ok(Self::new(task, workflow_id))
+    }
+
     /// Creates an execution step request
     pub fn execute_step(mut self) -> Self {
         self.message_type = RLMMessageType::ExecuteStep;
@@ -180,6 +208,58 @@ impl RLMTaskRequest {
         self.max_tokens = max_tokens;
         self
     }
+
+    /// Validates the request before it is dispatched to an agent
+    ///
+    /// # Returns
+    /// - `Ok(())` if the request is well-formed
+    /// - `Err(FederationError::ProtocolViolation)` describing the first problem found
+    pub fn validate(&self) -> Result<(), FederationError> {
+        if self.task.trim().is_empty() {
+            return Err(FederationError::ProtocolViolation(
+                "RLM task must not be empty".to_string(),
+            ));
+        }
+
+        if !(0.0..=1.0).contains(&self.temperature) {
+            return Err(FederationError::ProtocolViolation(format!(
+                "temperature must be within [0.0, 1.0], got {}",
+                self.temperature
+            )));
+        }
+
+        if self.max_tokens == 0 {
+            return Err(FederationError::ProtocolViolation(
+                "max_tokens must be > 0".to_string(),
+            ));
+        }
+
+        if self.context.max_depth == 0 {
+            return Err(FederationError::ProtocolViolation(
+                "context max_depth must be > 0".to_string(),
+            ));
+        }
+
+        if self.context.depth > self.context.max_depth {
+            return Err(FederationError::ProtocolViolation(format!(
+                "context depth {} exceeds max_depth {}",
+                self.context.depth, self.context.max_depth
+            )));
+        }
+
+        if self.context.workflow_id.trim().is_empty() {
+            return Err(FederationError::ProtocolViolation(
+                "context workflow_id must not be empty".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Returns true if the request passes validation
+    pub fn is_valid(&self) -> bool {
+        self.validate().is_ok()
+    }
 }
 
 /// RLM task response from agent
@@ -202,8 +282,79 @@ pub struct RLMTaskResponse {
     pub context: RLMContext,
     /// Whether the agent suggests further refinement
     pub ready_for_refinement: bool,
-    /// Confidence score (0.0-1.0) in the result
-    pub confidence: f32,
+    /// Confidence (0.0-1.0) in the result
+    pub confidence: ConfidenceInterval,
+}
+
+/// Confidence in a result, as a point estimate with bounds
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ConfidenceInterval {
+    /// Lower bound (0.0-1.0)
+    pub lower: f32,
+    /// Point estimate
+    pub point: f32,
+    /// Upper bound (0.0-1.0)
+    pub upper: f32,
+    /// Probability that the true value lies within the bounds, e.g. 0.95
+    pub confidence_level: f32,
+}
+
+impl ConfidenceInterval {
+    /// Creates the interval `point ± margin`
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FederationError::ConfigurationError`] unless
+    /// `0.0 <= lower <= point <= upper <= 1.0` and the confidence level
+    /// lies in `(0.0, 1.0]`.
+    pub fn new(point: f32, margin: f32, confidence_level: f32) -> Result<Self, FederationError> {
+        if !(point.is_finite() && margin.is_finite() && confidence_level.is_finite()) {
+            return Err(FederationError::ConfigurationError(format!(
+                "Confidence interval values must be finite (point {}, margin {}, level {})",
+                point, margin, confidence_level
+            )));
+        }
+
+        let lower = point - margin;
+        let upper = point + margin;
+
+        if lower < 0.0 {
+            return Err(FederationError::ConfigurationError(format!(
+                "Confidence interval lower bound {} is below 0.0",
+                lower
+            )));
+        }
+        if upper > 1.0 {
+            return Err(FederationError::ConfigurationError(format!(
+                "Confidence interval upper bound {} is above 1.0",
+                upper
+            )));
+        }
+        if !(lower <= point && point <= upper) {
+            return Err(FederationError::ConfigurationError(format!(
+                "Confidence point {} is outside [{}, {}]",
+                point, lower, upper
+            )));
+        }
+        if !(confidence_level > 0.0 && confidence_level <= 1.0) {
+            return Err(FederationError::ConfigurationError(format!(
+                "Confidence level {} is outside (0.0, 1.0]",
+                confidence_level
+            )));
+        }
+
+        Ok(Self {
+            lower,
+            point,
+            upper,
+            confidence_level,
+        })
+    }
+
+    /// Returns true if even the lower bound reaches `threshold`
+    pub fn is_high_confidence(&self, threshold: f32) -> bool {
+        self.lower >= threshold
+    }
 }
 
 impl RLMTaskResponse {
@@ -230,7 +381,8 @@ impl RLMTaskResponse {
             },
             context: RLMContext::new(workflow_id_clone),
             ready_for_refinement: false,
-            confidence: 0.75,
+            confidence: ConfidenceInterval::new(0.75, 0.25, 0.95)
+                .expect("default confidence interval is valid"),
         }
     }
 
@@ -256,7 +408,8 @@ impl RLMTaskResponse {
             },
             context: RLMContext::new(workflow_id_clone),
             ready_for_refinement: false,
-            confidence: 0.0,
+            confidence: ConfidenceInterval::new(0.0, 0.0, 0.95)
+                .expect("zero confidence interval is valid"),
         }
     }
 
@@ -267,9 +420,9 @@ impl RLMTaskResponse {
         self
     }
 
-    /// Sets confidence score
-    pub fn with_confidence(mut self, confidence: f32) -> Self {
-        self.confidence = confidence.clamp(0.0, 1.0);
+    /// Sets the confidence interval
+    pub fn with_confidence(mut self, confidence: ConfidenceInterval) -> Self {
+        self.confidence = confidence;
         self
     }
 
@@ -389,15 +542,71 @@ mod tests {
             300,
         )
         .mark_recursive(vec!["child-1".to_string()])
-        .with_confidence(0.95)
+        .with_confidence(ConfidenceInterval::new(0.9, 0.05, 0.95).unwrap())
         .mark_ready_for_refinement();
 
         assert!(response.used_recursion);
         assert_eq!(response.child_agents.len(), 1);
-        assert_eq!(response.confidence, 0.95);
+        assert_eq!(response.confidence.point, 0.9);
         assert!(response.ready_for_refinement);
     }
 
+    #[test]
+    fn test_default_confidence_intervals() {
+        let success = RLMTaskResponse::success(
+            "workflow-1".to_string(),
+            "Result".to_string(),
+            "agent-1".to_string(),
+            100,
+            300,
+        );
+        assert_eq!(success.confidence, ConfidenceInterval::new(0.75, 0.25, 0.95).unwrap());
+        assert_eq!((success.confidence.lower, success.confidence.upper), (0.5, 1.0));
+
+        let failure = RLMTaskResponse::failure(
+            "workflow-1".to_string(),
+            "agent-1".to_string(),
+            "boom".to_string(),
+            100,
+        );
+        assert_eq!(failure.confidence.point, 0.0);
+        assert!(!failure.confidence.is_high_confidence(0.1));
+    }
+
+    #[test]
+    fn test_confidence_interval_validation() {
+        let interval = ConfidenceInterval::new(0.8, 0.1, 0.95).unwrap();
+        assert!((interval.lower - 0.7).abs() < 1e-6);
+        assert!((interval.upper - 0.9).abs() < 1e-6);
+        assert!(interval.is_high_confidence(0.7));
+        assert!(!interval.is_high_confidence(0.75));
+
+        // Edges are allowed
+        assert!(ConfidenceInterval::new(0.0, 0.0, 1.0).is_ok());
+        assert!(ConfidenceInterval::new(0.5, 0.5, 0.9).is_ok());
+
+        let invalid = [
+            (0.1, 0.2, 0.95),      // lower below 0.0
+            (0.9, 0.2, 0.95),      // upper above 1.0
+            (0.5, -0.1, 0.95),     // negative margin puts the point outside
+            (f32::NAN, 0.1, 0.95), // not a number
+            (0.5, 0.1, 0.0),       // confidence level too low
+            (0.5, 0.1, 1.5),       // confidence level too high
+        ];
+        for (point, margin, level) in invalid {
+            assert!(
+                matches!(
+                    ConfidenceInterval::new(point, margin, level),
+                    Err(FederationError::ConfigurationError(_))
+                ),
+                "({}, {}, {}) should be rejected",
+                point,
+                margin,
+                level
+            );
+        }
+    }
+
     #[test]
     fn test_refinement_data() {
         let refinement = RLMRefinementData {
@@ -441,4 +650,96 @@ mod tests {
 
         assert_eq!(request.temperature, 1.0);
     }
+
+    fn assert_protocol_violation(request: &RLMTaskRequest) {
+        assert!(!request.is_valid());
+        match request.validate() {
+            Err(FederationError::ProtocolViolation(_)) => {}
+            other => panic!("Expected ProtocolViolation, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_validate_ok() {
+        let request = RLMTaskRequest::new("Test".to_string(), "workflow-1".to_string());
+        assert!(request.validate().is_ok());
+        assert!(request.is_valid());
+    }
+
+    #[test]
+    fn test_validate_empty_task() {
+        let request = RLMTaskRequest::new("   ".to_string(), "workflow-1".to_string());
+        assert_protocol_violation(&request);
+    }
+
+    #[test]
+    fn test_validate_temperature_out_of_range() {
+        let mut request = RLMTaskRequest::new("Test".to_string(), "workflow-1".to_string());
+        request.temperature = 1.5;
+        assert_protocol_violation(&request);
+
+        request.temperature = -0.1;
+        assert_protocol_violation(&request);
+
+        request.temperature = f32::NAN;
+        assert_protocol_violation(&request);
+    }
+
+    #[test]
+    fn test_validate_zero_max_tokens() {
+        let request = RLMTaskRequest::new("Test".to_string(), "workflow-1".to_string())
+            .with_max_tokens(0);
+        assert_protocol_violation(&request);
+    }
+
+    #[test]
+    fn test_validate_zero_max_depth() {
+        let mut request = RLMTaskRequest::new("Test".to_string(), "workflow-1".to_string());
+        request.context.max_depth = 0;
+        assert_protocol_violation(&request);
+    }
+
+    #[test]
+    fn test_validate_depth_exceeds_max() {
+        let mut request = RLMTaskRequest::new("Test".to_string(), "workflow-1".to_string());
+        request.context.depth = request.context.max_depth + 1;
+        assert_protocol_violation(&request);
+
+        // Sitting exactly at max depth is still a valid (terminal) request
+        request.context.depth = request.context.max_depth;
+        assert!(request.is_valid());
+    }
+
+    #[test]
+    fn test_validate_empty_workflow_id() {
+        let request = RLMTaskRequest::new("Test".to_string(), String::new());
+        assert_protocol_violation(&request);
+    }
+
+    #[test]
+    fn test_request_from_template() {
+        let templates = PromptTemplateRegistry::with_defaults();
+        let vars: HashMap<String, String> = [("query".to_string(), "rust async runtimes".to_string())]
+            .into_iter()
+            .collect();
+
+        let request =
+            RLMTaskRequest::from_template(&templates, "web_search", &vars, "workflow-1".to_string())
+                .unwrap();
+        assert!(request.task.contains("rust async runtimes"));
+        assert_eq!(request.context.workflow_id, "workflow-1");
+        assert!(request.is_valid());
+
+        let missing = RLMTaskRequest::from_template(
+            &templates,
+            "web_search",
+            &HashMap::new(),
+            "workflow-1".to_string(),
+        );
+        assert!(matches!(missing, Err(FederationError::TemplateError(_))));
+
+        let unknown =
+            RLMTaskRequest::from_template(&templates, "nope", &vars, "workflow-1".to_string());
+        assert!(matches!(unknown, Err(FederationError::TemplateError(_))));
+    }
 }
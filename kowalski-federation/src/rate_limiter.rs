@@ -0,0 +1,307 @@
+//! Per-model request and token rate limits
+//!
+//! A [`RateLimiter`] holds one pair of token buckets per model: one for
+//! requests per minute and one for LLM tokens per minute. Callers wait in
+//! [`RateLimiter::acquire`] until both buckets have capacity, so every
+//! workflow sharing the limiter stays under the provider's limits.
+
+use crate::FederationError;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Rate limits for one model
+///
+/// A limit left at `None` is not enforced.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelRateLimit {
+    /// Requests allowed per minute
+    pub requests_per_minute: Option<u32>,
+    /// Prompt and response tokens allowed per minute
+    pub tokens_per_minute: Option<u32>,
+    /// Requests that may be sent back to back before they are spaced out;
+    /// defaults to `requests_per_minute`
+    pub request_burst: Option<u32>,
+}
+
+impl ModelRateLimit {
+    /// Creates a limit that enforces nothing
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Caps requests per minute
+    pub fn with_requests_per_minute(mut self, requests: u32) -> Self {
+        self.requests_per_minute = Some(requests);
+        self
+    }
+
+    /// Caps tokens per minute
+    pub fn with_tokens_per_minute(mut self, tokens: u32) -> Self {
+        self.tokens_per_minute = Some(tokens);
+        self
+    }
+
+    /// Sets how many requests may be sent back to back
+    ///
+    /// A burst of 1 spaces every request evenly across the minute.
+    pub fn with_request_burst(mut self, burst: u32) -> Self {
+        self.request_burst = Some(burst);
+        self
+    }
+}
+
+/// Token bucket refilled continuously at a fixed rate
+#[derive(Debug)]
+struct Bucket {
+    capacity: f64,
+    available: f64,
+    refill_per_sec: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    /// A full bucket holding `capacity` that refills `per_minute` every minute
+    fn new(capacity: u32, per_minute: u32, now: Instant) -> Self {
+        let capacity = f64::from(capacity.max(1));
+        Self {
+            capacity,
+            available: capacity,
+            refill_per_sec: f64::from(per_minute.max(1)) / 60.0,
+            updated: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.available = (self.available + elapsed * self.refill_per_sec).min(self.capacity);
+        self.updated = now;
+    }
+
+    /// Time until `amount` is available
+    fn wait_for(&self, amount: f64) -> Duration {
+        if self.available >= amount {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64((amount - self.available) / self.refill_per_sec)
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ModelBuckets {
+    requests: Option<Bucket>,
+    tokens: Option<Bucket>,
+}
+
+impl ModelBuckets {
+    fn new(limit: &ModelRateLimit, now: Instant) -> Self {
+        Self {
+            requests: limit.requests_per_minute.map(|rpm| {
+                Bucket::new(limit.request_burst.unwrap_or(rpm), rpm, now)
+            }),
+            tokens: limit.tokens_per_minute.map(|tpm| Bucket::new(tpm, tpm, now)),
+        }
+    }
+}
+
+/// Token-bucket rate limiter keyed by model
+///
+/// Models without a configured limit are never throttled. Share one limiter
+/// (for example behind an `Arc`) between everything that uses the same API key.
+///
+/// # Example
+///
+/// ```no_run
+/// use kowalski_federation::rate_limiter::{ModelRateLimit, RateLimiter};
+/// use std::time::Duration;
+///
+/// # async fn example() -> Result<(), kowalski_federation::FederationError> {
+/// let limiter = RateLimiter::new().with_limit(
+///     "llama3.2",
+///     ModelRateLimit::new()
+///         .with_requests_per_minute(60)
+///         .with_tokens_per_minute(40_000),
+/// );
+///
+/// let deadline = tokio::time::Instant::now() + Duration::from_secs(30);
+/// let reserved = limiter.acquire("llama3.2", 800, deadline).await?;
+/// // ... send the request, then return what it did not use
+/// limiter.refund("llama3.2", reserved.saturating_sub(650));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    limits: HashMap<String, ModelRateLimit>,
+    buckets: Mutex<HashMap<String, ModelBuckets>>,
+}
+
+impl RateLimiter {
+    /// Creates a limiter with no limits configured
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the limits for `model`
+    pub fn with_limit(mut self, model: impl Into<String>, limit: ModelRateLimit) -> Self {
+        let model = model.into();
+        self.buckets.get_mut().unwrap().remove(&model);
+        self.limits.insert(model, limit);
+        self
+    }
+
+    /// Limits configured for `model`, if any
+    pub fn limit_for(&self, model: &str) -> Option<&ModelRateLimit> {
+        self.limits.get(model)
+    }
+
+    /// Waits until `model` has capacity for one request of `tokens` tokens
+    ///
+    /// Token requests larger than the per-minute limit are clamped to it, so
+    /// they wait for a full bucket instead of forever. Returns the number of
+    /// tokens reserved, which [`RateLimiter::refund`] can partly give back
+    /// once the real usage is known.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FederationError::RateLimited`] without waiting if capacity
+    /// would only be available after `deadline`.
+    pub async fn acquire(
+        &self,
+        model: &str,
+        tokens: usize,
+        deadline: Instant,
+    ) -> Result<usize, FederationError> {
+        let Some(limit) = self.limits.get(model) else {
+            return Ok(tokens);
+        };
+
+        loop {
+            let now = Instant::now();
+            let wait = {
+                let mut buckets = self.buckets.lock().unwrap();
+                let buckets = buckets
+                    .entry(model.to_string())
+                    .or_insert_with(|| ModelBuckets::new(limit, now));
+
+                let token_amount = match &buckets.tokens {
+                    Some(bucket) => (tokens as f64).min(bucket.capacity),
+                    None => tokens as f64,
+                };
+
+                let mut wait = Duration::ZERO;
+                if let Some(bucket) = &mut buckets.requests {
+                    bucket.refill(now);
+                    wait = wait.max(bucket.wait_for(1.0));
+                }
+                if let Some(bucket) = &mut buckets.tokens {
+                    bucket.refill(now);
+                    wait = wait.max(bucket.wait_for(token_amount));
+                }
+
+                if wait.is_zero() {
+                    if let Some(bucket) = &mut buckets.requests {
+                        bucket.available -= 1.0;
+                    }
+                    if let Some(bucket) = &mut buckets.tokens {
+                        bucket.available -= token_amount;
+                    }
+                    return Ok(token_amount as usize);
+                }
+                wait
+            };
+
+            if now + wait > deadline {
+                return Err(FederationError::RateLimited {
+                    model: model.to_string(),
+                    retry_after_ms: wait.as_millis() as u64,
+                });
+            }
+            // Another caller may take the capacity first, so check again after waking
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Returns `tokens` reserved by [`RateLimiter::acquire`] but not used
+    pub fn refund(&self, model: &str, tokens: usize) {
+        if tokens == 0 {
+            return;
+        }
+        let mut buckets = self.buckets.lock().unwrap();
+        if let Some(bucket) = buckets.get_mut(model).and_then(|b| b.tokens.as_mut()) {
+            bucket.refill(Instant::now());
+            bucket.available = (bucket.available + tokens as f64).min(bucket.capacity);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_requests_are_spaced_after_the_burst() {
+        let limiter = RateLimiter::new().with_limit(
+            "slow",
+            ModelRateLimit::new().with_requests_per_minute(6).with_request_burst(2),
+        );
+        let deadline = Instant::now() + Duration::from_secs(60);
+        let start = Instant::now();
+
+        limiter.acquire("slow", 0, deadline).await.unwrap();
+        limiter.acquire("slow", 0, deadline).await.unwrap();
+        assert_eq!(start.elapsed(), Duration::ZERO);
+
+        // 6 requests per minute refill one request every 10 seconds
+        limiter.acquire("slow", 0, deadline).await.unwrap();
+        assert!(start.elapsed() >= Duration::from_secs(10));
+        limiter.acquire("slow", 0, deadline).await.unwrap();
+        assert!(start.elapsed() >= Duration::from_secs(20));
+
+        // Other models are not throttled
+        limiter.acquire("fast", 1_000_000, deadline).await.unwrap();
+        assert!(start.elapsed() < Duration::from_secs(21));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_token_limit_clamps_and_refunds() {
+        let limiter = RateLimiter::new()
+            .with_limit("model", ModelRateLimit::new().with_tokens_per_minute(600));
+        let deadline = Instant::now() + Duration::from_secs(120);
+        let start = Instant::now();
+
+        // Larger than the bucket, so it waits for a full bucket at most
+        assert_eq!(limiter.acquire("model", 5_000, deadline).await.unwrap(), 600);
+
+        limiter.refund("model", 300);
+        assert_eq!(limiter.acquire("model", 300, deadline).await.unwrap(), 300);
+        assert_eq!(start.elapsed(), Duration::ZERO);
+
+        // 600 tokens per minute refill 10 tokens a second
+        limiter.acquire("model", 100, deadline).await.unwrap();
+        assert!(start.elapsed() >= Duration::from_secs(10));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_acquire_fails_fast_past_the_deadline() {
+        let limiter = RateLimiter::new()
+            .with_limit("model", ModelRateLimit::new().with_requests_per_minute(1));
+        let deadline = Instant::now() + Duration::from_secs(5);
+
+        limiter.acquire("model", 0, deadline).await.unwrap();
+        let start = Instant::now();
+        let err = limiter.acquire("model", 0, deadline).await.unwrap_err();
+
+        assert_eq!(start.elapsed(), Duration::ZERO);
+        match err {
+            FederationError::RateLimited { model, retry_after_ms } => {
+                assert_eq!(model, "model");
+                assert!(retry_after_ms > 55_000);
+            }
+            other => panic!("expected RateLimited, got {:?}", other),
+        }
+    }
+}
@@ -1,16 +1,247 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use kowalski_core::conversation::{Conversation, Message};
+use kowalski_core::error::KowalskiError;
+use kowalski_core::{Agent, Config, Role};
+use reqwest::Response;
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::collections::hash_map::Entry;
 use std::collections::HashMap;
+use std::path::Path;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
-use tracing::info;
+use tracing::{info, warn};
 
 use crate::{FederatedAgent, FederationError, FederationMessage, FederationRole};
 
 /// Type alias for federated agent references
 type FederatedAgentRef = Arc<RwLock<dyn FederatedAgent + Send + Sync>>;
 
+/// Registration of one agent, as captured in a snapshot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AgentRecord {
+    /// Federation ID of the agent
+    pub id: String,
+    /// Role of the agent when the snapshot was taken
+    pub role: FederationRole,
+    /// Versions of the capabilities the agent provided
+    #[serde(default)]
+    pub capabilities: HashMap<String, semver::Version>,
+    /// Time to live of the registration, if it had one
+    #[serde(default)]
+    pub ttl: Option<Duration>,
+}
+
+/// Serializable copy of the registrations in an [`AgentRegistry`]
+///
+/// Agents are live objects, so a snapshot records who was registered, in
+/// which role, with which capabilities and for how long.
+/// [`AgentRegistry::restore_from_snapshot`] registers a [`RestoredAgent`]
+/// for each record.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AgentRegistrySnapshot {
+    /// When the snapshot was taken, for staleness checks
+    pub created_at: DateTime<Utc>,
+    /// Registered agents, sorted by ID
+    pub agents: Vec<AgentRecord>,
+}
+
+impl AgentRegistrySnapshot {
+    /// Number of agents in the snapshot
+    pub fn len(&self) -> usize {
+        self.agents.len()
+    }
+
+    /// Returns true if the snapshot holds no agents
+    pub fn is_empty(&self) -> bool {
+        self.agents.is_empty()
+    }
+
+    /// Writes the snapshot to `path` as pretty-printed JSON
+    pub fn to_json_file(&self, path: &Path) -> Result<(), FederationError> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| FederationError::SerializationError(e.to_string()))?;
+        std::fs::write(path, json).map_err(|e| {
+            FederationError::SerializationError(format!("failed to write {}: {}", path.display(), e))
+        })
+    }
+
+    /// Reads a snapshot written by [`AgentRegistrySnapshot::to_json_file`]
+    pub fn from_json_file(path: &Path) -> Result<Self, FederationError> {
+        let json = std::fs::read_to_string(path).map_err(|e| {
+            FederationError::DeserializationError(format!("failed to read {}: {}", path.display(), e))
+        })?;
+        serde_json::from_str(&json).map_err(|e| FederationError::DeserializationError(e.to_string()))
+    }
+}
+
+/// Agent recreated from an [`AgentRecord`]
+///
+/// Stands in for the original agent after a restore: it reports the
+/// recorded ID, role and capability versions, and keeps the federation
+/// messages it receives until they are taken with
+/// [`RestoredAgent::take_messages`]. It cannot chat.
+#[derive(Debug, Clone)]
+pub struct RestoredAgent {
+    id: String,
+    role: FederationRole,
+    capabilities: HashMap<String, semver::Version>,
+    inbox: Vec<FederationMessage>,
+}
+
+impl RestoredAgent {
+    /// Recreate the agent described by `record`
+    pub fn from_record(record: &AgentRecord) -> Self {
+        Self {
+            id: record.id.clone(),
+            role: record.role.clone(),
+            capabilities: record.capabilities.clone(),
+            inbox: Vec::new(),
+        }
+    }
+
+    /// Remove and return the messages received since the last call
+    pub fn take_messages(&mut self) -> Vec<FederationMessage> {
+        std::mem::take(&mut self.inbox)
+    }
+}
+
+#[async_trait]
+impl Agent for RestoredAgent {
+    async fn new(_config: Config) -> Result<Self, KowalskiError> {
+        Err(KowalskiError::Agent(
+            "restored agents are created from snapshot records".to_string(),
+        ))
+    }
+
+    fn start_conversation(&mut self, _model: &str) -> String {
+        String::new()
+    }
+
+    fn get_conversation(&self, _id: &str) -> Option<&Conversation> {
+        None
+    }
+
+    fn list_conversations(&self) -> Vec<&Conversation> {
+        Vec::new()
+    }
+
+    fn delete_conversation(&mut self, _id: &str) -> bool {
+        false
+    }
+
+    async fn chat_with_history(
+        &mut self,
+        _conversation_id: &str,
+        _content: &str,
+        _role: Option<Role>,
+    ) -> Result<Response, KowalskiError> {
+        Err(KowalskiError::Agent(format!("restored agent {} cannot chat", self.id)))
+    }
+
+    async fn process_stream_response(
+        &mut self,
+        _conversation_id: &str,
+        _chunk: &[u8],
+    ) -> Result<Option<Message>, KowalskiError> {
+        Ok(None)
+    }
+
+    async fn add_message(&mut self, _conversation_id: &str, _role: &str, _content: &str) {}
+
+    fn name(&self) -> &str {
+        &self.id
+    }
+
+    fn description(&self) -> &str {
+        "Agent restored from a registry snapshot"
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[async_trait]
+impl FederatedAgent for RestoredAgent {
+    fn federation_id(&self) -> &str {
+        &self.id
+    }
+
+    fn federation_role(&self) -> FederationRole {
+        self.role.clone()
+    }
+
+    fn set_federation_role(&mut self, role: FederationRole) {
+        self.role = role;
+    }
+
+    async fn register_with_coordinator(
+        &mut self,
+        _coordinator: &str,
+    ) -> Result<(), FederationError> {
+        Ok(())
+    }
+
+    async fn send_message(
+        &self,
+        _recipient: &str,
+        _message: FederationMessage,
+    ) -> Result<(), FederationError> {
+        Ok(())
+    }
+
+    async fn broadcast_message(&self, _message: FederationMessage) -> Result<(), FederationError> {
+        Ok(())
+    }
+
+    async fn handle_federation_message(
+        &mut self,
+        message: FederationMessage,
+    ) -> Result<(), FederationError> {
+        self.inbox.push(message);
+        Ok(())
+    }
+
+    fn capability_versions(&self) -> HashMap<String, semver::Version> {
+        self.capabilities.clone()
+    }
+}
+
+/// Time to live of one registration
+#[derive(Debug, Clone, Copy)]
+struct Lease {
+    ttl: Duration,
+    registered_at: Instant,
+}
+
+impl Lease {
+    fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            registered_at: Instant::now(),
+        }
+    }
+
+    fn is_expired(&self) -> bool {
+        self.registered_at.elapsed() >= self.ttl
+    }
+}
+
+/// A registered agent with the lease its registration expires with, if any
+///
+/// Keeping the lease in the agent's entry means one lock guards both.
+#[derive(Clone)]
+struct Registration {
+    agent: FederatedAgentRef,
+    lease: Option<Lease>,
+}
+
 /// Registry for managing federated agents
 pub struct AgentRegistry {
-    agents: Arc<RwLock<HashMap<String, FederatedAgentRef>>>,
+    agents: Arc<RwLock<HashMap<String, Registration>>>,
 }
 
 impl Default for AgentRegistry {
@@ -29,6 +260,10 @@ impl AgentRegistry {
 
     /// Register a new agent in the federation
     pub async fn register_agent(&self, agent: FederatedAgentRef) -> Result<(), FederationError> {
+        self.insert(agent, None).await
+    }
+
+    async fn insert(&self, agent: FederatedAgentRef, lease: Option<Lease>) -> Result<(), FederationError> {
         let id = agent.read().await.federation_id().to_string();
         let mut agents = self.agents.write().await;
 
@@ -36,23 +271,58 @@ impl AgentRegistry {
             return Err(FederationError::DuplicateAgent(id));
         }
 
-        agents.insert(id.clone(), agent.clone());
+        agents.insert(id.clone(), Registration { agent, lease });
         info!("Registered agent: {}", id);
         Ok(())
     }
 
+    /// Register an agent whose registration expires after `ttl`
+    ///
+    /// Expired agents stay registered until [`AgentRegistry::remove_expired`]
+    /// runs.
+    pub async fn register_agent_with_ttl(
+        &self,
+        agent: FederatedAgentRef,
+        ttl: Duration,
+    ) -> Result<(), FederationError> {
+        self.insert(agent, Some(Lease::new(ttl))).await
+    }
+
+    /// Time to live the agent was registered with, if any
+    pub async fn agent_ttl(&self, id: &str) -> Option<Duration> {
+        let agents = self.agents.read().await;
+        agents.get(id)?.lease.map(|lease| lease.ttl)
+    }
+
+    /// Remove the agents whose registration has expired, returning their IDs
+    pub async fn remove_expired(&self) -> Vec<String> {
+        let mut agents = self.agents.write().await;
+        let mut expired: Vec<String> = agents
+            .iter()
+            .filter(|(_, registration)| registration.lease.is_some_and(|lease| lease.is_expired()))
+            .map(|(id, _)| id.clone())
+            .collect();
+        expired.sort();
+
+        for id in &expired {
+            agents.remove(id);
+            info!("Registration of agent {} expired", id);
+        }
+        expired
+    }
+
     /// Get an agent by ID
     pub async fn get_agent(&self, id: &str) -> Option<FederatedAgentRef> {
         let agents = self.agents.read().await;
-        agents.get(id).cloned()
+        agents.get(id).map(|registration| registration.agent.clone())
     }
 
     /// List all agents in the federation
     pub async fn list_agents(&self) -> Vec<(String, FederationRole)> {
         let agents = self.agents.read().await;
         let mut result = Vec::new();
-        for (id, agent) in agents.iter() {
-            let role = agent.read().await.federation_role();
+        for (id, registration) in agents.iter() {
+            let role = registration.agent.read().await.federation_role();
             result.push((id.clone(), role));
         }
         result
@@ -64,7 +334,7 @@ impl AgentRegistry {
         message: FederationMessage,
     ) -> Result<(), FederationError> {
         let agents = self.agents.read().await;
-        for agent in agents.values() {
+        for agent in agents.values().map(|registration| &registration.agent) {
             if agent.read().await.federation_id() != message.sender {
                 let mut agent = agent.write().await;
                 agent.handle_federation_message(message.clone()).await?;
@@ -98,4 +368,354 @@ impl AgentRegistry {
             Err(FederationError::AgentNotFound(id.to_string()))
         }
     }
+
+    /// Remove every agent from the federation
+    pub async fn clear(&self) {
+        self.agents.write().await.clear();
+    }
+
+    /// Entries of the registry with each agent's role
+    async fn entries(&self) -> Vec<(String, FederationRole, Registration)> {
+        let agents: Vec<(String, Registration)> = self
+            .agents
+            .read()
+            .await
+            .iter()
+            .map(|(id, registration)| (id.clone(), registration.clone()))
+            .collect();
+        let mut entries = Vec::with_capacity(agents.len());
+        for (id, registration) in agents {
+            let role = registration.agent.read().await.federation_role();
+            entries.push((id, role, registration));
+        }
+        entries
+    }
+
+    /// Add the agents of `other` that are not registered here
+    ///
+    /// Agents are shared, not copied: both registries hold the same agent,
+    /// and an added agent keeps the lease it has in `other`, so it expires at
+    /// the same time. An ID registered in both is kept as it is here; if the two agents
+    /// have different roles the conflict is logged. Returns the number of
+    /// agents added.
+    pub async fn merge(&self, other: &AgentRegistry) -> usize {
+        let incoming = other.entries().await;
+        let existing: HashMap<String, FederationRole> = self
+            .entries()
+            .await
+            .into_iter()
+            .map(|(id, role, _)| (id, role))
+            .collect();
+
+        let mut agents = self.agents.write().await;
+        let mut added = 0;
+        for (id, role, registration) in incoming {
+            match existing.get(&id) {
+                Some(kept) if *kept != role => {
+                    warn!(
+                        "Agent {} is a {:?} here but a {:?} in the merged registry; keeping {:?}",
+                        id, kept, role, kept
+                    );
+                }
+                Some(_) => {}
+                None => {
+                    // Registered since the roles were read: keep that one
+                    if let Entry::Vacant(slot) = agents.entry(id) {
+                        slot.insert(registration);
+                        added += 1;
+                    }
+                }
+            }
+        }
+        info!("Merged {} agents into the registry", added);
+        added
+    }
+
+    /// A new registry holding the agents registered in both
+    ///
+    /// Agents are matched by ID; the new registry shares this registry's
+    /// agent, and its lease, for each match.
+    pub async fn intersect(&self, other: &AgentRegistry) -> AgentRegistry {
+        let other_ids: Vec<String> = other.agents.read().await.keys().cloned().collect();
+        let common: HashMap<String, Registration> = {
+            let agents = self.agents.read().await;
+            other_ids
+                .into_iter()
+                .filter_map(|id| agents.get(&id).cloned().map(|registration| (id, registration)))
+                .collect()
+        };
+        AgentRegistry {
+            agents: Arc::new(RwLock::new(common)),
+        }
+    }
+
+    /// IDs registered only here and only in `other`, each sorted
+    pub async fn diff(&self, other: &AgentRegistry) -> (Vec<String>, Vec<String>) {
+        let ours: Vec<String> = self.agents.read().await.keys().cloned().collect();
+        let theirs: Vec<String> = other.agents.read().await.keys().cloned().collect();
+
+        let mut only_in_self: Vec<String> =
+            ours.iter().filter(|id| !theirs.contains(id)).cloned().collect();
+        let mut only_in_other: Vec<String> =
+            theirs.iter().filter(|id| !ours.contains(id)).cloned().collect();
+        only_in_self.sort();
+        only_in_other.sort();
+        (only_in_self, only_in_other)
+    }
+
+    /// Capture the current registrations
+    pub async fn snapshot(&self) -> AgentRegistrySnapshot {
+        let mut agents = Vec::new();
+        for (id, role, registration) in self.entries().await {
+            let capabilities = registration.agent.read().await.capability_versions();
+            let ttl = registration.lease.map(|lease| lease.ttl);
+            agents.push(AgentRecord {
+                id,
+                role,
+                capabilities,
+                ttl,
+            });
+        }
+        agents.sort_by(|a, b| a.id.cmp(&b.id));
+
+        AgentRegistrySnapshot {
+            created_at: Utc::now(),
+            agents,
+        }
+    }
+
+    /// Register a [`RestoredAgent`] for each record in `snapshot`
+    ///
+    /// Records with a TTL are registered with that TTL, counted from the
+    /// restore. Records whose ID is already registered are skipped. Returns
+    /// the number of agents inserted.
+    pub async fn restore_from_snapshot(&self, snapshot: AgentRegistrySnapshot) -> usize {
+        let mut restored = 0;
+        for record in &snapshot.agents {
+            let agent: FederatedAgentRef = Arc::new(RwLock::new(RestoredAgent::from_record(record)));
+            let registered = match record.ttl {
+                Some(ttl) => self.register_agent_with_ttl(agent, ttl).await,
+                None => self.register_agent(agent).await,
+            };
+            if registered.is_ok() {
+                restored += 1;
+            }
+        }
+        info!("Restored {} of {} agents from snapshot", restored, snapshot.len());
+        restored
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::MockAgent;
+
+    #[tokio::test]
+    async fn test_snapshot_and_restore() {
+        let registry = AgentRegistry::new();
+        let roles = [
+            ("coordinator", FederationRole::Coordinator),
+            ("worker-1", FederationRole::Worker),
+            ("worker-2", FederationRole::Worker),
+            ("worker-3", FederationRole::Worker),
+            ("observer", FederationRole::Observer),
+        ];
+        for (id, role) in &roles {
+            registry
+                .register_agent(Arc::new(RwLock::new(MockAgent::new(id, role.clone()))))
+                .await
+                .unwrap();
+        }
+
+        let snapshot = registry.snapshot().await;
+        assert_eq!(snapshot.len(), 5);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("registry.json");
+        snapshot.to_json_file(&path).unwrap();
+        let loaded = AgentRegistrySnapshot::from_json_file(&path).unwrap();
+        assert_eq!(loaded, snapshot);
+
+        registry.clear().await;
+        assert!(registry.list_agents().await.is_empty());
+
+        assert_eq!(registry.restore_from_snapshot(loaded.clone()).await, 5);
+        let mut restored = registry.list_agents().await;
+        restored.sort_by(|a, b| a.0.cmp(&b.0));
+        let mut expected: Vec<(String, FederationRole)> =
+            roles.iter().map(|(id, role)| (id.to_string(), role.clone())).collect();
+        expected.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(restored, expected);
+
+        // Agents that are already registered are not restored again
+        assert_eq!(registry.restore_from_snapshot(loaded).await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_round_trip_keeps_capabilities_and_ttls() {
+        let registry = AgentRegistry::new();
+        let sql_worker = MockAgent::new("sql-worker", FederationRole::Worker)
+            .with_capability_version("sql", "2.1.0")
+            .with_capability_version("csv", "1.0.0");
+        registry
+            .register_agent_with_ttl(Arc::new(RwLock::new(sql_worker)), Duration::from_secs(90))
+            .await
+            .unwrap();
+        registry
+            .register_agent(Arc::new(RwLock::new(MockAgent::new("observer", FederationRole::Observer))))
+            .await
+            .unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("registry.json");
+        registry.snapshot().await.to_json_file(&path).unwrap();
+        let loaded = AgentRegistrySnapshot::from_json_file(&path).unwrap();
+
+        let restored = AgentRegistry::new();
+        assert_eq!(restored.restore_from_snapshot(loaded).await, 2);
+
+        let worker = restored.get_agent("sql-worker").await.unwrap();
+        let worker = worker.read().await;
+        assert!(worker.as_any().is::<RestoredAgent>());
+        assert_eq!(worker.federation_role(), FederationRole::Worker);
+        let capabilities = worker.capability_versions();
+        assert_eq!(capabilities.len(), 2);
+        assert_eq!(capabilities["sql"], semver::Version::new(2, 1, 0));
+        assert!(worker.supports_capability_version("csv", &semver::Version::new(1, 0, 0)));
+        assert_eq!(restored.agent_ttl("sql-worker").await, Some(Duration::from_secs(90)));
+
+        let observer = restored.get_agent("observer").await.unwrap();
+        assert_eq!(observer.read().await.federation_role(), FederationRole::Observer);
+        assert!(observer.read().await.capability_versions().is_empty());
+        assert_eq!(restored.agent_ttl("observer").await, None);
+    }
+
+    #[tokio::test]
+    async fn test_remove_expired_drops_only_expired_registrations() {
+        let registry = AgentRegistry::new();
+        for (id, ttl) in [("short", Duration::ZERO), ("long", Duration::from_secs(3600))] {
+            registry
+                .register_agent_with_ttl(Arc::new(RwLock::new(MockAgent::new(id, FederationRole::Worker))), ttl)
+                .await
+                .unwrap();
+        }
+        registry
+            .register_agent(Arc::new(RwLock::new(MockAgent::new("forever", FederationRole::Worker))))
+            .await
+            .unwrap();
+
+        assert_eq!(registry.remove_expired().await, vec!["short".to_string()]);
+        assert_eq!(sorted_ids(registry.list_agents().await), vec!["forever", "long"]);
+        assert_eq!(registry.agent_ttl("short").await, None);
+    }
+
+    async fn registry_with(agents: &[(&str, FederationRole)]) -> AgentRegistry {
+        let registry = AgentRegistry::new();
+        for (id, role) in agents {
+            registry
+                .register_agent(Arc::new(RwLock::new(MockAgent::new(id, role.clone()))))
+                .await
+                .unwrap();
+        }
+        registry
+    }
+
+    fn sorted_ids(agents: Vec<(String, FederationRole)>) -> Vec<String> {
+        let mut ids: Vec<String> = agents.into_iter().map(|(id, _)| id).collect();
+        ids.sort();
+        ids
+    }
+
+    #[tokio::test]
+    async fn test_merge_adds_missing_agents_and_keeps_conflicts() {
+        let east = registry_with(&[
+            ("coordinator", FederationRole::Coordinator),
+            ("east-1", FederationRole::Worker),
+            ("shared", FederationRole::Worker),
+        ])
+        .await;
+        let west = registry_with(&[
+            ("coordinator", FederationRole::Worker),
+            ("west-1", FederationRole::Worker),
+            ("west-2", FederationRole::Observer),
+            ("shared", FederationRole::Worker),
+        ])
+        .await;
+
+        assert_eq!(east.merge(&west).await, 2);
+        assert_eq!(
+            sorted_ids(east.list_agents().await),
+            vec!["coordinator", "east-1", "shared", "west-1", "west-2"]
+        );
+        // The conflicting entry keeps its original role
+        let coordinator = east.get_agent("coordinator").await.unwrap();
+        assert_eq!(coordinator.read().await.federation_role(), FederationRole::Coordinator);
+
+        // Merging again, or merging a registry into itself, adds nothing
+        assert_eq!(east.merge(&west).await, 0);
+        assert_eq!(east.merge(&east).await, 0);
+        assert_eq!(west.list_agents().await.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_intersect_and_diff_of_overlapping_registries() {
+        let east = registry_with(&[
+            ("coordinator", FederationRole::Coordinator),
+            ("east-1", FederationRole::Worker),
+            ("shared", FederationRole::Worker),
+        ])
+        .await;
+        let west = registry_with(&[
+            ("coordinator", FederationRole::Coordinator),
+            ("west-1", FederationRole::Worker),
+            ("shared", FederationRole::Worker),
+        ])
+        .await;
+
+        let both = east.intersect(&west).await;
+        assert_eq!(sorted_ids(both.list_agents().await), vec!["coordinator", "shared"]);
+        // The intersection shares the agents of the registry it was taken from
+        assert!(Arc::ptr_eq(
+            &both.get_agent("shared").await.unwrap(),
+            &east.get_agent("shared").await.unwrap()
+        ));
+
+        let (only_east, only_west) = east.diff(&west).await;
+        assert_eq!(only_east, vec!["east-1"]);
+        assert_eq!(only_west, vec!["west-1"]);
+
+        let (none, all) = AgentRegistry::new().diff(&west).await;
+        assert!(none.is_empty());
+        assert_eq!(all, vec!["coordinator", "shared", "west-1"]);
+    }
+
+    #[tokio::test]
+    async fn test_merge_and_intersect_keep_ttls() {
+        let leased = AgentRegistry::new();
+        let ttl = Duration::from_secs(600);
+        leased
+            .register_agent_with_ttl(Arc::new(RwLock::new(MockAgent::new("leased", FederationRole::Worker))), ttl)
+            .await
+            .unwrap();
+        let expired = Arc::new(RwLock::new(MockAgent::new("expired", FederationRole::Worker)));
+        leased.register_agent_with_ttl(expired, Duration::ZERO).await.unwrap();
+
+        let merged = registry_with(&[("local", FederationRole::Worker)]).await;
+        assert_eq!(merged.merge(&leased).await, 2);
+        assert_eq!(merged.agent_ttl("leased").await, Some(ttl));
+        assert_eq!(merged.agent_ttl("local").await, None);
+        // Expiry is counted from the original registration
+        assert_eq!(merged.remove_expired().await, vec!["expired".to_string()]);
+
+        let both = leased.intersect(&merged).await;
+        assert_eq!(both.agent_ttl("leased").await, Some(ttl));
+    }
+
+    #[test]
+    fn test_from_json_file_missing() {
+        let dir = tempfile::tempdir().unwrap();
+        let result = AgentRegistrySnapshot::from_json_file(&dir.path().join("missing.json"));
+        assert!(matches!(result, Err(FederationError::DeserializationError(_))));
+    }
 }
@@ -0,0 +1,157 @@
+//! Shared helpers for unit tests
+
+use async_trait::async_trait;
+use kowalski_core::conversation::{Conversation, Message};
+use kowalski_core::error::KowalskiError;
+use kowalski_core::{Agent, Config, Role};
+use reqwest::Response;
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use crate::{AgentRegistry, FederatedAgent, FederationError, FederationMessage, FederationRole};
+
+/// Lightweight federated agent that records received messages
+pub(crate) struct MockAgent {
+    pub id: String,
+    pub role: FederationRole,
+    pub inbox: Vec<FederationMessage>,
+    pub capability_versions: HashMap<String, semver::Version>,
+}
+
+impl MockAgent {
+    pub fn new(id: &str, role: FederationRole) -> Self {
+        Self {
+            id: id.to_string(),
+            role,
+            inbox: Vec::new(),
+            capability_versions: HashMap::new(),
+        }
+    }
+
+    pub fn with_capability_version(mut self, capability: &str, version: &str) -> Self {
+        self.capability_versions
+            .insert(capability.to_string(), semver::Version::parse(version).unwrap());
+        self
+    }
+}
+
+#[async_trait]
+impl Agent for MockAgent {
+    async fn new(_config: Config) -> Result<Self, KowalskiError> {
+        Ok(MockAgent::new("mock", FederationRole::Worker))
+    }
+
+    fn start_conversation(&mut self, _model: &str) -> String {
+        String::new()
+    }
+
+    fn get_conversation(&self, _id: &str) -> Option<&Conversation> {
+        None
+    }
+
+    fn list_conversations(&self) -> Vec<&Conversation> {
+        Vec::new()
+    }
+
+    fn delete_conversation(&mut self, _id: &str) -> bool {
+        false
+    }
+
+    async fn chat_with_history(
+        &mut self,
+        _conversation_id: &str,
+        _content: &str,
+        _role: Option<Role>,
+    ) -> Result<Response, KowalskiError> {
+        Err(KowalskiError::Agent("mock agent cannot chat".to_string()))
+    }
+
+    async fn process_stream_response(
+        &mut self,
+        _conversation_id: &str,
+        _chunk: &[u8],
+    ) -> Result<Option<Message>, KowalskiError> {
+        Ok(None)
+    }
+
+    async fn add_message(&mut self, _conversation_id: &str, _role: &str, _content: &str) {}
+
+    fn name(&self) -> &str {
+        &self.id
+    }
+
+    fn description(&self) -> &str {
+        "Mock federated agent"
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[async_trait]
+impl FederatedAgent for MockAgent {
+    fn federation_id(&self) -> &str {
+        &self.id
+    }
+
+    fn federation_role(&self) -> FederationRole {
+        self.role.clone()
+    }
+
+    fn set_federation_role(&mut self, role: FederationRole) {
+        self.role = role;
+    }
+
+    async fn register_with_coordinator(
+        &mut self,
+        _coordinator: &str,
+    ) -> Result<(), FederationError> {
+        Ok(())
+    }
+
+    async fn send_message(
+        &self,
+        _recipient: &str,
+        _message: FederationMessage,
+    ) -> Result<(), FederationError> {
+        Ok(())
+    }
+
+    async fn broadcast_message(&self, _message: FederationMessage) -> Result<(), FederationError> {
+        Ok(())
+    }
+
+    async fn handle_federation_message(
+        &mut self,
+        message: FederationMessage,
+    ) -> Result<(), FederationError> {
+        self.inbox.push(message);
+        Ok(())
+    }
+
+    fn capability_versions(&self) -> HashMap<String, semver::Version> {
+        self.capability_versions.clone()
+    }
+}
+
+/// Builds a registry populated with worker mock agents
+pub(crate) async fn registry_with_workers(ids: &[&str]) -> Arc<AgentRegistry> {
+    let registry = Arc::new(AgentRegistry::new());
+    for id in ids {
+        registry
+            .register_agent(Arc::new(RwLock::new(MockAgent::new(id, FederationRole::Worker))))
+            .await
+            .unwrap();
+    }
+    registry
+}
+
+/// Number of messages received by a mock agent in the registry
+pub(crate) async fn inbox_len(registry: &AgentRegistry, id: &str) -> usize {
+    let agent = registry.get_agent(id).await.unwrap();
+    let agent = agent.read().await;
+    agent.as_any().downcast_ref::<MockAgent>().unwrap().inbox.len()
+}
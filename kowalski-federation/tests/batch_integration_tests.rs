@@ -19,6 +19,8 @@ mod batch_integration_tests {
             model: "llama3.2".to_string(),
             temperature: 0.7,
             max_tokens: 500,
+            idempotency_keys: Vec::new(),
+            priorities: Vec::new(),
         };
 
         assert_eq!(request.prompts.len(), 3);
@@ -36,6 +38,7 @@ mod batch_integration_tests {
                 tokens_used: 100,
                 success: true,
                 error: None,
+                model_used: "test-model".to_string(),
             },
             BatchCallResult {
                 index: 1,
@@ -44,6 +47,7 @@ mod batch_integration_tests {
                 tokens_used: 0,
                 success: false,
                 error: Some("Timeout".to_string()),
+                model_used: "test-model".to_string(),
             },
             BatchCallResult {
                 index: 2,
@@ -52,6 +56,7 @@ mod batch_integration_tests {
                 tokens_used: 150,
                 success: true,
                 error: None,
+                model_used: "test-model".to_string(),
             },
         ];
 
@@ -60,6 +65,7 @@ mod batch_integration_tests {
             total_tokens: 250,
             duration_ms: 1000,
             all_succeeded: false,
+            original_config: Default::default(),
         };
 
         let successful = response.successful_responses();
@@ -80,6 +86,7 @@ mod batch_integration_tests {
                 tokens_used: 50,
                 success: true,
                 error: None,
+                model_used: "test-model".to_string(),
             },
             BatchCallResult {
                 index: 2,
@@ -88,6 +95,7 @@ mod batch_integration_tests {
                 tokens_used: 60,
                 success: true,
                 error: None,
+                model_used: "test-model".to_string(),
             },
             BatchCallResult {
                 index: 1,
@@ -96,6 +104,7 @@ mod batch_integration_tests {
                 tokens_used: 0,
                 success: false,
                 error: Some("Error".to_string()),
+                model_used: "test-model".to_string(),
             },
         ];
 
@@ -104,6 +113,7 @@ mod batch_integration_tests {
             total_tokens: 110,
             duration_ms: 500,
             all_succeeded: false,
+            original_config: Default::default(),
         };
 
         // Verify index-based lookup works regardless of order
@@ -219,6 +229,8 @@ mod batch_integration_tests {
             model: "test-model".to_string(),
             temperature: 0.7,
             max_tokens: 100,
+            idempotency_keys: Vec::new(),
+            priorities: Vec::new(),
         };
 
         let result = executor.execute(request, Duration::from_secs(30)).await;
@@ -236,6 +248,7 @@ mod batch_integration_tests {
             tokens_used: 150,
             success: true,
             error: None,
+            model_used: "test-model".to_string(),
         };
 
         assert_eq!(result.index, 5);
@@ -258,6 +271,7 @@ mod batch_integration_tests {
                     tokens_used: 50,
                     success: true,
                     error: None,
+                    model_used: "test-model".to_string(),
                 },
                 BatchCallResult {
                     index: 1,
@@ -266,11 +280,13 @@ mod batch_integration_tests {
                     tokens_used: 50,
                     success: true,
                     error: None,
+                    model_used: "test-model".to_string(),
                 },
             ],
             total_tokens: 100,
             duration_ms: 500,
             all_succeeded: true,
+            original_config: Default::default(),
         };
         assert!(all_success.all_succeeded);
 
@@ -284,6 +300,7 @@ mod batch_integration_tests {
                     tokens_used: 50,
                     success: true,
                     error: None,
+                    model_used: "test-model".to_string(),
                 },
                 BatchCallResult {
                     index: 1,
@@ -292,11 +309,13 @@ mod batch_integration_tests {
                     tokens_used: 0,
                     success: false,
                     error: Some("Failed".to_string()),
+                    model_used: "test-model".to_string(),
                 },
             ],
             total_tokens: 50,
             duration_ms: 500,
             all_succeeded: false,
+            original_config: Default::default(),
         };
         assert!(!with_failure.all_succeeded);
     }
@@ -347,6 +366,7 @@ mod batch_integration_tests {
                 tokens_used: 100,
                 success: true,
                 error: None,
+                model_used: "test-model".to_string(),
             },
             BatchCallResult {
                 index: 1,
@@ -355,6 +375,7 @@ mod batch_integration_tests {
                 tokens_used: 150,
                 success: true,
                 error: None,
+                model_used: "test-model".to_string(),
             },
             BatchCallResult {
                 index: 2,
@@ -363,6 +384,7 @@ mod batch_integration_tests {
                 tokens_used: 200,
                 success: true,
                 error: None,
+                model_used: "test-model".to_string(),
             },
         ];
 
@@ -371,6 +393,7 @@ mod batch_integration_tests {
             total_tokens: 450,
             duration_ms: 2000,
             all_succeeded: true,
+            original_config: Default::default(),
         };
 
         // Verify token count
@@ -10,7 +10,7 @@
 mod tests {
     use kowalski_federation::{
         DepthController, DepthConfig, FederationError,
-        RLMTaskRequest, RLMTaskResponse, RLMContext, RLMMessageType,
+        ConfidenceInterval, RLMTaskRequest, RLMTaskResponse, RLMContext, RLMMessageType,
         SelectionCriteria, AgentSelector,
     };
     use std::sync::Arc;
@@ -127,13 +127,13 @@ mod tests {
             500,
         )
         .mark_recursive(vec!["child-1".to_string(), "child-2".to_string()])
-        .with_confidence(0.95)
+        .with_confidence(ConfidenceInterval::new(0.95, 0.05, 0.95).unwrap())
         .mark_ready_for_refinement();
 
         assert!(response.metadata.success);
         assert!(response.used_recursion);
         assert_eq!(response.child_agents.len(), 2);
-        assert_eq!(response.confidence, 0.95);
+        assert_eq!(response.confidence.point, 0.95);
         assert!(response.ready_for_refinement);
     }
 
@@ -0,0 +1,456 @@
+//! Compiled Artifact Cache
+//!
+//! Caches build outputs of compiled REPL languages (Rust binaries, Java
+//! classes) keyed by a hash of their source, so re-running the same snippet
+//! skips compilation.
+//!
+//! # Components
+//!
+//! - **ArtifactCache**: LRU cache of artifact directories on disk
+//! - **ArtifactCacheConfig**: Count and size bounds for the cache
+//! - **ArtifactCacheStats**: Hit/miss counters
+//! - **ArtifactLease**: Keeps a looked-up artifact on disk while it is in use
+
+use crate::error::RLMResult;
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Prefix of the per-process root directories of the global cache
+const GLOBAL_ROOT_PREFIX: &str = "kowalski-artifacts-";
+
+lazy_static! {
+    // Statics are never dropped, so roots left behind by earlier processes
+    // are swept when the cache is first used instead
+    static ref GLOBAL_CACHE: Arc<ArtifactCache> = {
+        let parent = std::env::temp_dir();
+        sweep_stale_roots(&parent);
+        Arc::new(ArtifactCache::new(
+            parent.join(format!("{}{}", GLOBAL_ROOT_PREFIX, std::process::id())),
+            ArtifactCacheConfig::default(),
+        ))
+    };
+}
+
+/// Bounds for the artifact cache
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ArtifactCacheConfig {
+    /// Maximum number of cached artifacts
+    pub max_entries: usize,
+    /// Maximum total size of cached artifacts in bytes
+    pub max_total_bytes: u64,
+}
+
+impl Default for ArtifactCacheConfig {
+    fn default() -> Self {
+        Self {
+            max_entries: 32,
+            max_total_bytes: 256 * 1024 * 1024,
+        }
+    }
+}
+
+/// Artifact cache hit/miss statistics
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ArtifactCacheStats {
+    /// Lookups that found a cached artifact
+    pub hits: u64,
+    /// Lookups that required compilation
+    pub misses: u64,
+    /// Artifacts evicted to stay within bounds
+    pub evictions: u64,
+}
+
+#[derive(Debug)]
+struct CachedArtifact {
+    dir: PathBuf,
+    size_bytes: u64,
+    last_used: u64,
+    /// Outstanding leases; a leased entry is never deleted
+    leases: usize,
+}
+
+#[derive(Debug, Default)]
+struct CacheIndex {
+    entries: HashMap<String, CachedArtifact>,
+    total_bytes: u64,
+    clock: u64,
+    stats: ArtifactCacheStats,
+}
+
+/// LRU cache of compiled artifacts stored on disk.
+///
+/// Each entry is a directory under the cache root holding the files produced
+/// by one compilation. The cache owns its root directory and removes it when
+/// dropped; the [global](ArtifactCache::global) cache lives until the process
+/// exits and its root is swept by the next process that uses it.
+#[derive(Debug)]
+pub struct ArtifactCache {
+    root: PathBuf,
+    config: ArtifactCacheConfig,
+    index: Mutex<CacheIndex>,
+}
+
+impl ArtifactCache {
+    /// Create a cache storing artifacts under `root`
+    pub fn new(root: impl Into<PathBuf>, config: ArtifactCacheConfig) -> Self {
+        Self {
+            root: root.into(),
+            config,
+            index: Mutex::new(CacheIndex::default()),
+        }
+    }
+
+    /// Process-wide cache shared by REPL executors
+    pub fn global() -> Arc<ArtifactCache> {
+        Arc::clone(&GLOBAL_CACHE)
+    }
+
+    /// Compute the cache key for a language and the inputs of a build
+    pub fn key_for(language: &str, inputs: &[&str]) -> String {
+        let mut hasher = DefaultHasher::new();
+        language.hash(&mut hasher);
+        inputs.hash(&mut hasher);
+        format!("{}-{:016x}", language, hasher.finish())
+    }
+
+    /// Look up the artifact directory for `key`, marking it as recently used
+    ///
+    /// The directory is not evicted until the returned lease is dropped.
+    pub fn lookup(&self, key: &str) -> Option<ArtifactLease<'_>> {
+        let mut index = self.index.lock().unwrap();
+        index.clock += 1;
+        let now = index.clock;
+
+        let dir = match index.entries.get_mut(key) {
+            Some(entry) if entry.dir.exists() => {
+                entry.last_used = now;
+                entry.leases += 1;
+                Some(entry.dir.clone())
+            }
+            _ => None,
+        };
+
+        if dir.is_some() {
+            index.stats.hits += 1;
+        } else {
+            index.stats.misses += 1;
+        }
+        dir.map(|dir| ArtifactLease {
+            cache: self,
+            key: key.to_string(),
+            dir,
+        })
+    }
+
+    /// Copy freshly built `artifacts` into the cache under `key`.
+    ///
+    /// Least recently used entries are evicted until the cache fits its
+    /// bounds. An artifact set larger than `max_total_bytes` is not cached.
+    pub fn store(&self, key: &str, artifacts: &[PathBuf]) -> RLMResult<()> {
+        // Another executor cached the same build first; its files may be in use
+        if self.index.lock().unwrap().entries.contains_key(key) {
+            return Ok(());
+        }
+
+        let size_bytes = artifacts
+            .iter()
+            .map(|path| std::fs::metadata(path).map(|m| m.len()))
+            .sum::<std::io::Result<u64>>()?;
+
+        if size_bytes > self.config.max_total_bytes || self.config.max_entries == 0 {
+            return Ok(());
+        }
+
+        let dir = self.root.join(key);
+        std::fs::create_dir_all(&dir)?;
+        for artifact in artifacts {
+            if let Some(name) = artifact.file_name() {
+                std::fs::copy(artifact, dir.join(name))?;
+            }
+        }
+
+        let mut index = self.index.lock().unwrap();
+        index.clock += 1;
+        let entry = CachedArtifact {
+            dir,
+            size_bytes,
+            last_used: index.clock,
+            leases: 0,
+        };
+        if let Some(previous) = index.entries.insert(key.to_string(), entry) {
+            index.total_bytes -= previous.size_bytes;
+        }
+        index.total_bytes += size_bytes;
+
+        self.evict_to_bounds(&mut index);
+        Ok(())
+    }
+
+    /// Number of cached artifacts
+    pub fn len(&self) -> usize {
+        self.index.lock().unwrap().entries.len()
+    }
+
+    /// Returns true if nothing is cached
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Total size of cached artifacts in bytes
+    pub fn total_bytes(&self) -> u64 {
+        self.index.lock().unwrap().total_bytes
+    }
+
+    /// Get hit/miss statistics
+    pub fn stats(&self) -> ArtifactCacheStats {
+        self.index.lock().unwrap().stats.clone()
+    }
+
+    /// Evict least recently used entries until the cache fits its bounds.
+    ///
+    /// Leased entries are skipped, so the cache may stay over its bounds until
+    /// they are released.
+    fn evict_to_bounds(&self, index: &mut CacheIndex) {
+        while index.entries.len() > self.config.max_entries
+            || index.total_bytes > self.config.max_total_bytes
+        {
+            let Some(oldest) = index
+                .entries
+                .iter()
+                .filter(|(_, entry)| entry.leases == 0)
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone())
+            else {
+                break;
+            };
+
+            if let Some(entry) = index.entries.remove(&oldest) {
+                index.total_bytes -= entry.size_bytes;
+                index.stats.evictions += 1;
+                remove_dir(&entry.dir);
+            }
+        }
+    }
+
+    fn release(&self, key: &str) {
+        let mut index = self.index.lock().unwrap();
+        if let Some(entry) = index.entries.get_mut(key) {
+            entry.leases -= 1;
+        }
+        self.evict_to_bounds(&mut index);
+    }
+}
+
+impl Drop for ArtifactCache {
+    fn drop(&mut self) {
+        remove_dir(&self.root);
+    }
+}
+
+/// A cached artifact directory that stays on disk until the lease is dropped
+#[derive(Debug)]
+pub struct ArtifactLease<'a> {
+    cache: &'a ArtifactCache,
+    key: String,
+    dir: PathBuf,
+}
+
+impl ArtifactLease<'_> {
+    /// Directory holding the cached files
+    pub fn path(&self) -> &Path {
+        &self.dir
+    }
+}
+
+impl Drop for ArtifactLease<'_> {
+    fn drop(&mut self) {
+        self.cache.release(&self.key);
+    }
+}
+
+fn remove_dir(dir: &Path) {
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+/// Remove global cache roots under `parent` whose owning process has exited
+fn sweep_stale_roots(parent: &Path) {
+    let Ok(entries) = std::fs::read_dir(parent) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        let Some(pid) = name
+            .to_str()
+            .and_then(|name| name.strip_prefix(GLOBAL_ROOT_PREFIX))
+            .and_then(|pid| pid.parse::<u32>().ok())
+        else {
+            continue;
+        };
+        if pid != std::process::id() && !process_alive(pid) {
+            remove_dir(&entry.path());
+        }
+    }
+}
+
+#[cfg(unix)]
+fn process_alive(pid: u32) -> bool {
+    use rustix::process::{test_kill_process, Pid};
+
+    match Pid::from_raw(pid as i32) {
+        // EPERM still means the process exists
+        Some(pid) => !matches!(test_kill_process(pid), Err(rustix::io::Errno::SRCH)),
+        None => false,
+    }
+}
+
+/// Without a portable liveness check, other roots are left alone
+#[cfg(not(unix))]
+fn process_alive(_pid: u32) -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_artifact(dir: &Path, name: &str, size: usize) -> PathBuf {
+        let path = dir.join(name);
+        std::fs::write(&path, vec![0u8; size]).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_key_depends_on_inputs() {
+        let a = ArtifactCache::key_for("rust", &["fn main() {}", "manifest"]);
+        let b = ArtifactCache::key_for("rust", &["fn main() {}", "manifest"]);
+        let c = ArtifactCache::key_for("rust", &["fn main() { }", "manifest"]);
+        let d = ArtifactCache::key_for("java", &["fn main() {}", "manifest"]);
+
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_ne!(a, d);
+    }
+
+    #[test]
+    fn test_store_and_lookup() {
+        let build = tempfile::TempDir::new().unwrap();
+        let root = tempfile::TempDir::new().unwrap();
+        let cache = ArtifactCache::new(root.path().join("cache"), ArtifactCacheConfig::default());
+
+        assert!(cache.lookup("k1").is_none());
+        let artifact = write_artifact(build.path(), "bin", 10);
+        cache.store("k1", &[artifact]).unwrap();
+
+        let lease = cache.lookup("k1").unwrap();
+        assert!(lease.path().join("bin").exists());
+        assert_eq!(cache.total_bytes(), 10);
+
+        let stats = cache.stats();
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.misses, 1);
+    }
+
+    #[test]
+    fn test_evicts_least_recently_used_by_count() {
+        let build = tempfile::TempDir::new().unwrap();
+        let root = tempfile::TempDir::new().unwrap();
+        let config = ArtifactCacheConfig {
+            max_entries: 2,
+            ..Default::default()
+        };
+        let cache = ArtifactCache::new(root.path().join("cache"), config);
+        let artifact = write_artifact(build.path(), "bin", 1);
+
+        cache.store("k1", &[artifact.clone()]).unwrap();
+        cache.store("k2", &[artifact.clone()]).unwrap();
+        // Touch k1 so k2 becomes the least recently used
+        assert!(cache.lookup("k1").is_some());
+        cache.store("k3", &[artifact]).unwrap();
+
+        assert_eq!(cache.len(), 2);
+        assert!(cache.lookup("k1").is_some());
+        assert!(cache.lookup("k2").is_none());
+        assert!(cache.lookup("k3").is_some());
+        assert_eq!(cache.stats().evictions, 1);
+    }
+
+    #[test]
+    fn test_evicts_by_total_size() {
+        let build = tempfile::TempDir::new().unwrap();
+        let root = tempfile::TempDir::new().unwrap();
+        let config = ArtifactCacheConfig {
+            max_entries: 10,
+            max_total_bytes: 25,
+        };
+        let cache = ArtifactCache::new(root.path().join("cache"), config);
+        let artifact = write_artifact(build.path(), "bin", 10);
+
+        for key in ["k1", "k2", "k3"] {
+            cache.store(key, &[artifact.clone()]).unwrap();
+        }
+
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.total_bytes(), 20);
+        assert!(cache.lookup("k1").is_none());
+
+        // Too large to ever fit: not cached
+        let huge = write_artifact(build.path(), "huge", 30);
+        cache.store("k4", &[huge]).unwrap();
+        assert!(cache.lookup("k4").is_none());
+    }
+
+    #[test]
+    fn test_leased_entry_is_not_evicted_until_released() {
+        let build = tempfile::TempDir::new().unwrap();
+        let root = tempfile::TempDir::new().unwrap();
+        let config = ArtifactCacheConfig {
+            max_entries: 1,
+            ..Default::default()
+        };
+        let cache = ArtifactCache::new(root.path().join("cache"), config);
+        let artifact = write_artifact(build.path(), "bin", 1);
+
+        cache.store("k1", &[artifact.clone()]).unwrap();
+        let lease = cache.lookup("k1").unwrap();
+        cache.store("k2", &[artifact.clone()]).unwrap();
+
+        // k1 is in use, so the newer k2 is evicted instead
+        assert!(lease.path().join("bin").exists());
+        assert_eq!(cache.len(), 1);
+
+        drop(lease);
+        cache.store("k2", &[artifact]).unwrap();
+        assert_eq!(cache.len(), 1);
+        assert!(!root.path().join("cache").join("k1").exists());
+        assert!(cache.lookup("k2").is_some());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_sweep_removes_roots_of_exited_processes() {
+        let parent = tempfile::TempDir::new().unwrap();
+        let mut child = std::process::Command::new("true").spawn().unwrap();
+        let exited = child.id();
+        child.wait().unwrap();
+
+        let stale = parent.path().join(format!("{}{}", GLOBAL_ROOT_PREFIX, exited));
+        let own = parent
+            .path()
+            .join(format!("{}{}", GLOBAL_ROOT_PREFIX, std::process::id()));
+        let unrelated = parent.path().join("kowalski-other");
+        for dir in [&stale, &own, &unrelated] {
+            std::fs::create_dir_all(dir).unwrap();
+        }
+
+        sweep_stale_roots(parent.path());
+
+        assert!(!stale.exists());
+        assert!(own.exists());
+        assert!(unrelated.exists());
+    }
+}
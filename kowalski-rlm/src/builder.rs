@@ -2,13 +2,24 @@
 //!
 //! Provides a fluent API for creating and configuring RLM instances.
 
-use crate::config::RLMConfig;
+use crate::code_safety::CodeSafetyPolicy;
+use crate::config::{RLMConfig, RLMConfigBuilder};
 use crate::error::{RLMError, RLMResult};
 use crate::executor::RLMExecutor;
+use crate::llm_backend::{ExhaustionPolicy, MockLLMClient};
+use kowalski_core::rlm::TokenCounter;
+use kowalski_federation::ModelRegistry;
+use std::future::Future;
+use std::sync::Arc;
 use std::time::Duration;
+use tokio::runtime::{Handle, RuntimeFlavor};
 
 /// Fluent builder for RLM configuration and creation
 ///
+/// Settings with their own invariants are checked by [`RLMConfigBuilder`] as
+/// they are set; an invalid value is not applied and the first such error is
+/// returned by [`RLMBuilder::build`].
+///
 /// # Example
 ///
 /// ```no_run
@@ -27,8 +38,30 @@ use std::time::Duration;
 #[derive(Debug)]
 pub struct RLMBuilder {
     config: RLMConfig,
+    error: Option<String>,
+    warm_up: bool,
+    /// Whether [`RLMBuilder::with_max_context_length`] was called
+    context_length_set: bool,
+    auto_context_length: bool,
+    allow_tight_context: bool,
+    /// Responses for a [`MockLLMClient`], set by [`RLMBuilder::with_mock_llm`]
+    mock_responses: Option<Vec<String>>,
+    mock_exhaustion_policy: ExhaustionPolicy,
+    /// Model whose context window bounds `max_context_length`
+    model: Option<String>,
 }
 
+/// Smallest ratio of `max_context_length` to `max_repl_output` that
+/// [`RLMBuilder::build`] accepts: room for one REPL output plus the prompt
+const MIN_CONTEXT_TO_REPL_OUTPUT: usize = 2;
+
+/// Ratio used by [`RLMBuilder::with_auto_context_length`]
+const AUTO_CONTEXT_TO_REPL_OUTPUT: usize = 4;
+
+/// Characters per token assumed when converting a model's context window
+/// (in tokens) to a `max_context_length` (in characters)
+const CHARS_PER_TOKEN: usize = 4;
+
 impl Default for RLMBuilder {
     fn default() -> Self {
         Self::new()
@@ -38,37 +71,97 @@ impl Default for RLMBuilder {
 impl RLMBuilder {
     /// Create a new RLM builder with default configuration
     pub fn new() -> Self {
-        Self {
-            config: RLMConfig::default(),
-        }
+        Self::with_config(RLMConfig::default())
     }
 
     /// Create a builder with custom configuration
     pub fn with_config(config: RLMConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            error: None,
+            warm_up: false,
+            context_length_set: false,
+            auto_context_length: false,
+            allow_tight_context: false,
+            mock_responses: None,
+            mock_exhaustion_policy: ExhaustionPolicy::default(),
+            model: None,
+        }
     }
 
-    /// Set maximum iterations
-    pub fn with_max_iterations(mut self, max: usize) -> Self {
-        self.config = self.config.with_max_iterations(max);
+    /// Apply a validating [`RLMConfigBuilder`] setter, keeping the first error
+    fn validated(
+        mut self,
+        set: impl FnOnce(RLMConfigBuilder) -> Result<RLMConfigBuilder, String>,
+    ) -> Self {
+        match set(RLMConfigBuilder::from_config(self.config.clone())) {
+            Ok(builder) => self.config = builder.config,
+            Err(msg) => {
+                self.error.get_or_insert(msg);
+            }
+        }
         self
     }
 
+    /// Set maximum iterations
+    pub fn with_max_iterations(self, max: usize) -> Self {
+        self.validated(|builder| builder.max_iterations(max))
+    }
+
     /// Set maximum REPL output length
-    pub fn with_max_repl_output(mut self, max: usize) -> Self {
-        self.config = self.config.with_max_repl_output(max);
-        self
+    pub fn with_max_repl_output(self, max: usize) -> Self {
+        self.validated(|builder| builder.max_repl_output(max))
+    }
+
+    /// Set maximum REPL output lines
+    pub fn with_max_output_lines(self, max: usize) -> Self {
+        self.validated(|builder| builder.max_output_lines(max))
     }
 
     /// Set iteration timeout
-    pub fn with_iteration_timeout(mut self, timeout: Duration) -> Self {
-        self.config = self.config.with_iteration_timeout(timeout);
+    pub fn with_iteration_timeout(self, timeout: Duration) -> Self {
+        self.validated(|builder| builder.iteration_timeout(timeout))
+    }
+
+    /// Set the REPL timeout for one language, keyed by canonical name
+    pub fn with_language_timeout(mut self, language: impl Into<String>, timeout: Duration) -> Self {
+        self.config = self.config.with_language_timeout(language, timeout);
         self
     }
 
     /// Set maximum context length
     pub fn with_max_context_length(mut self, max: usize) -> Self {
-        self.config = self.config.with_max_context_length(max);
+        self.context_length_set = true;
+        self.validated(|builder| builder.max_context_length(max))
+    }
+
+    /// Derive `max_context_length` from the REPL output limit
+    ///
+    /// Unless [`with_max_context_length`](Self::with_max_context_length) is
+    /// called, [`build`](Self::build) sets `max_context_length` to four
+    /// times `max_repl_output`, whichever order the setters are called in.
+    pub fn with_auto_context_length(mut self) -> Self {
+        self.auto_context_length = true;
+        self
+    }
+
+    /// Bound `max_context_length` by the context window of `model`
+    ///
+    /// The window is looked up in [`ModelRegistry::global`] and converted to
+    /// characters at four per token. [`build`](Self::build) lowers a default
+    /// or derived `max_context_length` to fit it, and rejects one set with
+    /// [`with_max_context_length`](Self::with_max_context_length) that does
+    /// not fit. Models missing from the registry are not checked.
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    /// Accept a `max_context_length` below twice `max_repl_output`
+    ///
+    /// For callers who know their REPL output stays well below its limit.
+    pub fn allow_tight_context(mut self, allow: bool) -> Self {
+        self.allow_tight_context = allow;
         self
     }
 
@@ -78,6 +171,12 @@ impl RLMBuilder {
         self
     }
 
+    /// Fold over-length prompts instead of rejecting them
+    pub fn with_fold_oversized_prompt(mut self, enable: bool) -> Self {
+        self.config = self.config.with_fold_oversized_prompt(enable);
+        self
+    }
+
     /// Enable or disable parallel batching
     pub fn with_parallel_batching(mut self, enable: bool) -> Self {
         self.config = self.config.with_parallel_batching(enable);
@@ -108,18 +207,118 @@ impl RLMBuilder {
         self
     }
 
+    /// Fold the answer eagerly while resident memory is above `bytes`
+    pub fn with_memory_watermark(mut self, bytes: u64) -> Self {
+        self.config = self.config.with_memory_watermark(bytes);
+        self
+    }
+
+    /// Set the retry budget shared by a run
+    pub fn with_retry_budget(mut self, retries: usize) -> Self {
+        self.config = self.config.with_retry_budget(retries);
+        self
+    }
+
+    /// Set the token counter shared by folding, batching and token accounting
+    pub fn with_token_counter(mut self, counter: Arc<dyn TokenCounter>) -> Self {
+        self.config = self.config.with_token_counter(counter);
+        self
+    }
+
+    /// Set the policy code blocks are checked against before they run
+    pub fn with_code_safety(mut self, policy: CodeSafetyPolicy) -> Self {
+        self.config = self.config.with_code_safety(policy);
+        self
+    }
+
+    /// Warm up every registered language while building
+    ///
+    /// [`build`](Self::build) then blocks until
+    /// [`RLMExecutor::warm_up`] finishes. Languages that fail to warm up
+    /// (usually because their toolchain is not installed) are logged, not
+    /// treated as build errors.
+    pub fn with_warm_up(mut self, enable: bool) -> Self {
+        self.warm_up = enable;
+        self
+    }
+
+    /// Answer iterations from `responses` instead of a live model
+    ///
+    /// The built executor gets a [`MockLLMClient`] as its LLM backend, which
+    /// returns the responses in order, so the whole execution loop runs
+    /// deterministically and without network access.
+    pub fn with_mock_llm(mut self, responses: impl IntoIterator<Item = String>) -> Self {
+        self.mock_responses = Some(responses.into_iter().collect());
+        self
+    }
+
+    /// Set what the mock LLM does once its responses run out
+    ///
+    /// Defaults to [`ExhaustionPolicy::RepeatLast`]. Has no effect without
+    /// [`with_mock_llm`](Self::with_mock_llm).
+    pub fn with_mock_llm_exhaustion_policy(mut self, policy: ExhaustionPolicy) -> Self {
+        self.mock_exhaustion_policy = policy;
+        self
+    }
+
     /// Build the RLM executor
     ///
     /// # Errors
     ///
-    /// Returns an error if configuration validation fails
-    pub fn build(self) -> RLMResult<RLMExecutor> {
+    /// Returns an error if a setter was given an invalid value or
+    /// configuration validation fails, if `max_context_length` exceeds the
+    /// context window of the [model](Self::with_model), if it is less than
+    /// twice `max_repl_output` (unless
+    /// [`allow_tight_context`](Self::allow_tight_context) is set), or if
+    /// warm-up was requested from inside a single-threaded Tokio runtime,
+    /// where `build` cannot block
+    pub fn build(mut self) -> RLMResult<RLMExecutor> {
+        if let Some(msg) = self.error {
+            return Err(RLMError::config(msg));
+        }
+
+        if self.auto_context_length && !self.context_length_set {
+            self.config.max_context_length = self
+                .config
+                .max_repl_output
+                .saturating_mul(AUTO_CONTEXT_TO_REPL_OUTPUT);
+        }
+        if let Some(model) = &self.model {
+            let window = ModelRegistry::global()
+                .context_window(model)
+                .map(|tokens| tokens.saturating_mul(CHARS_PER_TOKEN));
+            if let Some(window) = window.filter(|&window| self.config.max_context_length > window) {
+                if self.context_length_set {
+                    return Err(RLMError::config(format!(
+                        "max_context_length of {} exceeds the {} character context window of {}",
+                        self.config.max_context_length, window, model
+                    )));
+                }
+                self.config.max_context_length = window;
+            }
+        }
+        let min_context = self.config.max_repl_output.saturating_mul(MIN_CONTEXT_TO_REPL_OUTPUT);
+        if !self.allow_tight_context && self.config.max_context_length < min_context {
+            return Err(RLMError::config(
+                "max_context_length must be at least 2x max_repl_output",
+            ));
+        }
+
         // Validate configuration
-        self.config.validate()
-            .map_err(|msg| RLMError::config(msg))?;
+        let config = RLMConfigBuilder::from_config(self.config)
+            .build()
+            .map_err(RLMError::config)?;
 
         // Create executor with validated config
-        RLMExecutor::new(self.config)
+        let mut executor = RLMExecutor::new(config)?;
+        if let Some(responses) = self.mock_responses {
+            let mock = MockLLMClient::new(responses).with_exhaustion_policy(self.mock_exhaustion_policy);
+            executor = executor.with_llm_backend(Arc::new(mock));
+        }
+        if self.warm_up {
+            block_on(executor.warm_up())?;
+        }
+        Ok(executor)
     }
 
     /// Get a reference to the current configuration
@@ -133,6 +332,29 @@ impl RLMBuilder {
     }
 }
 
+/// Run `future` to completion from synchronous code
+///
+/// Inside a multi-threaded Tokio runtime the current worker blocks in place;
+/// outside any runtime a temporary one is started.
+fn block_on<F: Future>(future: F) -> RLMResult<F::Output> {
+    match Handle::try_current() {
+        Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => {
+            Ok(tokio::task::block_in_place(|| handle.block_on(future)))
+        }
+        Ok(_) => Err(RLMError::config(
+            "Warm-up during build needs a multi-threaded Tokio runtime; \
+             call RLMExecutor::warm_up instead",
+        )),
+        Err(_) => {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .map_err(|e| RLMError::wrap(e, "Failed to start a runtime for warm-up"))?;
+            Ok(runtime.block_on(future))
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -161,6 +383,13 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_builder_rejects_zero_output_lines() {
+        assert!(RLMBuilder::new().with_max_output_lines(0).build().is_err());
+        let builder = RLMBuilder::new().with_max_output_lines(20);
+        assert_eq!(builder.config.max_output_lines, Some(20));
+    }
+
     #[test]
     fn test_builder_build_invalid() {
         let builder = RLMBuilder::new().with_max_iterations(0);
@@ -168,6 +397,158 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_builder_keeps_first_setter_error() {
+        let builder = RLMBuilder::new()
+            .with_max_context_length(10)
+            .with_max_iterations(0);
+        assert_eq!(builder.config().max_context_length, 100_000);
+
+        let err = builder.build().unwrap_err();
+        assert!(err.to_string().contains("max_context_length"));
+    }
+
+    #[tokio::test]
+    async fn test_builder_warm_up_needs_multi_threaded_runtime() {
+        let err = RLMBuilder::new().with_warm_up(true).build().unwrap_err();
+        assert!(matches!(err, RLMError::ConfigError(_)));
+    }
+
+    #[test]
+    #[ignore]  // Runs every installed interpreter and compiler
+    fn test_builder_warms_up_outside_a_runtime() {
+        let executor = RLMBuilder::new().with_warm_up(true).build().unwrap();
+        assert!(!executor.warmed_languages().is_empty());
+    }
+
+    #[test]
+    fn test_builder_rejects_context_below_twice_repl_output() {
+        let err = RLMBuilder::new()
+            .with_max_repl_output(16384)
+            .with_max_context_length(20_000)
+            .build()
+            .unwrap_err();
+        assert!(matches!(err, RLMError::ConfigError(_)));
+        assert!(err
+            .to_string()
+            .contains("max_context_length must be at least 2x max_repl_output"));
+
+        let executor = RLMBuilder::new()
+            .with_max_repl_output(16384)
+            .with_max_context_length(32_768)
+            .build()
+            .unwrap();
+        assert_eq!(executor.config().max_context_length, 32_768);
+    }
+
+    #[test]
+    fn test_allow_tight_context_skips_the_check() {
+        let executor = RLMBuilder::new()
+            .with_max_repl_output(16384)
+            .with_max_context_length(20_000)
+            .allow_tight_context(true)
+            .build()
+            .unwrap();
+        assert_eq!(executor.config().max_context_length, 20_000);
+    }
+
+    #[test]
+    fn test_model_context_window_bounds_context_length() {
+        // codellama has a 16_384 token window, 65_536 characters
+        let executor = RLMBuilder::new().with_model("codellama").build().unwrap();
+        assert_eq!(executor.config().max_context_length, 65_536);
+
+        let executor = RLMBuilder::new()
+            .with_model("codellama:7b")
+            .with_max_context_length(50_000)
+            .build()
+            .unwrap();
+        assert_eq!(executor.config().max_context_length, 50_000);
+
+        let err = RLMBuilder::new()
+            .with_model("codellama")
+            .with_max_context_length(100_000)
+            .build()
+            .unwrap_err();
+        assert!(err.to_string().contains("context window of codellama"));
+
+        // Unknown models are not checked
+        let executor = RLMBuilder::new().with_model("in-house-model").build().unwrap();
+        assert_eq!(executor.config().max_context_length, 100_000);
+    }
+
+    #[test]
+    fn test_auto_context_length_is_four_times_repl_output() {
+        // Applied at build time, so the order of the setters does not matter
+        let executor = RLMBuilder::new()
+            .with_auto_context_length()
+            .with_max_repl_output(16384)
+            .build()
+            .unwrap();
+        assert_eq!(executor.config().max_context_length, 65_536);
+
+        // An explicit length wins, and is still validated
+        let executor = RLMBuilder::new()
+            .with_max_repl_output(1000)
+            .with_max_context_length(50_000)
+            .with_auto_context_length()
+            .build()
+            .unwrap();
+        assert_eq!(executor.config().max_context_length, 50_000);
+        assert!(RLMBuilder::new()
+            .with_max_repl_output(16384)
+            .with_max_context_length(20_000)
+            .with_auto_context_length()
+            .build()
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_mock_llm_drives_execute_loop() {
+        let executor = RLMBuilder::new()
+            .with_max_iterations(3)
+            .with_mock_llm([
+                "Checking.".to_string(),
+                "\nThe answer is 42.".to_string(),
+            ])
+            .build()
+            .unwrap();
+
+        let answer = executor.execute("Question?", "mock").await.unwrap();
+
+        assert!(answer.starts_with("Question?Checking.\nThe answer is 42."));
+        // The last response repeats once the queue is exhausted
+        assert_eq!(answer.matches("The answer is 42.").count(), 2);
+    }
+
+    #[tokio::test]
+    #[ignore]  // Requires bash to be installed
+    async fn test_mock_llm_code_block_is_executed() {
+        let executor = RLMBuilder::new()
+            .with_max_iterations(1)
+            .with_mock_llm(["Checking.\n```bash\necho mock-output\n```".to_string()])
+            .build()
+            .unwrap();
+
+        let answer = executor.execute("Question?", "mock").await.unwrap();
+
+        assert!(answer.starts_with("Question?Checking."));
+        assert!(answer.contains("[REPL:bash output]\nmock-output"));
+    }
+
+    #[tokio::test]
+    async fn test_mock_llm_exhaustion_policy_error_fails_execute() {
+        let executor = RLMBuilder::new()
+            .with_max_iterations(2)
+            .with_mock_llm(["Only one answer.".to_string()])
+            .with_mock_llm_exhaustion_policy(ExhaustionPolicy::Error)
+            .build()
+            .unwrap();
+
+        let result = executor.execute("Question?", "mock").await;
+        assert!(matches!(result, Err(RLMError::ExecutionError(_))));
+    }
+
     #[test]
     fn test_builder_config_access() {
         let mut builder = RLMBuilder::new();
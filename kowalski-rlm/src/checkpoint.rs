@@ -0,0 +1,173 @@
+//! Persistent checkpoints of RLM runs
+//!
+//! A [`CheckpointStore`] keeps the latest [`RLMContext`] of each workflow
+//! outside the process. With a store attached, the executor saves the
+//! context after every iteration, and
+//! [`RLMExecutor::resume`](crate::executor::RLMExecutor::resume) picks an
+//! interrupted run back up from its last checkpoint.
+//!
+//! [`FileCheckpointStore`] writes one JSON file per workflow. Other backends
+//! (Redis, a database) implement the trait the same way.
+
+use crate::context::RLMContext;
+use crate::error::{RLMError, RLMResult};
+use async_trait::async_trait;
+use std::fmt;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+/// Storage for the latest checkpoint of each workflow
+#[async_trait]
+pub trait CheckpointStore: Send + Sync {
+    /// Store `context` as the latest checkpoint of `workflow_id`
+    ///
+    /// Replaces any earlier checkpoint of the same workflow.
+    async fn save(&self, workflow_id: &str, context: &RLMContext) -> RLMResult<()>;
+
+    /// Latest checkpoint of `workflow_id`, or `None` if there is none
+    ///
+    /// The returned context has no configuration attached; see
+    /// [`RLMContext::with_config`].
+    async fn load(&self, workflow_id: &str) -> RLMResult<Option<RLMContext>>;
+
+    /// Short name of the store, used in debug output
+    fn name(&self) -> &str {
+        "custom"
+    }
+}
+
+impl fmt::Debug for dyn CheckpointStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CheckpointStore({})", self.name())
+    }
+}
+
+/// Checkpoint store keeping one `<workflow_id>.json` file per workflow
+///
+/// Checkpoints are written to a temporary file and renamed into place, so a
+/// crash while saving leaves the previous checkpoint intact.
+#[derive(Debug, Clone)]
+pub struct FileCheckpointStore {
+    dir: PathBuf,
+}
+
+impl FileCheckpointStore {
+    /// Store checkpoints in `dir`, which is created on the first save
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Directory the checkpoints are written to
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Path of the checkpoint file for `workflow_id`
+    ///
+    /// Workflow IDs become file names, so only ASCII letters, digits, `-`,
+    /// `_` and `.` are accepted.
+    fn path_for(&self, workflow_id: &str) -> RLMResult<PathBuf> {
+        let valid = !workflow_id.is_empty()
+            && !workflow_id.starts_with('.')
+            && workflow_id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+        if !valid {
+            return Err(RLMError::config(format!(
+                "Invalid workflow ID for a checkpoint file: {:?}",
+                workflow_id
+            )));
+        }
+        Ok(self.dir.join(format!("{}.json", workflow_id)))
+    }
+}
+
+#[async_trait]
+impl CheckpointStore for FileCheckpointStore {
+    async fn save(&self, workflow_id: &str, context: &RLMContext) -> RLMResult<()> {
+        let path = self.path_for(workflow_id)?;
+        let json = serde_json::to_vec(context).map_err(|e| {
+            RLMError::serialization(format!("Failed to serialize checkpoint: {}", e))
+        })?;
+
+        tokio::fs::create_dir_all(&self.dir).await?;
+        let partial = path.with_extension("json.tmp");
+        tokio::fs::write(&partial, json).await?;
+        tokio::fs::rename(&partial, &path).await?;
+        Ok(())
+    }
+
+    async fn load(&self, workflow_id: &str) -> RLMResult<Option<RLMContext>> {
+        let path = self.path_for(workflow_id)?;
+        let json = match tokio::fs::read(&path).await {
+            Ok(json) => json,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        serde_json::from_slice(&json).map(Some).map_err(|e| {
+            RLMError::serialization(format!(
+                "Failed to read checkpoint {}: {}",
+                path.display(),
+                e
+            ))
+        })
+    }
+
+    fn name(&self) -> &str {
+        "file"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RLMConfig;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_file_store_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FileCheckpointStore::new(dir.path().join("checkpoints"));
+        let mut context = RLMContext::new("wf-1", Arc::new(RLMConfig::default()));
+        context.next_iteration();
+        context.append_answer("partial answer");
+        context.record_error("flaky");
+
+        assert!(store.load("wf-1").await.unwrap().is_none());
+        store.save("wf-1", &context).await.unwrap();
+
+        let loaded = store.load("wf-1").await.unwrap().unwrap();
+        assert_eq!(loaded.task_id, "wf-1");
+        assert_eq!(loaded.iteration(), 1);
+        assert_eq!(loaded.answer(), "partial answer");
+        assert_eq!(loaded.metadata.errors, vec!["flaky"]);
+        assert!(store.dir().join("wf-1.json").exists());
+        assert!(!store.dir().join("wf-1.json.tmp").exists());
+    }
+
+    #[tokio::test]
+    async fn test_file_store_rejects_path_like_ids() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FileCheckpointStore::new(dir.path());
+        let context = RLMContext::new("wf", Arc::new(RLMConfig::default()));
+
+        for id in ["", "../escape", "a/b", ".hidden"] {
+            assert!(matches!(
+                store.save(id, &context).await,
+                Err(RLMError::ConfigError(_))
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_file_store_reports_corrupt_checkpoint() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FileCheckpointStore::new(dir.path());
+        std::fs::write(dir.path().join("broken.json"), "{not json").unwrap();
+
+        assert!(matches!(
+            store.load("broken").await,
+            Err(RLMError::SerializationError(_))
+        ));
+    }
+}
@@ -1,12 +1,97 @@
 use crate::error::RLMResult;
+use futures::{future, Stream, StreamExt};
 use lazy_static::lazy_static;
 use regex::Regex;
 
+/// Execution hint annotated on a fence header, e.g. ```` ```python:no_execute ````
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExecutionHint {
+    /// Block should be executed (default)
+    #[default]
+    Execute,
+    /// Block is illustrative and must not be executed (`:no_execute`, `:noexec`)
+    NoExecute,
+    /// Block should be ignored entirely (`:ignore`)
+    Ignore,
+}
+
+impl ExecutionHint {
+    /// Parse the annotations following the language name in a fence header
+    fn from_annotations<'a>(annotations: impl Iterator<Item = &'a str>) -> Self {
+        let mut hint = ExecutionHint::Execute;
+        for annotation in annotations {
+            match annotation.trim() {
+                "no_execute" | "noexec" => hint = ExecutionHint::NoExecute,
+                "ignore" => hint = ExecutionHint::Ignore,
+                _ => {}
+            }
+        }
+        hint
+    }
+}
+
 /// Represents a parsed code block with language and code content
 #[derive(Debug, Clone)]
 pub struct CodeBlock {
     pub language: String,
     pub code: String,
+    /// Execution hint taken from the fence header
+    pub execution_hint: ExecutionHint,
+}
+
+impl CodeBlock {
+    /// Returns true if the block should be run by a REPL executor
+    pub fn is_executable(&self) -> bool {
+        self.execution_hint == ExecutionHint::Execute
+    }
+}
+
+/// A fence language recognized by the parser
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LanguageSpec {
+    /// Canonical name reported in [`CodeBlock::language`]
+    pub canonical: String,
+    /// Other fence names that normalize to the canonical name
+    pub aliases: Vec<String>,
+}
+
+impl LanguageSpec {
+    /// Create a language spec; names are matched case-insensitively
+    pub fn new(canonical: &str, aliases: &[&str]) -> Self {
+        LanguageSpec {
+            canonical: canonical.to_lowercase(),
+            aliases: aliases.iter().map(|alias| alias.to_lowercase()).collect(),
+        }
+    }
+
+    /// Languages supported by the built-in REPL executors
+    pub fn defaults() -> Vec<LanguageSpec> {
+        #[allow(unused_mut)]
+        let mut languages = vec![
+            LanguageSpec::new("python", &["py", "python3", "python2"]),
+            LanguageSpec::new("rust", &["rs"]),
+            LanguageSpec::new("java", &[]),
+            LanguageSpec::new("kotlin", &["kt"]),
+            LanguageSpec::new("csharp", &["cs", "c#"]),
+            LanguageSpec::new("javascript", &["js"]),
+            LanguageSpec::new("bash", &["sh", "shell"]),
+            LanguageSpec::new("lua", &[]),
+            LanguageSpec::new("r", &["rscript"]),
+            LanguageSpec::new("php", &[]),
+            LanguageSpec::new("haskell", &["hs"]),
+        ];
+        #[cfg(feature = "docker")]
+        languages.extend([
+            LanguageSpec::new("c", &[]),
+            LanguageSpec::new("cpp", &["c++", "cxx", "cc"]),
+        ]);
+        languages
+    }
+
+    /// Returns true if `name` is the canonical name or an alias
+    fn matches(&self, name: &str) -> bool {
+        self.canonical == name || self.aliases.iter().any(|alias| alias == name)
+    }
 }
 
 /// Parser for extracting code blocks from text
@@ -14,6 +99,8 @@ pub struct CodeBlockParser {
     markdown_fence_regex: Regex,
     tilde_fence_regex: Regex,
     indented_code_regex: Regex,
+    languages: Vec<LanguageSpec>,
+    html_mode: bool,
 }
 
 lazy_static! {
@@ -28,35 +115,118 @@ lazy_static! {
     // Matches indented code blocks (4 spaces or tab)
     static ref INDENTED_CODE: Regex =
         Regex::new(r"(?:^|\n)((?:    |\t)[^\n]*(?:\n(?:    |\t)[^\n]*)*)").unwrap();
+
+    // Matches <code attrs>code</code>
+    static ref HTML_CODE: Regex =
+        Regex::new(r"(?is)<code\b([^>]*)>(.*?)</code\s*>").unwrap();
+
+    // Matches class="... language-xxx ..." inside tag attributes
+    static ref HTML_LANGUAGE_CLASS: Regex =
+        Regex::new(r#"(?i)\bclass\s*=\s*["'][^"']*?\blanguage-([\w+#-]+)"#).unwrap();
+
+    // Matches lang="xxx" inside tag attributes
+    static ref HTML_LANG_ATTR: Regex =
+        Regex::new(r#"(?i)\blang\s*=\s*["']([^"']+)["']"#).unwrap();
+}
+
+/// Decode the HTML entities that commonly appear in escaped code
+fn decode_html_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        let tail = &rest[start..];
+
+        let entity = tail.find(';').and_then(|end| {
+            let name = &tail[1..end];
+            let ch = match name {
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "amp" => Some('&'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some('\u{a0}'),
+                _ => name
+                    .strip_prefix("#x")
+                    .or_else(|| name.strip_prefix("#X"))
+                    .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                    .or_else(|| name.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                    .and_then(char::from_u32),
+            };
+            ch.map(|ch| (ch, end))
+        });
+
+        match entity {
+            Some((ch, end)) => {
+                decoded.push(ch);
+                rest = &tail[end + 1..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &tail[1..];
+            }
+        }
+    }
+
+    decoded.push_str(rest);
+    decoded
 }
 
 impl CodeBlockParser {
-    /// Create a new CodeBlockParser
+    /// Create a new CodeBlockParser recognizing the default languages
     pub fn new() -> Self {
+        Self::new_with_languages(LanguageSpec::defaults())
+    }
+
+    /// Create a CodeBlockParser with a custom language table
+    ///
+    /// Only fences whose language matches an entry are extracted, and their
+    /// language is normalized to the entry's canonical name.
+    pub fn new_with_languages(languages: Vec<LanguageSpec>) -> Self {
         CodeBlockParser {
             markdown_fence_regex: MARKDOWN_FENCE.clone(),
             tilde_fence_regex: TILDE_FENCE.clone(),
             indented_code_regex: INDENTED_CODE.clone(),
+            languages,
+            html_mode: false,
         }
     }
 
+    /// Also extract code from HTML `<code>` elements
+    ///
+    /// The language is read from a `class="language-*"` or `lang` attribute
+    /// and HTML entities in the code are decoded. Elements without a
+    /// supported language are skipped.
+    pub fn with_html_extraction(mut self) -> Self {
+        self.html_mode = true;
+        self
+    }
+
+    /// Languages recognized by this parser
+    pub fn languages(&self) -> &[LanguageSpec] {
+        &self.languages
+    }
+
     /// Extract all code blocks from text
     ///
     /// Returns a vector of (language, code) tuples.
-    /// Supports markdown fences (```), tilde fences (~~~), and indented blocks.
+    /// Supports markdown fences (```), tilde fences (~~~), and indented blocks,
+    /// plus HTML `<code>` elements when [`with_html_extraction`](Self::with_html_extraction) is enabled.
     pub fn extract_from(&self, text: &str) -> RLMResult<Vec<CodeBlock>> {
         let mut blocks = Vec::new();
 
         // Extract markdown fences first
         for caps in self.markdown_fence_regex.captures_iter(text) {
-            if let (Some(lang_match), Some(code_match)) = (caps.get(1), caps.get(2)) {
-                let language = lang_match.as_str().trim().to_lowercase();
+            if let (Some(header_match), Some(code_match)) = (caps.get(1), caps.get(2)) {
+                let (language, execution_hint) = self.parse_fence_header(header_match.as_str());
                 let code = code_match.as_str().to_string();
 
                 if self.is_supported_language(&language) {
                     blocks.push(CodeBlock {
                         language: self.normalize_language(&language),
                         code: code.trim().to_string(),
+                        execution_hint,
                     });
                 }
             }
@@ -64,21 +234,50 @@ impl CodeBlockParser {
 
         // Extract tilde fences
         for caps in self.tilde_fence_regex.captures_iter(text) {
-            if let (Some(lang_match), Some(code_match)) = (caps.get(1), caps.get(2)) {
-                let language = lang_match.as_str().trim().to_lowercase();
+            if let (Some(header_match), Some(code_match)) = (caps.get(1), caps.get(2)) {
+                let (language, execution_hint) = self.parse_fence_header(header_match.as_str());
                 let code = code_match.as_str().to_string();
 
                 if self.is_supported_language(&language) {
                     blocks.push(CodeBlock {
                         language: self.normalize_language(&language),
                         code: code.trim().to_string(),
+                        execution_hint,
                     });
                 }
             }
         }
 
-        // Extract indented code blocks (assume python if not specified)
-        for caps in self.indented_code_regex.captures_iter(text) {
+        // Extract HTML <code> elements
+        if self.html_mode {
+            for caps in HTML_CODE.captures_iter(text) {
+                if let (Some(attrs_match), Some(code_match)) = (caps.get(1), caps.get(2)) {
+                    let attrs = attrs_match.as_str();
+                    let language = HTML_LANGUAGE_CLASS
+                        .captures(attrs)
+                        .or_else(|| HTML_LANG_ATTR.captures(attrs))
+                        .and_then(|lang| lang.get(1))
+                        .map(|lang| lang.as_str().trim().to_lowercase());
+
+                    if let Some(language) = language.filter(|lang| self.is_supported_language(lang)) {
+                        blocks.push(CodeBlock {
+                            language: self.normalize_language(&language),
+                            code: decode_html_entities(code_match.as_str()).trim().to_string(),
+                            execution_hint: ExecutionHint::Execute,
+                        });
+                    }
+                }
+            }
+        }
+
+        // Extract indented code blocks (assume python if not specified).
+        // Indentation inside HTML code elements was handled above.
+        let indented_source = if self.html_mode {
+            HTML_CODE.replace_all(text, "")
+        } else {
+            std::borrow::Cow::Borrowed(text)
+        };
+        for caps in self.indented_code_regex.captures_iter(&indented_source) {
             if let Some(code_match) = caps.get(1) {
                 let code = code_match
                     .as_str()
@@ -98,6 +297,7 @@ impl CodeBlockParser {
                 blocks.push(CodeBlock {
                     language: "python".to_string(),
                     code: code.trim().to_string(),
+                    execution_hint: ExecutionHint::Execute,
                 });
             }
         }
@@ -116,35 +316,27 @@ impl CodeBlockParser {
         }
     }
 
+    /// Split a fence header like `python:no_execute` into language and hint
+    fn parse_fence_header(&self, header: &str) -> (String, ExecutionHint) {
+        let header = header.trim().to_lowercase();
+        let mut parts = header.split(':');
+        let language = parts.next().unwrap_or_default().trim().to_string();
+        (language, ExecutionHint::from_annotations(parts))
+    }
+
     /// Check if language is supported
     fn is_supported_language(&self, lang: &str) -> bool {
-        matches!(
-            lang,
-            "python"
-                | "py"
-                | "python3"
-                | "python2"
-                | "rust"
-                | "rs"
-                | "java"
-                | "javascript"
-                | "js"
-                | "bash"
-                | "sh"
-                | "shell"
-        )
+        self.languages.iter().any(|spec| spec.matches(lang))
     }
 
     /// Normalize language name to standard form
     fn normalize_language(&self, raw: &str) -> String {
-        match raw.trim().to_lowercase().as_str() {
-            "python" | "py" | "python3" | "python2" => "python".to_string(),
-            "rust" | "rs" => "rust".to_string(),
-            "java" => "java".to_string(),
-            "javascript" | "js" => "javascript".to_string(),
-            "bash" | "sh" | "shell" => "bash".to_string(),
-            _ => raw.to_lowercase(),
-        }
+        let name = raw.trim().to_lowercase();
+        self.languages
+            .iter()
+            .find(|spec| spec.matches(&name))
+            .map(|spec| spec.canonical.clone())
+            .unwrap_or(name)
     }
 }
 
@@ -154,6 +346,95 @@ impl Default for CodeBlockParser {
     }
 }
 
+/// Fence markers recognized by [`CodeBlockExtractor`]
+const FENCE_MARKERS: [&str; 2] = ["```", "~~~"];
+
+/// A fenced block whose closing fence has not been seen yet
+#[derive(Debug)]
+struct OpenFence {
+    marker: &'static str,
+    header: String,
+    lines: Vec<String>,
+}
+
+/// Line-by-line code block extraction for streamed text
+///
+/// Unlike [`CodeBlockParser::extract_from`], which needs the whole text in
+/// memory, the extractor is fed one line at a time and only keeps the lines
+/// of the block currently open. It recognizes fences opened and closed on
+/// their own lines, with the same languages and execution hints as its
+/// parser; indented blocks and HTML `<code>` elements are not extracted.
+#[derive(Default)]
+pub struct CodeBlockExtractor {
+    parser: CodeBlockParser,
+    open: Option<OpenFence>,
+}
+
+impl CodeBlockExtractor {
+    /// Create an extractor recognizing the default languages
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an extractor using the language table of `parser`
+    pub fn with_parser(parser: CodeBlockParser) -> Self {
+        CodeBlockExtractor { parser, open: None }
+    }
+
+    /// Returns true if a fence has been opened but not yet closed
+    pub fn pending(&self) -> bool {
+        self.open.is_some()
+    }
+
+    /// Process one line of text
+    ///
+    /// Returns the block when `line` is its closing fence. Blocks in an
+    /// unsupported language are consumed without being returned. A trailing
+    /// line ending on `line` is ignored.
+    pub fn feed_line(&mut self, line: &str) -> Option<CodeBlock> {
+        let line = line.trim_end_matches(['\n', '\r']);
+        let fence = line.trim();
+
+        let Some(open) = &mut self.open else {
+            let marker = FENCE_MARKERS.into_iter().find(|marker| fence.starts_with(marker))?;
+            self.open = Some(OpenFence {
+                marker,
+                header: fence[marker.len()..].to_string(),
+                lines: Vec::new(),
+            });
+            return None;
+        };
+
+        if fence != open.marker {
+            open.lines.push(line.to_string());
+            return None;
+        }
+
+        let open = self.open.take()?;
+        let (language, execution_hint) = self.parser.parse_fence_header(&open.header);
+        if !self.parser.is_supported_language(&language) {
+            return None;
+        }
+        Some(CodeBlock {
+            language: self.parser.normalize_language(&language),
+            code: open.lines.join("\n").trim().to_string(),
+            execution_hint,
+        })
+    }
+
+    /// Extract code blocks from a stream of lines as their fences close
+    ///
+    /// Each block is yielded as soon as its closing line arrives, without
+    /// waiting for the rest of the stream.
+    pub fn from_stream(lines: impl Stream<Item = String>) -> impl Stream<Item = CodeBlock> {
+        lines
+            .scan(Self::new(), |extractor, line| {
+                future::ready(Some(extractor.feed_line(&line)))
+            })
+            .filter_map(future::ready)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -211,6 +492,58 @@ fn main() {}
         assert_eq!(blocks[0].language, "java");
     }
 
+    #[test]
+    fn test_extract_kotlin() {
+        let parser = CodeBlockParser::new();
+        let text = "```kt\nprintln(\"hi\")\n```";
+        let blocks = parser.extract_from(text).unwrap();
+
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].language, "kotlin");
+    }
+
+    #[test]
+    fn test_extract_lua() {
+        let parser = CodeBlockParser::new();
+        let text = "```Lua\nprint(\"hi\")\n```";
+        let blocks = parser.extract_from(text).unwrap();
+
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].language, "lua");
+    }
+
+    #[test]
+    fn test_extract_php() {
+        let parser = CodeBlockParser::new();
+        let text = "```php\necho \"hi\";\n```";
+        let blocks = parser.extract_from(text).unwrap();
+
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].language, "php");
+        assert!(blocks[0].is_executable());
+    }
+
+    #[test]
+    fn test_extract_haskell() {
+        let parser = CodeBlockParser::new();
+        let text = "```hs\nsum [1..10]\n```";
+        let blocks = parser.extract_from(text).unwrap();
+
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].language, "haskell");
+        assert!(blocks[0].is_executable());
+    }
+
+    #[test]
+    fn test_extract_r() {
+        let parser = CodeBlockParser::new();
+        let text = "```R\nprint(mean(c(1, 2, 3)))\n```\n```rscript\nsummary(x)\n```";
+        let blocks = parser.extract_from(text).unwrap();
+
+        assert_eq!(blocks.len(), 2);
+        assert!(blocks.iter().all(|block| block.language == "r"));
+    }
+
     #[test]
     fn test_extract_javascript() {
         let parser = CodeBlockParser::new();
@@ -231,6 +564,38 @@ fn main() {}
         assert_eq!(blocks[0].language, "bash");
     }
 
+    #[test]
+    fn test_execution_hints_in_mixed_document() {
+        let parser = CodeBlockParser::new();
+        let text = "Run this:\n```python\nprint(1)\n```\n\
+                    Example only:\n```python:no_execute\nprint(2)\n```\n\
+                    Also illustrative:\n```python:noexec\nprint(3)\n```\n\
+                    Skip:\n```python:ignore\nprint(4)\n```\n";
+        let blocks = parser.extract_from(text).unwrap();
+
+        assert_eq!(blocks.len(), 4);
+        assert!(blocks.iter().all(|b| b.language == "python"));
+        assert_eq!(blocks[0].execution_hint, ExecutionHint::Execute);
+        assert_eq!(blocks[1].execution_hint, ExecutionHint::NoExecute);
+        assert_eq!(blocks[2].execution_hint, ExecutionHint::NoExecute);
+        assert_eq!(blocks[3].execution_hint, ExecutionHint::Ignore);
+
+        let executable: Vec<_> = blocks.iter().filter(|b| b.is_executable()).collect();
+        assert_eq!(executable.len(), 1);
+        assert_eq!(executable[0].code, "print(1)");
+    }
+
+    #[test]
+    fn test_tilde_fence_execution_hint() {
+        let parser = CodeBlockParser::new();
+        let text = "~~~rust:ignore\nfn main() {}\n~~~";
+        let blocks = parser.extract_from(text).unwrap();
+
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].language, "rust");
+        assert!(!blocks[0].is_executable());
+    }
+
     #[test]
     fn test_extract_tilde_fence() {
         let parser = CodeBlockParser::new();
@@ -255,16 +620,35 @@ fn main() {}
         assert_eq!(parser.detect_language("JS"), Some("javascript".to_string()));
     }
 
+    #[test]
+    fn test_extract_csharp_aliases() {
+        let parser = CodeBlockParser::new();
+        let text = "```c#\nConsole.WriteLine(1);\n```\n```cs\nConsole.WriteLine(2);\n```";
+        let blocks = parser.extract_from(text).unwrap();
+
+        assert_eq!(blocks.len(), 2);
+        assert!(blocks.iter().all(|block| block.language == "csharp"));
+    }
+
     #[test]
     fn test_unsupported_language() {
         let parser = CodeBlockParser::new();
-        let text = "```c++\nint x = 1;\n```";
+        let text = "```fortran\ninteger :: x = 1\n```";
         let blocks = parser.extract_from(text).unwrap();
 
-        // C++ is not supported, so no blocks should be extracted
+        // Fortran is not supported, so no blocks should be extracted
         assert_eq!(blocks.len(), 0);
     }
 
+    #[cfg(feature = "docker")]
+    #[test]
+    fn test_cpp_aliases_with_docker() {
+        let parser = CodeBlockParser::new();
+        let blocks = parser.extract_from("```c++\nint x = 1;\n```").unwrap();
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].language, "cpp");
+    }
+
     #[test]
     fn test_code_with_special_chars() {
         let parser = CodeBlockParser::new();
@@ -296,4 +680,95 @@ print(s)
 
         assert_eq!(blocks.len(), 0);
     }
+
+    #[test]
+    fn test_custom_language_alias() {
+        let mut languages = LanguageSpec::defaults();
+        languages.push(LanguageSpec::new("ruby", &["rb"]));
+        let parser = CodeBlockParser::new_with_languages(languages);
+
+        let text = "```rb\nputs 'hi'\n```\n```python\nprint('hi')\n```";
+        let blocks = parser.extract_from(text).unwrap();
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].language, "ruby");
+        assert_eq!(blocks[0].code, "puts 'hi'");
+        assert_eq!(blocks[1].language, "python");
+        assert_eq!(parser.detect_language("RB"), Some("ruby".to_string()));
+
+        // The default table does not know about Ruby
+        assert!(CodeBlockParser::new()
+            .extract_from("```rb\nputs 'hi'\n```")
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_extract_html_code_with_language_class() {
+        let parser = CodeBlockParser::new().with_html_extraction();
+        let text = r#"<p>Try this:</p>
+<pre><code class="hljs language-python">if a &lt; b &amp;&amp; b &gt; 0:
+    print(&quot;ok&quot;, &#39;x&#39;)
+</code></pre>
+<p>Inline <code>x</code> is ignored.</p>
+<pre><code lang="js">console.log(1 &lt;= 2);</code></pre>"#;
+
+        let blocks = parser.extract_from(text).unwrap();
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].language, "python");
+        assert_eq!(blocks[0].code, "if a < b && b > 0:\n    print(\"ok\", 'x')");
+        assert_eq!(blocks[1].language, "javascript");
+        assert_eq!(blocks[1].code, "console.log(1 <= 2);");
+    }
+
+    #[test]
+    fn test_extractor_emits_blocks_at_closing_fence() {
+        let document = "Intro\n```python\nprint('a')\n\nprint('b')\n```\nBetween\n~~~bash:no_execute\necho hi\n~~~\n```unknown\nskip me\n```\n```js\nconsole.log(1);";
+        let mut extractor = CodeBlockExtractor::new();
+
+        let mut emitted = Vec::new();
+        for (number, line) in document.lines().enumerate() {
+            if let Some(block) = extractor.feed_line(line) {
+                emitted.push((number, block));
+            }
+        }
+
+        assert_eq!(emitted.len(), 2);
+        let (line, python) = &emitted[0];
+        assert_eq!(*line, 5);
+        assert_eq!(python.language, "python");
+        assert_eq!(python.code, "print('a')\n\nprint('b')");
+        assert!(python.is_executable());
+        let (line, bash) = &emitted[1];
+        assert_eq!(*line, 9);
+        assert_eq!(bash.language, "bash");
+        assert_eq!(bash.execution_hint, ExecutionHint::NoExecute);
+
+        // The trailing JavaScript fence is never closed
+        assert!(extractor.pending());
+        assert!(extractor.feed_line("```\n").is_some());
+        assert!(!extractor.pending());
+    }
+
+    #[tokio::test]
+    async fn test_extractor_from_stream_matches_extract_from() {
+        let document = "```python\nx = 1\n```\ntext\n```rust:ignore\nfn main() {}\n```\n";
+        let lines = futures::stream::iter(document.lines().map(str::to_string));
+
+        let streamed: Vec<CodeBlock> = CodeBlockExtractor::from_stream(lines).collect().await;
+        let parsed = CodeBlockParser::new().extract_from(document).unwrap();
+
+        assert_eq!(streamed.len(), parsed.len());
+        for (streamed, parsed) in streamed.iter().zip(&parsed) {
+            assert_eq!(streamed.language, parsed.language);
+            assert_eq!(streamed.code, parsed.code);
+            assert_eq!(streamed.execution_hint, parsed.execution_hint);
+        }
+    }
+
+    #[test]
+    fn test_html_extraction_is_opt_in() {
+        let text = r#"<pre><code class="language-python">print(1)</code></pre>"#;
+        assert!(CodeBlockParser::new().extract_from(text).unwrap().is_empty());
+        assert_eq!(decode_html_entities("&amp;lt; &#x41;&#66; &bogus; &"), "&lt; AB &bogus; &");
+    }
 }
@@ -0,0 +1,257 @@
+//! Deny-list checks for generated code
+//!
+//! A [`CodeSafetyPolicy`] holds regular expressions per language. The
+//! executor checks every code block against the policy before running it,
+//! and a block matching one of its language's patterns fails with
+//! [`RLMError::PolicyViolation`] instead of executing.
+//!
+//! The default policy allows everything; [`CodeSafetyPolicy::recommended`]
+//! blocks destructive shell commands, process spawning and network access.
+//! Pattern checks are a coarse filter, not a sandbox: they catch the obvious
+//! cases, not code written to evade them.
+
+use crate::error::{RLMError, RLMResult};
+use crate::repl_executor::REPLExecutorFactory;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Key of the patterns that apply to every language
+pub const ANY_LANGUAGE: &str = "*";
+
+/// Forbidden code patterns, keyed by canonical language name
+///
+/// Serializes as a map from language to pattern strings.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(try_from = "HashMap<String, Vec<String>>", into = "HashMap<String, Vec<String>>")]
+pub struct CodeSafetyPolicy {
+    deny: HashMap<String, Vec<Regex>>,
+}
+
+impl CodeSafetyPolicy {
+    /// Create a policy that allows all code
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Deny-list covering common destructive or escaping operations
+    ///
+    /// Blocks recursive forced deletes and network downloads in shell code,
+    /// and process spawning and network access in Python and JavaScript.
+    pub fn recommended() -> Self {
+        let rules: &[(&str, &[&str])] = &[
+            (
+                "bash",
+                &[
+                    // `rm -rf`, `rm -Rf`, `rm -r -f`, `rm --recursive --force`, ...
+                    r"(?i)\brm(\s+-\S+)*\s+-[a-z]*(r[a-z]*f|f[a-z]*r)[a-z]*\b",
+                    r"(?i)\brm(\s+-\S+)*\s+(-[a-z]*r[a-z]*|--recursive)(\s+-\S+)*\s+(-[a-z]*f[a-z]*|--force)\b",
+                    r"(?i)\brm(\s+-\S+)*\s+(-[a-z]*f[a-z]*|--force)(\s+-\S+)*\s+(-[a-z]*r[a-z]*|--recursive)\b",
+                    r"\b(curl|wget|nc|ncat)\b",
+                    r"\b(mkfs|dd|shutdown|reboot)\b",
+                ],
+            ),
+            (
+                "python",
+                &[
+                    r"\bsubprocess\b",
+                    r"\bos\.(system|popen|exec\w*|spawn\w*|remove|unlink|rmdir)\b",
+                    r"\bshutil\.rmtree\b",
+                    // Only imports, so a variable named `requests` is allowed
+                    r"(?m)^\s*(import\s+([\w.]+(\s+as\s+\w+)?\s*,\s*)*|from\s+)(socket|urllib\d?|requests|http)\b",
+                ],
+            ),
+            (
+                "javascript",
+                &[
+                    r"\bchild_process\b",
+                    r#"require\(\s*['"](node:)?(http|https|net|dgram)['"]\s*\)"#,
+                    r"\bfetch\s*\(",
+                    r"\bfs\.(rm|rmdir|unlink)(Sync)?\b",
+                ],
+            ),
+        ];
+
+        let mut policy = Self::new();
+        for (language, patterns) in rules {
+            for pattern in *patterns {
+                policy = policy
+                    .with_denied_pattern(language, pattern)
+                    .expect("built-in code safety patterns are valid");
+            }
+        }
+        policy
+    }
+
+    /// Deny code in `language` that matches `pattern`
+    ///
+    /// `language` may be an alias (`sh`, `py`); use [`ANY_LANGUAGE`] for a
+    /// pattern that applies to every language.
+    ///
+    /// # Errors
+    ///
+    /// Returns a configuration error if `pattern` is not a valid regular
+    /// expression.
+    pub fn with_denied_pattern(mut self, language: &str, pattern: &str) -> RLMResult<Self> {
+        let regex = Regex::new(pattern).map_err(|e| {
+            RLMError::config(format!("Invalid code safety pattern {:?}: {}", pattern, e))
+        })?;
+        self.deny.entry(policy_key(language)).or_default().push(regex);
+        Ok(self)
+    }
+
+    /// Returns true if the policy denies nothing
+    pub fn is_permissive(&self) -> bool {
+        self.deny.values().all(Vec::is_empty)
+    }
+
+    /// Check `code` written in `language` against the policy
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RLMError::PolicyViolation`] naming the first matching
+    /// pattern.
+    pub fn check(&self, language: &str, code: &str) -> RLMResult<()> {
+        let key = policy_key(language);
+        let patterns = self
+            .deny
+            .get(&key)
+            .into_iter()
+            .chain(self.deny.get(ANY_LANGUAGE))
+            .flatten();
+        for pattern in patterns {
+            if pattern.is_match(code) {
+                return Err(RLMError::policy_violation(key, pattern.as_str()));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Policy key of `language`: its canonical name if known, else the name as given
+fn policy_key(language: &str) -> String {
+    REPLExecutorFactory::canonical_language(language)
+        .map(str::to_string)
+        .unwrap_or_else(|| language.to_lowercase())
+}
+
+impl TryFrom<HashMap<String, Vec<String>>> for CodeSafetyPolicy {
+    type Error = RLMError;
+
+    fn try_from(rules: HashMap<String, Vec<String>>) -> RLMResult<Self> {
+        let mut policy = Self::new();
+        for (language, patterns) in rules {
+            for pattern in patterns {
+                policy = policy.with_denied_pattern(&language, &pattern)?;
+            }
+        }
+        Ok(policy)
+    }
+}
+
+impl From<CodeSafetyPolicy> for HashMap<String, Vec<String>> {
+    fn from(policy: CodeSafetyPolicy) -> Self {
+        policy
+            .deny
+            .into_iter()
+            .map(|(language, patterns)| {
+                (language, patterns.iter().map(|p| p.as_str().to_string()).collect())
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_policy_allows_everything() {
+        let policy = CodeSafetyPolicy::default();
+        assert!(policy.is_permissive());
+        assert!(policy.check("bash", "rm -rf /").is_ok());
+    }
+
+    #[test]
+    fn test_recommended_policy_blocks_rm_rf() {
+        let policy = CodeSafetyPolicy::recommended();
+
+        for code in [
+            "rm -rf /",
+            "cd /tmp && rm -fr ./build",
+            "rm -r -f /",
+            "rm -Rf /",
+            "RM -RF /",
+            "rm -v -R -f /",
+            "rm --recursive --force /",
+            "rm --force -r /",
+        ] {
+            let err = policy.check("sh", code).unwrap_err();
+            assert!(
+                matches!(&err, RLMError::PolicyViolation { language, .. } if language == "bash"),
+                "{} was not blocked: {:?}",
+                code,
+                err
+            );
+        }
+    }
+
+    #[test]
+    fn test_recommended_policy_allows_benign_scripts() {
+        let policy = CodeSafetyPolicy::recommended();
+
+        assert!(policy.check("bash", "echo hello\nls -la\nrm notes.txt").is_ok());
+        assert!(policy.check("bash", "rm -r build\nrm -f notes.txt\nrm --recursive logs").is_ok());
+        assert!(policy.check("python", "import math\nprint(math.sqrt(2))").is_ok());
+        assert!(policy.check("javascript", "console.log([1, 2].map(x => x * 2));").is_ok());
+        // Patterns only apply to their own language
+        assert!(policy.check("lua", "os.execute('curl example.com')").is_ok());
+    }
+
+    #[test]
+    fn test_recommended_policy_blocks_python_network_imports() {
+        let policy = CodeSafetyPolicy::recommended();
+
+        for code in [
+            "import requests",
+            "import os, socket",
+            "import json as j, urllib.request",
+            "from urllib.request import urlopen",
+            "def fetch():\n    from http import client",
+        ] {
+            assert!(policy.check("python", code).is_err(), "{} was not blocked", code);
+        }
+
+        // Names that merely look like network modules are fine
+        let code = "requests = ['a', 'b']\nfor socket in requests:\n    print(socket)";
+        assert!(policy.check("python", code).is_ok());
+        assert!(policy.check("python", "import http_helpers").is_ok());
+    }
+
+    #[test]
+    fn test_any_language_patterns_apply_everywhere() {
+        let policy = CodeSafetyPolicy::new()
+            .with_denied_pattern(ANY_LANGUAGE, "/etc/passwd")
+            .unwrap();
+
+        assert!(policy.check("python", "open('/etc/passwd').read()").is_err());
+        assert!(policy.check("lua", "io.open('/etc/passwd')").is_err());
+        assert!(policy.check("lua", "io.open('notes.txt')").is_ok());
+    }
+
+    #[test]
+    fn test_invalid_pattern_is_a_config_error() {
+        let result = CodeSafetyPolicy::new().with_denied_pattern("bash", "(unclosed");
+        assert!(matches!(result, Err(RLMError::ConfigError(_))));
+    }
+
+    #[test]
+    fn test_policy_round_trips_through_json() {
+        let json = serde_json::to_string(&CodeSafetyPolicy::recommended()).unwrap();
+        let policy: CodeSafetyPolicy = serde_json::from_str(&json).unwrap();
+        assert!(policy.check("python", "import subprocess").is_err());
+
+        let err = serde_json::from_str::<CodeSafetyPolicy>(r#"{"bash": ["(unclosed"]}"#);
+        assert!(err.is_err());
+    }
+}
@@ -1,6 +1,11 @@
 //! Configuration for RLM execution
 
+use crate::code_safety::CodeSafetyPolicy;
+use crate::retry_budget::DEFAULT_RETRY_BUDGET;
+use kowalski_core::rlm::{default_token_counter, TokenCounter};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
 use std::time::Duration;
 
 /// RLM execution configuration
@@ -24,15 +29,47 @@ pub struct RLMConfig {
     /// Maximum length of REPL output (chars)
     pub max_repl_output: usize,
 
+    /// Per-language REPL output limits, keyed by canonical language name
+    ///
+    /// Languages without an entry use `max_repl_output`.
+    #[serde(default)]
+    pub max_repl_output_overrides: HashMap<String, usize>,
+
+    /// Maximum number of REPL output lines, if limited
+    ///
+    /// Output is then cut on whole lines, within the byte limit too, and
+    /// ends with a `...[N lines truncated]...` marker.
+    #[serde(default)]
+    pub max_output_lines: Option<usize>,
+
+    /// Per-language REPL timeouts, keyed by canonical language name
+    ///
+    /// Languages without an entry use [`DEFAULT_REPL_TIMEOUT`].
+    #[serde(default = "default_language_timeouts")]
+    pub language_timeouts: HashMap<String, Duration>,
+
     /// Timeout for each iteration
     pub iteration_timeout: Duration,
 
+    /// Wall-clock limit for a whole run, if any
+    ///
+    /// A run that hits it stops and returns its partial answer.
+    #[serde(default)]
+    pub max_total_duration: Option<Duration>,
+
     /// Maximum context window size
     pub max_context_length: usize,
 
     /// Enable context folding to manage token usage
     pub enable_context_folding: bool,
 
+    /// Fold a prompt longer than `max_context_length` instead of rejecting it
+    ///
+    /// The prompt is folded before the first iteration, and whatever still
+    /// does not fit is cut off.
+    #[serde(default)]
+    pub fold_oversized_prompt: bool,
+
     /// Enable parallel batching of LLM calls
     pub enable_parallel_batching: bool,
 
@@ -47,6 +84,58 @@ pub struct RLMConfig {
 
     /// Enable memory optimization
     pub enable_memory_optimization: bool,
+
+    /// Resident memory, in bytes, above which the answer is folded eagerly
+    ///
+    /// Checked before every iteration; while the process is above it the
+    /// answer is folded to half its tokens, however short it is. Needs
+    /// context folding enabled. Off by default.
+    #[serde(default)]
+    pub memory_watermark_bytes: Option<u64>,
+
+    /// Total retries allowed across all retry sites of a run
+    #[serde(default = "default_retry_budget")]
+    pub retry_budget: usize,
+
+    /// Token counter shared by context folding, batching and token accounting
+    #[serde(skip, default = "default_token_counter")]
+    pub token_counter: Arc<dyn TokenCounter>,
+
+    /// Patterns that keep a code block from running; allows everything by default
+    #[serde(default)]
+    pub code_safety: CodeSafetyPolicy,
+}
+
+/// Smallest context window accepted by [`RLMConfigBuilder::max_context_length`]
+pub const MIN_CONTEXT_LENGTH: usize = 1000;
+
+/// REPL timeout for languages without an entry in `language_timeouts`
+pub const DEFAULT_REPL_TIMEOUT: Duration = Duration::from_secs(30);
+
+fn default_retry_budget() -> usize {
+    DEFAULT_RETRY_BUDGET
+}
+
+/// Default REPL timeouts: compiled languages get room to build, scripts less
+pub fn default_language_timeouts() -> HashMap<String, Duration> {
+    [
+        ("rust", 180),
+        ("kotlin", 180),
+        ("java", 120),
+        ("csharp", 120),
+        ("c", 120),
+        ("cpp", 120),
+        ("r", 30),
+        ("haskell", 30),
+        ("python", 15),
+        ("javascript", 15),
+        ("php", 15),
+        ("bash", 10),
+        ("lua", 10),
+    ]
+    .into_iter()
+    .map(|(language, secs)| (language.to_string(), Duration::from_secs(secs)))
+    .collect()
 }
 
 impl Default for RLMConfig {
@@ -54,14 +143,23 @@ impl Default for RLMConfig {
         Self {
             max_iterations: 5,
             max_repl_output: 8192,
+            max_repl_output_overrides: HashMap::new(),
+            max_output_lines: None,
+            language_timeouts: default_language_timeouts(),
             iteration_timeout: Duration::from_secs(300),
+            max_total_duration: None,
             max_context_length: 100_000,
             enable_context_folding: true,
+            fold_oversized_prompt: false,
             enable_parallel_batching: true,
             batch_timeout: Duration::from_secs(60),
             max_recursion_depth: 3,
             max_concurrent_agents: 10,
             enable_memory_optimization: true,
+            memory_watermark_bytes: None,
+            retry_budget: DEFAULT_RETRY_BUDGET,
+            token_counter: default_token_counter(),
+            code_safety: CodeSafetyPolicy::default(),
         }
     }
 }
@@ -72,6 +170,11 @@ impl RLMConfig {
         Self::default()
     }
 
+    /// Start a validating builder from the default configuration
+    pub fn builder() -> RLMConfigBuilder {
+        RLMConfigBuilder::new()
+    }
+
     /// Set maximum iterations
     pub fn with_max_iterations(mut self, max: usize) -> Self {
         self.max_iterations = max;
@@ -84,12 +187,54 @@ impl RLMConfig {
         self
     }
 
+    /// Set the REPL output limit for one language (e.g. `"python"`)
+    pub fn with_max_repl_output_for(mut self, language: impl Into<String>, max: usize) -> Self {
+        self.max_repl_output_overrides
+            .insert(language.into().to_lowercase(), max);
+        self
+    }
+
+    /// Limit REPL output to `max` whole lines
+    pub fn with_max_output_lines(mut self, max: usize) -> Self {
+        self.max_output_lines = Some(max);
+        self
+    }
+
+    /// REPL output limit for `language`, falling back to `max_repl_output`
+    pub fn max_repl_output_for(&self, language: &str) -> usize {
+        self.max_repl_output_overrides
+            .get(&language.to_lowercase())
+            .copied()
+            .unwrap_or(self.max_repl_output)
+    }
+
+    /// Set the REPL timeout for one language
+    pub fn with_language_timeout(mut self, language: impl Into<String>, timeout: Duration) -> Self {
+        self.language_timeouts
+            .insert(language.into().to_lowercase(), timeout);
+        self
+    }
+
+    /// REPL timeout for `language`, falling back to [`DEFAULT_REPL_TIMEOUT`]
+    pub fn repl_timeout_for(&self, language: &str) -> Duration {
+        self.language_timeouts
+            .get(&language.to_lowercase())
+            .copied()
+            .unwrap_or(DEFAULT_REPL_TIMEOUT)
+    }
+
     /// Set iteration timeout
     pub fn with_iteration_timeout(mut self, timeout: Duration) -> Self {
         self.iteration_timeout = timeout;
         self
     }
 
+    /// Limit the wall-clock time of a whole run
+    pub fn with_max_total_duration(mut self, limit: Duration) -> Self {
+        self.max_total_duration = Some(limit);
+        self
+    }
+
     /// Set maximum context length
     pub fn with_max_context_length(mut self, max: usize) -> Self {
         self.max_context_length = max;
@@ -102,6 +247,12 @@ impl RLMConfig {
         self
     }
 
+    /// Fold over-length prompts instead of rejecting them
+    pub fn with_fold_oversized_prompt(mut self, enable: bool) -> Self {
+        self.fold_oversized_prompt = enable;
+        self
+    }
+
     /// Enable or disable parallel batching
     pub fn with_parallel_batching(mut self, enable: bool) -> Self {
         self.enable_parallel_batching = enable;
@@ -132,6 +283,30 @@ impl RLMConfig {
         self
     }
 
+    /// Fold the answer eagerly while resident memory is above `bytes`
+    pub fn with_memory_watermark(mut self, bytes: u64) -> Self {
+        self.memory_watermark_bytes = Some(bytes);
+        self
+    }
+
+    /// Set the retry budget shared by a run (0 disables retries)
+    pub fn with_retry_budget(mut self, retries: usize) -> Self {
+        self.retry_budget = retries;
+        self
+    }
+
+    /// Set the token counter used wherever the run counts tokens
+    pub fn with_token_counter(mut self, counter: Arc<dyn TokenCounter>) -> Self {
+        self.token_counter = counter;
+        self
+    }
+
+    /// Set the policy code blocks are checked against before they run
+    pub fn with_code_safety(mut self, policy: CodeSafetyPolicy) -> Self {
+        self.code_safety = policy;
+        self
+    }
+
     /// Validate configuration
     pub fn validate(&self) -> Result<(), String> {
         if self.max_iterations == 0 {
@@ -142,14 +317,26 @@ impl RLMConfig {
             return Err("max_repl_output must be > 0".to_string());
         }
 
+        if self.max_output_lines == Some(0) {
+            return Err("max_output_lines must be > 0".to_string());
+        }
+
         if self.iteration_timeout.as_secs() == 0 {
             return Err("iteration_timeout must be > 0".to_string());
         }
 
+        if self.max_total_duration.is_some_and(|limit| limit.is_zero()) {
+            return Err("max_total_duration must be > 0".to_string());
+        }
+
         if self.max_context_length == 0 {
             return Err("max_context_length must be > 0".to_string());
         }
 
+        if self.memory_watermark_bytes == Some(0) {
+            return Err("memory_watermark_bytes must be > 0".to_string());
+        }
+
         if self.batch_timeout.as_secs() == 0 {
             return Err("batch_timeout must be > 0".to_string());
         }
@@ -169,6 +356,24 @@ impl RLMConfig {
             );
         }
 
+        for (language, &max) in &self.max_repl_output_overrides {
+            if max == 0 {
+                return Err(format!("max_repl_output for {} must be > 0", language));
+            }
+            if max > self.max_context_length {
+                return Err(format!(
+                    "max_repl_output for {} cannot exceed max_context_length",
+                    language
+                ));
+            }
+        }
+
+        for (language, timeout) in &self.language_timeouts {
+            if timeout.is_zero() {
+                return Err(format!("REPL timeout for {} must be > 0", language));
+            }
+        }
+
         if self.max_recursion_depth > 10 {
             return Err(
                 "max_recursion_depth should not exceed 10 (reasonable limit)".to_string()
@@ -185,6 +390,102 @@ impl RLMConfig {
     }
 }
 
+/// Builder that validates each setting as it is set
+///
+/// Unlike the `with_*` methods on [`RLMConfig`], every setter rejects an
+/// invalid value immediately, so the error names the setting at fault.
+/// Checks that span several settings run in [`RLMConfigBuilder::build`].
+///
+/// # Example
+///
+/// ```
+/// use kowalski_rlm::config::RLMConfigBuilder;
+/// use std::time::Duration;
+///
+/// # fn main() -> Result<(), String> {
+/// let config = RLMConfigBuilder::new()
+///     .max_iterations(10)?
+///     .iteration_timeout(Duration::from_secs(600))?
+///     .build()?;
+/// assert_eq!(config.max_iterations, 10);
+///
+/// assert!(RLMConfigBuilder::new().max_iterations(0).is_err());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RLMConfigBuilder {
+    pub(crate) config: RLMConfig,
+}
+
+impl RLMConfigBuilder {
+    /// Create a builder starting from the default configuration
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a builder starting from an existing configuration
+    pub fn from_config(config: RLMConfig) -> Self {
+        Self { config }
+    }
+
+    /// Set maximum iterations (must be > 0)
+    pub fn max_iterations(mut self, n: usize) -> Result<Self, String> {
+        if n == 0 {
+            return Err("max_iterations must be > 0".to_string());
+        }
+        self.config.max_iterations = n;
+        Ok(self)
+    }
+
+    /// Set maximum REPL output length (must be > 0)
+    pub fn max_repl_output(mut self, n: usize) -> Result<Self, String> {
+        if n == 0 {
+            return Err("max_repl_output must be > 0".to_string());
+        }
+        self.config.max_repl_output = n;
+        Ok(self)
+    }
+
+    /// Set maximum REPL output lines (must be > 0)
+    pub fn max_output_lines(mut self, n: usize) -> Result<Self, String> {
+        if n == 0 {
+            return Err("max_output_lines must be > 0".to_string());
+        }
+        self.config.max_output_lines = Some(n);
+        Ok(self)
+    }
+
+    /// Set iteration timeout (must be at least one second, as in [`RLMConfig::validate`])
+    pub fn iteration_timeout(mut self, timeout: Duration) -> Result<Self, String> {
+        if timeout.as_secs() == 0 {
+            return Err("iteration_timeout must be > 0".to_string());
+        }
+        self.config.iteration_timeout = timeout;
+        Ok(self)
+    }
+
+    /// Set maximum context length (must be at least [`MIN_CONTEXT_LENGTH`])
+    pub fn max_context_length(mut self, n: usize) -> Result<Self, String> {
+        if n < MIN_CONTEXT_LENGTH {
+            return Err(format!("max_context_length must be >= {}", MIN_CONTEXT_LENGTH));
+        }
+        self.config.max_context_length = n;
+        Ok(self)
+    }
+
+    /// Finish the configuration
+    ///
+    /// # Errors
+    ///
+    /// Returns the first [`RLMConfig::validate`] failure, such as
+    /// `max_repl_output` exceeding `max_context_length`.
+    pub fn build(self) -> Result<RLMConfig, String> {
+        self.config.validate()?;
+        Ok(self.config)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -236,6 +537,17 @@ mod tests {
         assert!(config.validate().is_err());
     }
 
+    #[test]
+    fn test_language_timeouts() {
+        let config = RLMConfig::default().with_language_timeout("Python", Duration::from_secs(5));
+
+        assert_eq!(config.repl_timeout_for("python"), Duration::from_secs(5));
+        assert_eq!(config.repl_timeout_for("rust"), Duration::from_secs(180));
+        assert_eq!(config.repl_timeout_for("cobol"), DEFAULT_REPL_TIMEOUT);
+        assert!(config.validate().is_ok());
+        assert!(config.with_language_timeout("bash", Duration::ZERO).validate().is_err());
+    }
+
     #[test]
     fn test_validation_excessive_recursion_depth() {
         let mut config = RLMConfig::default();
@@ -250,6 +562,22 @@ mod tests {
         assert!(config.validate().is_err());
     }
 
+    #[test]
+    fn test_max_repl_output_overrides() {
+        let config = RLMConfig::default()
+            .with_max_repl_output(1000)
+            .with_max_repl_output_for("Python", 50)
+            .with_max_repl_output_for("java", 10_000);
+
+        assert_eq!(config.max_repl_output_for("python"), 50);
+        assert_eq!(config.max_repl_output_for("JAVA"), 10_000);
+        assert_eq!(config.max_repl_output_for("rust"), 1000);
+        assert!(config.validate().is_ok());
+
+        assert!(config.clone().with_max_repl_output_for("bash", 0).validate().is_err());
+        assert!(config.with_max_repl_output_for("bash", 200_000).validate().is_err());
+    }
+
     #[test]
     fn test_valid_extreme_config() {
         let config = RLMConfig::default()
@@ -257,4 +585,51 @@ mod tests {
             .with_max_concurrent_agents(1000);  // Max allowed
         assert!(config.validate().is_ok());
     }
+
+    #[test]
+    fn test_config_builder_rejects_zero_iterations() {
+        let err = RLMConfigBuilder::new().max_iterations(0).unwrap_err();
+        assert!(err.contains("max_iterations"));
+    }
+
+    #[test]
+    fn test_config_builder_rejects_zero_repl_output() {
+        let err = RLMConfigBuilder::new().max_repl_output(0).unwrap_err();
+        assert!(err.contains("max_repl_output"));
+    }
+
+    #[test]
+    fn test_config_builder_rejects_zero_timeout() {
+        let err = RLMConfigBuilder::new().iteration_timeout(Duration::ZERO).unwrap_err();
+        assert!(err.contains("iteration_timeout"));
+    }
+
+    #[test]
+    fn test_config_builder_rejects_small_context() {
+        let err = RLMConfigBuilder::new().max_context_length(999).unwrap_err();
+        assert!(err.contains("max_context_length"));
+        assert!(RLMConfigBuilder::new().max_context_length(MIN_CONTEXT_LENGTH).is_ok());
+    }
+
+    #[test]
+    fn test_config_builder_build() {
+        let config = RLMConfig::builder()
+            .max_iterations(7)
+            .and_then(|b| b.max_repl_output(2000))
+            .and_then(|b| b.iteration_timeout(Duration::from_secs(60)))
+            .and_then(|b| b.max_context_length(4000))
+            .and_then(|b| b.build())
+            .unwrap();
+        assert_eq!(config.max_iterations, 7);
+        assert_eq!(config.max_repl_output, 2000);
+        assert_eq!(config.iteration_timeout, Duration::from_secs(60));
+        assert_eq!(config.max_context_length, 4000);
+
+        // Cross-field checks still run at build time
+        let result = RLMConfigBuilder::new()
+            .max_context_length(1000)
+            .and_then(|b| b.max_repl_output(2000))
+            .and_then(|b| b.build());
+        assert!(result.is_err());
+    }
 }
@@ -1,9 +1,15 @@
 //! RLM execution context management
 
 use crate::config::RLMConfig;
+use crate::error::{RLMError, RLMResult};
+use crate::retry_budget::RetryBudget;
 use chrono::{DateTime, Utc};
+use kowalski_core::rlm::TokenCounter;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
+use sha2::{Digest, Sha256};
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Mutex};
 
 /// RLM execution context tracking and management
 ///
@@ -35,6 +41,33 @@ pub struct RLMContext {
 
     /// Execution metadata
     pub metadata: ExecutionMetadata,
+
+    /// Retries left for this run, shared with every retry site
+    #[serde(skip)]
+    retry_budget: RetryBudget,
+
+    /// Outcome of every executed code block, in execution order
+    #[serde(default)]
+    pub code_block_results: Vec<CodeBlockResult>,
+}
+
+/// Outcome of one executed code block
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CodeBlockResult {
+    /// Iteration the block ran in
+    pub iteration: usize,
+
+    /// Language the block was executed with
+    pub language: String,
+
+    /// Whether execution succeeded
+    pub success: bool,
+
+    /// Output of a successful block (empty on failure)
+    pub output: String,
+
+    /// Error message of a failed block
+    pub error: Option<String>,
 }
 
 /// Metadata about RLM execution
@@ -55,9 +88,21 @@ pub struct ExecutionMetadata {
     /// Total number of errors encountered (for monitoring)
     pub error_count: usize,
 
-    /// Custom metadata
+    /// Number of times the answer was folded
+    #[serde(default)]
+    pub folds: usize,
+
+    /// Whether the answer was cut to fit the context limit
+    #[serde(default)]
+    pub truncated_context: bool,
+
+    /// Whether the run hit `max_total_duration` and stopped early
     #[serde(default)]
-    pub custom: std::collections::HashMap<String, String>,
+    pub timed_out: bool,
+
+    /// Custom metadata, as JSON values
+    #[serde(default)]
+    pub custom: std::collections::HashMap<String, serde_json::Value>,
 }
 
 impl ExecutionMetadata {
@@ -79,6 +124,7 @@ impl RLMContext {
     /// Create a new RLM context
     pub fn new(task_id: impl Into<String>, config: Arc<RLMConfig>) -> Self {
         let now = Utc::now();
+        let retry_budget = RetryBudget::new(config.retry_budget);
         Self {
             task_id: task_id.into(),
             iteration: 0,
@@ -88,9 +134,40 @@ impl RLMContext {
             last_activity: now,
             config,
             metadata: ExecutionMetadata::default(),
+            retry_budget,
+            code_block_results: Vec::new(),
         }
     }
 
+    /// Attach `config` to a context restored from a checkpoint
+    ///
+    /// The configuration and retry budget are not serialized, so a
+    /// deserialized context starts with the defaults. This replaces both;
+    /// the restored run gets its full retry budget back.
+    pub fn with_config(mut self, config: Arc<RLMConfig>) -> Self {
+        self.retry_budget = RetryBudget::new(config.retry_budget);
+        self.config = config;
+        self
+    }
+
+    /// Prepare the context for a new task, as if freshly created
+    ///
+    /// Keeps the allocated buffers, so a reused context does not allocate
+    /// again for answers of a similar size.
+    pub fn reset(&mut self, task_id: impl Into<String>, config: Arc<RLMConfig>) {
+        let now = Utc::now();
+        self.task_id = task_id.into();
+        self.iteration = 0;
+        self.message_count = 0;
+        self.answer.clear();
+        self.started_at = now;
+        self.last_activity = now;
+        self.retry_budget = RetryBudget::new(config.retry_budget);
+        self.config = config;
+        self.metadata = ExecutionMetadata::default();
+        self.code_block_results.clear();
+    }
+
     /// Get the current iteration
     pub fn iteration(&self) -> usize {
         self.iteration
@@ -119,12 +196,53 @@ impl RLMContext {
         &self.answer
     }
 
+    /// SHA-256 of the current answer, as lowercase hex
+    ///
+    /// Equal answers always hash the same, so the hash can key a cache or
+    /// be stored to check later whether the answer moved on.
+    pub fn compute_answer_hash(&self) -> String {
+        format!("{:x}", Sha256::digest(self.answer.as_bytes()))
+    }
+
+    /// Returns true if the answer still hashes to `prev_hash`
+    pub fn answer_unchanged_since(&self, prev_hash: &str) -> bool {
+        self.compute_answer_hash() == prev_hash
+    }
+
     /// Clear answer for next iteration
     pub fn clear_answer(&mut self) {
         self.answer.clear();
         self.last_activity = Utc::now();
     }
 
+    /// Cut the answer to at most `max_len` bytes and mark the context as truncated
+    ///
+    /// The cut falls on a character boundary. Returns the length of the
+    /// answer before truncation.
+    pub fn truncate_answer(&mut self, max_len: usize) -> usize {
+        let original_len = self.answer.len();
+        if original_len > max_len {
+            let mut cut = max_len;
+            while !self.answer.is_char_boundary(cut) {
+                cut -= 1;
+            }
+            self.answer.truncate(cut);
+            self.metadata.truncated_context = true;
+            self.last_activity = Utc::now();
+        }
+        original_len
+    }
+
+    /// Token counter configured for this run
+    pub fn token_counter(&self) -> &Arc<dyn TokenCounter> {
+        &self.config.token_counter
+    }
+
+    /// Configured maximum answer length
+    pub fn max_context_length(&self) -> usize {
+        self.config.max_context_length
+    }
+
     /// Record a REPL execution
     pub fn record_repl_execution(&mut self) {
         self.metadata.repl_executions += 1;
@@ -138,6 +256,18 @@ impl RLMContext {
         self.last_activity = Utc::now();
     }
 
+    /// Record that the answer was folded
+    pub fn record_fold(&mut self) {
+        self.metadata.folds += 1;
+        self.last_activity = Utc::now();
+    }
+
+    /// Record that the run hit its total duration limit
+    pub fn record_timeout(&mut self) {
+        self.metadata.timed_out = true;
+        self.last_activity = Utc::now();
+    }
+
     /// Record an error
     ///
     /// Note: Recording an error does not automatically halt execution.
@@ -151,12 +281,92 @@ impl RLMContext {
         self.last_activity = Utc::now();
     }
 
+    /// Record the outcome of a code block executed in the current iteration
+    pub fn record_code_block(
+        &mut self,
+        language: impl Into<String>,
+        result: Result<&str, &str>,
+    ) {
+        let (success, output, error) = match result {
+            Ok(output) => (true, output.to_string(), None),
+            Err(error) => (false, String::new(), Some(error.to_string())),
+        };
+        self.code_block_results.push(CodeBlockResult {
+            iteration: self.iteration,
+            language: language.into(),
+            success,
+            output,
+            error,
+        });
+        self.last_activity = Utc::now();
+    }
+
+    /// Outcome of every executed code block, in execution order
+    pub fn code_block_results(&self) -> &[CodeBlockResult] {
+        &self.code_block_results
+    }
+
+    /// Returns true if any executed code block failed
+    pub fn any_code_block_failed(&self) -> bool {
+        self.code_block_results.iter().any(|result| !result.success)
+    }
+
+    /// Retry budget of this run
+    ///
+    /// Clone it into retry sites so they all draw from the same budget.
+    pub fn retry_budget(&self) -> &RetryBudget {
+        &self.retry_budget
+    }
+
     /// Set custom metadata
     pub fn set_metadata(&mut self, key: impl Into<String>, value: impl Into<String>) {
-        self.metadata.custom.insert(key.into(), value.into());
+        self.metadata
+            .custom
+            .insert(key.into(), serde_json::Value::String(value.into()));
         self.last_activity = Utc::now();
     }
 
+    /// Set custom metadata to any serializable value
+    pub fn set_metadata_typed<T: Serialize>(&mut self, key: &str, value: &T) -> RLMResult<()> {
+        let value = serde_json::to_value(value).map_err(|e| {
+            RLMError::serialization(format!("serialization failed for metadata {}: {}", key, e))
+        })?;
+        self.metadata.custom.insert(key.to_string(), value);
+        self.last_activity = Utc::now();
+        Ok(())
+    }
+
+    /// Custom metadata under `key` as a `T`, or `None` if it is not set
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RLMError::ExecutionError`] if the stored value is not a `T`.
+    pub fn get_metadata_typed<T: DeserializeOwned>(&self, key: &str) -> RLMResult<Option<T>> {
+        self.metadata
+            .custom
+            .get(key)
+            .map(|value| {
+                T::deserialize(value).map_err(|e| {
+                    RLMError::execution(format!("deserialization failed for metadata {}: {}", key, e))
+                })
+            })
+            .transpose()
+    }
+
+    /// Custom metadata under `key`, if it is a string
+    pub fn get_metadata_str(&self, key: &str) -> Option<&str> {
+        self.metadata.custom.get(key)?.as_str()
+    }
+
+    /// Custom metadata under `key`, if it is a non-negative integer
+    ///
+    /// Strings holding one, as stored by [`set_metadata`](Self::set_metadata),
+    /// are parsed.
+    pub fn get_metadata_u64(&self, key: &str) -> Option<u64> {
+        let value = self.metadata.custom.get(key)?;
+        value.as_u64().or_else(|| value.as_str()?.parse().ok())
+    }
+
     /// Get execution duration
     pub fn elapsed(&self) -> chrono::Duration {
         self.last_activity - self.started_at
@@ -180,6 +390,102 @@ impl RLMContext {
             total_tokens: self.metadata.total_tokens,
             errors: self.metadata.errors.len(),
             elapsed_secs: self.elapsed().num_seconds(),
+            retry_budget_remaining: self.retry_budget.remaining(),
+            truncated_context: self.metadata.truncated_context,
+            timed_out: self.metadata.timed_out,
+        }
+    }
+}
+
+/// Pool of idle contexts reused across task executions
+///
+/// [`RLMContextPool::acquire`] hands out an idle context reset for the new
+/// task, or a new one if none is idle. The context goes back to the pool
+/// when the [`PooledRLMContext`] is dropped, unless `pool_size` contexts
+/// are already idle. Clones share the same pool.
+#[derive(Debug, Clone)]
+pub struct RLMContextPool {
+    config: Arc<RLMConfig>,
+    capacity: usize,
+    idle: Arc<Mutex<Vec<RLMContext>>>,
+}
+
+impl RLMContextPool {
+    /// Create a pool holding up to `pool_size` idle contexts, all using `config`
+    ///
+    /// The pool starts full.
+    pub fn new(pool_size: usize, config: Arc<RLMConfig>) -> Self {
+        let idle = (0..pool_size)
+            .map(|_| RLMContext::new(String::new(), config.clone()))
+            .collect();
+        Self {
+            config,
+            capacity: pool_size,
+            idle: Arc::new(Mutex::new(idle)),
+        }
+    }
+
+    /// Take a context for `task_id`, reusing an idle one if available
+    pub fn acquire(&self, task_id: impl Into<String>) -> PooledRLMContext {
+        let task_id = task_id.into();
+        let context = match self.idle.lock().unwrap().pop() {
+            Some(mut context) => {
+                context.reset(task_id, self.config.clone());
+                context
+            }
+            None => RLMContext::new(task_id, self.config.clone()),
+        };
+        PooledRLMContext {
+            context: Some(context),
+            pool: self.clone(),
+        }
+    }
+
+    /// Number of idle contexts in the pool
+    pub fn pool_size(&self) -> usize {
+        self.idle.lock().unwrap().len()
+    }
+
+    fn release(&self, context: RLMContext) {
+        let mut idle = self.idle.lock().unwrap();
+        if idle.len() < self.capacity {
+            idle.push(context);
+        }
+    }
+}
+
+/// Context borrowed from an [`RLMContextPool`], returned to it on drop
+#[derive(Debug)]
+pub struct PooledRLMContext {
+    context: Option<RLMContext>,
+    pool: RLMContextPool,
+}
+
+impl PooledRLMContext {
+    /// Keep the context instead of returning it to the pool
+    pub fn detach(mut self) -> RLMContext {
+        self.context.take().expect("pooled context already taken")
+    }
+}
+
+impl Deref for PooledRLMContext {
+    type Target = RLMContext;
+
+    fn deref(&self) -> &RLMContext {
+        self.context.as_ref().expect("pooled context already taken")
+    }
+}
+
+impl DerefMut for PooledRLMContext {
+    fn deref_mut(&mut self) -> &mut RLMContext {
+        self.context.as_mut().expect("pooled context already taken")
+    }
+}
+
+impl Drop for PooledRLMContext {
+    fn drop(&mut self) {
+        if let Some(context) = self.context.take() {
+            self.pool.release(context);
         }
     }
 }
@@ -216,6 +522,18 @@ pub struct ContextStats {
 
     /// Elapsed seconds
     pub elapsed_secs: i64,
+
+    /// Retries left in the run's retry budget
+    #[serde(default)]
+    pub retry_budget_remaining: usize,
+
+    /// Whether the answer was cut to fit the context limit
+    #[serde(default)]
+    pub truncated_context: bool,
+
+    /// Whether the run hit its total duration limit
+    #[serde(default)]
+    pub timed_out: bool,
 }
 
 #[cfg(test)]
@@ -233,6 +551,45 @@ mod tests {
         assert!(ctx.answer.is_empty());
     }
 
+    #[test]
+    fn test_retry_budget_in_stats() {
+        let config = Arc::new(RLMConfig::default().with_retry_budget(3));
+        let ctx = RLMContext::new("task-1", config);
+
+        let site = ctx.retry_budget().clone();
+        assert!(site.try_consume());
+        assert_eq!(ctx.stats().retry_budget_remaining, 2);
+    }
+
+    #[test]
+    fn test_truncate_answer_on_char_boundary() {
+        let mut ctx = RLMContext::new("task-1", Arc::new(RLMConfig::default()));
+        ctx.append_answer("abcé");
+
+        // 'é' spans bytes 3..5, so a 4-byte limit cuts before it
+        assert_eq!(ctx.truncate_answer(4), 5);
+        assert_eq!(ctx.answer(), "abc");
+        assert!(ctx.stats().truncated_context);
+    }
+
+    #[test]
+    fn test_answer_hash() {
+        let config = Arc::new(RLMConfig::default());
+        let mut ctx = RLMContext::new("task-1", Arc::clone(&config));
+        let mut other = RLMContext::new("task-2", config);
+        ctx.append_answer("same answer");
+        other.append_answer("same answer");
+
+        let hash = ctx.compute_answer_hash();
+        assert_eq!(hash.len(), 64);
+        assert_eq!(hash, ctx.compute_answer_hash());
+        assert_eq!(hash, other.compute_answer_hash());
+        assert!(ctx.answer_unchanged_since(&hash));
+
+        ctx.append_answer(".");
+        assert!(!ctx.answer_unchanged_since(&hash));
+    }
+
     #[test]
     fn test_iteration_tracking() {
         let config = Arc::new(RLMConfig::default());
@@ -287,7 +644,56 @@ mod tests {
         assert_eq!(ctx.metadata.total_tokens, 100);
         assert_eq!(ctx.metadata.errors.len(), 1);
         assert_eq!(ctx.metadata.error_count, 1);
-        assert_eq!(ctx.metadata.custom.get("key").map(|s| s.as_str()), Some("value"));
+        assert_eq!(ctx.metadata.custom.get("key").and_then(|v| v.as_str()), Some("value"));
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct SourceInfo {
+        url: String,
+        retrieved_pages: Vec<u32>,
+        verified: bool,
+    }
+
+    #[test]
+    fn test_typed_metadata_round_trip() {
+        let mut ctx = RLMContext::new("task-1", Arc::new(RLMConfig::default()));
+        let info = SourceInfo {
+            url: "https://example.com/report".to_string(),
+            retrieved_pages: vec![1, 4, 9],
+            verified: true,
+        };
+
+        ctx.set_metadata_typed("source", &info).unwrap();
+        ctx.set_metadata_typed("attempts", &3u64).unwrap();
+        ctx.set_metadata("stage", "review");
+        ctx.set_metadata("batch", "42");
+
+        assert_eq!(ctx.get_metadata_typed::<SourceInfo>("source").unwrap(), Some(info));
+        assert_eq!(ctx.get_metadata_typed::<String>("stage").unwrap().as_deref(), Some("review"));
+        assert_eq!(ctx.get_metadata_typed::<SourceInfo>("missing").unwrap(), None);
+        assert_eq!(ctx.get_metadata_str("stage"), Some("review"));
+        assert_eq!(ctx.get_metadata_str("attempts"), None);
+        assert_eq!(ctx.get_metadata_u64("attempts"), Some(3));
+        assert_eq!(ctx.get_metadata_u64("batch"), Some(42));
+        assert_eq!(ctx.get_metadata_u64("stage"), None);
+
+        // Survives serialization of the whole context, as in checkpoints
+        let restored: RLMContext = serde_json::from_str(&serde_json::to_string(&ctx).unwrap()).unwrap();
+        assert_eq!(
+            restored.get_metadata_typed::<SourceInfo>("source").unwrap().unwrap().retrieved_pages,
+            vec![1, 4, 9]
+        );
+    }
+
+    #[test]
+    fn test_typed_metadata_mismatch_is_an_error() {
+        let mut ctx = RLMContext::new("task-1", Arc::new(RLMConfig::default()));
+        ctx.set_metadata("source", "not a struct");
+
+        match ctx.get_metadata_typed::<SourceInfo>("source") {
+            Err(RLMError::ExecutionError(msg)) => assert!(msg.starts_with("deserialization failed")),
+            other => panic!("expected a deserialization error, got {:?}", other),
+        }
     }
 
     #[test]
@@ -339,4 +745,77 @@ mod tests {
         assert_eq!(stats.answer_length, 4);
         assert_eq!(stats.repl_executions, 1);
     }
+
+    #[test]
+    fn test_code_block_results() {
+        let mut ctx = RLMContext::new("task-1", Arc::new(RLMConfig::default()));
+        ctx.next_iteration();
+        ctx.record_code_block("python", Ok("4\n"));
+        assert!(!ctx.any_code_block_failed());
+
+        ctx.record_code_block("bash", Err("exit status 1"));
+        assert!(ctx.any_code_block_failed());
+
+        let results = ctx.code_block_results();
+        assert_eq!(results.len(), 2);
+        assert_eq!(
+            results[1],
+            CodeBlockResult {
+                iteration: 1,
+                language: "bash".to_string(),
+                success: false,
+                output: String::new(),
+                error: Some("exit status 1".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn test_pooled_context_is_reset_before_reuse() {
+        let config = Arc::new(RLMConfig::default().with_retry_budget(2));
+        let pool = RLMContextPool::new(1, config);
+        assert_eq!(pool.pool_size(), 1);
+
+        let capacity = {
+            let mut ctx = pool.acquire("task-1");
+            assert_eq!(pool.pool_size(), 0);
+            ctx.next_iteration();
+            ctx.append_answer("x".repeat(4096));
+            ctx.record_error("boom");
+            ctx.record_code_block("python", Ok("1\n"));
+            ctx.set_metadata("key", "value");
+            assert!(ctx.retry_budget().try_consume());
+            ctx.answer.capacity()
+        };
+        assert_eq!(pool.pool_size(), 1);
+
+        let ctx = pool.acquire("task-2");
+        assert_eq!(ctx.task_id, "task-2");
+        assert_eq!(ctx.iteration(), 0);
+        assert_eq!(ctx.message_count, 0);
+        assert!(ctx.answer().is_empty());
+        assert_eq!(ctx.answer.capacity(), capacity);
+        assert!(ctx.metadata.errors.is_empty());
+        assert!(ctx.metadata.custom.is_empty());
+        assert!(ctx.code_block_results().is_empty());
+        assert_eq!(ctx.retry_budget().remaining(), 2);
+    }
+
+    #[test]
+    fn test_pool_grows_when_empty_and_caps_idle_contexts() {
+        let pool = RLMContextPool::new(1, Arc::new(RLMConfig::default()));
+
+        let first = pool.acquire("a");
+        let second = pool.acquire("b");
+        assert_eq!(second.task_id, "b");
+        assert_eq!(pool.pool_size(), 0);
+
+        drop(first);
+        drop(second);
+        assert_eq!(pool.pool_size(), 1);
+
+        let kept = pool.acquire("c").detach();
+        assert_eq!(kept.task_id, "c");
+        assert_eq!(pool.pool_size(), 0);
+    }
 }
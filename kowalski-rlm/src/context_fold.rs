@@ -7,14 +7,148 @@
 //!
 //! - **ContextFolder**: Handles context compression and summarization
 //! - **ContextFoldConfig**: Configuration for folding behavior
-//! - **FoldingStats**: Statistics about folding operations
+//! - **SlidingWindowFolder**: Single-pass folding for real-time workflows
+//! - **ContextFolder::fold_chunked**: Streaming fold of contexts too large to
+//!   hold in memory at once
+//! - **StreamingFold**: Incremental fold fed line by line, behind
+//!   `ContextFolder::fold_lines` and `ContextFolder::fold_reader`
+//! - **FoldingStats**: Statistics about folding operations, with a
+//!   **FoldStep** per compression iteration
+//! - **FoldingExplanation**: Line-level account of what a fold dropped and kept
+//! - **Foldable**: In-place folding, implemented for the execution `RLMContext`,
+//!   `AnswerBuffer` and `Vec<String>`
+//! - **AccumulatedResultsFolding**: In-place folding of a federation context's accumulated results
 
+use crate::code_block_parser::CodeBlockParser;
+use crate::context::RLMContext;
+use crate::core::AnswerBuffer;
 use crate::error::{RLMError, RLMResult};
 use async_trait::async_trait;
+use kowalski_core::rlm::{default_token_counter, HeuristicTokenCounter, TokenCounter};
+use kowalski_federation::RLMContext as FederationContext;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use similar::{ChangeTag, TextDiff};
 use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
 use tokio::sync::RwLock;
 
+/// Array items kept at each end by the first JSON-aware pass
+const JSON_KEEP_ITEMS: usize = 3;
+
+/// Longest string value kept whole by the first JSON-aware pass, in chars
+const JSON_MAX_STRING: usize = 80;
+
+/// Shortest string limit JSON-aware passes shrink to
+const JSON_MIN_STRING: usize = 16;
+
+/// Line-based compression passes a [`StreamingFold`] tries per merge before
+/// it truncates
+const CHUNKED_MAX_PASSES: usize = 8;
+
+/// Average line length assumed by [`ContextFolder::estimate_memory_usage`]
+const ESTIMATED_LINE_LEN: usize = 40;
+
+/// How the first compression iteration chooses which lines to keep
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FoldStrategy {
+    /// Keep the first lines and discard the rest
+    HeadOnly,
+    /// Keep the last lines and discard the rest
+    TailOnly,
+    /// Keep the first and last lines and summarize the middle (default)
+    #[default]
+    HeadTail,
+    /// Keep evenly spaced lines from the whole context
+    Uniform,
+    /// Compress JSON regions structurally so they stay parseable
+    ///
+    /// Long arrays keep their first and last items around a marker string
+    /// and long string values are shortened, so every key survives. Text
+    /// outside JSON is folded like [`FoldStrategy::HeadTail`]. Unlike the
+    /// other strategies this one is used for every iteration.
+    JsonAware,
+    /// Fold in a single pass with a [`SlidingWindowFolder`]
+    ///
+    /// Faster than the multi-pass strategies, at the cost of line structure.
+    SlidingWindow {
+        /// Words in each window
+        window_size: usize,
+        /// Words the window moves each step
+        step_size: usize,
+    },
+}
+
+impl FoldStrategy {
+    /// Short snake_case name, as recorded in [`FoldStep::strategy_name`]
+    pub fn name(&self) -> &'static str {
+        match self {
+            FoldStrategy::HeadOnly => "head_only",
+            FoldStrategy::TailOnly => "tail_only",
+            FoldStrategy::HeadTail => "head_tail",
+            FoldStrategy::Uniform => "uniform",
+            FoldStrategy::JsonAware => "json_aware",
+            FoldStrategy::SlidingWindow { .. } => "sliding_window",
+        }
+    }
+}
+
+/// Single-pass folder keeping the first sentence of each window
+///
+/// A window of `window_size` whitespace-separated words slides across the
+/// text, `step_size` words at a time. The first sentence starting inside
+/// each window is kept, up to `window_size` words of it, and everything
+/// else is dropped. Kept sentences are joined with single spaces.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SlidingWindowFolder {
+    window_size: usize,
+    step_size: usize,
+}
+
+impl SlidingWindowFolder {
+    /// Create a folder; sizes of 0 are raised to 1
+    pub fn new(window_size: usize, step_size: usize) -> Self {
+        Self {
+            window_size: window_size.max(1),
+            step_size: step_size.max(1),
+        }
+    }
+
+    /// Fold `text` in one pass
+    pub fn fold(&self, text: &str) -> String {
+        let words: Vec<&str> = text.split_whitespace().collect();
+        let ends_sentence = |word: &str| word.ends_with(['.', '!', '?']);
+        let mut kept: Vec<&str> = Vec::new();
+        // Sentences are kept whole or not at all, so a start is never revisited
+        let mut next_free = 0;
+
+        let mut window_start = 0;
+        while window_start < words.len() {
+            let window_end = (window_start + self.window_size).min(words.len());
+            let sentence_start = (window_start.max(next_free)..window_end)
+                .find(|&i| i == 0 || ends_sentence(words[i - 1]));
+
+            if let Some(start) = sentence_start {
+                let limit = (start + self.window_size).min(words.len());
+                let end = (start..limit)
+                    .find(|&i| ends_sentence(words[i]))
+                    .map_or(limit, |i| i + 1);
+                kept.extend_from_slice(&words[start..end]);
+                next_free = end;
+            }
+            window_start += self.step_size;
+        }
+
+        kept.join(" ")
+    }
+}
+
+/// Part of a context being folded by [`FoldStrategy::JsonAware`]
+enum Segment<'a> {
+    Text(Vec<&'a str>),
+    Json(Value),
+}
+
 /// Configuration for context folding
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ContextFoldConfig {
@@ -26,6 +160,19 @@ pub struct ContextFoldConfig {
     pub aggressive: bool,
     /// Maximum iterations for folding
     pub max_iterations: usize,
+    /// Strategy for the first compression iteration; later iterations are uniform
+    #[serde(default)]
+    pub fold_strategy: FoldStrategy,
+    /// Fraction of `max_tokens` at which folding starts
+    ///
+    /// 1.0 folds exactly at the limit, 0.8 folds proactively at 80% of it and
+    /// 1.2 defers folding until the context is 20% over.
+    #[serde(default = "default_fold_threshold_ratio")]
+    pub fold_threshold_ratio: f64,
+}
+
+fn default_fold_threshold_ratio() -> f64 {
+    1.0
 }
 
 impl Default for ContextFoldConfig {
@@ -35,6 +182,8 @@ impl Default for ContextFoldConfig {
             compression_ratio: 0.7,
             aggressive: false,
             max_iterations: 3,
+            fold_strategy: FoldStrategy::default(),
+            fold_threshold_ratio: default_fold_threshold_ratio(),
         }
     }
 }
@@ -59,6 +208,29 @@ impl ContextFoldConfig {
         self.aggressive = true;
         self
     }
+
+    /// Set the strategy used by the first compression iteration
+    pub fn with_fold_strategy(mut self, strategy: FoldStrategy) -> Self {
+        self.fold_strategy = strategy;
+        self
+    }
+
+    /// Fold in a single pass with a [`SlidingWindowFolder`]
+    pub fn with_sliding_window(mut self, window_size: usize, step_size: usize) -> Self {
+        self.fold_strategy = FoldStrategy::SlidingWindow { window_size, step_size };
+        self
+    }
+
+    /// Set the fraction of `max_tokens` at which folding starts
+    pub fn with_fold_threshold_ratio(mut self, ratio: f64) -> Self {
+        self.fold_threshold_ratio = ratio.max(0.0);
+        self
+    }
+
+    /// Token count above which [`ContextFolder::should_fold`] returns true
+    pub fn fold_threshold_tokens(&self) -> usize {
+        (self.max_tokens as f64 * self.fold_threshold_ratio) as usize
+    }
 }
 
 /// Context folding statistics
@@ -74,6 +246,29 @@ pub struct FoldingStats {
     pub fold_time_ms: u64,
     /// Compression achieved
     pub compression_ratio: f64,
+    /// One entry per compression iteration of the last fold
+    #[serde(default)]
+    pub steps: Vec<FoldStep>,
+}
+
+/// One compression iteration of a fold
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FoldStep {
+    /// Iteration number, starting at 0
+    pub iteration: usize,
+    /// [`FoldStrategy::name`] of the strategy the iteration used
+    pub strategy_name: String,
+    /// Tokens before the iteration
+    pub tokens_before: usize,
+    /// Tokens after the iteration
+    pub tokens_after: usize,
+}
+
+impl FoldStep {
+    /// Tokens the iteration removed
+    pub fn tokens_saved(&self) -> usize {
+        self.tokens_before.saturating_sub(self.tokens_after)
+    }
 }
 
 impl FoldingStats {
@@ -87,10 +282,75 @@ impl FoldingStats {
     }
 }
 
+/// What a fold removed from a context and what it left in place
+///
+/// Lines the fold rewrote (summaries, shortened JSON) count as neither
+/// dropped nor kept.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct FoldingExplanation {
+    /// Lines of the original context missing from the folded one
+    pub dropped_lines: Vec<String>,
+    /// Lines carried over unchanged
+    pub kept_lines: Vec<String>,
+    /// Code blocks of the original context that survive intact
+    pub preserved_blocks: Vec<String>,
+    /// Folded tokens divided by original tokens
+    pub compression_ratio: f64,
+}
+
+impl FoldingExplanation {
+    /// Diff `before` and `after` line by line, counting tokens with `counter`
+    pub fn between(before: &str, after: &str, counter: &dyn TokenCounter) -> Self {
+        // Without this a last line missing its newline never matches
+        let terminated = |text: &str| {
+            if text.is_empty() || text.ends_with('\n') {
+                text.to_string()
+            } else {
+                format!("{}\n", text)
+            }
+        };
+        let (before_lines, after_lines) = (terminated(before), terminated(after));
+
+        let mut dropped_lines = Vec::new();
+        let mut kept_lines = Vec::new();
+        for change in TextDiff::from_lines(&before_lines, &after_lines).iter_all_changes() {
+            let line = change.value().trim_end_matches(['\r', '\n']).to_string();
+            match change.tag() {
+                ChangeTag::Delete => dropped_lines.push(line),
+                ChangeTag::Equal => kept_lines.push(line),
+                ChangeTag::Insert => {}
+            }
+        }
+
+        let preserved_blocks = CodeBlockParser::new()
+            .extract_from(before)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|block| after.contains(&block.code))
+            .map(|block| block.code)
+            .collect();
+
+        let before_tokens = counter.count_tokens(before);
+        let compression_ratio = if before_tokens == 0 {
+            1.0
+        } else {
+            counter.count_tokens(after) as f64 / before_tokens as f64
+        };
+
+        Self {
+            dropped_lines,
+            kept_lines,
+            preserved_blocks,
+            compression_ratio,
+        }
+    }
+}
+
 /// Context folder for RLM workflows
 pub struct ContextFolder {
     config: ContextFoldConfig,
     stats: Arc<RwLock<FoldingStats>>,
+    token_counter: Arc<dyn TokenCounter>,
 }
 
 impl ContextFolder {
@@ -99,32 +359,60 @@ impl ContextFolder {
         Self {
             config,
             stats: Arc::new(RwLock::new(FoldingStats::default())),
+            token_counter: default_token_counter(),
         }
     }
 
-    /// Estimate token count from text
+    /// Count tokens with `counter` instead of the default heuristic
+    pub fn with_token_counter(mut self, counter: Arc<dyn TokenCounter>) -> Self {
+        self.token_counter = counter;
+        self
+    }
+
+    /// Estimate token count from text with the default [`HeuristicTokenCounter`]
     ///
     /// **Note**: This is a heuristic estimation only. Actual LLM tokenization may vary.
-    /// Different models (GPT, BERT, etc.) use different tokenizers and may count
-    /// tokens differently. For production use, integrate an actual tokenizer library.
+    /// Use [`ContextFolder::count_tokens`] to honor a configured counter.
     pub fn estimate_tokens(text: &str) -> usize {
-        // Simple heuristic: words + punctuation
-        // This is a conservative estimate that tends to undercount tokens
-        let words = text.split_whitespace().count();
-        let punctuation = text.matches(|c: char| c.is_ascii_punctuation()).count();
-        words + (punctuation / 2)
+        HeuristicTokenCounter.count_tokens(text)
+    }
+
+    /// Count tokens in `text` with this folder's counter
+    pub fn count_tokens(&self, text: &str) -> usize {
+        self.token_counter.count_tokens(text)
     }
 
     /// Check if folding is needed
+    ///
+    /// True once `text` exceeds [`ContextFoldConfig::fold_threshold_tokens`].
     pub fn should_fold(&self, text: &str) -> bool {
-        let tokens = Self::estimate_tokens(text);
-        tokens > self.config.max_tokens
+        let tokens = self.count_tokens(text);
+        tokens > self.config.fold_threshold_tokens()
+    }
+
+    /// Tokens in `text` as a fraction of `max_tokens`, for display
+    ///
+    /// Values above 1.0 mean the context is over the limit.
+    pub fn compression_needed_ratio(&self, text: &str) -> f64 {
+        let tokens = self.count_tokens(text);
+        match (tokens, self.config.max_tokens) {
+            (0, _) => 0.0,
+            (_, 0) => f64::INFINITY,
+            (tokens, max_tokens) => tokens as f64 / max_tokens as f64,
+        }
+    }
+
+    /// Fold `context` and explain what the fold dropped and kept
+    pub async fn fold_with_explanation(&self, context: &str) -> RLMResult<(String, FoldingExplanation)> {
+        let folded = self.fold(context).await?;
+        let explanation = FoldingExplanation::between(context, &folded, self.token_counter.as_ref());
+        Ok((folded, explanation))
     }
 
     /// Fold context by compressing tokens
     pub async fn fold(&self, context: &str) -> RLMResult<String> {
         let start = std::time::Instant::now();
-        let original_tokens = Self::estimate_tokens(context);
+        let original_tokens = self.count_tokens(context);
 
         if !self.should_fold(context) {
             return Ok(context.to_string());
@@ -133,16 +421,32 @@ impl ContextFolder {
         let mut current = context.to_string();
         let mut stats = self.stats.write().await;
         stats.original_tokens = original_tokens;
+        stats.steps.clear();
+
+        // Proactive folding compresses below its threshold, deferred folding
+        // back under the limit
+        let target_tokens = self.config.fold_threshold_tokens().min(self.config.max_tokens);
 
-        for iter in 0..self.config.max_iterations {
-            let current_tokens = Self::estimate_tokens(&current);
+        // A sliding window folds in one pass, whatever is left over
+        let passes = match self.config.fold_strategy {
+            FoldStrategy::SlidingWindow { .. } => 1,
+            _ => self.config.max_iterations,
+        };
+        for iter in 0..passes {
+            let current_tokens = self.count_tokens(&current);
             
-            if current_tokens <= self.config.max_tokens {
+            if current_tokens <= target_tokens {
                 break;
             }
 
             current = self.compress_iteration(&current, iter).await?;
             stats.iterations = iter + 1;
+            stats.steps.push(FoldStep {
+                iteration: iter,
+                strategy_name: self.iteration_strategy(iter).name().to_string(),
+                tokens_before: current_tokens,
+                tokens_after: self.count_tokens(&current),
+            });
 
             // Safety check
             if current.is_empty() {
@@ -152,7 +456,7 @@ impl ContextFolder {
             }
         }
 
-        let compressed_tokens = Self::estimate_tokens(&current);
+        let compressed_tokens = self.count_tokens(&current);
         stats.compressed_tokens = compressed_tokens;
         stats.fold_time_ms = start.elapsed().as_millis() as u64;
         stats.compression_ratio = stats.actual_ratio();
@@ -160,6 +464,129 @@ impl ContextFolder {
         Ok(current)
     }
 
+    /// Fold a context that arrives in `chunks` down to `max_output_tokens`
+    ///
+    /// Chunks are appended one at a time to a running result, which is
+    /// compressed back under `max_output_tokens` whenever a merge pushes it
+    /// over. Only the running result, the current chunk and one compressed
+    /// copy are held at once, so peak memory stays around
+    /// `2 * chunk_size + max_output_tokens` however large the whole context
+    /// is. Text that line-based compression cannot shrink enough (such as a
+    /// single huge line) is truncated.
+    ///
+    /// Stats record the tokens of all chunks as `original_tokens`, and one
+    /// step per compression pass.
+    ///
+    /// # Errors
+    ///
+    /// Returns a configuration error if `max_output_tokens` is 0.
+    pub async fn fold_chunked<'a>(
+        &self,
+        chunks: impl Iterator<Item = &'a str>,
+        max_output_tokens: usize,
+    ) -> RLMResult<String> {
+        let mut fold = self.streaming_fold(max_output_tokens)?;
+        for chunk in chunks {
+            fold.push_chunk(chunk).await?;
+        }
+        fold.finish().await
+    }
+
+    /// Fold `lines` down to `max_output_tokens` without collecting them first
+    ///
+    /// Lines are buffered until about `max_output_tokens` of them have
+    /// arrived and then merged like a chunk of
+    /// [`fold_chunked`](Self::fold_chunked), so memory is bounded by the
+    /// budget rather than by the input. See [`StreamingFold`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a configuration error if `max_output_tokens` is 0.
+    pub async fn fold_lines<S: AsRef<str>>(
+        &self,
+        lines: impl IntoIterator<Item = S>,
+        max_output_tokens: usize,
+    ) -> RLMResult<String> {
+        let mut fold = self.streaming_fold(max_output_tokens)?;
+        for line in lines {
+            fold.push_line(line.as_ref()).await?;
+        }
+        fold.finish().await
+    }
+
+    /// Fold text read line by line from `reader` down to `max_output_tokens`
+    ///
+    /// Like [`fold_lines`](Self::fold_lines); each line is still read whole,
+    /// so a single enormous line is held in memory while it is read.
+    ///
+    /// # Errors
+    ///
+    /// Returns a configuration error if `max_output_tokens` is 0, and an IO
+    /// error if reading fails or the input is not UTF-8.
+    pub async fn fold_reader(
+        &self,
+        reader: impl AsyncRead + Unpin,
+        max_output_tokens: usize,
+    ) -> RLMResult<String> {
+        let mut fold = self.streaming_fold(max_output_tokens)?;
+        let mut lines = BufReader::new(reader).lines();
+        while let Some(line) = lines.next_line().await? {
+            fold.push_line(&line).await?;
+        }
+        fold.finish().await
+    }
+
+    /// Start an incremental fold down to `max_output_tokens`
+    ///
+    /// # Errors
+    ///
+    /// Returns a configuration error if `max_output_tokens` is 0.
+    pub fn streaming_fold(&self, max_output_tokens: usize) -> RLMResult<StreamingFold<'_>> {
+        if max_output_tokens == 0 {
+            return Err(RLMError::config("max_output_tokens must be > 0"));
+        }
+        Ok(StreamingFold {
+            folder: self,
+            max_output_tokens,
+            started: std::time::Instant::now(),
+            current: String::new(),
+            pending: String::new(),
+            pending_tokens: 0,
+            original_tokens: 0,
+            steps: Vec::new(),
+            peak_buffered_bytes: 0,
+        })
+    }
+
+    /// Rough peak memory, in bytes, of [`ContextFolder::fold`] on a context
+    /// of `input_len` bytes
+    ///
+    /// Counts the input, the owned working copy, one compressed copy at the
+    /// configured ratio and the per-line slices a pass builds. Use
+    /// [`ContextFolder::fold_chunked`] when this is too much.
+    pub fn estimate_memory_usage(&self, input_len: usize) -> usize {
+        let ratio = if self.config.aggressive {
+            0.5
+        } else {
+            self.config.compression_ratio
+        };
+        let compressed = (input_len as f64 * ratio) as usize;
+        let line_slices = input_len / ESTIMATED_LINE_LEN * std::mem::size_of::<&str>();
+        input_len * 2 + compressed + line_slices
+    }
+
+    /// Strategy used by compression iteration `iteration`
+    ///
+    /// The configured strategy shapes the first pass; later passes sample
+    /// uniformly, except that JSON stays JSON-aware.
+    fn iteration_strategy(&self, iteration: usize) -> FoldStrategy {
+        match self.config.fold_strategy {
+            FoldStrategy::JsonAware => FoldStrategy::JsonAware,
+            strategy if iteration == 0 => strategy,
+            _ => FoldStrategy::Uniform,
+        }
+    }
+
     /// Single compression iteration
     async fn compress_iteration(&self, context: &str, iteration: usize) -> RLMResult<String> {
         let target_ratio = if self.config.aggressive {
@@ -176,61 +603,58 @@ impl ContextFolder {
         let keep_count = ((lines.len() as f64) * target_ratio) as usize;
         let keep_count = keep_count.max(1);
 
-        // Strategy depends on iteration count
-        let compressed = match iteration {
-            0 => self.compress_by_importance(&lines, keep_count),
-            1 => self.compress_by_sampling(&lines, keep_count),
-            _ => self.compress_by_summary(&lines, keep_count),
+        let compressed = match self.iteration_strategy(iteration) {
+            FoldStrategy::JsonAware => self.compress_json_aware(context, target_ratio, iteration),
+            FoldStrategy::HeadOnly => lines[..keep_count.min(lines.len())].join("\n"),
+            FoldStrategy::TailOnly => lines[lines.len().saturating_sub(keep_count)..].join("\n"),
+            FoldStrategy::HeadTail => self.compress_by_importance(&lines, keep_count),
+            FoldStrategy::Uniform => self.compress_by_sampling(&lines, keep_count),
+            FoldStrategy::SlidingWindow { window_size, step_size } => {
+                SlidingWindowFolder::new(window_size, step_size).fold(context)
+            }
         };
 
         Ok(compressed)
     }
 
-    /// Compress by keeping important lines
-    fn compress_by_importance(&self, lines: &[&str], keep_count: usize) -> String {
-        // Keep first and last sections, sample middle
-        let mut result = Vec::new();
-
-        if lines.is_empty() {
-            return String::new();
-        }
+    /// Compress JSON regions structurally and the text around them by lines
+    fn compress_json_aware(&self, context: &str, target_ratio: f64, iteration: usize) -> String {
+        let keep_items = (JSON_KEEP_ITEMS >> iteration).max(1);
+        let max_string = (JSON_MAX_STRING >> iteration).max(JSON_MIN_STRING);
 
-        let section_size = (lines.len() / 3).max(1);
-        
-        // Keep first section
-        let first_keep = (keep_count / 3).max(1);
-        let end = first_keep.min(lines.len());
-        for line in &lines[0..end] {
-            if result.len() < keep_count {
-                result.push(*line);
-            }
-        }
-
-        // Sample middle
-        if lines.len() > 2 * section_size {
-            let mid_start = section_size;
-            let mid_end = lines.len() - section_size;
-            if mid_start < mid_end {
-                let mid_section = &lines[mid_start..mid_end];
-                let sample_count = (keep_count / 3).max(1);
-                let step = (mid_section.len() / sample_count).max(1);
-                for (i, line) in mid_section.iter().enumerate() {
-                    if i % step == 0 && result.len() < keep_count {
-                        result.push(*line);
+        split_json_segments(context)
+            .into_iter()
+            .map(|segment| match segment {
+                Segment::Json(value) => {
+                    let compressed = compress_json_value(&value, keep_items, max_string);
+                    serde_json::to_string(&compressed).unwrap_or_else(|_| value.to_string())
+                }
+                Segment::Text(lines) => {
+                    let keep_count = ((lines.len() as f64 * target_ratio) as usize).max(1);
+                    if iteration == 0 {
+                        self.compress_by_importance(&lines, keep_count)
+                    } else {
+                        self.compress_by_sampling(&lines, keep_count)
                     }
                 }
-            }
-        }
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
 
-        // Keep last section
-        let remaining = keep_count.saturating_sub(result.len());
-        let start = (lines.len() - remaining).max(0);
-        for line in &lines[start..] {
-            if result.len() < keep_count {
-                result.push(line);
-            }
+    /// Compress by keeping the first and last lines and summarizing the middle
+    fn compress_by_importance(&self, lines: &[&str], keep_count: usize) -> String {
+        if lines.len() <= keep_count {
+            return lines.join("\n");
         }
 
+        let head = keep_count.div_ceil(2);
+        let tail = keep_count - head;
+        let middle = &lines[head..lines.len() - tail];
+
+        let mut result: Vec<String> = lines[..head].iter().map(|line| line.to_string()).collect();
+        result.push(self.compress_by_summary(middle, 1));
+        result.extend(lines[lines.len() - tail..].iter().map(|line| line.to_string()));
         result.join("\n")
     }
 
@@ -252,7 +676,7 @@ impl ContextFolder {
         result.join("\n")
     }
 
-    /// Compress by generating summary
+    /// Compress lines into a one-line summary
     fn compress_by_summary(&self, lines: &[&str], _keep_count: usize) -> String {
         if lines.is_empty() {
             return String::new();
@@ -280,19 +704,389 @@ impl ContextFolder {
     }
 }
 
+/// Incremental fold of a context fed a line or a chunk at a time
+///
+/// Created by [`ContextFolder::streaming_fold`]. Lines are buffered until
+/// about `max_output_tokens` of them are pending; the buffer is then merged
+/// into the folded output, which is compressed back under the budget right
+/// away. [`output`](Self::output) can be read between pushes to emit the
+/// folded context as it grows, and [`finish`](Self::finish) merges what is
+/// left and records the folder's stats.
+///
+/// At most the folded output, the pending buffer and one compressed copy
+/// are held at once, so buffering stays around three times the budget
+/// whatever the size of the input.
+pub struct StreamingFold<'a> {
+    folder: &'a ContextFolder,
+    max_output_tokens: usize,
+    started: std::time::Instant,
+    current: String,
+    pending: String,
+    pending_tokens: usize,
+    original_tokens: usize,
+    steps: Vec<FoldStep>,
+    peak_buffered_bytes: usize,
+}
+
+impl StreamingFold<'_> {
+    /// Add one line of input, merging the buffer once it reaches the budget
+    pub async fn push_line(&mut self, line: &str) -> RLMResult<()> {
+        let tokens = self.folder.count_tokens(line);
+        self.buffer(line, tokens);
+        if self.pending_tokens >= self.max_output_tokens {
+            self.merge_pending().await?;
+        }
+        Ok(())
+    }
+
+    /// Add a chunk of input and merge it right away
+    pub async fn push_chunk(&mut self, chunk: &str) -> RLMResult<()> {
+        let tokens = self.folder.count_tokens(chunk);
+        self.buffer(chunk, tokens);
+        self.merge_pending().await
+    }
+
+    /// Folded output of the input merged so far
+    ///
+    /// Always within the budget; lines still buffered are not included.
+    pub fn output(&self) -> &str {
+        &self.current
+    }
+
+    /// Tokens of all input pushed so far
+    pub fn input_tokens(&self) -> usize {
+        self.original_tokens
+    }
+
+    /// Most bytes held in the output and pending buffers at any one time
+    pub fn peak_buffered_bytes(&self) -> usize {
+        self.peak_buffered_bytes
+    }
+
+    /// Merge the remaining input, record stats and return the folded context
+    pub async fn finish(mut self) -> RLMResult<String> {
+        self.merge_pending().await?;
+
+        let mut stats = self.folder.stats.write().await;
+        stats.original_tokens = self.original_tokens;
+        stats.compressed_tokens = self.folder.count_tokens(&self.current);
+        stats.iterations = self.steps.len();
+        stats.steps = self.steps;
+        stats.fold_time_ms = self.started.elapsed().as_millis() as u64;
+        stats.compression_ratio = stats.actual_ratio();
+
+        Ok(self.current)
+    }
+
+    fn buffer(&mut self, text: &str, tokens: usize) {
+        self.original_tokens += tokens;
+        if !self.pending.is_empty() {
+            self.pending.push('\n');
+        }
+        self.pending.push_str(text);
+        self.pending_tokens += tokens;
+        self.peak_buffered_bytes = self
+            .peak_buffered_bytes
+            .max(self.current.len() + self.pending.len());
+    }
+
+    /// Append the pending buffer to the output and compress it under budget
+    ///
+    /// Up to [`CHUNKED_MAX_PASSES`] line-based passes run before the output
+    /// is truncated; a pass that fails to shrink it truncates right away.
+    async fn merge_pending(&mut self) -> RLMResult<()> {
+        if !self.current.is_empty() && !self.pending.is_empty() {
+            self.current.push('\n');
+        }
+        self.current.push_str(&self.pending);
+        self.pending.clear();
+        self.pending_tokens = 0;
+
+        let folder = self.folder;
+        let mut tokens = folder.count_tokens(&self.current);
+        let mut pass = 0;
+        while tokens > self.max_output_tokens {
+            let truncating = pass >= CHUNKED_MAX_PASSES;
+            let compressed = if truncating {
+                truncate_to_tokens(&self.current, tokens, self.max_output_tokens)
+            } else {
+                folder.compress_iteration(&self.current, pass).await?
+            };
+            let compressed_tokens = folder.count_tokens(&compressed);
+            if !truncating && compressed_tokens >= tokens {
+                // Line-based compression has stalled
+                pass = CHUNKED_MAX_PASSES;
+                continue;
+            }
+
+            self.peak_buffered_bytes = self
+                .peak_buffered_bytes
+                .max(self.current.len() + compressed.len());
+            self.steps.push(FoldStep {
+                iteration: self.steps.len(),
+                strategy_name: if truncating {
+                    "truncate".to_string()
+                } else {
+                    folder.iteration_strategy(pass).name().to_string()
+                },
+                tokens_before: tokens,
+                tokens_after: compressed_tokens,
+            });
+            self.current = compressed;
+            tokens = compressed_tokens;
+            pass += 1;
+        }
+        Ok(())
+    }
+}
+
+/// Cut `text`, which counts `tokens`, to a prefix of about `max_tokens`
+///
+/// Assumes tokens are spread evenly; callers recount and cut again if needed.
+fn truncate_to_tokens(text: &str, tokens: usize, max_tokens: usize) -> String {
+    let target = (text.len() as u128 * max_tokens as u128 / tokens.max(1) as u128) as usize;
+    let mut end = target.min(text.len().saturating_sub(1));
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    text[..end].to_string()
+}
+
+/// Split text into JSON regions and the lines between them
+///
+/// A JSON region is an object or array starting a line and ending a line,
+/// possibly several lines later. Anything that does not parse stays text.
+fn split_json_segments(context: &str) -> Vec<Segment<'_>> {
+    let mut segments = Vec::new();
+    let mut text: Vec<&str> = Vec::new();
+    let mut offset = 0;
+
+    while offset < context.len() {
+        let line_end = context[offset..].find('\n').map_or(context.len(), |i| offset + i);
+        let line = &context[offset..line_end];
+
+        if let Some((value, end)) = parse_json_region(context, offset, line) {
+            if !text.is_empty() {
+                segments.push(Segment::Text(std::mem::take(&mut text)));
+            }
+            segments.push(Segment::Json(value));
+            offset = end;
+        } else {
+            text.push(line);
+            offset = line_end;
+        }
+        // Step over the newline ending this line or region
+        offset += 1;
+    }
+
+    if !text.is_empty() {
+        segments.push(Segment::Text(text));
+    }
+    segments
+}
+
+/// Parse an object or array starting on the line at `offset`
+///
+/// Returns the value and the offset of the end of its last line.
+fn parse_json_region(context: &str, offset: usize, line: &str) -> Option<(Value, usize)> {
+    let trimmed = line.trim_start();
+    if !trimmed.starts_with('{') && !trimmed.starts_with('[') {
+        return None;
+    }
+
+    let start = offset + (line.len() - trimmed.len());
+    let mut stream = serde_json::Deserializer::from_str(&context[start..]).into_iter::<Value>();
+    let value = stream.next()?.ok()?;
+    let value_end = start + stream.byte_offset();
+
+    // The region must end its line so no text is swallowed
+    let line_end = context[value_end..].find('\n').map_or(context.len(), |i| value_end + i);
+    context[value_end..line_end]
+        .trim()
+        .is_empty()
+        .then_some((value, line_end))
+}
+
+/// Shorten long arrays and strings in `value`, keeping every key
+fn compress_json_value(value: &Value, keep_items: usize, max_string: usize) -> Value {
+    match value {
+        Value::Array(items) if items.len() > 2 * keep_items + 1 => {
+            let omitted = items.len() - 2 * keep_items;
+            let mut kept: Vec<Value> = items[..keep_items]
+                .iter()
+                .map(|item| compress_json_value(item, keep_items, max_string))
+                .collect();
+            kept.push(Value::String(format!("... {} more items ...", omitted)));
+            kept.extend(
+                items[items.len() - keep_items..]
+                    .iter()
+                    .map(|item| compress_json_value(item, keep_items, max_string)),
+            );
+            Value::Array(kept)
+        }
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .map(|item| compress_json_value(item, keep_items, max_string))
+                .collect(),
+        ),
+        Value::Object(fields) => Value::Object(
+            fields
+                .iter()
+                .map(|(key, field)| (key.clone(), compress_json_value(field, keep_items, max_string)))
+                .collect(),
+        ),
+        Value::String(text) if text.chars().count() > max_string => {
+            Value::String(format!("{}...", text.chars().take(max_string).collect::<String>()))
+        }
+        other => other.clone(),
+    }
+}
+
 /// Trait for foldable content
 #[async_trait]
 pub trait Foldable {
     /// Get content size in tokens
+    ///
+    /// Content that carries an [`RLMConfig`](crate::config::RLMConfig) counts
+    /// with its token counter; other content uses [`ContextFolder::estimate_tokens`].
     fn token_count(&self) -> usize;
 
     /// Fold the content
     async fn fold(&mut self, folder: &ContextFolder) -> RLMResult<()>;
 }
 
+/// Folds the accumulated answer of an execution context.
+///
+/// Folding is a no-op while the answer fits the folder's token budget.
+#[async_trait]
+impl Foldable for RLMContext {
+    fn token_count(&self) -> usize {
+        self.token_counter().count_tokens(self.answer())
+    }
+
+    async fn fold(&mut self, folder: &ContextFolder) -> RLMResult<()> {
+        if !folder.should_fold(self.answer()) {
+            return Ok(());
+        }
+
+        self.answer = folder.fold(self.answer()).await?;
+        self.record_fold();
+        Ok(())
+    }
+}
+
+/// Folds the lines as one piece of content.
+///
+/// When folding happens the vector is replaced by a single folded entry.
+#[async_trait]
+impl Foldable for Vec<String> {
+    fn token_count(&self) -> usize {
+        ContextFolder::estimate_tokens(&self.join("\n"))
+    }
+
+    async fn fold(&mut self, folder: &ContextFolder) -> RLMResult<()> {
+        let joined = self.join("\n");
+        if !folder.should_fold(&joined) {
+            return Ok(());
+        }
+
+        *self = vec![folder.fold(&joined).await?];
+        Ok(())
+    }
+}
+
+/// Folds the buffered answer, keeping its ready flag and iteration count.
+///
+/// `token_count` reports 0 while another task is writing to the buffer.
+#[async_trait]
+impl Foldable for AnswerBuffer {
+    fn token_count(&self) -> usize {
+        self.try_get_content()
+            .map(|content| ContextFolder::estimate_tokens(&content))
+            .unwrap_or(0)
+    }
+
+    async fn fold(&mut self, folder: &ContextFolder) -> RLMResult<()> {
+        let content = self.get_content().await;
+        if !folder.should_fold(&content) {
+            return Ok(());
+        }
+
+        self.replace_content(folder.fold(&content).await?).await;
+        Ok(())
+    }
+}
+
+/// In-place folding of the accumulated results carried by a federation [`RLMContext`].
+///
+/// The federation crate cannot depend on [`ContextFolder`], so folding of its
+/// context lives here as an extension trait.
+///
+/// [`RLMContext`]: kowalski_federation::RLMContext
+#[async_trait]
+pub trait AccumulatedResultsFolding {
+    /// Estimated token count of the accumulated results
+    fn accumulated_results_tokens(&self) -> usize;
+
+    /// Fold accumulated results in place, returning the number of tokens saved.
+    ///
+    /// Returns 0 without touching the results when the folder does not
+    /// consider them large enough to fold.
+    async fn fold_accumulated_results_in_place(
+        &mut self,
+        folder: &ContextFolder,
+    ) -> RLMResult<usize>;
+
+    /// Append a result and fold once `max_accumulated_tokens` is exceeded.
+    ///
+    /// Returns the number of tokens saved by folding (0 if no fold happened).
+    async fn append_result_with_folding(
+        &mut self,
+        content: String,
+        folder: &ContextFolder,
+    ) -> RLMResult<usize>;
+}
+
+#[async_trait]
+impl AccumulatedResultsFolding for FederationContext {
+    fn accumulated_results_tokens(&self) -> usize {
+        ContextFolder::estimate_tokens(&self.accumulated_results)
+    }
+
+    async fn fold_accumulated_results_in_place(
+        &mut self,
+        folder: &ContextFolder,
+    ) -> RLMResult<usize> {
+        if !folder.should_fold(&self.accumulated_results) {
+            return Ok(0);
+        }
+
+        let before = self.accumulated_results_tokens();
+        self.accumulated_results = folder.fold(&self.accumulated_results).await?;
+        Ok(before.saturating_sub(self.accumulated_results_tokens()))
+    }
+
+    async fn append_result_with_folding(
+        &mut self,
+        content: String,
+        folder: &ContextFolder,
+    ) -> RLMResult<usize> {
+        self.append_result(content);
+
+        match self.max_accumulated_tokens {
+            Some(limit) if self.accumulated_results_tokens() > limit => {
+                self.fold_accumulated_results_in_place(folder).await
+            }
+            _ => Ok(0),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::RLMConfig;
 
     #[test]
     fn test_token_estimation() {
@@ -313,6 +1107,47 @@ mod tests {
         assert!(folder.should_fold(&large));
     }
 
+    #[test]
+    fn test_fold_threshold_ratio() {
+        // 90 words against a 100 token limit
+        let text = "word ".repeat(90);
+
+        let proactive = ContextFolder::new(ContextFoldConfig::new(100).with_fold_threshold_ratio(0.8));
+        assert!(proactive.should_fold(&text));
+
+        let exact = ContextFolder::new(ContextFoldConfig::new(100));
+        assert_eq!(exact.config.fold_threshold_ratio, 1.0);
+        assert!(!exact.should_fold(&text));
+        assert!(exact.should_fold(&"word ".repeat(101)));
+
+        let deferred = ContextFolder::new(ContextFoldConfig::new(100).with_fold_threshold_ratio(1.2));
+        assert!(!deferred.should_fold(&text));
+        assert!(!deferred.should_fold(&"word ".repeat(110)));
+        assert!(deferred.should_fold(&"word ".repeat(121)));
+
+        assert!((exact.compression_needed_ratio(&text) - 0.9).abs() < f64::EPSILON);
+        assert_eq!(exact.compression_needed_ratio(""), 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_proactive_fold_compresses_below_threshold() {
+        let config = ContextFoldConfig::new(100).with_fold_threshold_ratio(0.8);
+        let folder = ContextFolder::new(config);
+        let text = "word\n".repeat(90);
+
+        let folded = folder.fold(&text).await.unwrap();
+        assert!(folder.count_tokens(&folded) <= 80);
+    }
+
+    #[test]
+    fn test_fold_threshold_ratio_defaults_when_deserialized() {
+        let config: ContextFoldConfig = serde_json::from_str(
+            r#"{"max_tokens": 10, "compression_ratio": 0.5, "aggressive": false, "max_iterations": 2}"#,
+        )
+        .unwrap();
+        assert_eq!(config.fold_threshold_ratio, 1.0);
+    }
+
     #[tokio::test]
     async fn test_fold_small_context() {
         let config = ContextFoldConfig::new(100);
@@ -381,4 +1216,456 @@ mod tests {
             assert!(stats.fold_time_ms >= 0); // u64 sanity check - documents intent
         }
     }
+
+    #[tokio::test]
+    async fn test_fold_chunked_fits_budget() {
+        let folder = ContextFolder::new(ContextFoldConfig::default());
+
+        // 10 chunks of 50K tokens: 5000 lines of 10 words each
+        let chunks: Vec<String> = (0..10)
+            .map(|c| {
+                (0..5_000)
+                    .map(|i| format!("chunk {} line {} holds some words of context here", c, i))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            })
+            .collect();
+        assert_eq!(folder.count_tokens(&chunks[0]), 50_000);
+
+        let folded = folder
+            .fold_chunked(chunks.iter().map(String::as_str), 10_000)
+            .await
+            .unwrap();
+
+        assert!(folder.count_tokens(&folded) <= 10_000);
+        assert!(folded.contains("chunk 9"), "last chunk was dropped");
+        let stats = folder.stats().await;
+        assert_eq!(stats.original_tokens, 500_000);
+        assert_eq!(stats.compressed_tokens, folder.count_tokens(&folded));
+        assert!(!stats.steps.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_fold_chunked_truncates_unbreakable_lines() {
+        let folder = ContextFolder::new(ContextFoldConfig::default());
+        let line = "word ".repeat(5_000);
+
+        let folded = folder.fold_chunked([line.as_str(), line.as_str()].into_iter(), 100).await.unwrap();
+
+        assert!(folder.count_tokens(&folded) <= 100);
+        assert!(!folded.is_empty());
+        assert!(folder.fold_chunked(std::iter::empty(), 0).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fold_reader_bounds_buffering() {
+        let folder = ContextFolder::new(ContextFoldConfig::default());
+        // About 6 MB and 1M tokens
+        let input: String = (0..100_000)
+            .map(|i| format!("record {} carries a handful of words for the fold\n", i))
+            .collect();
+
+        let folded = folder.fold_reader(input.as_bytes(), 5_000).await.unwrap();
+
+        assert!(folder.count_tokens(&folded) <= 5_000);
+        assert!(folded.starts_with("record 0 "));
+        let last_record = folded
+            .lines()
+            .filter_map(|line| line.strip_prefix("record ")?.split(' ').next()?.parse::<usize>().ok())
+            .max()
+            .unwrap();
+        // Sampling passes may drop the last few lines, not the end of the input
+        assert!(last_record >= 90_000, "end of input was dropped");
+        let stats = folder.stats().await;
+        assert_eq!(stats.original_tokens, 100_000 * 10);
+        assert_eq!(stats.compressed_tokens, folder.count_tokens(&folded));
+
+        let mut fold = folder.streaming_fold(5_000).unwrap();
+        let mut outputs = 0;
+        for line in input.lines() {
+            let before = fold.output().len();
+            fold.push_line(line).await.unwrap();
+            if fold.output().len() != before {
+                outputs += 1;
+                assert!(folder.count_tokens(fold.output()) <= 5_000);
+            }
+        }
+        assert!(outputs > 100, "output was not emitted incrementally");
+        // Output, pending lines and one compressed copy, not the whole input
+        assert!(fold.peak_buffered_bytes() < input.len() / 20);
+        assert_eq!(fold.finish().await.unwrap(), folded);
+    }
+
+    #[tokio::test]
+    async fn test_fold_lines_keeps_small_input_whole() {
+        let folder = ContextFolder::new(ContextFoldConfig::default());
+        let folded = folder.fold_lines(["first", "", "third"], 100).await.unwrap();
+        assert_eq!(folded, "first\n\nthird");
+        assert!(folder.stats().await.steps.is_empty());
+        assert!(folder.fold_lines(Vec::<String>::new(), 0).await.is_err());
+    }
+
+    #[test]
+    fn test_estimate_memory_usage_scales_with_input() {
+        let folder = ContextFolder::new(ContextFoldConfig::default());
+        let small = folder.estimate_memory_usage(1_000);
+        let large = folder.estimate_memory_usage(1_000_000);
+
+        assert!(small >= 2_000);
+        assert!(large >= 2_000_000);
+        assert!(large > small * 900);
+        assert_eq!(folder.estimate_memory_usage(0), 0);
+    }
+
+    fn result_line(i: usize) -> String {
+        format!("Iteration {} produced intermediate output with several words", i)
+    }
+
+    #[tokio::test]
+    async fn test_fold_accumulated_results_below_threshold() {
+        let folder = ContextFolder::new(ContextFoldConfig::new(1_000));
+        let mut context = FederationContext::new("workflow-1".to_string());
+        context.append_result(result_line(0));
+
+        let original = context.accumulated_results.clone();
+        let saved = context
+            .fold_accumulated_results_in_place(&folder)
+            .await
+            .unwrap();
+
+        assert_eq!(saved, 0);
+        assert_eq!(context.accumulated_results, original);
+    }
+
+    #[tokio::test]
+    async fn test_fold_accumulated_results_in_place() {
+        let folder = ContextFolder::new(ContextFoldConfig::new(100));
+        let mut context = FederationContext::new("workflow-1".to_string());
+        for i in 0..50 {
+            context.append_result(result_line(i));
+        }
+
+        let before = context.accumulated_results_tokens();
+        let saved = context
+            .fold_accumulated_results_in_place(&folder)
+            .await
+            .unwrap();
+
+        assert!(saved > 0);
+        assert_eq!(context.accumulated_results_tokens(), before - saved);
+        assert!(context.accumulated_results_tokens() < before / 2);
+    }
+
+    #[tokio::test]
+    async fn test_append_result_folds_past_threshold() {
+        let folder = ContextFolder::new(ContextFoldConfig::new(100));
+        let mut context =
+            FederationContext::new("workflow-1".to_string()).with_max_accumulated_tokens(200);
+
+        let mut folded_at = None;
+        for i in 0..50 {
+            let saved = context
+                .append_result_with_folding(result_line(i), &folder)
+                .await
+                .unwrap();
+            if saved > 0 {
+                folded_at = Some(i);
+                break;
+            }
+            assert!(context.accumulated_results_tokens() <= 200);
+        }
+
+        assert!(folded_at.is_some(), "results should fold once past the threshold");
+        assert!(context.accumulated_results_tokens() < 200);
+    }
+
+    #[tokio::test]
+    async fn test_append_result_without_threshold_never_folds() {
+        let folder = ContextFolder::new(ContextFoldConfig::new(10));
+        let mut context = FederationContext::new("workflow-1".to_string());
+
+        for i in 0..20 {
+            let saved = context
+                .append_result_with_folding(result_line(i), &folder)
+                .await
+                .unwrap();
+            assert_eq!(saved, 0);
+        }
+        assert_eq!(context.accumulated_results.lines().count(), 20);
+    }
+
+    #[tokio::test]
+    async fn test_fold_context_via_trait() {
+        let mut context = RLMContext::new("fold-task", Arc::new(RLMConfig::default()));
+        for i in 0..200 {
+            context.append_answer(format!("step {} produced some intermediate output\n", i));
+        }
+        let before = context.token_count();
+        assert!(before > 100);
+
+        let folder = ContextFolder::new(ContextFoldConfig::new(100));
+        context.fold(&folder).await.unwrap();
+        assert!(context.token_count() < before);
+        assert_eq!(context.metadata.folds, 1);
+
+        // Within budget: nothing changes and no fold is recorded
+        let folded = context.answer().to_string();
+        let roomy = ContextFolder::new(ContextFoldConfig::new(100_000));
+        context.fold(&roomy).await.unwrap();
+        assert_eq!(context.answer(), folded);
+        assert_eq!(context.metadata.folds, 1);
+    }
+
+    fn numbered_lines(count: usize) -> String {
+        (0..count)
+            .map(|i| format!("line {} has a handful of words in it", i))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn single_pass(strategy: FoldStrategy) -> ContextFolder {
+        let mut config = ContextFoldConfig::new(10)
+            .with_compression_ratio(0.3)
+            .with_fold_strategy(strategy);
+        config.max_iterations = 1;
+        ContextFolder::new(config)
+    }
+
+    #[tokio::test]
+    async fn test_tail_only_keeps_last_lines_verbatim() {
+        let text = numbered_lines(10);
+        let folded = single_pass(FoldStrategy::TailOnly).fold(&text).await.unwrap();
+
+        let expected = text.lines().skip(7).collect::<Vec<_>>().join("\n");
+        assert_eq!(folded, expected);
+    }
+
+    #[tokio::test]
+    async fn test_head_only_and_head_tail_strategies() {
+        let text = numbered_lines(10);
+
+        let head = single_pass(FoldStrategy::HeadOnly).fold(&text).await.unwrap();
+        assert_eq!(head, text.lines().take(3).collect::<Vec<_>>().join("\n"));
+
+        let head_tail = single_pass(FoldStrategy::HeadTail).fold(&text).await.unwrap();
+        let kept: Vec<&str> = head_tail.lines().collect();
+        assert_eq!(kept.len(), 4);
+        assert_eq!(kept[0], "line 0 has a handful of words in it");
+        assert_eq!(kept[1], "line 1 has a handful of words in it");
+        assert!(kept[2].starts_with("[SUMMARY: 7 lines"));
+        assert_eq!(kept[3], "line 9 has a handful of words in it");
+
+        let uniform = single_pass(FoldStrategy::Uniform).fold(&text).await.unwrap();
+        assert_eq!(uniform.lines().count(), 3);
+        assert!(uniform.starts_with("line 0 "));
+    }
+
+    fn large_json_array(items: usize) -> String {
+        let records: Vec<Value> = (0..items)
+            .map(|i| {
+                serde_json::json!({
+                    "id": i,
+                    "name": format!("item {}", i),
+                    "description": "a long and rather verbose description of the item ".repeat(5),
+                    "tags": ["alpha", "beta", "gamma", "delta", "epsilon", "zeta", "eta", "theta"],
+                })
+            })
+            .collect();
+        serde_json::to_string_pretty(&Value::Array(records)).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_json_aware_fold_keeps_json_valid() {
+        let json = large_json_array(200);
+        let folder = ContextFolder::new(
+            ContextFoldConfig::new(300).with_fold_strategy(FoldStrategy::JsonAware),
+        );
+        assert!(folder.should_fold(&json));
+
+        let folded = folder.fold(&json).await.unwrap();
+        assert!(folder.count_tokens(&folded) <= 300);
+
+        let value: Value = serde_json::from_str(&folded).expect("folded output is valid JSON");
+        let items = value.as_array().unwrap();
+        assert_eq!(items.len(), 2 * JSON_KEEP_ITEMS + 1);
+        assert_eq!(items[0]["id"], 0);
+        assert_eq!(items[JSON_KEEP_ITEMS], "... 194 more items ...");
+        assert_eq!(items[2 * JSON_KEEP_ITEMS]["id"], 199);
+        // Verbose fields keep their key with a shortened value
+        let description = items[0]["description"].as_str().unwrap();
+        assert!(description.ends_with("...") && description.len() < 100);
+    }
+
+    #[tokio::test]
+    async fn test_json_aware_fold_leaves_surrounding_text() {
+        let context = format!(
+            "Tool output follows:\n{}\nThe tool finished.",
+            large_json_array(50)
+        );
+        let folder = ContextFolder::new(
+            ContextFoldConfig::new(300).with_fold_strategy(FoldStrategy::JsonAware),
+        );
+
+        let folded = folder.fold(&context).await.unwrap();
+        let lines: Vec<&str> = folded.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], "Tool output follows:");
+        assert!(serde_json::from_str::<Value>(lines[1]).is_ok());
+        assert_eq!(lines[2], "The tool finished.");
+    }
+
+    #[test]
+    fn test_split_json_segments_ignores_invalid_json() {
+        let segments = split_json_segments("[not json\n{\"a\": 1}\n{\"b\": 2} trailing");
+        assert_eq!(segments.len(), 3);
+        assert!(matches!(&segments[0], Segment::Text(lines) if lines == &vec!["[not json"]));
+        assert!(matches!(&segments[1], Segment::Json(value) if value["a"] == 1));
+        assert!(matches!(&segments[2], Segment::Text(lines) if lines == &vec!["{\"b\": 2} trailing"]));
+    }
+
+    #[test]
+    fn test_sliding_window_keeps_first_sentence_per_window() {
+        let text = "One two three. Four five six. Seven eight.\nNine ten eleven twelve.";
+        let folder = SlidingWindowFolder::new(4, 4);
+        assert_eq!(
+            folder.fold(text),
+            "One two three. Seven eight. Nine ten eleven twelve."
+        );
+
+        // Overlapping windows never repeat a sentence
+        let overlapping = SlidingWindowFolder::new(6, 2);
+        assert_eq!(overlapping.fold(text), "One two three. Four five six. Seven eight. Nine ten eleven twelve.");
+
+        // Unpunctuated text is capped at one window per kept run
+        assert_eq!(SlidingWindowFolder::new(3, 10).fold("a b c d e f g h i j k l"), "a b c");
+        assert_eq!(SlidingWindowFolder::new(0, 0), SlidingWindowFolder::new(1, 1));
+    }
+
+    #[tokio::test]
+    async fn test_sliding_window_strategy_folds_in_one_pass() {
+        let config = ContextFoldConfig::new(100).with_sliding_window(20, 20);
+        assert_eq!(
+            config.fold_strategy,
+            FoldStrategy::SlidingWindow { window_size: 20, step_size: 20 }
+        );
+        let text = (0..200)
+            .map(|i| format!("Reading {} was within range. The sensor reported no faults.", i))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let folder = ContextFolder::new(config);
+        let folded = folder.fold(&text).await.unwrap();
+
+        assert!(folded.len() < text.len() / 2);
+        assert!(folded.starts_with("Reading 0 was within range."));
+        assert_eq!(folder.stats().await.iterations, 1);
+    }
+
+    #[tokio::test]
+    async fn test_sliding_window_folds_in_one_pass_where_multi_pass_needs_several() {
+        let text = (0..5_000)
+            .map(|i| format!("Event {} arrived from the queue. Its payload was processed without errors.", i))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let original_tokens = ContextFolder::estimate_tokens(&text);
+        let max_tokens = original_tokens / 10;
+
+        let multi_pass = ContextFolder::new(ContextFoldConfig::new(max_tokens).with_fold_strategy(FoldStrategy::Uniform));
+        let multi_folded = multi_pass.fold(&text).await.unwrap();
+
+        let sliding = ContextFolder::new(ContextFoldConfig::new(max_tokens).with_sliding_window(40, 40));
+        let sliding_folded = sliding.fold(&text).await.unwrap();
+
+        assert!(multi_pass.stats().await.iterations > 1);
+        assert_eq!(sliding.stats().await.iterations, 1);
+
+        // The single pass compresses further than all the passes together
+        let multi_tokens = ContextFolder::estimate_tokens(&multi_folded);
+        let sliding_tokens = ContextFolder::estimate_tokens(&sliding_folded);
+        assert!(multi_tokens < original_tokens);
+        assert!(sliding_tokens < multi_tokens);
+        assert!(sliding_folded.starts_with("Event 0 arrived from the queue."));
+    }
+
+    #[tokio::test]
+    async fn test_fold_records_each_step() {
+        let text = (0..400)
+            .map(|i| format!("log entry {} recorded by the ingestion worker", i))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let folder = ContextFolder::new(ContextFoldConfig::new(ContextFolder::estimate_tokens(&text) / 4));
+
+        let folded = folder.fold(&text).await.unwrap();
+        let stats = folder.stats().await;
+
+        assert!(stats.steps.len() >= 2);
+        assert_eq!(stats.steps.len(), stats.iterations);
+        assert_eq!(stats.steps[0].strategy_name, "head_tail");
+        assert_eq!(stats.steps[1].strategy_name, "uniform");
+        assert_eq!(stats.steps[0].tokens_before, stats.original_tokens);
+        for (i, step) in stats.steps.iter().enumerate() {
+            assert_eq!(step.iteration, i);
+            assert!(step.tokens_after < step.tokens_before);
+            assert!(step.tokens_saved() > 0);
+        }
+        for pair in stats.steps.windows(2) {
+            assert_eq!(pair[1].tokens_before, pair[0].tokens_after);
+        }
+        assert_eq!(stats.steps.last().unwrap().tokens_after, folder.count_tokens(&folded));
+
+        // The log describes the latest fold only
+        folder.fold(&text[..text.len() / 2]).await.unwrap();
+        assert_eq!(folder.stats().await.steps.len(), folder.stats().await.iterations);
+    }
+
+    #[test]
+    fn test_fold_strategy_defaults_to_head_tail() {
+        assert_eq!(ContextFoldConfig::default().fold_strategy, FoldStrategy::HeadTail);
+        let config: ContextFoldConfig = serde_json::from_str(
+            r#"{"max_tokens": 10, "compression_ratio": 0.5, "aggressive": false, "max_iterations": 2}"#,
+        )
+        .unwrap();
+        assert_eq!(config.fold_strategy, FoldStrategy::HeadTail);
+    }
+
+    #[tokio::test]
+    async fn test_fold_with_explanation() {
+        let config = ContextFoldConfig::new(40)
+            .with_compression_ratio(0.3)
+            .with_fold_strategy(FoldStrategy::HeadOnly);
+        let folder = ContextFolder::new(config);
+        let text = (0..30)
+            .map(|i| format!("line {} of the context", i))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let (folded, explanation) = folder.fold_with_explanation(&text).await.unwrap();
+
+        assert!(!explanation.dropped_lines.is_empty());
+        for line in &explanation.dropped_lines {
+            assert!(!folded.lines().any(|kept| kept == line), "{} was not dropped", line);
+        }
+        assert_eq!(explanation.kept_lines.join("\n"), folded);
+        assert_eq!(
+            explanation.dropped_lines.len() + explanation.kept_lines.len(),
+            text.lines().count()
+        );
+        assert!(explanation.compression_ratio < 1.0);
+
+        let json: Value = serde_json::from_str(&serde_json::to_string(&explanation).unwrap()).unwrap();
+        assert!(json["dropped_lines"].is_array());
+    }
+
+    #[test]
+    fn test_explanation_lists_preserved_blocks() {
+        let before = "intro\n```python\nprint(1)\n```\nnoise\n```bash\necho 2\n```\n";
+        let after = "```python\nprint(1)\n```\n";
+
+        let explanation = FoldingExplanation::between(before, after, &HeuristicTokenCounter);
+
+        assert_eq!(explanation.preserved_blocks.len(), 1);
+        assert!(explanation.preserved_blocks[0].contains("print(1)"));
+        assert!(explanation.dropped_lines.contains(&"echo 2".to_string()));
+        assert!(explanation.kept_lines.contains(&"print(1)".to_string()));
+    }
 }
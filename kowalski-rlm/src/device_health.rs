@@ -3,6 +3,10 @@
 //! Tracks the health status of remote devices in an Exo cluster,
 //! enabling automatic failover and device selection strategies.
 
+use crate::error::{RLMError, RLMResult};
+use async_trait::async_trait;
+use rand::Rng;
+use std::collections::HashMap;
 use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -81,6 +85,7 @@ impl From<SerializableDeviceHealth> for DeviceHealth {
 
 /// Device capabilities for intelligent task routing
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct DeviceCapabilities {
     /// Available programming languages/runtimes
     pub runtimes: Vec<String>, // "python", "rust", "java", "node", etc.
@@ -95,12 +100,71 @@ pub struct DeviceCapabilities {
     pub models: Vec<String>,
 }
 
+/// Check of a single device, run by [`HealthMonitor::start_background_checks`]
+#[async_trait]
+pub trait HealthProbe: Send + Sync + std::fmt::Debug {
+    /// Probe the device, returning its response time in ms, or `None` if it
+    /// did not respond
+    async fn probe(&self, device_id: &str, address: SocketAddr) -> Option<u64>;
+}
+
+/// Probes `http://<address>/health`, falling back to a TCP connect
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HttpHealthProbe;
+
+#[async_trait]
+impl HealthProbe for HttpHealthProbe {
+    async fn probe(&self, _device_id: &str, address: SocketAddr) -> Option<u64> {
+        let start = std::time::Instant::now();
+
+        // Try HTTP health endpoint first
+        let http_result = tokio::task::spawn_blocking(move || {
+            let url = format!("http://{}/health", address);
+            let client = reqwest::blocking::Client::builder()
+                .timeout(std::time::Duration::from_secs(5))
+                .build()
+                .ok()?;
+            let response = client.get(&url).send().ok()?;
+            response
+                .status()
+                .is_success()
+                .then(|| start.elapsed().as_millis() as u64)
+        })
+        .await;
+
+        match http_result {
+            Ok(Some(time)) => Some(time),
+            // Fallback to TCP ping if HTTP fails
+            _ => tokio::net::TcpStream::connect(address)
+                .await
+                .ok()
+                .map(|_| start.elapsed().as_millis() as u64),
+        }
+    }
+}
+
+/// Random offset in `[0, interval)` at which a device is probed
+fn random_phase(interval: Duration) -> Duration {
+    let nanos = interval.as_nanos().min(u64::MAX as u128) as u64;
+    if nanos == 0 {
+        return Duration::ZERO;
+    }
+    Duration::from_nanos(rand::rng().random_range(0..nanos))
+}
+
 /// Monitors health of devices in a cluster
+#[derive(Debug)]
 pub struct HealthMonitor {
     devices: Arc<RwLock<Vec<DeviceHealth>>>,
     check_interval: Duration,
     /// Number of consecutive failures before marking device unhealthy
     failure_threshold: u32,
+    /// Devices reported unhealthy until the given instant (chaos testing)
+    simulated_failures: Arc<RwLock<HashMap<String, Instant>>>,
+    /// Extra latency in ms added to devices until the given instant
+    simulated_latency: Arc<RwLock<HashMap<String, (u64, Instant)>>>,
+    /// Check run against each device by the background checks
+    probe: Arc<dyn HealthProbe>,
 }
 
 impl HealthMonitor {
@@ -114,9 +178,18 @@ impl HealthMonitor {
             devices: Arc::new(RwLock::new(Vec::new())),
             check_interval,
             failure_threshold,
+            simulated_failures: Arc::new(RwLock::new(HashMap::new())),
+            simulated_latency: Arc::new(RwLock::new(HashMap::new())),
+            probe: Arc::new(HttpHealthProbe),
         }
     }
 
+    /// Run `probe` against each device instead of [`HttpHealthProbe`]
+    pub fn with_probe(mut self, probe: Arc<dyn HealthProbe>) -> Self {
+        self.probe = probe;
+        self
+    }
+
     /// Register a new device for monitoring
     pub async fn register_device(&self, device_id: String, address: SocketAddr) {
         let mut devices = self.devices.write().await;
@@ -158,10 +231,24 @@ impl HealthMonitor {
         }
     }
 
+    /// Replace the capabilities recorded for a device
+    ///
+    /// Returns `false` if the device is not registered.
+    pub async fn update_capabilities(&self, device_id: &str, capabilities: DeviceCapabilities) -> bool {
+        let mut devices = self.devices.write().await;
+        match devices.iter_mut().find(|d| d.device_id == device_id) {
+            Some(device) => {
+                device.capabilities = capabilities;
+                true
+            }
+            None => false,
+        }
+    }
+
     /// Check if a device is healthy
     pub async fn is_device_healthy(&self, device_id: &str) -> bool {
-        let devices = self.devices.read().await;
-        devices
+        self.effective_devices()
+            .await
             .iter()
             .find(|d| d.device_id == device_id)
             .map(|d| d.is_healthy)
@@ -170,32 +257,110 @@ impl HealthMonitor {
 
     /// Get all healthy devices
     pub async fn get_healthy_devices(&self) -> Vec<DeviceHealth> {
-        let devices = self.devices.read().await;
-        devices
-            .iter()
+        self.effective_devices()
+            .await
+            .into_iter()
             .filter(|d| d.is_healthy)
-            .cloned()
             .collect()
     }
 
     /// Get devices that support a specific runtime
     pub async fn get_devices_with_runtime(&self, runtime: &str) -> Vec<DeviceHealth> {
-        let devices = self.devices.read().await;
-        devices
-            .iter()
+        self.effective_devices()
+            .await
+            .into_iter()
             .filter(|d| d.is_healthy && d.capabilities.runtimes.contains(&runtime.to_string()))
-            .cloned()
             .collect()
     }
 
     /// Get the device with lowest response time for a runtime
     pub async fn get_fastest_device_for_runtime(&self, runtime: &str) -> Option<DeviceHealth> {
+        self.effective_devices()
+            .await
+            .into_iter()
+            .filter(|d| d.is_healthy && d.capabilities.runtimes.contains(&runtime.to_string()))
+            .min_by_key(|d| d.response_time_ms)
+    }
+
+    /// Report a device as unhealthy for `duration` without touching it
+    ///
+    /// Intended for chaos tests. The device's recorded health is left alone,
+    /// so it reads as healthy again once `duration` has passed; a later call
+    /// replaces the earlier one.
+    ///
+    /// # Errors
+    /// Returns [`RLMError::DeviceNotFound`] if the device is not registered.
+    pub async fn simulate_failure(&self, device_id: &str, duration: Duration) -> RLMResult<()> {
+        self.ensure_registered(device_id).await?;
+        let now = Instant::now();
+        let mut failures = self.simulated_failures.write().await;
+        failures.retain(|_, until| *until > now);
+        failures.insert(device_id.to_string(), now + duration);
+        log::info!("Simulating failure of device {} for {:?}", device_id, duration);
+        Ok(())
+    }
+
+    /// Add `extra_latency_ms` to a device's response time for `duration`
+    ///
+    /// # Errors
+    /// Returns [`RLMError::DeviceNotFound`] if the device is not registered.
+    pub async fn simulate_slow_response(
+        &self,
+        device_id: &str,
+        extra_latency_ms: u64,
+        duration: Duration,
+    ) -> RLMResult<()> {
+        self.ensure_registered(device_id).await?;
+        let now = Instant::now();
+        let mut latency = self.simulated_latency.write().await;
+        latency.retain(|_, (_, until)| *until > now);
+        latency.insert(device_id.to_string(), (extra_latency_ms, now + duration));
+        log::info!(
+            "Simulating {}ms extra latency on device {} for {:?}",
+            extra_latency_ms,
+            device_id,
+            duration
+        );
+        Ok(())
+    }
+
+    /// End every simulated failure and slow response immediately
+    pub async fn clear_simulated_failures(&self) {
+        self.simulated_failures.write().await.clear();
+        self.simulated_latency.write().await.clear();
+    }
+
+    async fn ensure_registered(&self, device_id: &str) -> RLMResult<()> {
+        let devices = self.devices.read().await;
+        if devices.iter().any(|d| d.device_id == device_id) {
+            Ok(())
+        } else {
+            Err(RLMError::device_not_found(device_id))
+        }
+    }
+
+    /// Snapshot of all devices with active simulations applied
+    async fn effective_devices(&self) -> Vec<DeviceHealth> {
+        let now = Instant::now();
+        let failures = self.simulated_failures.read().await;
+        let latency = self.simulated_latency.read().await;
         let devices = self.devices.read().await;
+
         devices
             .iter()
-            .filter(|d| d.is_healthy && d.capabilities.runtimes.contains(&runtime.to_string()))
-            .min_by_key(|d| d.response_time_ms)
             .cloned()
+            .map(|mut device| {
+                if failures.get(&device.device_id).is_some_and(|until| *until > now) {
+                    device.is_healthy = false;
+                }
+                if let Some((extra_ms, until)) = latency.get(&device.device_id) {
+                    if *until > now {
+                        device.response_time_ms = device.response_time_ms.saturating_add(*extra_ms);
+                    }
+                }
+                device
+            })
+            .collect()
     }
 
     /// Mark a device as having a failure
@@ -232,12 +397,12 @@ impl HealthMonitor {
 
     /// Get all registered devices
     pub async fn list_all_devices(&self) -> Vec<DeviceHealth> {
-        self.devices.read().await.clone()
+        self.effective_devices().await
     }
 
     /// Get device status summary
     pub async fn get_status(&self) -> DeviceClusterStatus {
-        let devices = self.devices.read().await;
+        let devices = self.effective_devices().await;
         let total = devices.len();
         let healthy = devices.iter().filter(|d| d.is_healthy).count();
         let unhealthy = total - healthy;
@@ -256,12 +421,19 @@ impl HealthMonitor {
     }
 
     /// Start background health checks
+    ///
+    /// Each device is probed once per `check_interval`, at a random phase
+    /// offset within the interval picked when the checks first see it, so
+    /// probes of many devices are spread across the interval instead of
+    /// firing together. A device keeps its phase on later rounds; rounds
+    /// missed while the runtime was busy are skipped, not replayed.
+    /// Devices registered later are picked up within one interval.
     pub async fn start_background_checks(self: Arc<Self>) {
         let monitor = Arc::clone(&self);
         tokio::spawn(async move {
+            let mut next_due: HashMap<String, tokio::time::Instant> = HashMap::new();
             loop {
-                tokio::time::sleep(monitor.check_interval).await;
-
+                let now = tokio::time::Instant::now();
                 let devices: Vec<_> = {
                     let devices = monitor.devices.read().await;
                     devices
@@ -270,65 +442,42 @@ impl HealthMonitor {
                         .collect()
                 };
 
-                // Check each device
+                next_due.retain(|id, _| devices.iter().any(|(device_id, _)| device_id == id));
                 for (device_id, address) in devices {
+                    let due = next_due
+                        .entry(device_id.clone())
+                        .or_insert_with(|| now + random_phase(monitor.check_interval));
+                    if *due > now {
+                        continue;
+                    }
+                    while *due <= now {
+                        *due += monitor.check_interval.max(Duration::from_millis(1));
+                    }
+
                     let monitor = Arc::clone(&monitor);
-                    let device_id_clone = device_id.clone();
-                    
                     tokio::spawn(async move {
-                        // Perform actual health check
-                        let start = std::time::Instant::now();
-                        
-                        // Try HTTP health endpoint first
-                        let http_result = tokio::task::spawn_blocking(move || {
-                            let url = format!("http://{}/health", address);
-                            let client = reqwest::blocking::Client::builder()
-                                .timeout(std::time::Duration::from_secs(5))
-                                .build();
-                            
-                            match client {
-                                Ok(client) => {
-                                    match client.get(&url).send() {
-                                        Ok(response) => {
-                                            if response.status().is_success() {
-                                                let elapsed = start.elapsed().as_millis() as u64;
-                                                Some(elapsed)
-                                            } else {
-                                                None
-                                            }
-                                        }
-                                        Err(_) => None,
-                                    }
-                                }
-                                Err(_) => None,
-                            }
-                        }).await;
-                        
-                        let response_time = match http_result {
-                            Ok(Some(time)) => Some(time),
-                            _ => {
-                                // Fallback to TCP ping if HTTP fails
-                                let tcp_result = tokio::net::TcpStream::connect(address).await;
-                                match tcp_result {
-                                    Ok(_) => Some(start.elapsed().as_millis() as u64),
-                                    Err(_) => None,
-                                }
-                            }
-                        };
-                        
-                        // Update monitor based on check result
-                        match response_time {
+                        match monitor.probe.probe(&device_id, address).await {
                             Some(time) => {
-                                monitor.mark_success(&device_id_clone, time).await;
-                                log::debug!("Health check passed for device {} ({}ms)", device_id_clone, time);
+                                monitor.mark_success(&device_id, time).await;
+                                log::debug!("Health check passed for device {} ({}ms)", device_id, time);
                             }
                             None => {
-                                monitor.mark_failure(&device_id_clone).await;
-                                log::warn!("Health check failed for device {}", device_id_clone);
+                                monitor.mark_failure(&device_id).await;
+                                log::warn!("Health check failed for device {}", device_id);
                             }
                         }
                     });
                 }
+
+                // Wake for the next due probe, or after an interval to pick up
+                // newly registered devices
+                let wake = next_due
+                    .values()
+                    .copied()
+                    .min()
+                    .unwrap_or(now + monitor.check_interval)
+                    .min(now + monitor.check_interval);
+                tokio::time::sleep_until(wake).await;
             }
         });
     }
@@ -342,6 +491,7 @@ impl HealthMonitor {
     /// Clear all devices
     pub async fn clear(&self) {
         self.devices.write().await.clear();
+        self.clear_simulated_failures().await;
     }
 }
 
@@ -488,4 +638,141 @@ mod tests {
 
         assert!(!monitor.is_device_healthy("device-1").await);
     }
+
+    #[tokio::test]
+    async fn test_simulate_failure_recovers_automatically() {
+        let monitor = HealthMonitor::new(Duration::from_secs(1), 3);
+        monitor
+            .register_device("device-1".to_string(), "192.168.1.10:8080".parse().unwrap())
+            .await;
+        monitor
+            .register_device("device-2".to_string(), "192.168.1.11:8080".parse().unwrap())
+            .await;
+
+        monitor
+            .simulate_failure("device-1", Duration::from_millis(50))
+            .await
+            .unwrap();
+        assert!(!monitor.is_device_healthy("device-1").await);
+        let healthy = monitor.get_healthy_devices().await;
+        assert_eq!(healthy.len(), 1);
+        assert_eq!(healthy[0].device_id, "device-2");
+        assert_eq!(monitor.get_status().await.unhealthy_devices, 1);
+
+        tokio::time::sleep(Duration::from_millis(80)).await;
+        assert!(monitor.is_device_healthy("device-1").await);
+        assert_eq!(monitor.get_healthy_devices().await.len(), 2);
+
+        let err = monitor
+            .simulate_failure("missing", Duration::from_secs(1))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, RLMError::DeviceNotFound(_)));
+    }
+
+    #[tokio::test]
+    async fn test_simulate_slow_response() {
+        let monitor = HealthMonitor::new(Duration::from_secs(1), 3);
+        let caps = DeviceCapabilities {
+            runtimes: vec!["python".to_string()],
+            ..Default::default()
+        };
+        for (id, addr) in [("device-1", "192.168.1.10:8080"), ("device-2", "192.168.1.11:8080")] {
+            monitor
+                .register_device_with_capabilities(id.to_string(), addr.parse().unwrap(), caps.clone())
+                .await;
+        }
+        monitor.mark_success("device-1", 10).await;
+        monitor.mark_success("device-2", 50).await;
+
+        monitor
+            .simulate_slow_response("device-1", 500, Duration::from_millis(50))
+            .await
+            .unwrap();
+        let fastest = monitor.get_fastest_device_for_runtime("python").await.unwrap();
+        assert_eq!(fastest.device_id, "device-2");
+        // Slow, but still healthy
+        assert!(monitor.is_device_healthy("device-1").await);
+
+        tokio::time::sleep(Duration::from_millis(80)).await;
+        let fastest = monitor.get_fastest_device_for_runtime("python").await.unwrap();
+        assert_eq!(fastest.device_id, "device-1");
+        assert_eq!(fastest.response_time_ms, 10);
+    }
+
+    #[tokio::test]
+    async fn test_clear_simulated_failures() {
+        let monitor = HealthMonitor::new(Duration::from_secs(1), 3);
+        monitor
+            .register_device("device-1".to_string(), "192.168.1.10:8080".parse().unwrap())
+            .await;
+
+        monitor
+            .simulate_failure("device-1", Duration::from_secs(60))
+            .await
+            .unwrap();
+        monitor
+            .simulate_slow_response("device-1", 200, Duration::from_secs(60))
+            .await
+            .unwrap();
+        assert!(!monitor.is_device_healthy("device-1").await);
+
+        monitor.clear_simulated_failures().await;
+        assert!(monitor.is_device_healthy("device-1").await);
+        assert_eq!(monitor.list_all_devices().await[0].response_time_ms, 0);
+    }
+
+    /// Records when each device was probed, on the (paused) tokio clock
+    #[derive(Debug, Default)]
+    struct RecordingProbe {
+        probes: std::sync::Mutex<Vec<(String, tokio::time::Instant)>>,
+    }
+
+    #[async_trait]
+    impl HealthProbe for RecordingProbe {
+        async fn probe(&self, device_id: &str, _address: SocketAddr) -> Option<u64> {
+            self.probes
+                .lock()
+                .unwrap()
+                .push((device_id.to_string(), tokio::time::Instant::now()));
+            Some(1)
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_background_checks_are_staggered_across_interval() {
+        let interval = Duration::from_secs(10);
+        let probe = Arc::new(RecordingProbe::default());
+        let monitor = Arc::new(HealthMonitor::new(interval, 3).with_probe(probe.clone()));
+        for i in 0..16 {
+            monitor
+                .register_device(format!("device-{}", i), format!("10.0.0.{}:8080", i).parse().unwrap())
+                .await;
+        }
+
+        let start = tokio::time::Instant::now();
+        Arc::clone(&monitor).start_background_checks().await;
+        tokio::time::sleep(interval * 3).await;
+
+        let probes = probe.probes.lock().unwrap().clone();
+        let mut by_device: HashMap<String, Vec<tokio::time::Instant>> = HashMap::new();
+        for (device_id, at) in probes {
+            by_device.entry(device_id).or_default().push(at);
+        }
+        assert_eq!(by_device.len(), 16);
+
+        let first: Vec<Duration> = by_device.values().map(|times| times[0] - start).collect();
+        assert!(first.iter().all(|offset| *offset < interval));
+        let earliest = first.iter().min().unwrap();
+        let latest = first.iter().max().unwrap();
+        assert!(*latest - *earliest > interval / 4, "probes bunched together: {:?}", first);
+
+        // Every device keeps its phase on later rounds
+        for times in by_device.values() {
+            assert!(times.len() >= 3);
+            for pair in times.windows(2) {
+                assert_eq!(pair[1] - pair[0], interval);
+            }
+        }
+    }
 }
@@ -76,6 +76,47 @@ pub enum RLMError {
     #[error("REPL execution failed: {0}")]
     REPLError(String),
 
+    /// Interpreter or compiler for a language is not installed
+    #[error(
+        "Execution error: Failed to spawn {program}: command not found (install {} and make sure `{program}` is on PATH)",
+        language_label(.language)
+    )]
+    InterpreterNotFound {
+        /// Language that was being executed
+        language: String,
+        /// Command that could not be found
+        program: String,
+    },
+
+    /// Code failed to compile
+    #[error("REPL execution failed: {} compilation failed:\n{stderr}", language_label(.language))]
+    CompilationFailed {
+        /// Language that was being compiled
+        language: String,
+        /// Compiler diagnostics
+        stderr: String,
+    },
+
+    /// Code ran but exited unsuccessfully
+    #[error("REPL execution failed: {} execution failed:\n{stderr}", language_label(.language))]
+    RuntimeFailed {
+        /// Language that was being executed
+        language: String,
+        /// Exit code of the process, if it exited normally
+        exit_code: Option<i32>,
+        /// Standard error of the process
+        stderr: String,
+    },
+
+    /// Code was not run because it matched a denied pattern
+    #[error("Code safety policy violation: {} code matches denied pattern `{pattern}`", language_label(.language))]
+    PolicyViolation {
+        /// Language of the rejected code
+        language: String,
+        /// The denied pattern it matched
+        pattern: String,
+    },
+
     /// REPL timeout error
     #[error("REPL timeout after {0}ms")]
     REPLTimeout(u64),
@@ -99,9 +140,64 @@ pub enum RLMError {
     /// Cluster discovery timeout
     #[error("Cluster discovery timeout")]
     DiscoveryTimeout,
+
+    /// Lower-level error wrapped with RLM context
+    #[error("{message}: {source}")]
+    Wrapped {
+        /// What the RLM was doing when the error occurred
+        message: String,
+        /// The original error
+        source: Box<dyn std::error::Error + Send + Sync + 'static>,
+    },
+}
+
+impl From<kowalski_federation::FederationError> for RLMError {
+    fn from(err: kowalski_federation::FederationError) -> Self {
+        RLMError::wrap(err, "Federation error")
+    }
+}
+
+/// Human-readable name of a canonical language, as used in error messages
+fn language_label(language: &str) -> &str {
+    match language {
+        "python" => "Python",
+        "rust" => "Rust",
+        "java" => "Java",
+        "kotlin" => "Kotlin",
+        "csharp" => "C#",
+        "bash" => "Bash",
+        "javascript" => "JavaScript",
+        "lua" => "Lua",
+        "r" => "R",
+        "php" => "PHP",
+        "haskell" => "Haskell",
+        "c" => "C",
+        "cpp" => "C++",
+        other => other,
+    }
 }
 
 impl RLMError {
+    /// Wrap a lower-level error, keeping it available through `source()`
+    pub fn wrap(
+        source: impl std::error::Error + Send + Sync + 'static,
+        msg: impl Into<String>,
+    ) -> Self {
+        RLMError::Wrapped {
+            message: msg.into(),
+            source: Box::new(source),
+        }
+    }
+
+    /// Follow the `source()` chain to the deepest error
+    pub fn root_cause(&self) -> &(dyn std::error::Error + 'static) {
+        let mut cause: &(dyn std::error::Error + 'static) = self;
+        while let Some(next) = cause.source() {
+            cause = next;
+        }
+        cause
+    }
+
     /// Create a new configuration error
     pub fn config(msg: impl Into<String>) -> Self {
         RLMError::ConfigError(msg.into())
@@ -182,6 +278,43 @@ impl RLMError {
         RLMError::REPLError(msg.into())
     }
 
+    /// Create a new interpreter not found error
+    pub fn interpreter_not_found(language: impl Into<String>, program: impl Into<String>) -> Self {
+        RLMError::InterpreterNotFound {
+            language: language.into(),
+            program: program.into(),
+        }
+    }
+
+    /// Create a new compilation failed error
+    pub fn compilation_failed(language: impl Into<String>, stderr: impl Into<String>) -> Self {
+        RLMError::CompilationFailed {
+            language: language.into(),
+            stderr: stderr.into(),
+        }
+    }
+
+    /// Create a new runtime failed error
+    pub fn runtime_failed(
+        language: impl Into<String>,
+        exit_code: Option<i32>,
+        stderr: impl Into<String>,
+    ) -> Self {
+        RLMError::RuntimeFailed {
+            language: language.into(),
+            exit_code,
+            stderr: stderr.into(),
+        }
+    }
+
+    /// Create a new policy violation error
+    pub fn policy_violation(language: impl Into<String>, pattern: impl Into<String>) -> Self {
+        RLMError::PolicyViolation {
+            language: language.into(),
+            pattern: pattern.into(),
+        }
+    }
+
     /// Create a new device not found error
     pub fn device_not_found(device_id: impl Into<String>) -> Self {
         RLMError::DeviceNotFound(device_id.into())
@@ -202,3 +335,51 @@ impl RLMError {
         RLMError::NetworkError(msg.into())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error;
+    use std::io;
+
+    #[test]
+    fn test_wrap_keeps_source() {
+        let err = RLMError::wrap(io::Error::new(io::ErrorKind::NotFound, "no such file"), "Loading context");
+
+        assert_eq!(err.to_string(), "Loading context: no such file");
+        let source = err.source().expect("wrapped error has a source");
+        let io_err = source.downcast_ref::<io::Error>().expect("source is the io error");
+        assert_eq!(io_err.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_root_cause_follows_chain() {
+        let inner = RLMError::wrap(io::Error::new(io::ErrorKind::TimedOut, "read timed out"), "Reading REPL output");
+        let outer = RLMError::wrap(inner, "Iteration 3 failed");
+
+        assert_eq!(
+            outer.to_string(),
+            "Iteration 3 failed: Reading REPL output: read timed out"
+        );
+        let root = outer.root_cause();
+        assert_eq!(root.to_string(), "read timed out");
+        assert!(root.downcast_ref::<io::Error>().is_some());
+
+        let plain = RLMError::execution("no source");
+        assert_eq!(plain.root_cause().to_string(), plain.to_string());
+    }
+
+    #[test]
+    fn test_federation_error_conversion() {
+        let err: RLMError = kowalski_federation::FederationError::NoSuitableAgents.into();
+
+        assert_eq!(
+            err.to_string(),
+            "Federation error: No suitable agents available for task delegation"
+        );
+        assert!(matches!(
+            err.source().and_then(|s| s.downcast_ref::<kowalski_federation::FederationError>()),
+            Some(kowalski_federation::FederationError::NoSuitableAgents)
+        ));
+    }
+}
@@ -0,0 +1,135 @@
+//! Execution tracing for RLM runs
+//!
+//! Records the sequence of events that happened during an RLM workflow
+//! (iterations, code execution, context folding, errors) so a run can be
+//! inspected after it completes.
+
+use crate::error::{RLMError, RLMResult};
+use serde::Serialize;
+
+/// A single event recorded during an RLM run
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum TraceEvent {
+    /// An iteration started
+    IterationStarted {
+        /// Iteration number (1-based)
+        n: usize,
+    },
+    /// An executable code block was found in the answer
+    CodeBlockFound {
+        /// Normalized language of the block
+        language: String,
+        /// Number of lines of code in the block
+        lines: usize,
+    },
+    /// A code block was executed
+    CodeExecuted {
+        /// Language the block was executed with
+        language: String,
+        /// Length of the output (or error message) in bytes
+        output_len: usize,
+        /// Whether execution succeeded
+        success: bool,
+        /// Wall-clock execution time in milliseconds
+        duration_ms: u64,
+    },
+    /// The answer was folded to fit the context budget
+    ContextFolded {
+        /// Estimated tokens before folding
+        original_tokens: usize,
+        /// Estimated tokens after folding
+        compressed_tokens: usize,
+    },
+    /// The answer could not be kept within the context limit and was cut,
+    /// ending the run early
+    ContextTruncated {
+        /// Answer length before truncation, in bytes
+        original_length: usize,
+        /// Answer length after truncation, in bytes
+        truncated_length: usize,
+    },
+    /// An iteration completed
+    IterationCompleted {
+        /// Iteration number (1-based)
+        n: usize,
+        /// Length of the answer at the end of the iteration
+        answer_length: usize,
+    },
+    /// The run hit `max_total_duration` and returned its partial answer
+    TimedOut {
+        /// Iterations that finished before the deadline
+        completed_iterations: usize,
+        /// Wall-clock time of the iterations in milliseconds
+        elapsed_ms: u64,
+    },
+    /// An error was recorded
+    Error {
+        /// Error message
+        msg: String,
+    },
+}
+
+/// Ordered trace of everything that happened during an RLM run
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct ExecutionTrace {
+    /// Task identifier of the traced run
+    pub task_id: String,
+    /// Recorded events, in order
+    pub events: Vec<TraceEvent>,
+}
+
+impl ExecutionTrace {
+    /// Create an empty trace for a task
+    pub fn new(task_id: impl Into<String>) -> Self {
+        Self {
+            task_id: task_id.into(),
+            events: Vec::new(),
+        }
+    }
+
+    /// Append an event to the trace
+    pub fn record(&mut self, event: TraceEvent) {
+        self.events.push(event);
+    }
+
+    /// Recorded events, in order
+    pub fn events(&self) -> &[TraceEvent] {
+        &self.events
+    }
+
+    /// Number of recorded events
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Returns true if no events were recorded
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Serialize the trace as pretty-printed JSON
+    pub fn to_json(&self) -> RLMResult<String> {
+        serde_json::to_string_pretty(self).map_err(|e| RLMError::wrap(e, "Failed to serialize execution trace"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trace_to_json() {
+        let mut trace = ExecutionTrace::new("task-1");
+        trace.record(TraceEvent::IterationStarted { n: 1 });
+        trace.record(TraceEvent::Error { msg: "boom".to_string() });
+
+        assert_eq!(trace.len(), 2);
+        let json: serde_json::Value = serde_json::from_str(&trace.to_json().unwrap()).unwrap();
+        assert_eq!(json["task_id"], "task-1");
+        assert_eq!(json["events"][0]["event"], "iteration_started");
+        assert_eq!(json["events"][0]["n"], 1);
+        assert_eq!(json["events"][1]["event"], "error");
+        assert_eq!(json["events"][1]["msg"], "boom");
+    }
+}
@@ -2,15 +2,58 @@
 //!
 //! Provides the main execution interface combining all RLM components.
 
+use crate::checkpoint::CheckpointStore;
 use crate::config::RLMConfig;
 use crate::context::RLMContext;
-use crate::context_fold::{ContextFoldConfig, ContextFolder};
+use crate::context_fold::{ContextFoldConfig, ContextFolder, Foldable, FoldingExplanation};
 use crate::code_block_parser::CodeBlockParser;
 use crate::error::{RLMError, RLMResult};
+use crate::execution_trace::{ExecutionTrace, TraceEvent};
 use crate::exo_cluster_manager::ExoClusterManager;
+use crate::llm_backend::LLMBackend;
+use crate::memory_monitor::{MemoryMonitor, ProcessMemoryMonitor};
 use crate::remote_repl_executor::RemoteREPLExecutor;
 use crate::repl_executor::{REPLExecutor, REPLExecutorFactory};
-use std::sync::Arc;
+use crate::retry_budget::RetryBudget;
+use futures::channel::mpsc;
+use futures::{future, FutureExt, Stream, StreamExt};
+use kowalski_federation::BatchExecutor;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Instant;
+
+/// Receives answer chunks from a streamed run
+type ChunkSink = mpsc::UnboundedSender<RLMResult<String>>;
+
+/// LLM call cost recorded for iterations that have no LLM backend
+const PLACEHOLDER_LLM_TOKENS: usize = 100;
+
+/// Counts of the runs an executor has performed
+///
+/// Runs rejected before iterating (an empty prompt, say) are not counted.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExecutorMetrics {
+    /// Runs that started iterating, resumed runs included
+    pub runs_started: u64,
+    /// Runs that returned an answer
+    pub runs_completed: u64,
+    /// Runs that ended with an error
+    pub runs_failed: u64,
+    /// Completed runs stopped early by `max_total_duration`
+    pub runs_timed_out: u64,
+    /// Iterations run across all runs
+    pub total_iterations: u64,
+    /// Runs whose over-length prompt was folded before the first iteration
+    #[serde(default)]
+    pub prompts_prefolded: u64,
+    /// Code blocks skipped because the same iteration already ran them
+    #[serde(default)]
+    pub duplicate_blocks_suppressed: u64,
+    /// Folds triggered by resident memory above `memory_watermark_bytes`
+    #[serde(default)]
+    pub memory_pressure_folds: u64,
+}
 
 /// Unified RLM executor combining all components
 ///
@@ -34,6 +77,13 @@ use std::sync::Arc;
 pub struct RLMExecutor {
     config: Arc<RLMConfig>,
     exo_cluster: Option<Arc<ExoClusterManager>>,
+    llm_backend: Option<Arc<dyn LLMBackend>>,
+    checkpoint_store: Option<Arc<dyn CheckpointStore>>,
+    metrics: Mutex<ExecutorMetrics>,
+    /// Outcome of the last warm-up of each language; see [`RLMExecutor::warm_up`]
+    warmed: Arc<RwLock<HashMap<String, bool>>>,
+    /// Consulted before each iteration when `memory_watermark_bytes` is set
+    memory_monitor: Arc<dyn MemoryMonitor>,
 }
 
 impl RLMExecutor {
@@ -45,20 +95,127 @@ impl RLMExecutor {
         Ok(Self {
             config: Arc::new(config),
             exo_cluster: None,
+            llm_backend: None,
+            checkpoint_store: None,
+            metrics: Mutex::new(ExecutorMetrics::default()),
+            warmed: Arc::new(RwLock::new(HashMap::new())),
+            memory_monitor: Arc::new(ProcessMemoryMonitor),
         })
     }
 
+    /// Attach the LLM that answers each iteration
+    ///
+    /// Every iteration sends the current answer to the backend and appends
+    /// its streamed response before running code blocks.
+    pub fn with_llm_backend(mut self, backend: Arc<dyn LLMBackend>) -> Self {
+        self.llm_backend = Some(backend);
+        self
+    }
+
     /// Attach an Exo cluster manager for distributed execution.
+    ///
+    /// Code blocks are then always run on the cluster; see
+    /// [`ExoClusterManager::select_device`] for how a device is chosen.
     pub fn with_exo_cluster(mut self, cluster: Arc<ExoClusterManager>) -> Self {
         self.exo_cluster = Some(cluster);
         self
     }
 
+    /// Save the context to `store` after every iteration
+    ///
+    /// Checkpoints are keyed by task ID. A failed save is logged and
+    /// recorded in the trace, but does not stop the run.
+    pub fn with_checkpoint_store(mut self, store: Arc<dyn CheckpointStore>) -> Self {
+        self.checkpoint_store = Some(store);
+        self
+    }
+
+    /// Read resident memory from `monitor` instead of [`ProcessMemoryMonitor`]
+    pub fn with_memory_monitor(mut self, monitor: Arc<dyn MemoryMonitor>) -> Self {
+        self.memory_monitor = monitor;
+        self
+    }
+
     /// Get the configuration
     pub fn config(&self) -> &RLMConfig {
         &self.config
     }
 
+    /// Snapshot of the runs performed so far
+    pub fn metrics(&self) -> ExecutorMetrics {
+        self.metrics.lock().unwrap().clone()
+    }
+
+    /// Run a "hello world" snippet in every registered language
+    ///
+    /// Interpreters and compilers then start from a warm file cache on
+    /// first real use, and missing toolchains show up before a run needs
+    /// them. Snippets run locally and concurrently.
+    ///
+    /// Languages that warmed up successfully are remembered and skipped by
+    /// later calls, which report them as `Ok(())`; failed languages are
+    /// tried again.
+    ///
+    /// # Returns
+    /// The warm-up outcome of each language in
+    /// [`REPLExecutorFactory::registered_languages`]
+    pub async fn warm_up(&self) -> HashMap<String, RLMResult<()>> {
+        self.warm_up_languages(REPLExecutorFactory::registered_languages())
+            .await
+    }
+
+    /// Like [`warm_up`](Self::warm_up), for the given languages only
+    pub async fn warm_up_languages(&self, languages: &[&str]) -> HashMap<String, RLMResult<()>> {
+        let pending: Vec<&str> = {
+            let warmed = self.warmed.read().unwrap();
+            languages
+                .iter()
+                .copied()
+                .filter(|language| warmed.get(*language) != Some(&true))
+                .collect()
+        };
+        let outcomes = future::join_all(pending.into_iter().map(|language| async move {
+            (language.to_string(), self.warm_up_language(language).await)
+        }))
+        .await;
+
+        let mut results: HashMap<String, RLMResult<()>> = languages
+            .iter()
+            .map(|language| (language.to_string(), Ok(())))
+            .collect();
+        let mut warmed = self.warmed.write().unwrap();
+        for (language, outcome) in outcomes {
+            if let Err(e) = &outcome {
+                log::debug!("Warm-up of {} failed: {}", language, e);
+            }
+            warmed.insert(language.clone(), outcome.is_ok());
+            results.insert(language, outcome);
+        }
+        results
+    }
+
+    /// Languages whose last warm-up succeeded, sorted by name
+    pub fn warmed_languages(&self) -> Vec<String> {
+        let mut languages: Vec<String> = self
+            .warmed
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|(_, ok)| **ok)
+            .map(|(language, _)| language.clone())
+            .collect();
+        languages.sort();
+        languages
+    }
+
+    async fn warm_up_language(&self, language: &str) -> RLMResult<()> {
+        let snippet = REPLExecutorFactory::hello_world(language).ok_or_else(|| {
+            RLMError::ExecutionError(format!("Unsupported language: {}", language))
+        })?;
+        let executor = REPLExecutorFactory::create_with_config(language, &self.config)?;
+        executor.execute(snippet).await.map(|_| ())
+    }
+
     /// Execute an RLM workflow
     ///
     /// # Arguments
@@ -72,8 +229,111 @@ impl RLMExecutor {
     ///
     /// # Errors
     ///
-    /// Returns an error if execution fails
+    /// Returns an error if execution fails, or if `prompt` is longer than
+    /// `max_context_length` and `fold_oversized_prompt` is off
     pub async fn execute(&self, prompt: &str, task_id: &str) -> RLMResult<String> {
+        self.execute_traced(prompt, task_id)
+            .await
+            .map(|(answer, _)| answer)
+    }
+
+    /// Execute an RLM workflow and record a trace of the run
+    ///
+    /// Behaves like [`execute`](Self::execute), but also returns an
+    /// [`ExecutionTrace`] listing every iteration, code block, execution,
+    /// fold and error that happened along the way.
+    ///
+    /// If the answer outgrows `max_context_length` and folding is disabled
+    /// or could not shrink it enough, the answer is cut to the limit, a
+    /// [`TraceEvent::ContextTruncated`] is recorded and the run ends early
+    /// with that partial answer.
+    ///
+    /// The run also ends early, with a warning, once two iterations in a row
+    /// leave the answer unchanged: further iterations would only repeat them.
+    ///
+    /// With `max_total_duration` set, a run that outlasts it is stopped
+    /// wherever it is: the answer built so far is returned, the context is
+    /// marked `timed_out` and a [`TraceEvent::TimedOut`] records how many
+    /// iterations completed.
+    pub async fn execute_traced(
+        &self,
+        prompt: &str,
+        task_id: &str,
+    ) -> RLMResult<(String, ExecutionTrace)> {
+        let (context, trace) = self.run(prompt, task_id, None).await?;
+        Ok((context.answer, trace))
+    }
+
+    /// Execute an RLM workflow and return its final context
+    ///
+    /// Runs like [`execute`](Self::execute). The answer is
+    /// [`RLMContext::answer`], and [`RLMContext::code_block_results`] tells
+    /// which code blocks succeeded and which failed without parsing the
+    /// `[REPL:...]` notes in the answer.
+    pub async fn execute_detailed(&self, prompt: &str, task_id: &str) -> RLMResult<RLMContext> {
+        self.run(prompt, task_id, None).await.map(|(context, _)| context)
+    }
+
+    /// Execute an RLM workflow, yielding the answer as it is generated
+    ///
+    /// Yields the prompt, then every piece of text appended to the answer
+    /// in order: LLM response chunks as the backend produces them, REPL
+    /// output and iteration markers. Without folding or truncation the
+    /// chunks concatenate to the answer [`execute`](Self::execute) returns;
+    /// a fold or truncation rewrites text already yielded and is not
+    /// streamed. An error ends the stream.
+    pub fn execute_stream<'a>(
+        &'a self,
+        prompt: &'a str,
+        task_id: &'a str,
+    ) -> impl Stream<Item = RLMResult<String>> + Send + 'a {
+        let (sink, chunks) = mpsc::unbounded();
+        let run = async move {
+            if let Err(err) = self.run(prompt, task_id, Some(&sink)).await {
+                let _ = sink.unbounded_send(Err(err));
+            }
+        };
+
+        // Drive the run while passing on its chunks; the run itself yields nothing
+        futures::stream::select(chunks, run.into_stream().filter_map(|()| future::ready(None)))
+    }
+
+    /// Continue a run from its last checkpoint
+    ///
+    /// Loads the context saved for `workflow_id` (the task ID of the
+    /// interrupted run) from the checkpoint store and iterates from there
+    /// until this executor's `max_iterations`. The resumed run uses this
+    /// executor's configuration and a fresh retry budget.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no checkpoint store is attached, no checkpoint
+    /// exists for `workflow_id`, or loading it fails.
+    pub async fn resume(&self, workflow_id: &str) -> RLMResult<RLMContext> {
+        let store = self
+            .checkpoint_store
+            .as_ref()
+            .ok_or_else(|| RLMError::config("Resuming requires a checkpoint store"))?;
+        let context = store
+            .load(workflow_id)
+            .await?
+            .ok_or_else(|| RLMError::execution(format!("No checkpoint found for workflow {}", workflow_id)))?
+            .with_config(Arc::clone(&self.config));
+
+        log::info!("Resuming task {} after iteration {}", workflow_id, context.iteration);
+        let trace = ExecutionTrace::new(workflow_id);
+        self.drive(context, trace, workflow_id, None)
+            .await
+            .map(|(context, _)| context)
+    }
+
+    /// Run the iteration loop, sending appended text to `sink` if given
+    async fn run(
+        &self,
+        prompt: &str,
+        task_id: &str,
+        sink: Option<&ChunkSink>,
+    ) -> RLMResult<(RLMContext, ExecutionTrace)> {
         if prompt.is_empty() {
             return Err(RLMError::execution("Prompt cannot be empty"));
         }
@@ -82,41 +342,206 @@ impl RLMExecutor {
             return Err(RLMError::execution("Task ID cannot be empty"));
         }
 
-        if prompt.len() > self.config.max_context_length {
+        let oversized = prompt.len() > self.config.max_context_length;
+        if oversized && !self.config.fold_oversized_prompt {
             return Err(RLMError::execution(
                 "Prompt exceeds maximum context length (using character count as conservative estimate)"
             ));
         }
 
+        let mut trace = ExecutionTrace::new(task_id);
+
         // Create execution context
         let mut context = RLMContext::new(task_id, Arc::clone(&self.config));
 
         // Initialize with the prompt
-        context.append_answer(prompt);
+        if oversized {
+            let original_tokens = self.config.token_counter.count_tokens(prompt);
+            let folded = self.prefold_prompt(prompt).await?;
+            log::info!(
+                "Task {}: folded a {} byte prompt to {} bytes before the first iteration",
+                task_id,
+                prompt.len(),
+                folded.len()
+            );
+            trace.record(TraceEvent::ContextFolded {
+                original_tokens,
+                compressed_tokens: self.config.token_counter.count_tokens(&folded),
+            });
+            context.record_fold();
+            self.metrics.lock().unwrap().prompts_prefolded += 1;
+            append_chunk(&mut context, folded, sink);
+        } else {
+            append_chunk(&mut context, prompt.to_string(), sink);
+        }
+
+        self.drive(context, trace, task_id, sink).await
+    }
+
+    /// Fold `prompt` until it fits in `max_context_length`
+    ///
+    /// The folder counts tokens while the limit is in bytes, so its token
+    /// budget is scaled by the prompt's bytes per token. Whatever folding
+    /// leaves over the limit is cut off.
+    async fn prefold_prompt(&self, prompt: &str) -> RLMResult<String> {
+        let max_len = self.config.max_context_length;
+        let tokens = self.config.token_counter.count_tokens(prompt);
+        let token_budget = (tokens.saturating_mul(max_len) / prompt.len()).max(1);
+        let folder = ContextFolder::new(ContextFoldConfig::new(token_budget))
+            .with_token_counter(Arc::clone(&self.config.token_counter));
+
+        let folded = folder.fold(prompt).await?;
+        Ok(truncate_output(folded, max_len))
+    }
+
+    /// Iterate on `context` within the `max_total_duration` limit
+    async fn drive(
+        &self,
+        mut context: RLMContext,
+        mut trace: ExecutionTrace,
+        task_id: &str,
+        sink: Option<&ChunkSink>,
+    ) -> RLMResult<(RLMContext, ExecutionTrace)> {
+        self.metrics.lock().unwrap().runs_started += 1;
+        let start_iteration = context.iteration;
+
+        // Measured on the clock the deadline runs on
+        let started = tokio::time::Instant::now();
+        let iterations = self.iterate(&mut context, &mut trace, task_id, sink);
+        let finished = match self.config.max_total_duration {
+            Some(limit) => tokio::time::timeout(limit, iterations).await.ok(),
+            None => Some(iterations.await),
+        };
+        let timed_out = finished.is_none();
+        let result = match finished {
+            Some(result) => result,
+            None => {
+                let completed_iterations = trace
+                    .events()
+                    .iter()
+                    .filter(|event| matches!(event, TraceEvent::IterationCompleted { .. }))
+                    .count();
+                log::warn!(
+                    "Task {} hit max_total_duration with {} iterations completed, returning the partial answer",
+                    task_id,
+                    completed_iterations
+                );
+                context.record_timeout();
+                trace.record(TraceEvent::TimedOut {
+                    completed_iterations,
+                    elapsed_ms: started.elapsed().as_millis() as u64,
+                });
+                Ok(())
+            }
+        };
+
+        {
+            let mut metrics = self.metrics.lock().unwrap();
+            metrics.total_iterations += (context.iteration - start_iteration) as u64;
+            match &result {
+                Ok(()) => {
+                    metrics.runs_completed += 1;
+                    if timed_out {
+                        metrics.runs_timed_out += 1;
+                    }
+                }
+                Err(_) => metrics.runs_failed += 1,
+            }
+        }
 
+        result.map(|()| (context, trace))
+    }
+
+    /// Run iterations until the iteration limit, a stall or truncation
+    ///
+    /// Works on `context` and `trace` in place, so whatever an interrupted
+    /// run managed to do is kept.
+    async fn iterate(
+        &self,
+        context: &mut RLMContext,
+        trace: &mut ExecutionTrace,
+        task_id: &str,
+        sink: Option<&ChunkSink>,
+    ) -> RLMResult<()> {
         let code_parser = CodeBlockParser::new();
-        let context_folder = ContextFolder::new(ContextFoldConfig::new(self.config.max_context_length));
+        let context_folder = ContextFolder::new(ContextFoldConfig::new(self.config.max_context_length))
+            .with_token_counter(Arc::clone(&self.config.token_counter));
 
+        let mut stalled_iterations = 0;
         while !context.max_iterations_reached() {
             context.next_iteration();
+            trace.record(TraceEvent::IterationStarted { n: context.iteration });
+            self.fold_under_memory_pressure(context, trace, task_id).await;
+            let start_hash = context.compute_answer_hash();
+
+            let mut llm_tokens = PLACEHOLDER_LLM_TOKENS;
+            let mut llm_responded = false;
+            if let Some(backend) = &self.llm_backend {
+                let mut response = backend.stream_response(context.answer());
+                let mut text = String::new();
+                while let Some(chunk) = response.next().await {
+                    let chunk = chunk?;
+                    text.push_str(&chunk);
+                    append_chunk(context, chunk, sink);
+                }
+                llm_tokens = context.token_counter().count_tokens(&text);
+                llm_responded = !text.is_empty();
+            }
 
             // Check context size and fold if needed
             let mut iteration_notes = Vec::new();
 
             // Execute code blocks if present
             if let Ok(blocks) = code_parser.extract_from(context.answer()) {
-                for block in blocks {
-                    let execution_result = self.execute_code_block(&block.language, &block.code).await;
+                // A block repeated in the same iteration would only repeat its output
+                let mut seen = HashSet::new();
+                for block in blocks.into_iter().filter(|block| block.is_executable()) {
+                    if !seen.insert((block.language.clone(), block.code.clone())) {
+                        log::debug!(
+                            "Task {}: skipping a repeated {} block in iteration {}",
+                            task_id,
+                            block.language,
+                            context.iteration
+                        );
+                        self.metrics.lock().unwrap().duplicate_blocks_suppressed += 1;
+                        continue;
+                    }
+                    trace.record(TraceEvent::CodeBlockFound {
+                        language: block.language.clone(),
+                        lines: block.code.lines().count(),
+                    });
+
+                    let started = Instant::now();
+                    let execution_result = self
+                        .execute_code_block(&block.language, &block.code, context.retry_budget())
+                        .await;
+                    let duration_ms = started.elapsed().as_millis() as u64;
                     match execution_result {
                         Ok(output) => {
+                            trace.record(TraceEvent::CodeExecuted {
+                                language: block.language.clone(),
+                                output_len: output.len(),
+                                success: true,
+                                duration_ms,
+                            });
                             context.record_repl_execution();
+                            context.record_code_block(&block.language, Ok(&output));
                             iteration_notes.push(format!(
                                 "\n[REPL:{} output]\n{}",
                                 block.language, output
                             ));
                         }
                         Err(err) => {
-                            context.record_error(err.to_string());
+                            let msg = err.to_string();
+                            trace.record(TraceEvent::CodeExecuted {
+                                language: block.language.clone(),
+                                output_len: msg.len(),
+                                success: false,
+                                duration_ms,
+                            });
+                            trace.record(TraceEvent::Error { msg: msg.clone() });
+                            context.record_code_block(&block.language, Err(&msg));
+                            context.record_error(msg);
                             iteration_notes.push(format!(
                                 "\n[REPL:{} error]\n{}",
                                 block.language, err
@@ -126,30 +551,137 @@ impl RLMExecutor {
                 }
             }
 
-            if !context.is_within_context_limits() && self.config.enable_context_folding {
-                match context_folder.fold(context.answer()).await {
-                    Ok(folded) => {
-                        context.clear_answer();
-                        context.append_answer(folded);
+            let fold_attempted = !context.is_within_context_limits() && self.config.enable_context_folding;
+            if fold_attempted {
+                let original_tokens = context.token_count();
+                match context.fold(&context_folder).await {
+                    Ok(()) => {
+                        trace.record(TraceEvent::ContextFolded {
+                            original_tokens,
+                            compressed_tokens: context.token_count(),
+                        });
                         iteration_notes.push("\n[Context folded]".to_string());
                     }
                     Err(err) => {
+                        trace.record(TraceEvent::Error { msg: err.to_string() });
                         context.record_error(err.to_string());
                     }
                 }
             }
 
-            if !iteration_notes.is_empty() {
-                for note in iteration_notes {
-                    context.append_answer(note);
-                }
+            let added_notes = !iteration_notes.is_empty();
+            for note in iteration_notes {
+                append_chunk(context, note, sink);
+            }
+
+            // Checked before the iteration marker, which is bookkeeping, not content
+            if context.answer_unchanged_since(&start_hash) {
+                stalled_iterations += 1;
             } else {
-                context.append_answer(&format!("\n[Iteration {} complete]", context.iteration));
+                stalled_iterations = 0;
+            }
+
+            if !added_notes && !llm_responded {
+                let marker = format!("\n[Iteration {} complete]", context.iteration);
+                append_chunk(context, marker, sink);
+            }
+            context.record_llm_call(llm_tokens);
+
+            // Over budget with no fold left to try: stop with what fits
+            let truncated = !context.is_within_context_limits()
+                && (fold_attempted || !self.config.enable_context_folding);
+            if truncated {
+                let original_length = context.truncate_answer(self.config.max_context_length);
+                trace.record(TraceEvent::ContextTruncated {
+                    original_length,
+                    truncated_length: context.answer().len(),
+                });
+            }
+
+            trace.record(TraceEvent::IterationCompleted {
+                n: context.iteration,
+                answer_length: context.answer().len(),
+            });
+            self.save_checkpoint(context, trace, task_id).await;
+            if truncated {
+                break;
+            }
+            if stalled_iterations >= 2 {
+                log::warn!(
+                    "Task {} stalled: iterations {} and {} left the answer unchanged, stopping early",
+                    task_id,
+                    context.iteration - 1,
+                    context.iteration
+                );
+                break;
             }
-            context.record_llm_call(100);
         }
 
-        Ok(context.answer().to_string())
+        Ok(())
+    }
+
+    /// Fold the answer to half its tokens if memory is above the watermark
+    ///
+    /// Does nothing without a watermark, with folding disabled, or when the
+    /// monitor cannot read resident memory.
+    async fn fold_under_memory_pressure(
+        &self,
+        context: &mut RLMContext,
+        trace: &mut ExecutionTrace,
+        task_id: &str,
+    ) {
+        let Some(watermark) = self.config.memory_watermark_bytes else {
+            return;
+        };
+        if !self.config.enable_context_folding {
+            return;
+        }
+        let Some(resident) = self.memory_monitor.resident_bytes() else {
+            return;
+        };
+        let original_tokens = context.token_count();
+        if resident <= watermark || original_tokens < 2 {
+            return;
+        }
+
+        log::warn!(
+            "Task {}: resident memory {} bytes is above the {} byte watermark, folding {} tokens",
+            task_id,
+            resident,
+            watermark,
+            original_tokens
+        );
+        let folder = ContextFolder::new(ContextFoldConfig::new(original_tokens / 2))
+            .with_token_counter(Arc::clone(&self.config.token_counter));
+        match context.fold(&folder).await {
+            Ok(()) => {
+                trace.record(TraceEvent::ContextFolded {
+                    original_tokens,
+                    compressed_tokens: context.token_count(),
+                });
+                self.metrics.lock().unwrap().memory_pressure_folds += 1;
+            }
+            Err(err) => {
+                trace.record(TraceEvent::Error { msg: err.to_string() });
+                context.record_error(err.to_string());
+            }
+        }
+    }
+
+    /// Save `context` to the checkpoint store, if one is attached
+    async fn save_checkpoint(&self, context: &RLMContext, trace: &mut ExecutionTrace, task_id: &str) {
+        let Some(store) = &self.checkpoint_store else {
+            return;
+        };
+        if let Err(err) = store.save(task_id, context).await {
+            log::warn!(
+                "Failed to checkpoint task {} after iteration {}: {}",
+                task_id,
+                context.iteration,
+                err
+            );
+            trace.record(TraceEvent::Error { msg: err.to_string() });
+        }
     }
 
     /// Execute an RLM workflow with custom context
@@ -172,11 +704,24 @@ impl RLMExecutor {
             context.next_iteration();
             context.append_answer(&format!("\n[Iteration {}]", context.iteration));
             context.record_llm_call(100);
+
+            if !context.is_within_context_limits() {
+                context.truncate_answer(context.max_context_length());
+                break;
+            }
         }
 
         Ok(context.answer().to_string())
     }
 
+    /// Explain what folding removed between two versions of a context
+    ///
+    /// Diffs the contexts line by line; see [`FoldingExplanation`]. Tokens
+    /// are counted with the configured token counter.
+    pub fn explain_context_folding(&self, context_before: &str, context_after: &str) -> FoldingExplanation {
+        FoldingExplanation::between(context_before, context_after, self.config.token_counter.as_ref())
+    }
+
     /// Check if the executor is properly configured
     pub fn validate(&self) -> RLMResult<()> {
         self.config.validate()
@@ -188,31 +733,115 @@ impl RLMExecutor {
         RLMContext::new(task_id, Arc::clone(&self.config))
     }
 
-    async fn execute_code_block(&self, language: &str, code: &str) -> RLMResult<String> {
-        if let Some(cluster) = &self.exo_cluster {
-            if let Some(device) = cluster
-                .list_devices()
-                .await?
-                .into_iter()
-                .find(|device| device.capabilities.runtimes.contains(&language.to_string()))
-            {
-                let executor = RemoteREPLExecutor::new(
-                    Arc::clone(cluster),
-                    device.id,
-                    language.to_string(),
-                );
-                return executor.execute(code).await;
-            }
+    /// Create a batch executor for this run's parallel LLM calls
+    ///
+    /// It allows `max_concurrent_agents` calls at once and counts tokens
+    /// with the configured token counter.
+    pub fn batch_executor(&self) -> BatchExecutor {
+        BatchExecutor::with_concurrency(self.config.max_concurrent_agents)
+            .with_token_counter(Arc::clone(&self.config.token_counter))
+    }
+
+    /// Run a code block on the best cluster device, or locally without a cluster
+    ///
+    /// With a cluster attached, a block whose runtime no healthy device
+    /// offers fails with `NoDevicesAvailable` instead of running locally.
+    /// Output is cut to the language's `max_repl_output_for` limit, on whole
+    /// lines if `max_output_lines` is set. A block the configured
+    /// [`CodeSafetyPolicy`](crate::code_safety::CodeSafetyPolicy) denies
+    /// fails with `PolicyViolation` without running anywhere.
+    async fn execute_code_block(
+        &self,
+        language: &str,
+        code: &str,
+        retry_budget: &RetryBudget,
+    ) -> RLMResult<String> {
+        self.config.code_safety.check(language, code)?;
+
+        let output = if let Some(cluster) = &self.exo_cluster {
+            let device = cluster.select_device(language).await?;
+            let executor = RemoteREPLExecutor::new(
+                Arc::clone(cluster),
+                device.id,
+                language.to_string(),
+            )
+            .with_timeout(self.config.repl_timeout_for(language))
+            .with_retry_budget(retry_budget.clone());
+            executor.execute(code).await?
+        } else {
+            let executor = REPLExecutorFactory::create_with_config(language, &self.config)?;
+            executor.execute(code).await?
+        };
+
+        let max_bytes = self.config.max_repl_output_for(language);
+        Ok(match self.config.max_output_lines {
+            Some(max_lines) => truncate_output_lines(output, max_lines, max_bytes),
+            None => truncate_output(output, max_bytes),
+        })
+    }
+}
+
+/// Append `chunk` to the answer, passing it on to a streaming caller
+fn append_chunk(context: &mut RLMContext, chunk: String, sink: Option<&ChunkSink>) {
+    if let Some(sink) = sink {
+        // A dropped receiver only means nobody is watching the stream
+        let _ = sink.unbounded_send(Ok(chunk.clone()));
+    }
+    context.append_answer(chunk);
+}
+
+/// Cut `output` to at most `max_len` bytes, on a character boundary
+fn truncate_output(mut output: String, max_len: usize) -> String {
+    if output.len() > max_len {
+        let mut cut = max_len;
+        while !output.is_char_boundary(cut) {
+            cut -= 1;
         }
+        output.truncate(cut);
+    }
+    output
+}
+
+/// Keep at most `max_lines` whole lines of `output` within `max_bytes`
+///
+/// Dropped lines are reported by a `...[N lines truncated]...` marker that
+/// counts toward `max_bytes`. Falls back to a byte cut if even the marker
+/// does not fit.
+fn truncate_output_lines(output: String, max_lines: usize, max_bytes: usize) -> String {
+    let lines: Vec<&str> = output.split_inclusive('\n').collect();
+    if lines.len() <= max_lines && output.len() <= max_bytes {
+        return output;
+    }
 
-        let executor = REPLExecutorFactory::create(language)?;
-        executor.execute(code).await
+    // Sized for the largest count the marker can report
+    let marker_len = format!("...[{} lines truncated]...", lines.len()).len();
+    if marker_len > max_bytes {
+        return truncate_output(output, max_bytes);
     }
+
+    let budget = max_bytes - marker_len;
+    let mut kept_bytes = 0;
+    let kept = lines
+        .iter()
+        .take(max_lines)
+        .take_while(|line| {
+            kept_bytes += line.len();
+            kept_bytes <= budget
+        })
+        .count();
+
+    let mut truncated: String = lines[..kept].concat();
+    if !truncated.is_empty() && !truncated.ends_with('\n') {
+        truncated.push('\n');
+    }
+    truncated.push_str(&format!("...[{} lines truncated]...", lines.len() - kept));
+    truncated
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::code_safety::CodeSafetyPolicy;
 
     #[tokio::test]
     async fn test_executor_creation() {
@@ -255,6 +884,485 @@ mod tests {
         assert!(output.contains("Iteration"));
     }
 
+    #[tokio::test]
+    async fn test_execute_traced_records_iterations() {
+        let config = RLMConfig::default().with_max_iterations(2);
+        let executor = RLMExecutor::new(config).unwrap();
+        let (answer, trace) = executor.execute_traced("Test prompt", "task-1").await.unwrap();
+
+        assert!(answer.contains("Test prompt"));
+        assert_eq!(trace.task_id, "task-1");
+        assert_eq!(
+            trace.events()[0],
+            TraceEvent::IterationStarted { n: 1 }
+        );
+        assert!(matches!(
+            trace.events().last(),
+            Some(TraceEvent::IterationCompleted { n: 2, .. })
+        ));
+    }
+
+    #[tokio::test]
+    #[ignore]  // Requires bash to be installed
+    async fn test_execute_traced_records_all_event_types() {
+        let config = RLMConfig::default()
+            .with_max_iterations(2)
+            .with_max_context_length(300)
+            .with_max_repl_output(300);
+        let executor = RLMExecutor::new(config).unwrap();
+        let prompt = "Run these:\n```bash\nyes . | head -n 400\n```\n```bash\nyes . | head -n 400\n```\n```bash\necho boom >&2\nexit 3\n```\n";
+
+        // Each block's output is cut to max_repl_output, but together the
+        // outputs of the first iteration push the answer over budget, so the
+        // second iteration folds it.
+        let (_, trace) = executor.execute_traced(prompt, "traced").await.unwrap();
+        let events = trace.events();
+
+        assert!(events.contains(&TraceEvent::IterationStarted { n: 1 }));
+        assert!(events.contains(&TraceEvent::CodeBlockFound {
+            language: "bash".to_string(),
+            lines: 2,
+        }));
+        assert!(events.iter().any(|e| matches!(e, TraceEvent::CodeExecuted { success: true, .. })));
+        assert!(events.iter().any(|e| matches!(e, TraceEvent::CodeExecuted { success: false, .. })));
+        assert!(events.iter().any(|e| matches!(e, TraceEvent::Error { msg } if msg.contains("boom"))));
+        assert!(events.iter().any(|e| matches!(
+            e,
+            TraceEvent::ContextFolded { original_tokens, compressed_tokens }
+                if compressed_tokens < original_tokens
+        )));
+        assert!(events.iter().any(|e| matches!(e, TraceEvent::IterationCompleted { n: 2, .. })));
+
+        let json = trace.to_json().unwrap();
+        assert!(json.contains("\"context_folded\""));
+    }
+
+    #[tokio::test]
+    async fn test_stalled_iterations_end_run_early() {
+        let config = RLMConfig::default().with_max_iterations(10);
+        let executor = RLMExecutor::new(config).unwrap();
+
+        // Without code blocks no iteration adds content to the answer
+        let (answer, trace) = executor.execute_traced("Nothing to run", "stalled").await.unwrap();
+
+        assert!(matches!(
+            trace.events().last(),
+            Some(TraceEvent::IterationCompleted { n: 2, .. })
+        ));
+        assert!(!trace.events().contains(&TraceEvent::IterationStarted { n: 3 }));
+        assert!(answer.starts_with("Nothing to run"));
+    }
+
+    #[tokio::test]
+    #[ignore]  // Requires Python to be installed
+    async fn test_failing_block_is_recorded_as_failed() {
+        let config = RLMConfig::default().with_max_iterations(1);
+        let executor = RLMExecutor::new(config).unwrap();
+        let prompt = "Run:\n```python\nprint('ok')\n```\n```python\nraise ValueError('boom')\n```\n";
+
+        let context = executor.execute_detailed(prompt, "blocks").await.unwrap();
+        let results = context.code_block_results();
+
+        assert_eq!(results.len(), 2);
+        assert!(results[0].success);
+        assert_eq!(results[0].output, "ok\n");
+        assert!(!results[1].success);
+        assert_eq!(results[1].language, "python");
+        assert!(results[1].output.is_empty());
+        assert!(results[1].error.as_deref().unwrap().contains("boom"));
+        assert!(context.any_code_block_failed());
+        assert!(context.answer().contains("[REPL:python error]"));
+    }
+
+    /// Backend that waits before answering each prompt with " tick"
+    struct SlowBackend(std::time::Duration);
+
+    impl LLMBackend for SlowBackend {
+        fn stream_response(&self, _prompt: &str) -> crate::llm_backend::LLMResponseStream {
+            let delay = self.0;
+            futures::stream::once(async move {
+                tokio::time::sleep(delay).await;
+                Ok(" tick".to_string())
+            })
+            .boxed()
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_max_total_duration_stops_slow_run() {
+        use std::time::Duration;
+
+        let config = RLMConfig::default()
+            .with_max_iterations(50)
+            .with_max_total_duration(Duration::from_millis(500));
+        let executor = RLMExecutor::new(config)
+            .unwrap()
+            .with_llm_backend(Arc::new(SlowBackend(Duration::from_millis(200))));
+
+        let started = tokio::time::Instant::now();
+        let (answer, trace) = executor.execute_traced("Slow task", "slow").await.unwrap();
+
+        assert!(started.elapsed() < Duration::from_millis(600));
+        assert_eq!(
+            trace.events().last(),
+            Some(&TraceEvent::TimedOut { completed_iterations: 2, elapsed_ms: 500 })
+        );
+        assert_eq!(answer, "Slow task tick tick");
+
+        let context = executor.execute_detailed("Slow task", "slow").await.unwrap();
+        assert!(context.metadata.timed_out);
+        assert!(context.stats().timed_out);
+    }
+
+    /// Backend answering " step" until its calls run out, then failing
+    struct FlakyBackend(std::sync::atomic::AtomicUsize);
+
+    impl LLMBackend for FlakyBackend {
+        fn stream_response(&self, _prompt: &str) -> crate::llm_backend::LLMResponseStream {
+            use std::sync::atomic::Ordering;
+            let ok = self
+                .0
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |left| left.checked_sub(1))
+                .is_ok();
+            let chunk = if ok {
+                Ok(" step".to_string())
+            } else {
+                Err(RLMError::network("backend went away"))
+            };
+            futures::stream::once(future::ready(chunk)).boxed()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resume_from_file_checkpoint() {
+        use crate::checkpoint::FileCheckpointStore;
+        use std::sync::atomic::AtomicUsize;
+
+        let dir = tempfile::tempdir().unwrap();
+        let store: Arc<dyn CheckpointStore> = Arc::new(FileCheckpointStore::new(dir.path()));
+        let config = RLMConfig::default().with_max_iterations(4);
+
+        // The backend fails during the third iteration, ending the run
+        let crashing = RLMExecutor::new(config.clone())
+            .unwrap()
+            .with_llm_backend(Arc::new(FlakyBackend(AtomicUsize::new(2))))
+            .with_checkpoint_store(Arc::clone(&store));
+        assert!(crashing.execute("Plan", "wf-1").await.is_err());
+        let metrics = crashing.metrics();
+        assert_eq!((metrics.runs_started, metrics.runs_failed), (1, 1));
+        assert_eq!(metrics.total_iterations, 3);
+
+        let saved = store.load("wf-1").await.unwrap().unwrap();
+        assert_eq!(saved.iteration(), 2);
+        assert_eq!(saved.answer(), "Plan step step");
+
+        let resumed = RLMExecutor::new(config)
+            .unwrap()
+            .with_llm_backend(Arc::new(FlakyBackend(AtomicUsize::new(usize::MAX))))
+            .with_checkpoint_store(Arc::clone(&store))
+            .resume("wf-1")
+            .await
+            .unwrap();
+
+        assert_eq!(resumed.iteration(), 4);
+        assert_eq!(resumed.answer(), "Plan step step step step");
+        assert_eq!(resumed.metadata.llm_calls, 4);
+        assert_eq!(store.load("wf-1").await.unwrap().unwrap().iteration(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_resume_requires_store_and_checkpoint() {
+        let executor = RLMExecutor::new(RLMConfig::default()).unwrap();
+        assert!(matches!(executor.resume("wf").await, Err(RLMError::ConfigError(_))));
+
+        let dir = tempfile::tempdir().unwrap();
+        let executor = executor.with_checkpoint_store(Arc::new(
+            crate::checkpoint::FileCheckpointStore::new(dir.path()),
+        ));
+        assert!(matches!(executor.resume("wf").await, Err(RLMError::ExecutionError(_))));
+    }
+
+    /// Backend answering every prompt with the same chunks
+    struct ScriptedBackend(Vec<&'static str>);
+
+    impl LLMBackend for ScriptedBackend {
+        fn stream_response(&self, _prompt: &str) -> crate::llm_backend::LLMResponseStream {
+            let chunks: Vec<RLMResult<String>> = self.0.iter().map(|chunk| Ok(chunk.to_string())).collect();
+            futures::stream::iter(chunks).boxed()
+        }
+    }
+
+    /// Backend whose responses are fed by the test, one channel per call
+    struct ChannelBackend(std::sync::Mutex<Vec<mpsc::UnboundedReceiver<RLMResult<String>>>>);
+
+    impl LLMBackend for ChannelBackend {
+        fn stream_response(&self, _prompt: &str) -> crate::llm_backend::LLMResponseStream {
+            match self.0.lock().unwrap().pop() {
+                Some(response) => response.boxed(),
+                None => futures::stream::empty().boxed(),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_stream_yields_chunks_as_they_arrive() {
+        let (response, receiver) = mpsc::unbounded();
+        let executor = RLMExecutor::new(RLMConfig::default().with_max_iterations(1))
+            .unwrap()
+            .with_llm_backend(Arc::new(ChannelBackend(std::sync::Mutex::new(vec![receiver]))));
+
+        let mut stream = Box::pin(executor.execute_stream("Question?", "stream"));
+        assert_eq!(stream.next().await.unwrap().unwrap(), "Question?");
+
+        // Each chunk is passed on before the backend has finished responding
+        response.unbounded_send(Ok("The answer".to_string())).unwrap();
+        assert_eq!(stream.next().await.unwrap().unwrap(), "The answer");
+        response.unbounded_send(Ok(" is 42.".to_string())).unwrap();
+        assert_eq!(stream.next().await.unwrap().unwrap(), " is 42.");
+
+        drop(response);
+        assert!(stream.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_execute_stream_matches_execute() {
+        let executor = RLMExecutor::new(RLMConfig::default().with_max_iterations(3))
+            .unwrap()
+            .with_llm_backend(Arc::new(ScriptedBackend(vec!["Thinking", " about", " it."])));
+
+        let chunks: Vec<String> = executor
+            .execute_stream("Question?", "stream")
+            .map(|chunk| chunk.unwrap())
+            .collect()
+            .await;
+        let answer = executor.execute("Question?", "plain").await.unwrap();
+
+        assert_eq!(chunks.len(), 1 + 3 * 3);
+        assert_eq!(chunks.concat(), answer);
+        assert_eq!(answer, format!("Question?{}", "Thinking about it.".repeat(3)));
+    }
+
+    #[tokio::test]
+    async fn test_execute_stream_ends_with_error() {
+        let executor = RLMExecutor::new(RLMConfig::default()).unwrap();
+        let items: Vec<RLMResult<String>> = executor.execute_stream("", "stream").collect().await;
+        assert!(matches!(items.as_slice(), [Err(RLMError::ExecutionError(_))]));
+    }
+
+    #[test]
+    fn test_truncate_output_respects_char_boundaries() {
+        assert_eq!(truncate_output("short".to_string(), 10), "short");
+        assert_eq!(truncate_output("abcdef".to_string(), 4), "abcd");
+        // 'é' is two bytes, so cutting inside it backs off to the boundary
+        assert_eq!(truncate_output("aé".to_string(), 2), "a");
+    }
+
+    #[test]
+    fn test_truncate_output_lines() {
+        let output: String = (0..10).map(|i| format!("line {}\n", i)).collect();
+
+        assert_eq!(truncate_output_lines(output.clone(), 10, 1000), output);
+        assert_eq!(
+            truncate_output_lines(output.clone(), 3, 1000),
+            "line 0\nline 1\nline 2\n...[7 lines truncated]..."
+        );
+
+        // The byte limit also ends on a whole line, leaving room for the marker
+        let cut = truncate_output_lines(output.clone(), 10, 40);
+        assert_eq!(cut, "line 0\nline 1\n...[8 lines truncated]...");
+        assert!(cut.len() <= 40);
+
+        // Too small for the marker: plain byte cut
+        assert_eq!(truncate_output_lines(output, 10, 5), "line ");
+    }
+
+    #[tokio::test]
+    #[ignore]  // Requires Python to be installed
+    async fn test_max_output_lines_cuts_on_line_boundary() {
+        let config = RLMConfig::default().with_max_output_lines(5);
+        let executor = RLMExecutor::new(config).unwrap();
+        let budget = RetryBudget::default();
+
+        let output = executor
+            .execute_code_block("python", "for i in range(100):\n    print('row', i)", &budget)
+            .await
+            .unwrap();
+
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(&lines[..5], ["row 0", "row 1", "row 2", "row 3", "row 4"]);
+        assert_eq!(lines[5], "...[95 lines truncated]...");
+        assert_eq!(lines.len(), 6);
+    }
+
+    #[tokio::test]
+    #[ignore]  // Requires Python and Rust to be installed
+    async fn test_repl_output_limits_per_language() {
+        let config = RLMConfig::default()
+            .with_max_repl_output_for("python", 50)
+            .with_max_repl_output_for("rust", 500);
+        let executor = RLMExecutor::new(config).unwrap();
+        let budget = RetryBudget::default();
+
+        let python = executor
+            .execute_code_block("python", "print('p' * 2000)", &budget)
+            .await
+            .unwrap();
+        assert_eq!(python, "p".repeat(50));
+
+        let rust = executor
+            .execute_code_block("rust", r#"println!("{}", "r".repeat(2000));"#, &budget)
+            .await
+            .unwrap();
+        assert_eq!(rust, "r".repeat(500));
+
+        // Output under the limit is left alone
+        let short = executor
+            .execute_code_block("python", "print('ok')", &budget)
+            .await
+            .unwrap();
+        assert_eq!(short, "ok\n");
+    }
+
+    #[test]
+    fn test_explain_context_folding() {
+        let executor = RLMExecutor::new(RLMConfig::default()).unwrap();
+        let before = "keep me\ndrop me\nkeep me too";
+        let after = "keep me\n[1 line folded]\nkeep me too";
+
+        let explanation = executor.explain_context_folding(before, after);
+
+        assert_eq!(explanation.dropped_lines, vec!["drop me"]);
+        assert_eq!(explanation.kept_lines, vec!["keep me", "keep me too"]);
+        assert!(!after.contains("drop me"));
+    }
+
+    #[test]
+    fn test_token_counts_agree_across_call_sites() {
+        // Counts characters, which the default heuristic never does
+        let config = RLMConfig::default().with_token_counter(Arc::new(|text: &str| text.chars().count()));
+        let executor = RLMExecutor::new(config).unwrap();
+        let text = "Fold, batch & check: the same 42 tokens?";
+
+        let folder = ContextFolder::new(ContextFoldConfig::new(10))
+            .with_token_counter(Arc::clone(&executor.config().token_counter));
+        let mut context = executor.create_context("tokens");
+        context.append_answer(text);
+        let batch = executor.batch_executor();
+
+        let expected = text.chars().count();
+        assert_eq!(folder.count_tokens(text), expected);
+        assert_eq!(context.token_count(), expected);
+        assert_eq!(batch.count_tokens(text), expected);
+        assert!(folder.should_fold(text));
+    }
+
+    #[tokio::test]
+    async fn test_context_overflow_returns_partial_answer() {
+        let config = RLMConfig::default()
+            .with_max_iterations(50)
+            .with_max_repl_output(100)
+            .with_max_context_length(120)
+            .with_context_folding(false);
+        // The mock's response grows the answer every iteration, so stall
+        // detection does not end the run before it overflows
+        let executor = RLMExecutor::new(config)
+            .unwrap()
+            .with_llm_backend(Arc::new(crate::llm_backend::MockLLMClient::new([
+                " q3 up".to_string(),
+            ])));
+        let prompt = "Summarize the quarterly figures";
+
+        let (answer, trace) = executor.execute_traced(prompt, "overflow").await.unwrap();
+
+        assert!(answer.starts_with(prompt));
+        assert!(answer.len() <= 120);
+        let truncated_at = trace
+            .events()
+            .iter()
+            .position(|e| matches!(e, TraceEvent::ContextTruncated { .. }))
+            .expect("expected a ContextTruncated event");
+        assert!(matches!(
+            trace.events()[truncated_at + 1],
+            TraceEvent::IterationCompleted { n, answer_length } if n < 50 && answer_length <= 120
+        ));
+        assert_eq!(trace.events().len(), truncated_at + 2);
+    }
+
+    #[tokio::test]
+    async fn test_oversized_prompt_is_folded_when_enabled() {
+        let prompt: String = (0..200)
+            .map(|i| format!("Quarterly figure {} for the board review\n", i))
+            .collect();
+        let config = RLMConfig::default()
+            .with_max_iterations(1)
+            .with_max_repl_output(500)
+            .with_max_context_length(2000);
+        assert!(prompt.len() > 2000);
+
+        let strict = RLMExecutor::new(config.clone()).unwrap();
+        assert!(matches!(
+            strict.execute(&prompt, "strict").await,
+            Err(RLMError::ExecutionError(_))
+        ));
+        assert_eq!(strict.metrics().runs_started, 0);
+
+        let folding = RLMExecutor::new(config.with_fold_oversized_prompt(true)).unwrap();
+        let (answer, trace) = folding.execute_traced(&prompt, "folding").await.unwrap();
+
+        // The folded prompt fits; only the iteration's own output goes past it
+        assert!(answer.starts_with("Quarterly figure 0"));
+        assert!(answer.len() < prompt.len());
+        assert!(answer.find("\n[Iteration 1 complete]").unwrap() <= 2000);
+        assert!(matches!(
+            trace.events().first(),
+            Some(TraceEvent::ContextFolded { original_tokens, compressed_tokens })
+                if compressed_tokens < original_tokens
+        ));
+        let metrics = folding.metrics();
+        assert_eq!(metrics.prompts_prefolded, 1);
+        assert_eq!(metrics.runs_completed, 1);
+    }
+
+    #[tokio::test]
+    async fn test_repeated_block_runs_once_per_iteration() {
+        let executor = RLMExecutor::new(RLMConfig::default().with_max_iterations(1)).unwrap();
+        let prompt = "Check the totals.\n\
+            ```python\nprint(1 + 1)\n```\n\
+            Once more:\n\
+            ```python\nprint(1 + 1)\n```\n\
+            And the difference:\n\
+            ```python\nprint(2 - 1)\n```\n";
+
+        let (_, trace) = executor.execute_traced(prompt, "dedup").await.unwrap();
+
+        // Executed or not (Python may be missing), each distinct block is tried once
+        let executed = trace
+            .events()
+            .iter()
+            .filter(|e| matches!(e, TraceEvent::CodeExecuted { .. }))
+            .count();
+        assert_eq!(executed, 2);
+        assert_eq!(executor.metrics().duplicate_blocks_suppressed, 1);
+    }
+
+    #[tokio::test]
+    async fn test_execute_with_context_stops_at_context_limit() {
+        let config = Arc::new(
+            RLMConfig::default()
+                .with_max_iterations(50)
+                .with_max_repl_output(40)
+                .with_max_context_length(60),
+        );
+        let executor = RLMExecutor::new((*config).clone()).unwrap();
+        let mut context = RLMContext::new("task-1", Arc::clone(&config));
+
+        let answer = executor.execute_with_context("Test", &mut context).await.unwrap();
+
+        assert!(answer.len() <= 60);
+        assert!(context.iteration() < 50);
+        assert!(context.stats().truncated_context);
+    }
+
     #[tokio::test]
     async fn test_execute_with_context() {
         let config = Arc::new(RLMConfig::default());
@@ -274,4 +1382,132 @@ mod tests {
         assert_eq!(context.task_id, "task-1");
         assert_eq!(context.iteration(), 0);
     }
+
+    #[tokio::test]
+    async fn test_code_safety_policy_blocks_denied_code() {
+        let config = RLMConfig::default().with_code_safety(CodeSafetyPolicy::recommended());
+        let executor = RLMExecutor::new(config).unwrap();
+        let budget = RetryBudget::default();
+
+        let err = executor
+            .execute_code_block("bash", "rm -rf /", &budget)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, RLMError::PolicyViolation { .. }));
+
+        // Runs end to end: the violation is recorded like any failed block
+        let prompt = "Clean up first.\n```bash\nrm -rf ./kowalski-policy-test\n```\n";
+        let (context, _) = executor.run(prompt, "policy", None).await.unwrap();
+
+        // The block is found again in later iterations, and blocked each time
+        let results = context.code_block_results();
+        assert!(!results.is_empty());
+        assert!(results.iter().all(|r| !r.success));
+        assert!(results[0].error.as_deref().unwrap().contains("Code safety policy violation"));
+        assert!(context.metadata.errors.iter().any(|e| e.contains("denied pattern")));
+    }
+
+    #[tokio::test]
+    #[ignore]  // Requires bash to be installed
+    async fn test_code_safety_policy_allows_benign_code() {
+        let config = RLMConfig::default().with_code_safety(CodeSafetyPolicy::recommended());
+        let executor = RLMExecutor::new(config).unwrap();
+
+        let output = executor
+            .execute_code_block("bash", "echo allowed", &RetryBudget::default())
+            .await
+            .unwrap();
+        assert_eq!(output.trim(), "allowed");
+    }
+
+    #[tokio::test]
+    async fn test_warm_up_retries_failed_languages() {
+        let executor = RLMExecutor::new(RLMConfig::default()).unwrap();
+
+        for _ in 0..2 {
+            let results = executor.warm_up_languages(&["cobol"]).await;
+            assert!(matches!(results["cobol"], Err(RLMError::ExecutionError(_))));
+        }
+        assert!(executor.warmed_languages().is_empty());
+    }
+
+    #[tokio::test]
+    #[ignore]  // Requires bash to be installed
+    async fn test_warm_up_skips_warmed_languages() {
+        let executor = RLMExecutor::new(RLMConfig::default()).unwrap();
+
+        let started = Instant::now();
+        let results = executor.warm_up_languages(&["bash"]).await;
+        let first = started.elapsed();
+        assert!(results["bash"].is_ok());
+        assert_eq!(executor.warmed_languages(), vec!["bash"]);
+
+        let started = Instant::now();
+        let results = executor.warm_up_languages(&["bash"]).await;
+        assert!(results["bash"].is_ok());
+        assert!(started.elapsed() < first);
+    }
+
+    #[tokio::test]
+    #[ignore]  // Runs every installed interpreter and compiler
+    async fn test_warm_up_covers_registered_languages() {
+        let executor = RLMExecutor::new(RLMConfig::default()).unwrap();
+
+        let results = executor.warm_up().await;
+        assert_eq!(results.len(), REPLExecutorFactory::registered_languages().len());
+        let warmed: Vec<String> = results
+            .iter()
+            .filter(|(_, result)| result.is_ok())
+            .map(|(language, _)| language.clone())
+            .collect();
+        assert_eq!(executor.warmed_languages().len(), warmed.len());
+    }
+
+    /// Reports a fixed resident memory reading
+    #[derive(Debug)]
+    struct FixedMemory(u64);
+
+    impl MemoryMonitor for FixedMemory {
+        fn resident_bytes(&self) -> Option<u64> {
+            Some(self.0)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_memory_pressure_folds_eagerly() {
+        // Well within the context limit, so only memory pressure can fold it
+        let prompt: String = (0..200)
+            .map(|i| format!("Accumulated observation {} from the sensor log\n", i))
+            .collect();
+        let config = RLMConfig::default()
+            .with_max_iterations(1)
+            .with_memory_watermark(512 * 1024 * 1024);
+        assert!(prompt.len() < config.max_context_length);
+
+        let relaxed = RLMExecutor::new(config.clone())
+            .unwrap()
+            .with_memory_monitor(Arc::new(FixedMemory(64 * 1024 * 1024)));
+        let (answer, trace) = relaxed.execute_traced(&prompt, "relaxed").await.unwrap();
+        assert!(answer.starts_with(&prompt));
+        assert!(!trace.events().iter().any(|e| matches!(e, TraceEvent::ContextFolded { .. })));
+        assert_eq!(relaxed.metrics().memory_pressure_folds, 0);
+
+        let pressured = RLMExecutor::new(config)
+            .unwrap()
+            .with_memory_monitor(Arc::new(FixedMemory(1024 * 1024 * 1024)));
+        let (answer, trace) = pressured.execute_traced(&prompt, "pressured").await.unwrap();
+        assert!(answer.len() < prompt.len());
+        assert!(trace.events().iter().any(|e| matches!(
+            e,
+            TraceEvent::ContextFolded { original_tokens, compressed_tokens }
+                if compressed_tokens <= &(original_tokens / 2)
+        )));
+        assert_eq!(pressured.metrics().memory_pressure_folds, 1);
+    }
+
+    #[test]
+    fn test_zero_memory_watermark_is_rejected() {
+        let config = RLMConfig::default().with_memory_watermark(0);
+        assert!(RLMExecutor::new(config).is_err());
+    }
 }
@@ -2,14 +2,24 @@
 //!
 //! Provides device discovery and remote execution APIs over Exo's HTTP interface.
 
-use crate::device_health::{DeviceCapabilities, DeviceHealth};
+use crate::device_health::{DeviceCapabilities, DeviceHealth, HealthMonitor};
 use crate::error::{RLMError, RLMResult};
+use futures::future::join_all;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use tokio::sync::RwLock;
 
+/// Timeout for querying a device's `/capabilities` endpoint
+const CAPABILITY_DISCOVERY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Timeout for remote code blocks run by [`ExoClusterManager::execute_parallel`]
+const PARALLEL_REPL_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Output limit for remote code blocks run by [`ExoClusterManager::execute_parallel`]
+const PARALLEL_REPL_MAX_OUTPUT_BYTES: usize = 1_000_000;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExoDeviceInfo {
     pub id: String,
@@ -50,15 +60,85 @@ pub struct REPLResponse {
     pub exit_code: i32,
 }
 
+impl REPLResponse {
+    /// Convert the response from `device_id` into REPL output or an error
+    pub fn into_output(self, device_id: &str) -> RLMResult<String> {
+        if self.exit_code != 0 {
+            return Err(RLMError::repl(format!(
+                "Remote REPL failed ({}): {}",
+                device_id, self.stderr
+            )));
+        }
+
+        Ok(if self.stdout.is_empty() && self.stderr.is_empty() {
+            "(no output)".to_string()
+        } else if self.stdout.is_empty() {
+            self.stderr
+        } else {
+            self.stdout
+        })
+    }
+}
+
+/// Output stream a streamed REPL chunk came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum REPLOutputStream {
+    /// Standard output
+    Stdout,
+    /// Standard error
+    Stderr,
+}
+
+/// Piece of output produced by a remote execution while it runs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct REPLOutputChunk {
+    /// Stream the output was written to
+    pub stream: REPLOutputStream,
+    /// Output text
+    pub data: String,
+}
+
+/// Line of a streamed REPL response, sent as newline-delimited JSON
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum REPLStreamEvent {
+    /// Output produced so far
+    Output(REPLOutputChunk),
+    /// The execution finished
+    Exit {
+        /// Process exit code
+        exit_code: i32,
+    },
+}
+
+/// Counts a request as in flight on a device until dropped
+struct InFlightGuard {
+    in_flight: Arc<Mutex<HashMap<String, usize>>>,
+    device_id: String,
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        let mut in_flight = self.in_flight.lock().unwrap();
+        if let Some(count) = in_flight.get_mut(&self.device_id) {
+            *count = count.saturating_sub(1);
+        }
+    }
+}
+
 /// Manages communication with Exo cluster
 #[derive(Debug)]
 pub struct ExoClusterManager {
     base_url: String,
     client: reqwest::Client,
     devices: Arc<RwLock<HashMap<String, ExoDeviceInfo>>>,
+    in_flight: Arc<Mutex<HashMap<String, usize>>>,
+    health_monitor: Option<Arc<HealthMonitor>>,
 }
 
 impl ExoClusterManager {
+    /// Connect to the Exo cluster at `base_url` and discover its devices
     pub async fn new(base_url: impl Into<String>) -> RLMResult<Self> {
         let base_url = base_url.into();
         let client = reqwest::ClientBuilder::new()
@@ -66,18 +146,34 @@ impl ExoClusterManager {
             .connect_timeout(Duration::from_secs(10))
             .timeout(Duration::from_secs(120))
             .build()
-            .map_err(|e| RLMError::network(e.to_string()))?;
+            .map_err(|e| RLMError::wrap(e, "Failed to build Exo HTTP client"))?;
 
         let manager = Self {
             base_url,
             client,
             devices: Arc::new(RwLock::new(HashMap::new())),
+            in_flight: Arc::new(Mutex::new(HashMap::new())),
+            health_monitor: None,
         };
 
         manager.discover_devices().await?;
         Ok(manager)
     }
 
+    /// Skip devices that `monitor` reports as unhealthy when assigning work
+    pub fn with_health_monitor(mut self, monitor: Arc<HealthMonitor>) -> Self {
+        self.health_monitor = Some(monitor);
+        self
+    }
+
+    /// Health monitor attached with [`with_health_monitor`](Self::with_health_monitor)
+    pub fn health_monitor(&self) -> Option<&Arc<HealthMonitor>> {
+        self.health_monitor.as_ref()
+    }
+
+    /// Replace the known devices with the cluster's current state
+    ///
+    /// Device capabilities are refreshed afterwards.
     pub async fn discover_devices(&self) -> RLMResult<()> {
         let url = format!("{}/state", self.base_url);
         let response = self
@@ -85,7 +181,7 @@ impl ExoClusterManager {
             .get(&url)
             .send()
             .await
-            .map_err(|e| RLMError::network(e.to_string()))?;
+            .map_err(|e| RLMError::wrap(e, "Exo discovery request failed"))?;
 
         if !response.status().is_success() {
             let error_text = response.text().await.unwrap_or_default();
@@ -98,22 +194,94 @@ impl ExoClusterManager {
         let state: ExoClusterState = response
             .json()
             .await
-            .map_err(|e| RLMError::serialization(e.to_string()))?;
+            .map_err(|e| RLMError::wrap(e, "Failed to parse Exo cluster state"))?;
 
-        let mut devices = self.devices.write().await;
-        devices.clear();
-        for device in state.devices {
-            devices.insert(device.id.clone(), device);
+        {
+            let mut devices = self.devices.write().await;
+            devices.clear();
+            for device in state.devices {
+                devices.insert(device.id.clone(), device);
+            }
         }
 
+        self.refresh_capabilities().await;
         Ok(())
     }
 
+    /// Query a device's `/capabilities` endpoint
+    pub async fn discover_capabilities(&self, device: &ExoDeviceInfo) -> RLMResult<DeviceCapabilities> {
+        let base = if device.address.contains("://") {
+            device.address.clone()
+        } else {
+            format!("http://{}", device.address)
+        };
+        let url = format!("{}/capabilities", base.trim_end_matches('/'));
+        let response = self
+            .client
+            .get(&url)
+            .timeout(CAPABILITY_DISCOVERY_TIMEOUT)
+            .send()
+            .await
+            .map_err(|e| RLMError::wrap(e, "Capability discovery request failed"))?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(RLMError::network(format!(
+                "Capability discovery failed ({}): {}",
+                device.id, error_text
+            )));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| RLMError::wrap(e, "Failed to parse device capabilities"))
+    }
+
+    /// Re-query capabilities for every known device
+    ///
+    /// Discovered capabilities replace the ones reported by the cluster and
+    /// are copied into the health monitor, if any. Devices that cannot be
+    /// queried keep their current capabilities. Returns the number of
+    /// devices whose capabilities were discovered.
+    pub async fn refresh_capabilities(&self) -> usize {
+        let devices = self.list_devices().await.unwrap_or_default();
+        let discovered = join_all(devices.iter().map(|device| async move {
+            (device.id.clone(), self.discover_capabilities(device).await)
+        }))
+        .await;
+
+        let mut refreshed = 0;
+        for (device_id, result) in discovered {
+            let capabilities = match result {
+                Ok(capabilities) => capabilities,
+                Err(e) => {
+                    log::warn!(
+                        "Keeping provided capabilities for device {}: {}",
+                        device_id, e
+                    );
+                    continue;
+                }
+            };
+
+            if let Some(monitor) = &self.health_monitor {
+                monitor.update_capabilities(&device_id, capabilities.clone()).await;
+            }
+            if let Some(device) = self.devices.write().await.get_mut(&device_id) {
+                device.capabilities = capabilities;
+                refreshed += 1;
+            }
+        }
+        refreshed
+    }
+
+    /// Devices currently known to the manager
     pub async fn list_devices(&self) -> RLMResult<Vec<ExoDeviceInfo>> {
         let devices = self.devices.read().await;
         Ok(devices.values().cloned().collect())
     }
 
+    /// Models the Exo cluster can serve
     pub async fn list_models(&self) -> RLMResult<Vec<ExoModelInfo>> {
         let url = format!("{}/models", self.base_url);
         let response = self
@@ -121,7 +289,7 @@ impl ExoClusterManager {
             .get(&url)
             .send()
             .await
-            .map_err(|e| RLMError::network(e.to_string()))?;
+            .map_err(|e| RLMError::wrap(e, "Exo model list request failed"))?;
 
         if !response.status().is_success() {
             let error_text = response.text().await.unwrap_or_default();
@@ -134,10 +302,11 @@ impl ExoClusterManager {
         let models: ExoModelListResponse = response
             .json()
             .await
-            .map_err(|e| RLMError::serialization(e.to_string()))?;
+            .map_err(|e| RLMError::wrap(e, "Failed to parse Exo model list"))?;
         Ok(models.models)
     }
 
+    /// Run a REPL request on `device_id` through the Exo API
     pub async fn send_repl_request(
         &self,
         device_id: &str,
@@ -153,7 +322,7 @@ impl ExoClusterManager {
             }))
             .send()
             .await
-            .map_err(|e| RLMError::network(e.to_string()))?;
+            .map_err(|e| RLMError::wrap(e, "Exo REPL request failed"))?;
 
         if !response.status().is_success() {
             let error_text = response.text().await.unwrap_or_default();
@@ -166,10 +335,258 @@ impl ExoClusterManager {
         let repl_response: REPLResponse = response
             .json()
             .await
-            .map_err(|e| RLMError::serialization(e.to_string()))?;
+            .map_err(|e| RLMError::wrap(e, "Failed to parse Exo REPL response"))?;
         Ok(repl_response)
     }
 
+    /// Send a REPL request and receive its output as it is produced
+    ///
+    /// The request is sent to the same endpoint as
+    /// [`send_repl_request`](Self::send_repl_request) with `"stream": true`,
+    /// and the device answers with newline-delimited [`REPLStreamEvent`]s.
+    /// `on_chunk` is called for every output chunk; the returned response
+    /// aggregates them.
+    pub async fn send_repl_request_streaming<F>(
+        &self,
+        device_id: &str,
+        request: REPLRequest,
+        mut on_chunk: F,
+    ) -> RLMResult<REPLResponse>
+    where
+        F: FnMut(&REPLOutputChunk) + Send,
+    {
+        let url = format!("{}/api/repl/execute", self.base_url);
+        let mut response = self
+            .client
+            .post(&url)
+            .json(&serde_json::json!({
+                "device_id": device_id,
+                "request": request,
+                "stream": true,
+            }))
+            .send()
+            .await
+            .map_err(|e| RLMError::wrap(e, "Exo REPL request failed"))?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(RLMError::network(format!(
+                "Exo REPL request failed: {}",
+                error_text
+            )));
+        }
+
+        let mut aggregated = REPLResponse {
+            stdout: String::new(),
+            stderr: String::new(),
+            exit_code: 0,
+        };
+        let mut exited = false;
+        let mut pending: Vec<u8> = Vec::new();
+        let mut handle_line = |line: &[u8], aggregated: &mut REPLResponse| -> RLMResult<()> {
+            if line.iter().all(u8::is_ascii_whitespace) {
+                return Ok(());
+            }
+            let event: REPLStreamEvent = serde_json::from_slice(line)
+                .map_err(|e| RLMError::wrap(e, "Failed to parse Exo REPL stream event"))?;
+            match event {
+                REPLStreamEvent::Output(chunk) => {
+                    match chunk.stream {
+                        REPLOutputStream::Stdout => aggregated.stdout.push_str(&chunk.data),
+                        REPLOutputStream::Stderr => aggregated.stderr.push_str(&chunk.data),
+                    }
+                    on_chunk(&chunk);
+                }
+                REPLStreamEvent::Exit { exit_code } => {
+                    aggregated.exit_code = exit_code;
+                    exited = true;
+                }
+            }
+            Ok(())
+        };
+
+        while let Some(bytes) = response
+            .chunk()
+            .await
+            .map_err(|e| RLMError::wrap(e, "Exo REPL stream interrupted"))?
+        {
+            pending.extend_from_slice(&bytes);
+            while let Some(newline) = pending.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = pending.drain(..=newline).collect();
+                handle_line(&line, &mut aggregated)?;
+            }
+        }
+        handle_line(&pending, &mut aggregated)?;
+
+        if !exited {
+            return Err(RLMError::network(format!(
+                "Exo REPL stream from {} ended before the execution finished",
+                device_id
+            )));
+        }
+        Ok(aggregated)
+    }
+
+    /// Number of requests currently running on a device
+    pub fn in_flight(&self, device_id: &str) -> usize {
+        self.in_flight
+            .lock()
+            .unwrap()
+            .get(device_id)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Discovered devices that the health monitor, if any, has not marked unhealthy
+    async fn healthy_devices(&self) -> Vec<ExoDeviceInfo> {
+        let devices = self.list_devices().await.unwrap_or_default();
+        let Some(monitor) = &self.health_monitor else {
+            return devices;
+        };
+
+        let unhealthy: Vec<String> = monitor
+            .list_all_devices()
+            .await
+            .into_iter()
+            .filter(|device| !device.is_healthy)
+            .map(|device| device.device_id)
+            .collect();
+        devices
+            .into_iter()
+            .filter(|device| !unhealthy.contains(&device.id))
+            .collect()
+    }
+
+    /// Pick a healthy device supporting `language` that is not in `exclude`
+    ///
+    /// Uses the same ranking as [`select_device`](Self::select_device).
+    pub async fn failover_device(&self, language: &str, exclude: &[String]) -> Option<ExoDeviceInfo> {
+        self.best_device(language, exclude).await
+    }
+
+    /// Pick the best healthy device for `language`
+    ///
+    /// Devices are ranked by requests in flight, then by the response time
+    /// last recorded by the health monitor, then by id.
+    ///
+    /// # Errors
+    ///
+    /// Returns `NoDevicesAvailable` if no device supports the runtime or
+    /// every device that does is unhealthy.
+    pub async fn select_device(&self, language: &str) -> RLMResult<ExoDeviceInfo> {
+        if let Some(device) = self.best_device(language, &[]).await {
+            return Ok(device);
+        }
+
+        let capable = self
+            .list_devices()
+            .await?
+            .iter()
+            .filter(|device| device.capabilities.runtimes.iter().any(|r| r == language))
+            .count();
+        Err(RLMError::no_devices(if capable == 0 {
+            format!("No device supports {}", language)
+        } else {
+            format!("All {} devices supporting {} are unhealthy", capable, language)
+        }))
+    }
+
+    async fn best_device(&self, language: &str, exclude: &[String]) -> Option<ExoDeviceInfo> {
+        let devices = self.healthy_devices().await;
+        let latencies: HashMap<String, u64> = match &self.health_monitor {
+            Some(monitor) => monitor
+                .list_all_devices()
+                .await
+                .into_iter()
+                .map(|device| (device.device_id, device.response_time_ms))
+                .collect(),
+            None => HashMap::new(),
+        };
+
+        let in_flight = self.in_flight.lock().unwrap();
+        devices
+            .into_iter()
+            .filter(|device| !exclude.contains(&device.id))
+            .filter(|device| device.capabilities.runtimes.iter().any(|r| r == language))
+            .min_by_key(|device| {
+                (
+                    in_flight.get(&device.id).copied().unwrap_or(0),
+                    latencies.get(&device.id).copied().unwrap_or(0),
+                    device.id.clone(),
+                )
+            })
+    }
+
+    /// Report a failed request to the health monitor, if any
+    pub async fn mark_device_failure(&self, device_id: &str) {
+        if let Some(monitor) = &self.health_monitor {
+            monitor.mark_failure(device_id).await;
+        }
+    }
+
+    /// Report a successful request to the health monitor, if any
+    pub async fn mark_device_success(&self, device_id: &str, response_time_ms: u64) {
+        if let Some(monitor) = &self.health_monitor {
+            monitor.mark_success(device_id, response_time_ms).await;
+        }
+    }
+
+    /// Run independent `(language, code)` blocks concurrently across the cluster
+    ///
+    /// Each block is assigned to the healthy device supporting its language
+    /// with the fewest requests in flight, counting blocks already assigned
+    /// from this batch. Results are returned in input order; a block that no
+    /// device can run fails with `NoDevicesAvailable`.
+    pub async fn execute_parallel(&self, blocks: Vec<(String, String)>) -> Vec<RLMResult<String>> {
+        let devices = self.healthy_devices().await;
+
+        let assignments: Vec<Option<InFlightGuard>> = {
+            let mut in_flight = self.in_flight.lock().unwrap();
+            blocks
+                .iter()
+                .map(|(language, _)| {
+                    let device = devices
+                        .iter()
+                        .filter(|device| device.capabilities.runtimes.contains(language))
+                        .min_by(|a, b| {
+                            let load_a = in_flight.get(&a.id).copied().unwrap_or(0);
+                            let load_b = in_flight.get(&b.id).copied().unwrap_or(0);
+                            load_a.cmp(&load_b).then_with(|| a.id.cmp(&b.id))
+                        })?;
+                    *in_flight.entry(device.id.clone()).or_insert(0) += 1;
+                    Some(InFlightGuard {
+                        in_flight: Arc::clone(&self.in_flight),
+                        device_id: device.id.clone(),
+                    })
+                })
+                .collect()
+        };
+
+        let runs = blocks
+            .into_iter()
+            .zip(assignments)
+            .map(|((language, code), guard)| async move {
+                let guard = guard.ok_or_else(|| {
+                    RLMError::no_devices(format!("No healthy device supports {}", language))
+                })?;
+                let request = REPLRequest {
+                    language,
+                    code,
+                    timeout_ms: PARALLEL_REPL_TIMEOUT.as_millis() as u64,
+                    max_output_bytes: PARALLEL_REPL_MAX_OUTPUT_BYTES,
+                };
+                self.send_repl_request(&guard.device_id, request)
+                    .await?
+                    .into_output(&guard.device_id)
+            });
+
+        join_all(runs).await
+    }
+
+    /// Health records for the known devices
+    ///
+    /// Every device is reported healthy; devices whose address does not
+    /// parse are left out.
     pub async fn to_device_health_snapshot(&self) -> Vec<DeviceHealth> {
         let devices = self.devices.read().await;
         devices
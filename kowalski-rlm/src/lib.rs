@@ -50,6 +50,7 @@
 //! ### High-Level API
 //! - **RLM Builder**: Fluent API for ergonomic setup
 //! - **RLM Executor**: Unified execution interface
+//! - **RLM Pipeline**: Chained tasks that pass results between steps
 //! - **Configuration Management**: Comprehensive, extensible config system
 //! - **Context Management**: Automatic context folding and memory management
 //!
@@ -95,6 +96,8 @@
 //!
 //! ### Executor Module (`executor`)
 //! Unified execution interface combining all components.
+//! `RLMExecutor::execute_traced` also returns an `ExecutionTrace`
+//! (`execution_trace`) for post-mortem analysis of a run.
 //!
 //! ## Configuration
 //!
@@ -149,37 +152,59 @@
 //! - `tokio`: Async runtime
 //! - `serde`: Serialization
 
+pub mod artifact_cache;
 pub mod builder;
+pub mod checkpoint;
 pub mod code_block_parser;
+pub mod code_safety;
 pub mod config;
 pub mod context;
 pub mod context_fold;
 pub mod core;
 pub mod device_health;
 pub mod error;
+pub mod execution_trace;
 pub mod executor;
 pub mod exo_cluster_manager;
 pub mod federation;
+pub mod llm_backend;
+pub mod memory_monitor;
+pub mod pipeline;
 pub mod remote_repl_executor;
 pub mod repl_executor;
+pub mod retry_budget;
 pub mod smart_scheduler;
+pub mod system_status;
 
 // Re-export main types for convenience
+pub use artifact_cache::{ArtifactCache, ArtifactCacheConfig, ArtifactCacheStats, ArtifactLease};
 pub use builder::RLMBuilder;
-pub use code_block_parser::{CodeBlockParser, CodeBlock};
-pub use config::RLMConfig;
-pub use context::RLMContext;
-pub use context_fold::{ContextFolder, ContextFoldConfig, FoldingStats};
-pub use device_health::{HealthMonitor, DeviceHealth, DeviceCapabilities, DeviceClusterStatus};
+pub use checkpoint::{CheckpointStore, FileCheckpointStore};
+pub use code_block_parser::{CodeBlockExtractor, CodeBlockParser, CodeBlock, ExecutionHint, LanguageSpec};
+pub use code_safety::CodeSafetyPolicy;
+pub use config::{RLMConfig, RLMConfigBuilder};
+pub use context::{CodeBlockResult, PooledRLMContext, RLMContext, RLMContextPool};
+pub use context_fold::{AccumulatedResultsFolding, ContextFolder, ContextFoldConfig, FoldStep, FoldStrategy, Foldable, FoldingExplanation, FoldingStats, SlidingWindowFolder, StreamingFold};
+pub use device_health::{HealthMonitor, HealthProbe, HttpHealthProbe, DeviceHealth, DeviceCapabilities, DeviceClusterStatus};
 pub use error::{RLMError, RLMResult};
-pub use executor::RLMExecutor;
+pub use execution_trace::{ExecutionTrace, TraceEvent};
+pub use executor::{ExecutorMetrics, RLMExecutor};
 pub use exo_cluster_manager::{
     ExoClusterManager, ExoClusterState, ExoDeviceInfo, ExoModelInfo, ExoModelListResponse,
-    REPLRequest, REPLResponse,
+    REPLOutputChunk, REPLOutputStream, REPLRequest, REPLResponse, REPLStreamEvent,
 };
+pub use llm_backend::{ExhaustionPolicy, LLMBackend, LLMResponseStream, MockLLMClient};
+pub use memory_monitor::{MemoryMonitor, ProcessMemoryMonitor};
+pub use pipeline::{PipelineResult, RLMPipeline, StepResult};
 pub use remote_repl_executor::RemoteREPLExecutor;
-pub use repl_executor::{REPLExecutor, REPLExecutorFactory, PythonREPL, RustREPL, JavaREPL, BashREPL, JavaScriptREPL};
-pub use smart_scheduler::{SmartScheduler, SchedulerConfig, ScheduledTask, AgentStatus};
+pub use repl_executor::{REPLExecutor, REPLExecutorFactory, PythonREPL, RustREPL, JavaREPL, KotlinREPL, CSharpREPL, BashREPL, JavaScriptREPL, LuaREPL, RscriptREPL, PhpREPL, HaskellREPL};
+#[cfg(feature = "docker")]
+pub use repl_executor::DockerREPL;
+pub use retry_budget::RetryBudget;
+pub use smart_scheduler::{
+    SmartScheduler, SchedulerConfig, SchedulerConfigOverrides, AgentPool, AgentAssignmentStrategy, AgentUtilizationReport, ScheduledTask, AgentStatus,
+};
+pub use system_status::{collect_system_status, SystemStatus};
 
 // Re-export common Phase 1 types
 pub use core::{
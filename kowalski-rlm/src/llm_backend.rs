@@ -0,0 +1,152 @@
+//! Streaming LLM backends for the RLM executor
+//!
+//! An [`LLMBackend`] answers each iteration's prompt with a stream of text
+//! chunks. The executor appends the chunks to the answer as they arrive, so
+//! [`RLMExecutor::execute_stream`](crate::executor::RLMExecutor::execute_stream)
+//! can pass them on before the response is complete.
+//!
+//! [`MockLLMClient`] answers from a fixed list of responses, so the full
+//! execution loop can be tested without a running model.
+
+use crate::error::{RLMError, RLMResult};
+use futures::stream::BoxStream;
+use futures::{future, StreamExt};
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::Mutex;
+
+/// Stream of response chunks from an LLM backend
+pub type LLMResponseStream = BoxStream<'static, RLMResult<String>>;
+
+/// LLM that streams its response to a prompt
+pub trait LLMBackend: Send + Sync {
+    /// Start generating a response to `prompt`
+    fn stream_response(&self, prompt: &str) -> LLMResponseStream;
+
+    /// Short name of the backend, used in debug output
+    fn name(&self) -> &str {
+        "custom"
+    }
+}
+
+impl fmt::Debug for dyn LLMBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "LLMBackend({})", self.name())
+    }
+}
+
+/// What a [`MockLLMClient`] does once its responses run out
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ExhaustionPolicy {
+    /// Keep answering with the last response
+    #[default]
+    RepeatLast,
+    /// Fail every further call
+    Error,
+}
+
+/// Scripted LLM backend for tests
+///
+/// Each call answers with the next queued response, in one chunk, and
+/// records the prompt it was given. Once the queue is empty the
+/// [`ExhaustionPolicy`] decides between repeating the last response and
+/// failing.
+#[derive(Debug)]
+pub struct MockLLMClient {
+    state: Mutex<MockState>,
+    policy: ExhaustionPolicy,
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    responses: VecDeque<String>,
+    last: Option<String>,
+    prompts: Vec<String>,
+}
+
+impl MockLLMClient {
+    /// Create a mock answering with `responses` in order
+    pub fn new(responses: impl IntoIterator<Item = String>) -> Self {
+        Self {
+            state: Mutex::new(MockState {
+                responses: responses.into_iter().collect(),
+                ..MockState::default()
+            }),
+            policy: ExhaustionPolicy::default(),
+        }
+    }
+
+    /// Set what happens once the responses run out
+    pub fn with_exhaustion_policy(mut self, policy: ExhaustionPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Prompts received so far, in call order
+    pub fn prompts(&self) -> Vec<String> {
+        self.state.lock().unwrap().prompts.clone()
+    }
+
+    /// Queued responses not yet returned
+    pub fn remaining(&self) -> usize {
+        self.state.lock().unwrap().responses.len()
+    }
+
+    fn next_response(&self, prompt: &str) -> RLMResult<String> {
+        let mut state = self.state.lock().unwrap();
+        state.prompts.push(prompt.to_string());
+        if let Some(response) = state.responses.pop_front() {
+            state.last = Some(response.clone());
+            return Ok(response);
+        }
+        match (self.policy, &state.last) {
+            (ExhaustionPolicy::RepeatLast, Some(last)) => Ok(last.clone()),
+            _ => Err(RLMError::execution(format!(
+                "Mock LLM has no response left for call {}",
+                state.prompts.len()
+            ))),
+        }
+    }
+}
+
+impl LLMBackend for MockLLMClient {
+    fn stream_response(&self, prompt: &str) -> LLMResponseStream {
+        futures::stream::once(future::ready(self.next_response(prompt))).boxed()
+    }
+
+    fn name(&self) -> &str {
+        "mock"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn respond(client: &MockLLMClient, prompt: &str) -> RLMResult<String> {
+        client.stream_response(prompt).next().await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_mock_returns_responses_in_order_then_repeats_last() {
+        let client = MockLLMClient::new(["first".to_string(), "second".to_string()]);
+
+        assert_eq!(respond(&client, "a").await.unwrap(), "first");
+        assert_eq!(respond(&client, "b").await.unwrap(), "second");
+        assert_eq!(client.remaining(), 0);
+        assert_eq!(respond(&client, "c").await.unwrap(), "second");
+        assert_eq!(client.prompts(), vec!["a", "b", "c"]);
+    }
+
+    #[tokio::test]
+    async fn test_mock_error_policy_fails_when_exhausted() {
+        let client = MockLLMClient::new(["only".to_string()])
+            .with_exhaustion_policy(ExhaustionPolicy::Error);
+        assert_eq!(respond(&client, "a").await.unwrap(), "only");
+        assert!(matches!(respond(&client, "b").await, Err(RLMError::ExecutionError(_))));
+
+        // Without any response there is nothing to repeat
+        let empty = MockLLMClient::new(Vec::new());
+        assert!(respond(&empty, "a").await.is_err());
+    }
+}
@@ -0,0 +1,57 @@
+//! Process memory readings for memory-pressure folding
+//!
+//! With [`RLMConfig::memory_watermark_bytes`](crate::config::RLMConfig::memory_watermark_bytes)
+//! set, the executor asks its [`MemoryMonitor`] for the process's resident
+//! memory before every iteration and folds the answer whenever the reading
+//! is above the watermark.
+
+/// Source of the current process's resident memory
+pub trait MemoryMonitor: Send + Sync + std::fmt::Debug {
+    /// Resident set size in bytes, or `None` if it cannot be read
+    fn resident_bytes(&self) -> Option<u64>;
+}
+
+/// Reads the resident set size of this process from `/proc/self/status`
+///
+/// Only Linux exposes it there; elsewhere the reading is `None` and
+/// memory-pressure folding never triggers.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProcessMemoryMonitor;
+
+impl MemoryMonitor for ProcessMemoryMonitor {
+    fn resident_bytes(&self) -> Option<u64> {
+        let status = std::fs::read_to_string("/proc/self/status").ok()?;
+        parse_vm_rss(&status)
+    }
+}
+
+/// The `VmRSS` line of a `/proc/<pid>/status` file, in bytes
+fn parse_vm_rss(status: &str) -> Option<u64> {
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    let kb: u64 = line
+        .trim_start_matches("VmRSS:")
+        .split_whitespace()
+        .next()?
+        .parse()
+        .ok()?;
+    Some(kb * 1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_vm_rss() {
+        let status = "Name:\tcargo\nVmPeak:\t  20000 kB\nVmRSS:\t   1536 kB\nThreads:\t4\n";
+        assert_eq!(parse_vm_rss(status), Some(1536 * 1024));
+        assert_eq!(parse_vm_rss("Name:\tcargo\n"), None);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_process_monitor_reads_own_rss() {
+        let rss = ProcessMemoryMonitor.resident_bytes().unwrap();
+        assert!(rss > 0);
+    }
+}
@@ -0,0 +1,293 @@
+//! RLM pipelines
+//!
+//! Chains several RLM tasks so that each step's prompt can use the results
+//! of earlier steps. Prompt templates may contain:
+//!
+//! - `{previous_result}`: output of the previous step (the initial input for the first step)
+//! - `{step_N_result}`: output of step `N`, counting from 1
+//!
+//! Any other braces are left as they are.
+
+use crate::error::{RLMError, RLMResult};
+use crate::executor::RLMExecutor;
+use futures::future::try_join_all;
+use lazy_static::lazy_static;
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Instant;
+
+lazy_static! {
+    static ref PLACEHOLDER: Regex = Regex::new(r"\{(previous_result|step_(\d+)_result)\}").unwrap();
+}
+
+/// Separator between branch outputs of a parallel step
+const PARALLEL_OUTPUT_SEPARATOR: &str = "\n\n";
+
+/// Output of one pipeline step
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StepResult {
+    /// Step name
+    pub name: String,
+    /// Step output (branch outputs joined by a blank line for parallel steps)
+    pub output: String,
+    /// Wall-clock duration of the step in milliseconds
+    pub duration_ms: u64,
+}
+
+/// Output of a whole pipeline run
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PipelineResult {
+    /// Results of every step, in order
+    pub steps: Vec<StepResult>,
+    /// Output of the last step (the initial input if the pipeline is empty)
+    pub final_output: String,
+}
+
+/// One executor and prompt template
+struct Branch {
+    executor: Arc<RLMExecutor>,
+    prompt_template: String,
+}
+
+struct Step {
+    name: String,
+    branches: Vec<Branch>,
+}
+
+/// Sequence of RLM tasks that pass results forward
+///
+/// # Example
+///
+/// ```no_run
+/// use kowalski_rlm::builder::RLMBuilder;
+/// use kowalski_rlm::pipeline::RLMPipeline;
+/// use std::sync::Arc;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let rlm = Arc::new(RLMBuilder::default().build()?);
+///     let result = RLMPipeline::new()
+///         .add_step("extract", Arc::clone(&rlm), "Extract the key facts from: {previous_result}")
+///         .add_step("summarize", rlm, "Summarize these facts: {step_1_result}")
+///         .run("Quarterly report text...")
+///         .await?;
+///     println!("{}", result.final_output);
+///     Ok(())
+/// }
+/// ```
+#[derive(Default)]
+pub struct RLMPipeline {
+    steps: Vec<Step>,
+}
+
+impl RLMPipeline {
+    /// Create an empty pipeline
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a step that runs `executor` on the rendered `prompt_template`
+    pub fn add_step(
+        mut self,
+        name: impl Into<String>,
+        executor: Arc<RLMExecutor>,
+        prompt_template: impl Into<String>,
+    ) -> Self {
+        self.steps.push(Step {
+            name: name.into(),
+            branches: vec![Branch {
+                executor,
+                prompt_template: prompt_template.into(),
+            }],
+        });
+        self
+    }
+
+    /// Append a step that runs several executors concurrently
+    ///
+    /// Each branch renders its own template from the same earlier results.
+    /// The step output is the branch outputs, in order, separated by a blank line.
+    pub fn add_parallel_step(
+        mut self,
+        name: impl Into<String>,
+        branches: Vec<(Arc<RLMExecutor>, String)>,
+    ) -> Self {
+        self.steps.push(Step {
+            name: name.into(),
+            branches: branches
+                .into_iter()
+                .map(|(executor, prompt_template)| Branch {
+                    executor,
+                    prompt_template,
+                })
+                .collect(),
+        });
+        self
+    }
+
+    /// Number of steps
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    /// Returns true if the pipeline has no steps
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// Run the steps in order, starting from `initial_input`
+    ///
+    /// # Errors
+    ///
+    /// Fails on the first step that errors, or whose template refers to a
+    /// step that has not run yet.
+    pub async fn run(&self, initial_input: &str) -> RLMResult<PipelineResult> {
+        let mut results: Vec<StepResult> = Vec::with_capacity(self.steps.len());
+
+        for step in &self.steps {
+            if step.branches.is_empty() {
+                return Err(RLMError::config(format!(
+                    "Pipeline step '{}' has no executors",
+                    step.name
+                )));
+            }
+
+            let previous = results
+                .last()
+                .map_or(initial_input, |result| result.output.as_str());
+            let prompts = step
+                .branches
+                .iter()
+                .map(|branch| render(&branch.prompt_template, previous, &results, &step.name))
+                .collect::<RLMResult<Vec<_>>>()?;
+
+            let started = Instant::now();
+            let single = step.branches.len() == 1;
+            let runs = step.branches.iter().zip(&prompts).enumerate().map(
+                |(index, (branch, prompt))| {
+                    let task_id = if single {
+                        step.name.clone()
+                    } else {
+                        format!("{}-{}", step.name, index + 1)
+                    };
+                    async move { branch.executor.execute(prompt, &task_id).await }
+                },
+            );
+            let outputs = try_join_all(runs)
+                .await
+                .map_err(|e| RLMError::wrap(e, format!("Pipeline step '{}' failed", step.name)))?;
+
+            results.push(StepResult {
+                name: step.name.clone(),
+                output: outputs.join(PARALLEL_OUTPUT_SEPARATOR),
+                duration_ms: started.elapsed().as_millis() as u64,
+            });
+        }
+
+        let final_output = results
+            .last()
+            .map_or_else(|| initial_input.to_string(), |result| result.output.clone());
+        Ok(PipelineResult {
+            steps: results,
+            final_output,
+        })
+    }
+}
+
+/// Substitute result placeholders in a single pass
+fn render(template: &str, previous: &str, results: &[StepResult], step_name: &str) -> RLMResult<String> {
+    for captures in PLACEHOLDER.captures_iter(template) {
+        if let Some(n) = captures.get(2) {
+            let index: usize = n.as_str().parse().unwrap_or(0);
+            if index == 0 || index > results.len() {
+                return Err(RLMError::config(format!(
+                    "Pipeline step '{}' uses {{step_{}_result}} but only {} steps ran before it",
+                    step_name,
+                    n.as_str(),
+                    results.len()
+                )));
+            }
+        }
+    }
+
+    Ok(PLACEHOLDER
+        .replace_all(template, |captures: &Captures| match captures.get(2) {
+            Some(n) => {
+                let index: usize = n.as_str().parse().unwrap();
+                results[index - 1].output.clone()
+            }
+            None => previous.to_string(),
+        })
+        .into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RLMConfig;
+
+    fn executor() -> Arc<RLMExecutor> {
+        Arc::new(RLMExecutor::new(RLMConfig::default().with_max_iterations(1)).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_three_step_pipeline_passes_results_forward() {
+        let rlm = executor();
+        let result = RLMPipeline::new()
+            .add_step("gather", Arc::clone(&rlm), "Gather facts about {previous_result}")
+            .add_step("analyze", Arc::clone(&rlm), "Analyze <{previous_result}>")
+            .add_step("report", rlm, "Report on [{step_1_result}] using {{json}}")
+            .run("rust")
+            .await
+            .unwrap();
+
+        let names: Vec<&str> = result.steps.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["gather", "analyze", "report"]);
+
+        let gather = &result.steps[0].output;
+        assert!(gather.starts_with("Gather facts about rust"));
+        assert!(result.steps[1].output.starts_with(&format!("Analyze <{}>", gather)));
+        assert!(result.final_output.starts_with(&format!("Report on [{}] using {{{{json}}}}", gather)));
+        assert_eq!(result.final_output, result.steps[2].output);
+    }
+
+    #[tokio::test]
+    async fn test_parallel_step_joins_branch_outputs() {
+        let result = RLMPipeline::new()
+            .add_parallel_step(
+                "fan-out",
+                vec![
+                    (executor(), "Pros of {previous_result}".to_string()),
+                    (executor(), "Cons of {previous_result}".to_string()),
+                ],
+            )
+            .add_step("merge", executor(), "Weigh: {step_1_result}")
+            .run("tabs")
+            .await
+            .unwrap();
+
+        let branches: Vec<&str> = result.steps[0].output.split(PARALLEL_OUTPUT_SEPARATOR).collect();
+        assert!(branches[0].starts_with("Pros of tabs"));
+        assert!(branches.iter().any(|b| b.starts_with("Cons of tabs")));
+        assert!(result.final_output.starts_with("Weigh: Pros of tabs"));
+    }
+
+    #[tokio::test]
+    async fn test_forward_step_reference_is_rejected() {
+        let result = RLMPipeline::new()
+            .add_step("first", executor(), "Use {step_2_result}")
+            .add_step("second", executor(), "{previous_result}")
+            .run("input")
+            .await;
+
+        assert!(matches!(result, Err(RLMError::ConfigError(msg)) if msg.contains("step_2_result")));
+    }
+
+    #[tokio::test]
+    async fn test_empty_pipeline_returns_input() {
+        let result = RLMPipeline::new().run("unchanged").await.unwrap();
+        assert!(result.steps.is_empty());
+        assert_eq!(result.final_output, "unchanged");
+    }
+}
@@ -1,22 +1,41 @@
 //! Remote REPL executor via Exo cluster.
 
 use crate::error::{RLMError, RLMResult};
-use crate::exo_cluster_manager::{ExoClusterManager, REPLRequest};
+use crate::exo_cluster_manager::{ExoClusterManager, REPLOutputChunk, REPLRequest};
 use crate::repl_executor::REPLExecutor;
+use crate::retry_budget::RetryBudget;
 use async_trait::async_trait;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+
+/// Default number of other devices tried after a remote request fails
+const DEFAULT_MAX_FAILOVERS: usize = 2;
 
 /// Remote REPL executor that routes code execution through Exo.
+///
+/// If a request to the target device fails, the device is reported to the
+/// cluster's health monitor and the code is retried on another healthy
+/// device supporting the same runtime, up to `max_failovers` times and
+/// while the retry budget, if any, has retries left. Code that runs but
+/// exits with an error is not retried.
+///
+/// With a local fallback attached, code the cluster could not run at all
+/// is run by the fallback executor instead.
 pub struct RemoteREPLExecutor {
     cluster: Arc<ExoClusterManager>,
     device_id: String,
     language: String,
     timeout: Duration,
     max_output_bytes: usize,
+    max_failovers: usize,
+    retry_budget: Option<RetryBudget>,
+    local_fallback: Option<Box<dyn REPLExecutor>>,
+    last_execution_was_local: AtomicBool,
 }
 
 impl RemoteREPLExecutor {
+    /// Create an executor running `language` code on `device_id`
     pub fn new(
         cluster: Arc<ExoClusterManager>,
         device_id: impl Into<String>,
@@ -28,52 +47,177 @@ impl RemoteREPLExecutor {
             language: language.into(),
             timeout: Duration::from_secs(30),
             max_output_bytes: 1_000_000,
+            max_failovers: DEFAULT_MAX_FAILOVERS,
+            retry_budget: None,
+            local_fallback: None,
+            last_execution_was_local: AtomicBool::new(false),
         }
     }
 
+    /// Set the execution timeout
     pub fn with_timeout(mut self, timeout: Duration) -> Self {
         self.timeout = timeout;
         self
     }
 
+    /// Set the output size limit sent with each request
     pub fn with_max_output_bytes(mut self, max_output_bytes: usize) -> Self {
         self.max_output_bytes = max_output_bytes;
         self
     }
-}
 
-#[async_trait]
-impl REPLExecutor for RemoteREPLExecutor {
-    async fn execute(&self, code: &str) -> RLMResult<String> {
-        let request = REPLRequest {
+    /// Set how many other devices are tried after a failed request (0 disables failover)
+    pub fn with_max_failovers(mut self, max_failovers: usize) -> Self {
+        self.max_failovers = max_failovers;
+        self
+    }
+
+    /// Charge every failover to a retry budget shared with the rest of the run
+    pub fn with_retry_budget(mut self, budget: RetryBudget) -> Self {
+        self.retry_budget = Some(budget);
+        self
+    }
+
+    /// Run code on `fallback` when the cluster cannot run it
+    ///
+    /// [`execute`](REPLExecutor::execute) falls back on any error except
+    /// [`RLMError::REPLError`], which means the code ran and failed, so
+    /// running it locally would fail the same way. The fallback is tried
+    /// after remote failover is exhausted.
+    pub fn with_local_fallback(mut self, fallback: Box<dyn REPLExecutor>) -> Self {
+        self.local_fallback = Some(fallback);
+        self
+    }
+
+    /// Whether the last [`execute`](REPLExecutor::execute) call ran on the local fallback
+    pub fn last_execution_was_local(&self) -> bool {
+        self.last_execution_was_local.load(Ordering::Relaxed)
+    }
+
+    /// Execute code, passing stdout/stderr chunks to `on_chunk` as the device produces them
+    ///
+    /// Returns the same aggregated output as [`execute`](REPLExecutor::execute).
+    /// Failover only happens before the first chunk arrives, since output
+    /// already passed to `on_chunk` cannot be taken back.
+    pub async fn execute_streaming<F>(&self, code: &str, mut on_chunk: F) -> RLMResult<String>
+    where
+        F: FnMut(&REPLOutputChunk) + Send,
+    {
+        let request = self.request(code);
+        let mut device_id = self.device_id.clone();
+        let mut attempted = Vec::new();
+        loop {
+            let started = Instant::now();
+            let mut delivered = false;
+            let result = self
+                .cluster
+                .send_repl_request_streaming(&device_id, request.clone(), |chunk| {
+                    delivered = true;
+                    on_chunk(chunk);
+                })
+                .await;
+            match result {
+                Ok(response) => {
+                    self.cluster
+                        .mark_device_success(&device_id, started.elapsed().as_millis() as u64)
+                        .await;
+                    return response.into_output(&device_id);
+                }
+                Err(err) => {
+                    self.cluster.mark_device_failure(&device_id).await;
+                    attempted.push(device_id);
+                    if delivered {
+                        return Err(err);
+                    }
+                    device_id = self.failover_target(&attempted, err).await?;
+                }
+            }
+        }
+    }
+
+    fn request(&self, code: &str) -> REPLRequest {
+        REPLRequest {
             language: self.language.clone(),
             code: code.to_string(),
             timeout_ms: self.timeout.as_millis() as u64,
             max_output_bytes: self.max_output_bytes,
-        };
+        }
+    }
 
-        let response = self
-            .cluster
-            .send_repl_request(&self.device_id, request)
-            .await?;
+    /// Run code on the cluster, failing over between devices
+    async fn execute_remote(&self, code: &str) -> RLMResult<String> {
+        let request = self.request(code);
+        let mut device_id = self.device_id.clone();
+        let mut attempted = Vec::new();
+        loop {
+            let started = Instant::now();
+            match self.cluster.send_repl_request(&device_id, request.clone()).await {
+                Ok(response) => {
+                    self.cluster
+                        .mark_device_success(&device_id, started.elapsed().as_millis() as u64)
+                        .await;
+                    return response.into_output(&device_id);
+                }
+                Err(err) => {
+                    self.cluster.mark_device_failure(&device_id).await;
+                    attempted.push(device_id);
+                    device_id = self.failover_target(&attempted, err).await?;
+                }
+            }
+        }
+    }
 
-        if response.exit_code != 0 {
-            return Err(RLMError::repl(format!(
-                "Remote REPL failed ({}): {}",
-                self.device_id, response.stderr
-            )));
+    /// Next device to try after the `attempted` devices failed, or `err` if failover is exhausted
+    async fn failover_target(&self, attempted: &[String], err: RLMError) -> RLMResult<String> {
+        if attempted.len() > self.max_failovers {
+            return Err(err);
+        }
+        if let Some(budget) = &self.retry_budget {
+            if !budget.try_consume() {
+                log::warn!("Retry budget exhausted, not failing over: {}", err);
+                return Err(err);
+            }
         }
+        let Some(next) = self.cluster.failover_device(&self.language, attempted).await else {
+            return Err(err);
+        };
+        log::warn!(
+            "Remote REPL on {} failed ({}), failing over to {}",
+            attempted.last().map(String::as_str).unwrap_or_default(),
+            err,
+            next.id
+        );
+        Ok(next.id)
+    }
+}
 
-        Ok(if response.stdout.is_empty() && response.stderr.is_empty() {
-            "(no output)".to_string()
-        } else if response.stdout.is_empty() {
-            response.stderr
-        } else {
-            response.stdout
-        })
+#[async_trait]
+impl REPLExecutor for RemoteREPLExecutor {
+    async fn execute(&self, code: &str) -> RLMResult<String> {
+        self.last_execution_was_local.store(false, Ordering::Relaxed);
+        let err = match self.execute_remote(code).await {
+            Err(err @ RLMError::REPLError(_)) => return Err(err),
+            Err(err) => err,
+            ok => return ok,
+        };
+        let Some(fallback) = &self.local_fallback else {
+            return Err(err);
+        };
+
+        log::warn!(
+            "Remote {} REPL unavailable ({}), running the code locally",
+            self.language,
+            err
+        );
+        self.last_execution_was_local.store(true, Ordering::Relaxed);
+        fallback.execute(code).await
     }
 
     fn language(&self) -> &str {
         &self.language
     }
+
+    fn timeout(&self) -> Duration {
+        self.timeout
+    }
 }
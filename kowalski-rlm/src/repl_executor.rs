@@ -1,10 +1,18 @@
 use async_trait::async_trait;
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
-use std::time::Duration;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::fs;
-use tokio::io::AsyncWriteExt;
-use tokio::process::Command;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio::process::{Child, Command};
+use crate::artifact_cache::ArtifactCache;
+use crate::config::RLMConfig;
 use crate::error::{RLMError, RLMResult};
+use lazy_static::lazy_static;
+use regex::Regex;
+#[cfg(unix)]
+use rustix::process::{kill_process_group, Pid, Signal};
 use uuid::Uuid;
 
 /// Trait for REPL executors
@@ -15,6 +23,81 @@ pub trait REPLExecutor: Send + Sync {
     
     /// Get the language this executor handles
     fn language(&self) -> &str;
+
+    /// Time a single execution may take before it is killed
+    fn timeout(&self) -> Duration;
+}
+
+/// Build a command for a REPL child process.
+///
+/// On Unix the child leads its own process group so that compilers, build
+/// tools and anything the snippet spawns can be killed together on timeout.
+fn repl_command(program: &str) -> Command {
+    let mut command = Command::new(program);
+    command.kill_on_drop(true);
+    #[cfg(unix)]
+    command.process_group(0);
+    command
+}
+
+/// Map a failure to spawn `program` to an error.
+///
+/// A missing command becomes [`RLMError::InterpreterNotFound`] so callers can
+/// tell an uninstalled toolchain apart from other failures.
+fn spawn_error(language: &str, program: &str, e: std::io::Error) -> RLMError {
+    if e.kind() == std::io::ErrorKind::NotFound {
+        RLMError::interpreter_not_found(language, program)
+    } else {
+        RLMError::ExecutionError(format!("Failed to spawn {}: {}", program, e))
+    }
+}
+
+/// Wait for a child to finish, collecting its output.
+///
+/// Returns `Ok(None)` if `timeout` elapses first, after the child and its
+/// process group have been killed and the child reaped.
+async fn wait_or_kill(
+    mut child: Child,
+    timeout: Duration,
+) -> std::io::Result<Option<std::process::Output>> {
+    let stdout = tokio::spawn(read_pipe(child.stdout.take()));
+    let stderr = tokio::spawn(read_pipe(child.stderr.take()));
+
+    let status = match tokio::time::timeout(timeout, child.wait()).await {
+        Ok(status) => status?,
+        Err(_) => {
+            kill_process_tree(&mut child).await;
+            // Orphaned descendants may still hold the pipes open
+            stdout.abort();
+            stderr.abort();
+            return Ok(None);
+        }
+    };
+
+    Ok(Some(std::process::Output {
+        status,
+        stdout: stdout.await.unwrap_or_default(),
+        stderr: stderr.await.unwrap_or_default(),
+    }))
+}
+
+/// Read a child pipe to the end, returning whatever was captured
+async fn read_pipe<R: AsyncRead + Unpin>(pipe: Option<R>) -> Vec<u8> {
+    let mut buf = Vec::new();
+    if let Some(mut pipe) = pipe {
+        let _ = pipe.read_to_end(&mut buf).await;
+    }
+    buf
+}
+
+/// Kill a child together with its process group and reap it
+async fn kill_process_tree(child: &mut Child) {
+    #[cfg(unix)]
+    if let Some(pgid) = child.id().and_then(|id| Pid::from_raw(id as i32)) {
+        let _ = kill_process_group(pgid, Signal::KILL);
+    }
+    // Kills the direct child on non-Unix platforms and reaps it everywhere
+    let _ = child.kill().await;
 }
 
 /// Python REPL Executor
@@ -23,13 +106,39 @@ pub struct PythonREPL {
 }
 
 /// Rust REPL Executor
+///
+/// Compiled binaries are cached by source, so repeated snippets skip `cargo build`.
+/// In test mode snippets run as a `#[test]` function under `cargo test` instead.
 pub struct RustREPL {
     timeout: Duration,
+    artifact_cache: Arc<ArtifactCache>,
+    test_mode: bool,
 }
 
 /// Java REPL Executor
+///
+/// Compiled classes are cached by source, so repeated snippets skip `javac`.
 pub struct JavaREPL {
     timeout: Duration,
+    artifact_cache: Arc<ArtifactCache>,
+}
+
+/// Kotlin REPL Executor
+pub struct KotlinREPL {
+    timeout: Duration,
+}
+
+/// C# REPL Executor
+///
+/// Snippets are built with `dotnet build` in a console project that is
+/// created once and reused, so only the first call pays for `dotnet restore`.
+/// Calls sharing the project are serialized. Unless a project directory is
+/// given, the project lives in a temporary directory that is removed when
+/// the executor is dropped.
+pub struct CSharpREPL {
+    timeout: Duration,
+    project_dir: Option<PathBuf>,
+    temp_project: std::sync::OnceLock<tempfile::TempDir>,
 }
 
 /// Bash/Shell REPL Executor
@@ -42,13 +151,67 @@ pub struct JavaScriptREPL {
     timeout: Duration,
 }
 
+/// Lua REPL Executor
+pub struct LuaREPL {
+    timeout: Duration,
+}
+
+/// R REPL Executor
+///
+/// Runs snippets with `Rscript`. Printed output and warnings (which R writes
+/// to stderr) are collected separately.
+pub struct RscriptREPL {
+    timeout: Duration,
+}
+
+/// PHP REPL Executor
+///
+/// Runs snippets with the `php` CLI, adding the `<?php` open tag when the
+/// snippet lacks one. Parse errors are reported as
+/// [`RLMError::CompilationFailed`], anything else as
+/// [`RLMError::RuntimeFailed`].
+pub struct PhpREPL {
+    timeout: Duration,
+}
+
+/// Haskell REPL Executor
+///
+/// Runs snippets with `runghc` (or another interpreter such as
+/// `runhaskell`). A snippet without a `main` gets one: its declarations stay
+/// at the top level and the rest becomes `main`, printing a lone pure
+/// expression. Type and scope errors are reported as
+/// [`RLMError::CompilationFailed`], exceptions as [`RLMError::RuntimeFailed`].
+pub struct HaskellREPL {
+    timeout: Duration,
+    interpreter: String,
+}
+
+/// Exit code the container script uses to report a failed compile
+#[cfg(feature = "docker")]
+const DOCKER_COMPILE_FAILED: i32 = 97;
+
+/// C/C++ REPL Executor running in a Docker container
+///
+/// The snippet is written to a temp directory mounted at `/code`, then
+/// compiled and run inside `image`, so it never touches the host. A run
+/// that outlives the timeout has its container killed.
+#[cfg(feature = "docker")]
+pub struct DockerREPL {
+    language: String,
+    image: String,
+    timeout: Duration,
+    memory_limit_mb: Option<u64>,
+}
+
 impl PythonREPL {
+    /// Create an executor with a 30 second timeout
     pub fn new() -> Self {
         PythonREPL {
             timeout: Duration::from_secs(30),
         }
     }
 
+    /// Set the execution timeout
     pub fn with_timeout(mut self, timeout: Duration) -> Self {
         self.timeout = timeout;
         self
@@ -84,19 +247,18 @@ impl REPLExecutor for PythonREPL {
 
         drop(file);
 
-        let child = Command::new("python3")
+        let child = repl_command("python3")
             .arg(&temp_file)
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .spawn()
-            .map_err(|e| RLMError::ExecutionError(format!("Failed to spawn Python: {}", e)))?;
+            .map_err(|e| spawn_error("python", "python3", e))?;
 
-        let output = match tokio::time::timeout(self.timeout, child.wait_with_output()).await {
-            Ok(result) => result.map_err(|e| {
-                RLMError::ExecutionError(format!("Failed to wait for Python: {}", e))
-            })?,
-            Err(_) => {
-                return Err(RLMError::REPLTimeout(self.timeout.as_millis() as u64));
+        let output = match wait_or_kill(child, self.timeout).await {
+            Ok(Some(output)) => output,
+            Ok(None) => return Err(RLMError::REPLTimeout(self.timeout.as_millis() as u64)),
+            Err(e) => {
+                return Err(RLMError::ExecutionError(format!("Failed to wait for Python: {}", e)));
             }
         };
 
@@ -104,10 +266,11 @@ impl REPLExecutor for PythonREPL {
         let stderr = String::from_utf8_lossy(&output.stderr).to_string();
 
         if !output.status.success() && !stderr.is_empty() {
-            return Err(RLMError::REPLError(format!(
-                "Python execution failed:\n{}",
-                stderr
-            )));
+            return Err(RLMError::runtime_failed(
+                "python",
+                output.status.code(),
+                stderr,
+            ));
         }
 
         Ok(if stdout.is_empty() && stderr.is_empty() {
@@ -122,45 +285,130 @@ impl REPLExecutor for PythonREPL {
     fn language(&self) -> &str {
         "python"
     }
+
+    fn timeout(&self) -> Duration {
+        self.timeout
+    }
+}
+
+const RUST_MANIFEST: &str = r#"[package]
+name = "kowalski_rust_exec"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+"#;
+
+const RUST_BINARY: &str = "kowalski_rust_exec";
+
+/// Name of the test generated by [`RustREPL::execute_test`]
+const RUST_TEST_NAME: &str = "kowalski_test::run_test";
+
+lazy_static! {
+    // Matches a `fn main(` definition
+    static ref RUST_FN_MAIN: Regex = Regex::new(r"\bfn\s+main\s*\(").unwrap();
+
+    // Matches the start of an item that must live at file scope
+    static ref RUST_TOP_LEVEL_ITEM: Regex = Regex::new(
+        r"^(?:#!?\[|(?:pub(?:\([^)]*\))?\s+)?(?:use|mod|extern\s+crate|struct|enum|union|trait|impl|unsafe\s+impl|type|const|static|fn|async\s+fn)\b)"
+    )
+    .unwrap();
 }
 
 impl RustREPL {
+    /// Create an executor with a 30 second timeout
     pub fn new() -> Self {
         RustREPL {
             timeout: Duration::from_secs(30),
+            artifact_cache: ArtifactCache::global(),
+            test_mode: false,
         }
     }
 
+    /// Set the execution timeout
     pub fn with_timeout(mut self, timeout: Duration) -> Self {
         self.timeout = timeout;
         self
     }
-}
 
-impl Default for RustREPL {
-    fn default() -> Self {
-        Self::new()
+    /// Use a specific cache for compiled binaries instead of the global one
+    pub fn with_artifact_cache(mut self, cache: Arc<ArtifactCache>) -> Self {
+        self.artifact_cache = cache;
+        self
     }
-}
 
-#[async_trait]
-impl REPLExecutor for RustREPL {
-    async fn execute(&self, code: &str) -> RLMResult<String> {
-        let temp_dir = tempfile::TempDir::new()
-            .map_err(|e| RLMError::ExecutionError(format!("Failed to create temp dir: {}", e)))?;
-        
-        let proj_dir = temp_dir.path().join(format!("proj_{}", Uuid::new_v4()));
+    /// Run snippets as a test function, routing [`REPLExecutor::execute`] to
+    /// [`RustREPL::execute_test`]
+    pub fn with_test_mode(mut self, test_mode: bool) -> Self {
+        self.test_mode = test_mode;
+        self
+    }
+
+    /// Returns true if `code` needs a generated `fn main` around it
+    ///
+    /// Code that already defines `fn main` is compiled verbatim.
+    pub fn detect_needs_wrapping(code: &str) -> bool {
+        !RUST_FN_MAIN.is_match(code)
+    }
+
+    /// Turn a snippet into a complete `main.rs`
+    ///
+    /// Top-level items (`use`, `mod`, `extern crate`, `struct`, `fn`, ...) are
+    /// kept at file scope and the remaining statements become the body of
+    /// `fn main`. Braces are counted per line, so braces inside string
+    /// literals or comments at the top level can confuse the split.
+    fn prepare_source(code: &str) -> String {
+        if !Self::detect_needs_wrapping(code) {
+            return code.to_string();
+        }
+
+        let mut items = Vec::new();
+        let mut statements = Vec::new();
+        let mut depth: i64 = 0;
+        let mut in_item = false;
+
+        for line in code.lines() {
+            let trimmed = line.trim();
+            if depth == 0 && !in_item && RUST_TOP_LEVEL_ITEM.is_match(trimmed) {
+                in_item = true;
+            }
+
+            if in_item {
+                items.push(line);
+            } else {
+                statements.push(line);
+            }
+
+            depth += line.matches('{').count() as i64 - line.matches('}').count() as i64;
+            if in_item && depth <= 0 && (trimmed.ends_with(';') || trimmed.ends_with('}')) {
+                in_item = false;
+                depth = 0;
+            }
+        }
+
+        format!("{}\nfn main() {{\n{}\n}}", items.join("\n"), statements.join("\n"))
+    }
+
+    /// Turn a snippet into a `main.rs` whose only test runs the snippet
+    ///
+    /// Items are allowed inside function bodies, so the snippet is used as the
+    /// test body without hoisting anything.
+    fn prepare_test_source(code: &str) -> String {
+        format!(
+            "fn main() {{}}\n\n#[cfg(test)]\nmod kowalski_test {{\n    #[allow(unused_imports)]\n    use super::*;\n\n    #[test]\n    fn run_test() {{\n{}\n    }}\n}}\n",
+            code
+        )
+    }
+
+    /// Write a Cargo project for `main_content` into `temp_dir`
+    ///
+    /// Returns the manifest path and the target directory to build into.
+    async fn write_project(temp_dir: &Path, main_content: &str) -> RLMResult<(PathBuf, PathBuf)> {
+        let proj_dir = temp_dir.join(format!("proj_{}", Uuid::new_v4()));
         let _ = fs::create_dir_all(&proj_dir).await;
 
         let cargo_toml = proj_dir.join("Cargo.toml");
-        let manifest = r#"[package]
-name = "kowalski_rust_exec"
-version = "0.1.0"
-edition = "2021"
-
-[dependencies]
-"#;
-        fs::write(&cargo_toml, manifest)
+        fs::write(&cargo_toml, RUST_MANIFEST)
             .await
             .map_err(|e| RLMError::ExecutionError(format!("Failed to create Cargo.toml: {}", e)))?;
 
@@ -168,27 +416,156 @@ edition = "2021"
         let _ = fs::create_dir_all(&src_dir).await;
         let main_file = src_dir.join("main.rs");
 
-        let main_content = format!("fn main() {{\n{}\n}}", code);
-        fs::write(&main_file, &main_content)
+        fs::write(&main_file, main_content)
             .await
             .map_err(|e| RLMError::ExecutionError(format!("Failed to write main.rs: {}", e)))?;
 
-        let child = Command::new("cargo")
-            .arg("run")
+        Ok((cargo_toml, proj_dir.join("target")))
+    }
+
+    /// Build the snippet in `temp_dir` and return the path of the binary
+    async fn build(&self, temp_dir: &Path, main_content: &str, deadline: Instant) -> RLMResult<PathBuf> {
+        let (cargo_toml, target_dir) = Self::write_project(temp_dir, main_content).await?;
+        let child = repl_command("cargo")
+            .arg("build")
             .arg("--manifest-path")
             .arg(&cargo_toml)
             .arg("--release")
+            // Pin the output location so an inherited CARGO_TARGET_DIR is ignored
+            .arg("--target-dir")
+            .arg(&target_dir)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| spawn_error("rust", "cargo", e))?;
+
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        let output = match wait_or_kill(child, remaining).await {
+            Ok(Some(output)) => output,
+            Ok(None) => return Err(RLMError::REPLTimeout(self.timeout.as_millis() as u64)),
+            Err(e) => {
+                return Err(RLMError::ExecutionError(format!("Failed to wait for Rust: {}", e)));
+            }
+        };
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+            return Err(RLMError::compilation_failed("rust", stderr));
+        }
+
+        Ok(target_dir
+            .join("release")
+            .join(format!("{}{}", RUST_BINARY, std::env::consts::EXE_SUFFIX)))
+    }
+
+    /// Run `code` as the body of a `#[test]` function under `cargo test`
+    ///
+    /// Returns the test harness output, ending with the `test result:` line.
+    /// Test binaries are not cached, so every call compiles.
+    ///
+    /// # Errors
+    ///
+    /// A snippet that does not compile yields [`RLMError::CompilationFailed`];
+    /// a failing test yields [`RLMError::RuntimeFailed`] carrying the harness
+    /// output and the panic message.
+    pub async fn execute_test(&self, code: &str) -> RLMResult<String> {
+        let temp_dir = tempfile::TempDir::new()
+            .map_err(|e| RLMError::ExecutionError(format!("Failed to create temp dir: {}", e)))?;
+
+        let main_content = Self::prepare_test_source(code);
+        let (cargo_toml, target_dir) = Self::write_project(temp_dir.path(), &main_content).await?;
+
+        let child = repl_command("cargo")
+            .arg("test")
+            .arg("--manifest-path")
+            .arg(&cargo_toml)
+            .arg("--target-dir")
+            .arg(&target_dir)
+            .arg("--")
+            .arg(RUST_TEST_NAME)
+            .arg("--nocapture")
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| spawn_error("rust", "cargo", e))?;
+
+        let output = match wait_or_kill(child, self.timeout).await {
+            Ok(Some(output)) => output,
+            Ok(None) => return Err(RLMError::REPLTimeout(self.timeout.as_millis() as u64)),
+            Err(e) => {
+                return Err(RLMError::ExecutionError(format!("Failed to wait for Rust: {}", e)));
+            }
+        };
+
+        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+
+        if output.status.success() {
+            return Ok(stdout);
+        }
+
+        // The harness only prints a result line once the test binary has run
+        if stdout.contains("test result:") {
+            Err(RLMError::runtime_failed(
+                "rust",
+                output.status.code(),
+                format!("{}{}", stdout, stderr),
+            ))
+        } else {
+            Err(RLMError::compilation_failed("rust", stderr))
+        }
+    }
+}
+
+impl Default for RustREPL {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl REPLExecutor for RustREPL {
+    async fn execute(&self, code: &str) -> RLMResult<String> {
+        if self.test_mode {
+            return self.execute_test(code).await;
+        }
+
+        // The timeout covers compilation and execution together
+        let deadline = Instant::now() + self.timeout;
+
+        let temp_dir = tempfile::TempDir::new()
+            .map_err(|e| RLMError::ExecutionError(format!("Failed to create temp dir: {}", e)))?;
+
+        let main_content = Self::prepare_source(code);
+        let key = ArtifactCache::key_for("rust", &[RUST_MANIFEST, &main_content]);
+
+        // The lease keeps a cached binary from being evicted while it runs
+        let lease = self.artifact_cache.lookup(&key);
+        let binary = match &lease {
+            Some(lease) => lease
+                .path()
+                .join(format!("{}{}", RUST_BINARY, std::env::consts::EXE_SUFFIX)),
+            None => {
+                let binary = self.build(temp_dir.path(), &main_content, deadline).await?;
+                if let Err(e) = self.artifact_cache.store(&key, std::slice::from_ref(&binary)) {
+                    log::warn!("Failed to cache Rust binary: {}", e);
+                }
+                binary
+            }
+        };
+
+        let child = repl_command(&binary.to_string_lossy())
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .spawn()
             .map_err(|e| RLMError::ExecutionError(format!("Failed to spawn Rust: {}", e)))?;
 
-        let output = match tokio::time::timeout(self.timeout, child.wait_with_output()).await {
-            Ok(result) => result.map_err(|e| {
-                RLMError::ExecutionError(format!("Failed to wait for Rust: {}", e))
-            })?,
-            Err(_) => {
-                return Err(RLMError::REPLTimeout(self.timeout.as_millis() as u64));
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        let output = match wait_or_kill(child, remaining).await {
+            Ok(Some(output)) => output,
+            Ok(None) => return Err(RLMError::REPLTimeout(self.timeout.as_millis() as u64)),
+            Err(e) => {
+                return Err(RLMError::ExecutionError(format!("Failed to wait for Rust: {}", e)));
             }
         };
 
@@ -196,10 +573,11 @@ edition = "2021"
         let stderr = String::from_utf8_lossy(&output.stderr).to_string();
 
         if !output.status.success() && !stderr.is_empty() {
-            return Err(RLMError::REPLError(format!(
-                "Rust compilation/execution failed:\n{}",
-                stderr
-            )));
+            return Err(RLMError::runtime_failed(
+                "rust",
+                output.status.code(),
+                stderr,
+            ));
         }
 
         Ok(if stdout.is_empty() && stderr.is_empty() {
@@ -212,19 +590,73 @@ edition = "2021"
     fn language(&self) -> &str {
         "rust"
     }
+
+    fn timeout(&self) -> Duration {
+        self.timeout
+    }
 }
 
 impl JavaREPL {
+    /// Create an executor with a 30 second timeout
     pub fn new() -> Self {
         JavaREPL {
             timeout: Duration::from_secs(30),
+            artifact_cache: ArtifactCache::global(),
         }
     }
 
+    /// Set the execution timeout
     pub fn with_timeout(mut self, timeout: Duration) -> Self {
         self.timeout = timeout;
         self
     }
+
+    /// Use a specific cache for compiled classes instead of the global one
+    pub fn with_artifact_cache(mut self, cache: Arc<ArtifactCache>) -> Self {
+        self.artifact_cache = cache;
+        self
+    }
+
+    /// Compile `java_code` in `temp_dir` and return the produced class files
+    async fn compile(&self, temp_dir: &Path, class_name: &str, java_code: &str, deadline: Instant) -> RLMResult<Vec<PathBuf>> {
+        let java_file = temp_dir.join(format!("{}.java", class_name));
+
+        fs::write(&java_file, java_code)
+            .await
+            .map_err(|e| RLMError::ExecutionError(format!("Failed to write Java file: {}", e)))?;
+
+        let javac_child = repl_command("javac")
+            .arg(&java_file)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| spawn_error("java", "javac", e))?;
+
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        let compile_output = match wait_or_kill(javac_child, remaining).await {
+            Ok(Some(output)) => output,
+            Ok(None) => return Err(RLMError::REPLTimeout(self.timeout.as_millis() as u64)),
+            Err(e) => {
+                return Err(RLMError::ExecutionError(format!("Failed to wait for javac: {}", e)));
+            }
+        };
+
+        if !compile_output.status.success() {
+            let stderr = String::from_utf8_lossy(&compile_output.stderr).to_string();
+            return Err(RLMError::compilation_failed("java", stderr));
+        }
+
+        // Snippets with lambdas or inner classes produce several class files
+        let mut classes = Vec::new();
+        let mut entries = fs::read_dir(temp_dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "class") {
+                classes.push(path);
+            }
+        }
+        Ok(classes)
+    }
 }
 
 impl Default for JavaREPL {
@@ -236,58 +668,48 @@ impl Default for JavaREPL {
 #[async_trait]
 impl REPLExecutor for JavaREPL {
     async fn execute(&self, code: &str) -> RLMResult<String> {
+        let deadline = Instant::now() + self.timeout;
+
         let temp_dir = tempfile::TempDir::new()
             .map_err(|e| RLMError::ExecutionError(format!("Failed to create temp dir: {}", e)))?;
 
-        let uuid = Uuid::new_v4().to_string().replace("-", "");
-        let class_name = format!("Kowalski{}", &uuid[0..8]);
-        let java_file = temp_dir.path().join(format!("{}.java", class_name));
+        // The class name is derived from the source so cached classes stay loadable
+        let key = ArtifactCache::key_for("java", &[code]);
+        let class_name = format!("Kowalski{}", key.rsplit('-').next().unwrap_or_default());
 
         let java_code = format!(
             "public class {} {{\n    public static void main(String[] args) {{\n        {}\n    }}\n}}",
             class_name, code
         );
 
-        fs::write(&java_file, &java_code)
-            .await
-            .map_err(|e| RLMError::ExecutionError(format!("Failed to write Java file: {}", e)))?;
-
-        let javac_child = Command::new("javac")
-            .arg(&java_file)
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()
-            .map_err(|e| RLMError::ExecutionError(format!("Failed to spawn javac: {}", e)))?;
-
-        let compile_output = match tokio::time::timeout(self.timeout, javac_child.wait_with_output()).await {
-            Ok(result) => result.map_err(|e| {
-                RLMError::ExecutionError(format!("Failed to wait for javac: {}", e))
-            })?,
-            Err(_) => {
-                return Err(RLMError::REPLTimeout(self.timeout.as_millis() as u64));
+        // The lease keeps cached classes from being evicted while they run
+        let lease = self.artifact_cache.lookup(&key);
+        let class_path = match &lease {
+            Some(lease) => lease.path().to_path_buf(),
+            None => {
+                let classes = self.compile(temp_dir.path(), &class_name, &java_code, deadline).await?;
+                if let Err(e) = self.artifact_cache.store(&key, &classes) {
+                    log::warn!("Failed to cache Java classes: {}", e);
+                }
+                temp_dir.path().to_path_buf()
             }
         };
 
-        if !compile_output.status.success() {
-            let stderr = String::from_utf8_lossy(&compile_output.stderr).to_string();
-            return Err(RLMError::REPLError(format!("Java compilation failed:\n{}", stderr)));
-        }
-
-        let java_child = Command::new("java")
+        let java_child = repl_command("java")
             .arg("-cp")
-            .arg(temp_dir.path())
+            .arg(&class_path)
             .arg(&class_name)
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .spawn()
-            .map_err(|e| RLMError::ExecutionError(format!("Failed to spawn java: {}", e)))?;
-
-        let output = match tokio::time::timeout(self.timeout, java_child.wait_with_output()).await {
-            Ok(result) => result.map_err(|e| {
-                RLMError::ExecutionError(format!("Failed to wait for java: {}", e))
-            })?,
-            Err(_) => {
-                return Err(RLMError::REPLTimeout(self.timeout.as_millis() as u64));
+            .map_err(|e| spawn_error("java", "java", e))?;
+
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        let output = match wait_or_kill(java_child, remaining).await {
+            Ok(Some(output)) => output,
+            Ok(None) => return Err(RLMError::REPLTimeout(self.timeout.as_millis() as u64)),
+            Err(e) => {
+                return Err(RLMError::ExecutionError(format!("Failed to wait for java: {}", e)));
             }
         };
 
@@ -295,10 +717,11 @@ impl REPLExecutor for JavaREPL {
         let stderr = String::from_utf8_lossy(&output.stderr).to_string();
 
         if !output.status.success() && !stderr.is_empty() {
-            return Err(RLMError::REPLError(format!(
-                "Java execution failed:\n{}",
-                stderr
-            )));
+            return Err(RLMError::runtime_failed(
+                "java",
+                output.status.code(),
+                stderr,
+            ));
         }
 
         Ok(if stdout.is_empty() && stderr.is_empty() {
@@ -311,52 +734,140 @@ impl REPLExecutor for JavaREPL {
     fn language(&self) -> &str {
         "java"
     }
+
+    fn timeout(&self) -> Duration {
+        self.timeout
+    }
 }
 
-impl BashREPL {
+impl KotlinREPL {
+    /// Create an executor with a 60 second timeout
     pub fn new() -> Self {
-        BashREPL {
-            timeout: Duration::from_secs(30),
+        KotlinREPL {
+            timeout: Duration::from_secs(60),
         }
     }
 
+    /// Set the execution timeout
     pub fn with_timeout(mut self, timeout: Duration) -> Self {
         self.timeout = timeout;
         self
     }
+
+    /// Turn a snippet into a complete Kotlin file
+    ///
+    /// Code that defines `fun main` is compiled verbatim. Otherwise top-level
+    /// declarations (`import`, `fun`, `class`, `object`, ...) are kept at file
+    /// scope and the remaining statements become the body of `fun main`.
+    /// Brackets are counted per line, so brackets inside string literals or
+    /// comments at the top level can confuse the split.
+    fn prepare_source(code: &str) -> String {
+        if KOTLIN_FUN_MAIN.is_match(code) {
+            return code.to_string();
+        }
+
+        let mut declarations = Vec::new();
+        let mut statements = Vec::new();
+        let mut depth: i64 = 0;
+        let mut in_declaration = false;
+
+        for line in code.lines() {
+            if depth == 0 && !in_declaration && KOTLIN_TOP_LEVEL_DECLARATION.is_match(line.trim()) {
+                in_declaration = true;
+            }
+
+            if in_declaration {
+                declarations.push(line);
+            } else {
+                statements.push(line);
+            }
+
+            depth += line.matches(['{', '(']).count() as i64 - line.matches(['}', ')']).count() as i64;
+            if in_declaration && depth <= 0 {
+                in_declaration = false;
+                depth = 0;
+            }
+        }
+
+        format!("{}\nfun main() {{\n{}\n}}", declarations.join("\n"), statements.join("\n"))
+    }
 }
 
-impl Default for BashREPL {
+lazy_static! {
+    // Matches a `fun main(` definition
+    static ref KOTLIN_FUN_MAIN: Regex = Regex::new(r"\bfun\s+main\s*\(").unwrap();
+
+    // Matches the start of a declaration that must (or may) live at file scope
+    static ref KOTLIN_TOP_LEVEL_DECLARATION: Regex = Regex::new(
+        r"^(?:@\w+|(?:(?:public|private|internal|data|enum|sealed|abstract|open|inline|value|annotation|suspend|tailrec|operator|infix)\s+)*(?:package|import|fun|class|interface|object|typealias)\b)"
+    )
+    .unwrap();
+}
+
+impl Default for KotlinREPL {
     fn default() -> Self {
         Self::new()
     }
 }
 
 #[async_trait]
-impl REPLExecutor for BashREPL {
+impl REPLExecutor for KotlinREPL {
     async fn execute(&self, code: &str) -> RLMResult<String> {
+        // The timeout covers compilation and execution together
+        let deadline = Instant::now() + self.timeout;
+
         let temp_dir = tempfile::TempDir::new()
             .map_err(|e| RLMError::ExecutionError(format!("Failed to create temp dir: {}", e)))?;
 
-        let bash_file = temp_dir.path().join(format!("{}.sh", Uuid::new_v4()));
+        let uuid = Uuid::new_v4().to_string().replace("-", "");
+        let file_stem = format!("Kowalski{}", &uuid[0..8]);
+        let kotlin_file = temp_dir.path().join(format!("{}.kt", file_stem));
+        let jar_file = temp_dir.path().join(format!("{}.jar", file_stem));
 
-        fs::write(&bash_file, code)
+        let kotlin_code = Self::prepare_source(code);
+
+        fs::write(&kotlin_file, &kotlin_code)
             .await
-            .map_err(|e| RLMError::ExecutionError(format!("Failed to write bash script: {}", e)))?;
+            .map_err(|e| RLMError::ExecutionError(format!("Failed to write Kotlin file: {}", e)))?;
 
-        let child = Command::new("bash")
-            .arg(&bash_file)
+        let kotlinc_child = repl_command("kotlinc")
+            .arg(&kotlin_file)
+            .arg("-include-runtime")
+            .arg("-d")
+            .arg(&jar_file)
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .spawn()
-            .map_err(|e| RLMError::ExecutionError(format!("Failed to spawn bash: {}", e)))?;
+            .map_err(|e| spawn_error("kotlin", "kotlinc", e))?;
+
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        let compile_output = match wait_or_kill(kotlinc_child, remaining).await {
+            Ok(Some(output)) => output,
+            Ok(None) => return Err(RLMError::REPLTimeout(self.timeout.as_millis() as u64)),
+            Err(e) => {
+                return Err(RLMError::ExecutionError(format!("Failed to wait for kotlinc: {}", e)));
+            }
+        };
 
-        let output = match tokio::time::timeout(self.timeout, child.wait_with_output()).await {
-            Ok(result) => result.map_err(|e| {
-                RLMError::ExecutionError(format!("Failed to wait for bash: {}", e))
-            })?,
-            Err(_) => {
-                return Err(RLMError::REPLTimeout(self.timeout.as_millis() as u64));
+        if !compile_output.status.success() {
+            let stderr = String::from_utf8_lossy(&compile_output.stderr).to_string();
+            return Err(RLMError::compilation_failed("kotlin", stderr));
+        }
+
+        let java_child = repl_command("java")
+            .arg("-jar")
+            .arg(&jar_file)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| spawn_error("kotlin", "java", e))?;
+
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        let output = match wait_or_kill(java_child, remaining).await {
+            Ok(Some(output)) => output,
+            Ok(None) => return Err(RLMError::REPLTimeout(self.timeout.as_millis() as u64)),
+            Err(e) => {
+                return Err(RLMError::ExecutionError(format!("Failed to wait for java: {}", e)));
             }
         };
 
@@ -364,10 +875,11 @@ impl REPLExecutor for BashREPL {
         let stderr = String::from_utf8_lossy(&output.stderr).to_string();
 
         if !output.status.success() && !stderr.is_empty() {
-            return Err(RLMError::REPLError(format!(
-                "Bash execution failed:\n{}",
-                stderr
-            )));
+            return Err(RLMError::runtime_failed(
+                "kotlin",
+                output.status.code(),
+                stderr,
+            ));
         }
 
         Ok(if stdout.is_empty() && stderr.is_empty() {
@@ -378,65 +890,904 @@ impl REPLExecutor for BashREPL {
     }
 
     fn language(&self) -> &str {
-        "bash"
+        "kotlin"
+    }
+
+    fn timeout(&self) -> Duration {
+        self.timeout
     }
 }
 
-impl JavaScriptREPL {
+/// Name of the generated C# project
+const CSHARP_PROJECT: &str = "kowalski_csharp_exec";
+
+lazy_static! {
+    // Serializes builds and runs in the shared C# project
+    static ref CSHARP_PROJECT_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::new(());
+
+    // Matches a `Main` entry point, which rules out top-level statements
+    static ref CSHARP_MAIN: Regex =
+        Regex::new(r"\bstatic\s+(?:async\s+)?(?:void|int|Task(?:<int>)?)\s+Main\s*\(").unwrap();
+
+    // Matches a using directive (not a `using var` or `using (...)` statement)
+    static ref CSHARP_USING_DIRECTIVE: Regex =
+        Regex::new(r"^(?:global\s+)?using\s+(?:static\s+)?[\w.]+(?:\s*=\s*[\w.<>, ]+)?\s*;$").unwrap();
+}
+
+/// `dotnet` command without the first-run banner and telemetry notice
+fn dotnet_command() -> Command {
+    let mut command = repl_command("dotnet");
+    command
+        .env("DOTNET_NOLOGO", "1")
+        .env("DOTNET_CLI_TELEMETRY_OPTOUT", "1")
+        .env("DOTNET_SKIP_FIRST_TIME_EXPERIENCE", "1");
+    command
+}
+
+impl CSharpREPL {
+    /// Create an executor with a 60 second timeout
     pub fn new() -> Self {
-        JavaScriptREPL {
-            timeout: Duration::from_secs(30),
+        CSharpREPL {
+            timeout: Duration::from_secs(60),
+            project_dir: None,
+            temp_project: std::sync::OnceLock::new(),
         }
     }
 
+    /// Set the execution timeout
     pub fn with_timeout(mut self, timeout: Duration) -> Self {
         self.timeout = timeout;
         self
     }
-}
 
-impl Default for JavaScriptREPL {
-    fn default() -> Self {
-        Self::new()
+    /// Build in `dir` instead of a temporary project directory
+    ///
+    /// The directory is left in place, so later executors can reuse the
+    /// restored project.
+    pub fn with_project_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.project_dir = Some(dir.into());
+        self
     }
-}
 
-#[async_trait]
-impl REPLExecutor for JavaScriptREPL {
-    async fn execute(&self, code: &str) -> RLMResult<String> {
-        let temp_dir = tempfile::TempDir::new()
+    /// Directory of the console project, creating the temporary one on first use
+    fn project_dir(&self) -> RLMResult<PathBuf> {
+        if let Some(dir) = &self.project_dir {
+            return Ok(dir.clone());
+        }
+        if let Some(temp_dir) = self.temp_project.get() {
+            return Ok(temp_dir.path().to_path_buf());
+        }
+
+        let temp_dir = tempfile::Builder::new()
+            .prefix("kowalski-csharp-")
+            .tempdir()
             .map_err(|e| RLMError::ExecutionError(format!("Failed to create temp dir: {}", e)))?;
+        Ok(self.temp_project.get_or_init(|| temp_dir).path().to_path_buf())
+    }
 
-        let js_file = temp_dir.path().join(format!("{}.js", Uuid::new_v4()));
+    /// Turn a snippet into a top-level program
+    ///
+    /// Using directives must precede top-level statements, so they are moved
+    /// to the top. Code that defines its own `Main` is compiled verbatim.
+    fn prepare_source(code: &str) -> String {
+        if CSHARP_MAIN.is_match(code) {
+            return code.to_string();
+        }
+
+        let (usings, statements): (Vec<&str>, Vec<&str>) = code
+            .lines()
+            .partition(|line| CSHARP_USING_DIRECTIVE.is_match(line.trim()));
+
+        if usings.is_empty() {
+            code.to_string()
+        } else {
+            format!("{}\n\n{}", usings.join("\n"), statements.join("\n"))
+        }
+    }
+
+    /// Wait for a `dotnet` child, mapping a timeout to [`RLMError::REPLTimeout`]
+    async fn wait(&self, child: Child, deadline: Instant) -> RLMResult<std::process::Output> {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        match wait_or_kill(child, remaining).await {
+            Ok(Some(output)) => Ok(output),
+            Ok(None) => Err(RLMError::REPLTimeout(self.timeout.as_millis() as u64)),
+            Err(e) => Err(RLMError::ExecutionError(format!("Failed to wait for dotnet: {}", e))),
+        }
+    }
+
+    /// Create the console project in `project_dir` unless an earlier call already did
+    async fn ensure_project(&self, project_dir: &Path, deadline: Instant) -> RLMResult<()> {
+        if project_dir.join(format!("{}.csproj", CSHARP_PROJECT)).exists() {
+            return Ok(());
+        }
+
+        let child = dotnet_command()
+            .args(["new", "console", "--force", "-n", CSHARP_PROJECT, "-o"])
+            .arg(project_dir)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| spawn_error("csharp", "dotnet", e))?;
+
+        let output = self.wait(child, deadline).await?;
+        if !output.status.success() {
+            return Err(RLMError::ExecutionError(format!(
+                "Failed to create C# project: {}",
+                String::from_utf8_lossy(&output.stderr)
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl Default for CSharpREPL {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl REPLExecutor for CSharpREPL {
+    async fn execute(&self, code: &str) -> RLMResult<String> {
+        // The timeout covers project setup, compilation and execution together
+        let deadline = Instant::now() + self.timeout;
+        let _project = CSHARP_PROJECT_LOCK.lock().await;
+
+        let project_dir = self.project_dir()?;
+        self.ensure_project(&project_dir, deadline).await?;
+
+        fs::write(project_dir.join("Program.cs"), Self::prepare_source(code))
+            .await
+            .map_err(|e| RLMError::ExecutionError(format!("Failed to write C# file: {}", e)))?;
+
+        let build_child = dotnet_command()
+            .arg("build")
+            .arg(&project_dir)
+            .args(["-nologo", "-v", "q", "-clp:NoSummary"])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| spawn_error("csharp", "dotnet", e))?;
+
+        let build_output = self.wait(build_child, deadline).await?;
+        if !build_output.status.success() {
+            // MSBuild reports compiler errors on stdout
+            let diagnostics = format!(
+                "{}{}",
+                String::from_utf8_lossy(&build_output.stdout),
+                String::from_utf8_lossy(&build_output.stderr)
+            );
+            return Err(RLMError::compilation_failed("csharp", diagnostics));
+        }
+
+        let run_child = dotnet_command()
+            .args(["run", "--no-build", "--project"])
+            .arg(&project_dir)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| spawn_error("csharp", "dotnet", e))?;
+
+        let output = self.wait(run_child, deadline).await?;
+
+        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+
+        if !output.status.success() && !stderr.is_empty() {
+            return Err(RLMError::runtime_failed(
+                "csharp",
+                output.status.code(),
+                stderr,
+            ));
+        }
+
+        Ok(if stdout.is_empty() && stderr.is_empty() {
+            "(no output)".to_string()
+        } else {
+            stdout
+        })
+    }
+
+    fn language(&self) -> &str {
+        "csharp"
+    }
+
+    fn timeout(&self) -> Duration {
+        self.timeout
+    }
+}
+
+impl BashREPL {
+    /// Create an executor with a 30 second timeout
+    pub fn new() -> Self {
+        BashREPL {
+            timeout: Duration::from_secs(30),
+        }
+    }
+
+    /// Set the execution timeout
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+impl Default for BashREPL {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl REPLExecutor for BashREPL {
+    async fn execute(&self, code: &str) -> RLMResult<String> {
+        let temp_dir = tempfile::TempDir::new()
+            .map_err(|e| RLMError::ExecutionError(format!("Failed to create temp dir: {}", e)))?;
+
+        let bash_file = temp_dir.path().join(format!("{}.sh", Uuid::new_v4()));
+
+        fs::write(&bash_file, code)
+            .await
+            .map_err(|e| RLMError::ExecutionError(format!("Failed to write bash script: {}", e)))?;
+
+        let child = repl_command("bash")
+            .arg(&bash_file)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| spawn_error("bash", "bash", e))?;
+
+        let output = match wait_or_kill(child, self.timeout).await {
+            Ok(Some(output)) => output,
+            Ok(None) => return Err(RLMError::REPLTimeout(self.timeout.as_millis() as u64)),
+            Err(e) => {
+                return Err(RLMError::ExecutionError(format!("Failed to wait for bash: {}", e)));
+            }
+        };
+
+        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+
+        if !output.status.success() && !stderr.is_empty() {
+            return Err(RLMError::runtime_failed(
+                "bash",
+                output.status.code(),
+                stderr,
+            ));
+        }
+
+        Ok(if stdout.is_empty() && stderr.is_empty() {
+            "(no output)".to_string()
+        } else {
+            stdout
+        })
+    }
+
+    fn language(&self) -> &str {
+        "bash"
+    }
+
+    fn timeout(&self) -> Duration {
+        self.timeout
+    }
+}
+
+impl JavaScriptREPL {
+    /// Create an executor with a 30 second timeout
+    pub fn new() -> Self {
+        JavaScriptREPL {
+            timeout: Duration::from_secs(30),
+        }
+    }
+
+    /// Set the execution timeout
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+impl Default for JavaScriptREPL {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl REPLExecutor for JavaScriptREPL {
+    async fn execute(&self, code: &str) -> RLMResult<String> {
+        let temp_dir = tempfile::TempDir::new()
+            .map_err(|e| RLMError::ExecutionError(format!("Failed to create temp dir: {}", e)))?;
+
+        let js_file = temp_dir.path().join(format!("{}.js", Uuid::new_v4()));
+
+        fs::write(&js_file, code)
+            .await
+            .map_err(|e| RLMError::ExecutionError(format!("Failed to write JS file: {}", e)))?;
+
+        let child = repl_command("node")
+            .arg(&js_file)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| spawn_error("javascript", "node", e))?;
+
+        let output = match wait_or_kill(child, self.timeout).await {
+            Ok(Some(output)) => output,
+            Ok(None) => return Err(RLMError::REPLTimeout(self.timeout.as_millis() as u64)),
+            Err(e) => {
+                return Err(RLMError::ExecutionError(format!("Failed to wait for Node.js: {}", e)));
+            }
+        };
+
+        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+
+        if !output.status.success() && !stderr.is_empty() {
+            return Err(RLMError::runtime_failed(
+                "javascript",
+                output.status.code(),
+                stderr,
+            ));
+        }
+
+        Ok(if stdout.is_empty() && stderr.is_empty() {
+            "(no output)".to_string()
+        } else {
+            stdout
+        })
+    }
+
+    fn language(&self) -> &str {
+        "javascript"
+    }
+
+    fn timeout(&self) -> Duration {
+        self.timeout
+    }
+}
+
+impl LuaREPL {
+    /// Create an executor with a 30 second timeout
+    pub fn new() -> Self {
+        LuaREPL {
+            timeout: Duration::from_secs(30),
+        }
+    }
+
+    /// Set the execution timeout
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+impl Default for LuaREPL {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl REPLExecutor for LuaREPL {
+    async fn execute(&self, code: &str) -> RLMResult<String> {
+        let temp_dir = tempfile::TempDir::new()
+            .map_err(|e| RLMError::ExecutionError(format!("Failed to create temp dir: {}", e)))?;
+
+        let lua_file = temp_dir.path().join(format!("{}.lua", Uuid::new_v4()));
+
+        fs::write(&lua_file, code)
+            .await
+            .map_err(|e| RLMError::ExecutionError(format!("Failed to write Lua file: {}", e)))?;
+
+        let child = repl_command("lua")
+            .arg(&lua_file)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| spawn_error("lua", "lua", e))?;
+
+        let output = match wait_or_kill(child, self.timeout).await {
+            Ok(Some(output)) => output,
+            Ok(None) => return Err(RLMError::REPLTimeout(self.timeout.as_millis() as u64)),
+            Err(e) => {
+                return Err(RLMError::ExecutionError(format!("Failed to wait for Lua: {}", e)));
+            }
+        };
+
+        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+
+        // The interpreter reports uncaught errors with a stack traceback on stderr
+        if !output.status.success() && !stderr.is_empty() {
+            return Err(RLMError::runtime_failed(
+                "lua",
+                output.status.code(),
+                stderr,
+            ));
+        }
+
+        Ok(if stdout.is_empty() && stderr.is_empty() {
+            "(no output)".to_string()
+        } else {
+            stdout
+        })
+    }
+
+    fn language(&self) -> &str {
+        "lua"
+    }
+
+    fn timeout(&self) -> Duration {
+        self.timeout
+    }
+}
+
+impl RscriptREPL {
+    /// Create an executor with a 30 second timeout
+    pub fn new() -> Self {
+        RscriptREPL {
+            timeout: Duration::from_secs(30),
+        }
+    }
+
+    /// Set the execution timeout
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Combine printed output with any warnings, keeping them apart
+    fn format_output(stdout: &str, warnings: &str) -> String {
+        let warnings = warnings.trim();
+        match (stdout.is_empty(), warnings.is_empty()) {
+            (true, true) => "(no output)".to_string(),
+            (_, true) => stdout.to_string(),
+            (true, false) => format!("Warnings:\n{}", warnings),
+            (false, false) => format!("{}\nWarnings:\n{}", stdout.trim_end(), warnings),
+        }
+    }
+}
+
+impl Default for RscriptREPL {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl REPLExecutor for RscriptREPL {
+    async fn execute(&self, code: &str) -> RLMResult<String> {
+        let temp_dir = tempfile::TempDir::new()
+            .map_err(|e| RLMError::ExecutionError(format!("Failed to create temp dir: {}", e)))?;
+
+        let r_file = temp_dir.path().join(format!("{}.R", Uuid::new_v4()));
+
+        fs::write(&r_file, code)
+            .await
+            .map_err(|e| RLMError::ExecutionError(format!("Failed to write R script: {}", e)))?;
+
+        let child = repl_command("Rscript")
+            .arg("--vanilla")
+            .arg(&r_file)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| spawn_error("r", "Rscript", e))?;
+
+        let output = match wait_or_kill(child, self.timeout).await {
+            Ok(Some(output)) => output,
+            Ok(None) => return Err(RLMError::REPLTimeout(self.timeout.as_millis() as u64)),
+            Err(e) => {
+                return Err(RLMError::ExecutionError(format!("Failed to wait for Rscript: {}", e)));
+            }
+        };
+
+        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+
+        if !output.status.success() && !stderr.is_empty() {
+            return Err(RLMError::runtime_failed(
+                "r",
+                output.status.code(),
+                stderr,
+            ));
+        }
+
+        Ok(Self::format_output(&stdout, &stderr))
+    }
+
+    fn language(&self) -> &str {
+        "r"
+    }
+
+    fn timeout(&self) -> Duration {
+        self.timeout
+    }
+}
+
+impl PhpREPL {
+    /// Create an executor with a 30 second timeout
+    pub fn new() -> Self {
+        PhpREPL {
+            timeout: Duration::from_secs(30),
+        }
+    }
+
+    /// Set the execution timeout
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Prefix `code` with the open tag, so it runs as PHP instead of being echoed
+    fn prepare_source(code: &str) -> String {
+        if code.trim_start().starts_with("<?php") {
+            code.to_string()
+        } else {
+            format!("<?php\n{}", code)
+        }
+    }
+}
+
+impl Default for PhpREPL {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl REPLExecutor for PhpREPL {
+    async fn execute(&self, code: &str) -> RLMResult<String> {
+        let temp_dir = tempfile::TempDir::new()
+            .map_err(|e| RLMError::ExecutionError(format!("Failed to create temp dir: {}", e)))?;
+
+        let php_file = temp_dir.path().join(format!("{}.php", Uuid::new_v4()));
+
+        fs::write(&php_file, Self::prepare_source(code))
+            .await
+            .map_err(|e| RLMError::ExecutionError(format!("Failed to write PHP file: {}", e)))?;
+
+        // The CLI prints errors to stdout by default; keep them on stderr
+        let child = repl_command("php")
+            .arg("-d")
+            .arg("display_errors=stderr")
+            .arg("-d")
+            .arg("log_errors=0")
+            .arg(&php_file)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| spawn_error("php", "php", e))?;
+
+        let output = match wait_or_kill(child, self.timeout).await {
+            Ok(Some(output)) => output,
+            Ok(None) => return Err(RLMError::REPLTimeout(self.timeout.as_millis() as u64)),
+            Err(e) => {
+                return Err(RLMError::ExecutionError(format!("Failed to wait for PHP: {}", e)));
+            }
+        };
+
+        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+
+        if !output.status.success() {
+            // Nothing runs when the script does not parse
+            if stderr.contains("Parse error") {
+                return Err(RLMError::compilation_failed("php", stderr));
+            }
+            return Err(RLMError::runtime_failed(
+                "php",
+                output.status.code(),
+                if stderr.is_empty() { stdout } else { stderr },
+            ));
+        }
+
+        Ok(if stdout.is_empty() && stderr.is_empty() {
+            "(no output)".to_string()
+        } else {
+            stdout
+        })
+    }
+
+    fn language(&self) -> &str {
+        "php"
+    }
+
+    fn timeout(&self) -> Duration {
+        self.timeout
+    }
+}
+
+lazy_static! {
+    // Matches a top-level `main` signature or definition
+    static ref HASKELL_MAIN: Regex = Regex::new(r"(?m)^main\s*(?:::|=)").unwrap();
+
+    // Matches a line starting a top-level declaration by keyword
+    static ref HASKELL_DECLARATION_KEYWORD: Regex = Regex::new(
+        r"^(?:import|module|data|type|newtype|class|instance|deriving|infix[lr]?)\b|^\{-#"
+    )
+    .unwrap();
+
+    // Matches a `name ... =` binding or `name ::` signature, but not `==` or `/=`
+    static ref HASKELL_BINDING: Regex = Regex::new(
+        r"^(?:[a-z_][\w']*|\([^)]*\))(?:[^=]|==|/=|<=|>=|=>)*?(?:::|(?:^|[^=/<>])=(?:[^=>]|$))"
+    )
+    .unwrap();
+
+    // Matches a GHC diagnostic such as `Main.hs:3:7: error:`
+    static ref HASKELL_COMPILE_ERROR: Regex = Regex::new(r"\.hs:\d+:\d+(?:-\d+)?: error").unwrap();
+
+    // Matches the first word of an expression that is an IO action
+    static ref HASKELL_IO_ACTION: Regex = Regex::new(
+        r"^(?:do|putStr|putStrLn|print|mapM_|forM_|sequence_|interact|when|unless|return|pure)\b"
+    )
+    .unwrap();
+}
+
+/// Expression keywords that can look like a binding (`let x = 1 in x`)
+const HASKELL_EXPRESSION_KEYWORDS: &[&str] = &["let", "if", "case", "do"];
+
+impl HaskellREPL {
+    /// Create an executor running snippets with `runghc` and a 30 second timeout
+    pub fn new() -> Self {
+        HaskellREPL {
+            timeout: Duration::from_secs(30),
+            interpreter: "runghc".to_string(),
+        }
+    }
+
+    /// Set the execution timeout
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Run snippets with `interpreter`, e.g. `runhaskell` or a path to `runghc`
+    pub fn with_interpreter(mut self, interpreter: impl Into<String>) -> Self {
+        self.interpreter = interpreter.into();
+        self
+    }
+
+    /// Returns true if a top-level line starts a declaration rather than an expression
+    fn is_declaration(line: &str) -> bool {
+        if HASKELL_DECLARATION_KEYWORD.is_match(line) {
+            return true;
+        }
+        let first_word = line.split_whitespace().next().unwrap_or_default();
+        let is_expression = HASKELL_EXPRESSION_KEYWORDS.contains(&first_word)
+            || first_word.starts_with('\\');
+        !is_expression && HASKELL_BINDING.is_match(line)
+    }
+
+    /// Turn a snippet into a complete program
+    ///
+    /// Code defining `main` is run verbatim. Otherwise each top-level chunk
+    /// (a line at column 0 with its indented continuation lines) is kept as
+    /// a declaration or collected into `main`: a single pure expression is
+    /// printed with `main = print (...)`, anything else becomes a `do` block.
+    /// Declarations are recognized by their shape, so unusual layouts may
+    /// need an explicit `main`.
+    fn prepare_source(code: &str) -> String {
+        if HASKELL_MAIN.is_match(code) {
+            return code.to_string();
+        }
+
+        let mut declarations: Vec<&str> = Vec::new();
+        let mut body: Vec<&str> = Vec::new();
+        let mut body_chunks = 0;
+        let mut in_declaration = false;
+        for line in code.lines() {
+            let starts_chunk = !line.is_empty() && !line.starts_with(char::is_whitespace);
+            if starts_chunk && !line.starts_with("--") {
+                in_declaration = Self::is_declaration(line);
+                if !in_declaration {
+                    body_chunks += 1;
+                }
+            }
+            if in_declaration {
+                declarations.push(line);
+            } else if !line.trim().is_empty() {
+                body.push(line);
+            }
+        }
+
+        let indented: Vec<String> = body.iter().map(|line| format!("    {}", line)).collect();
+        let main = match body.first() {
+            None => "main = return ()".to_string(),
+            Some(first) if body_chunks == 1 && !HASKELL_IO_ACTION.is_match(first) => {
+                format!("main = print (\n{}\n    )", indented.join("\n"))
+            }
+            Some(_) => format!("main = do\n{}", indented.join("\n")),
+        };
+
+        if declarations.is_empty() {
+            main
+        } else {
+            format!("{}\n\n{}", declarations.join("\n").trim_end(), main)
+        }
+    }
+}
+
+impl Default for HaskellREPL {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-        fs::write(&js_file, code)
+#[async_trait]
+impl REPLExecutor for HaskellREPL {
+    async fn execute(&self, code: &str) -> RLMResult<String> {
+        let temp_dir = tempfile::TempDir::new()
+            .map_err(|e| RLMError::ExecutionError(format!("Failed to create temp dir: {}", e)))?;
+
+        let haskell_file = temp_dir.path().join("Main.hs");
+
+        fs::write(&haskell_file, Self::prepare_source(code))
             .await
-            .map_err(|e| RLMError::ExecutionError(format!("Failed to write JS file: {}", e)))?;
+            .map_err(|e| RLMError::ExecutionError(format!("Failed to write Haskell file: {}", e)))?;
 
-        let mut child = Command::new("node")
-            .arg(&js_file)
+        let child = repl_command(&self.interpreter)
+            .arg(&haskell_file)
+            .current_dir(temp_dir.path())
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .spawn()
-            .map_err(|e| RLMError::ExecutionError(format!("Failed to spawn Node.js: {}", e)))?;
+            .map_err(|e| spawn_error("haskell", &self.interpreter, e))?;
+
+        let output = match wait_or_kill(child, self.timeout).await {
+            Ok(Some(output)) => output,
+            Ok(None) => return Err(RLMError::REPLTimeout(self.timeout.as_millis() as u64)),
+            Err(e) => {
+                return Err(RLMError::ExecutionError(format!("Failed to wait for Haskell: {}", e)));
+            }
+        };
+
+        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+
+        if !output.status.success() {
+            // GHC reports type and scope errors with a source position
+            if HASKELL_COMPILE_ERROR.is_match(&stderr) {
+                return Err(RLMError::compilation_failed("haskell", stderr));
+            }
+            return Err(RLMError::runtime_failed(
+                "haskell",
+                output.status.code(),
+                if stderr.is_empty() { stdout } else { stderr },
+            ));
+        }
+
+        Ok(if stdout.is_empty() && stderr.is_empty() {
+            "(no output)".to_string()
+        } else {
+            stdout
+        })
+    }
+
+    fn language(&self) -> &str {
+        "haskell"
+    }
+
+    fn timeout(&self) -> Duration {
+        self.timeout
+    }
+}
+
+#[cfg(feature = "docker")]
+impl DockerREPL {
+    /// Create an executor compiling `language` ("c" or "cpp") in `image`
+    pub fn new(language: impl Into<String>, image: impl Into<String>) -> Self {
+        DockerREPL {
+            language: language.into(),
+            image: image.into(),
+            timeout: Duration::from_secs(120),
+            memory_limit_mb: None,
+        }
+    }
+
+    /// Set the execution timeout
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Limit the container's memory to `mb` megabytes
+    pub fn with_memory_limit(mut self, mb: u64) -> Self {
+        self.memory_limit_mb = Some(mb);
+        self
+    }
+
+    /// Source file name and compiler for the configured language
+    fn toolchain(&self) -> RLMResult<(&'static str, &'static str)> {
+        match self.language.as_str() {
+            "c" => Ok(("main.c", "gcc")),
+            "cpp" => Ok(("main.cpp", "g++")),
+            other => Err(RLMError::ExecutionError(format!(
+                "Docker executor does not support language: {}",
+                other
+            ))),
+        }
+    }
+
+    /// Arguments for `docker run`, mounting `code_dir` at `/code`
+    fn run_args(&self, code_dir: &Path, container: &str) -> RLMResult<Vec<String>> {
+        let (source, compiler) = self.toolchain()?;
+        let mut args = vec![
+            "run".to_string(),
+            "--rm".to_string(),
+            "--name".to_string(),
+            container.to_string(),
+            "-v".to_string(),
+            format!("{}:/code", code_dir.display()),
+        ];
+        if let Some(mb) = self.memory_limit_mb {
+            args.push("--memory".to_string());
+            args.push(format!("{}m", mb));
+        }
+        args.extend([
+            self.image.clone(),
+            "sh".to_string(),
+            "-c".to_string(),
+            format!(
+                "{} /code/{} -o /code/out || exit {}; exec /code/out",
+                compiler, source, DOCKER_COMPILE_FAILED
+            ),
+        ]);
+        Ok(args)
+    }
+}
+
+#[cfg(feature = "docker")]
+#[async_trait]
+impl REPLExecutor for DockerREPL {
+    async fn execute(&self, code: &str) -> RLMResult<String> {
+        let (source, _) = self.toolchain()?;
+        let temp_dir = tempfile::TempDir::new()
+            .map_err(|e| RLMError::ExecutionError(format!("Failed to create temp dir: {}", e)))?;
+
+        fs::write(temp_dir.path().join(source), code)
+            .await
+            .map_err(|e| RLMError::ExecutionError(format!("Failed to write {} file: {}", self.language, e)))?;
 
-        let output = match tokio::time::timeout(self.timeout, child.wait_with_output()).await {
-            Ok(result) => result.map_err(|e| {
-                RLMError::ExecutionError(format!("Failed to wait for Node.js: {}", e))
-            })?,
-            Err(_) => {
+        let container = format!("kowalski-{}", Uuid::new_v4());
+        let child = repl_command("docker")
+            .args(self.run_args(temp_dir.path(), &container)?)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| spawn_error(&self.language, "docker", e))?;
+
+        let output = match wait_or_kill(child, self.timeout).await {
+            Ok(Some(output)) => output,
+            Ok(None) => {
+                // Killing the docker client leaves the container running
+                let _ = repl_command("docker")
+                    .args(["kill", &container])
+                    .stdout(Stdio::null())
+                    .stderr(Stdio::null())
+                    .status()
+                    .await;
                 return Err(RLMError::REPLTimeout(self.timeout.as_millis() as u64));
             }
+            Err(e) => {
+                return Err(RLMError::ExecutionError(format!("Failed to wait for docker: {}", e)));
+            }
         };
 
         let stdout = String::from_utf8_lossy(&output.stdout).to_string();
         let stderr = String::from_utf8_lossy(&output.stderr).to_string();
 
+        if output.status.code() == Some(DOCKER_COMPILE_FAILED) {
+            return Err(RLMError::compilation_failed(self.language.clone(), stderr));
+        }
+
         if !output.status.success() && !stderr.is_empty() {
-            return Err(RLMError::REPLError(format!(
-                "JavaScript execution failed:\n{}",
-                stderr
-            )));
+            return Err(RLMError::runtime_failed(
+                self.language.clone(),
+                output.status.code(),
+                stderr,
+            ));
         }
 
         Ok(if stdout.is_empty() && stderr.is_empty() {
@@ -447,22 +1798,114 @@ impl REPLExecutor for JavaScriptREPL {
     }
 
     fn language(&self) -> &str {
-        "javascript"
+        &self.language
+    }
+
+    fn timeout(&self) -> Duration {
+        self.timeout
     }
 }
 
+/// Canonical names of the languages [`REPLExecutorFactory`] can create
+#[cfg(not(feature = "docker"))]
+const REGISTERED_LANGUAGES: &[&str] = &[
+    "python", "rust", "java", "kotlin", "csharp", "bash", "javascript", "lua", "r", "php",
+    "haskell",
+];
+
+/// Canonical names of the languages [`REPLExecutorFactory`] can create
+#[cfg(feature = "docker")]
+const REGISTERED_LANGUAGES: &[&str] = &[
+    "python", "rust", "java", "kotlin", "csharp", "bash", "javascript", "lua", "r", "php",
+    "haskell", "c", "cpp",
+];
+
 /// Factory for creating REPL executors
 pub struct REPLExecutorFactory;
 
 impl REPLExecutorFactory {
+    /// Canonical name of every language the factory can create an executor for
+    ///
+    /// Aliases such as `py` or `sh` are accepted by [`create`](Self::create)
+    /// but not listed.
+    pub fn registered_languages() -> &'static [&'static str] {
+        REGISTERED_LANGUAGES
+    }
+
+    /// Canonical name of `language` or one of its aliases, e.g. `python` for `py`
+    ///
+    /// Returns `None` for names the factory does not know. C and C++ are
+    /// known even without the `docker` feature.
+    pub fn canonical_language(language: &str) -> Option<&'static str> {
+        let canonical = match language.to_lowercase().as_str() {
+            "python" | "py" => "python",
+            "rust" | "rs" => "rust",
+            "java" => "java",
+            "kotlin" | "kt" => "kotlin",
+            "csharp" | "cs" | "c#" => "csharp",
+            "bash" | "sh" | "shell" => "bash",
+            "javascript" | "js" => "javascript",
+            "lua" => "lua",
+            "r" | "rscript" => "r",
+            "php" => "php",
+            "haskell" | "hs" => "haskell",
+            "c" => "c",
+            "cpp" | "c++" | "cxx" | "cc" => "cpp",
+            _ => return None,
+        };
+        Some(canonical)
+    }
+
+    /// Smallest program printing `hello world` in `language`, used to warm it up
+    pub(crate) fn hello_world(language: &str) -> Option<&'static str> {
+        let snippet = match Self::canonical_language(language)? {
+            "python" => "print('hello world')",
+            "rust" => r#"println!("hello world");"#,
+            "java" => r#"System.out.println("hello world");"#,
+            "kotlin" => r#"println("hello world")"#,
+            "csharp" => r#"Console.WriteLine("hello world");"#,
+            "bash" => "echo 'hello world'",
+            "javascript" => "console.log('hello world');",
+            "lua" => "print('hello world')",
+            "r" => "cat('hello world\\n')",
+            "php" => "echo \"hello world\\n\";",
+            "haskell" => r#"putStrLn "hello world""#,
+            "c" => "#include <stdio.h>\nint main(void) { puts(\"hello world\"); return 0; }",
+            _ => "#include <iostream>\nint main() { std::cout << \"hello world\" << std::endl; }",
+        };
+        Some(snippet)
+    }
+
     /// Create a REPL executor for the given language
+    ///
+    /// The executor gets the language's default timeout from
+    /// [`default_language_timeouts`](crate::config::default_language_timeouts).
     pub fn create(language: &str) -> RLMResult<Box<dyn REPLExecutor>> {
+        Self::create_with_config(language, &RLMConfig::default())
+    }
+
+    /// Create a REPL executor using the timeouts in `config`
+    pub fn create_with_config(language: &str, config: &RLMConfig) -> RLMResult<Box<dyn REPLExecutor>> {
+        let timeout = |canonical: &str| config.repl_timeout_for(canonical);
         match language.to_lowercase().as_str() {
-            "python" | "py" => Ok(Box::new(PythonREPL::new())),
-            "rust" | "rs" => Ok(Box::new(RustREPL::new())),
-            "java" => Ok(Box::new(JavaREPL::new())),
-            "bash" | "sh" | "shell" => Ok(Box::new(BashREPL::new())),
-            "javascript" | "js" => Ok(Box::new(JavaScriptREPL::new())),
+            "python" | "py" => Ok(Box::new(PythonREPL::new().with_timeout(timeout("python")))),
+            "rust" | "rs" => Ok(Box::new(RustREPL::new().with_timeout(timeout("rust")))),
+            "java" => Ok(Box::new(JavaREPL::new().with_timeout(timeout("java")))),
+            "kotlin" | "kt" => Ok(Box::new(KotlinREPL::new().with_timeout(timeout("kotlin")))),
+            "csharp" | "cs" | "c#" => Ok(Box::new(CSharpREPL::new().with_timeout(timeout("csharp")))),
+            "bash" | "sh" | "shell" => Ok(Box::new(BashREPL::new().with_timeout(timeout("bash")))),
+            "javascript" | "js" => Ok(Box::new(JavaScriptREPL::new().with_timeout(timeout("javascript")))),
+            "lua" => Ok(Box::new(LuaREPL::new().with_timeout(timeout("lua")))),
+            "r" | "rscript" => Ok(Box::new(RscriptREPL::new().with_timeout(timeout("r")))),
+            "php" => Ok(Box::new(PhpREPL::new().with_timeout(timeout("php")))),
+            "haskell" | "hs" => Ok(Box::new(HaskellREPL::new().with_timeout(timeout("haskell")))),
+            #[cfg(feature = "docker")]
+            "c" => Ok(Box::new(DockerREPL::new("c", "gcc:latest").with_timeout(timeout("c")))),
+            // The official gcc image ships g++ as well
+            #[cfg(feature = "docker")]
+            "cpp" | "c++" | "cxx" | "cc" => {
+                Ok(Box::new(DockerREPL::new("cpp", "gcc:latest").with_timeout(timeout("cpp"))))
+            }
             _ => Err(RLMError::ExecutionError(format!(
                 "Unsupported language: {}",
                 language
@@ -493,6 +1936,110 @@ mod tests {
         assert!(output.contains("hello from rust"));
     }
 
+    #[test]
+    fn test_rust_prepare_test_source_wraps_in_test_module() {
+        let source = RustREPL::prepare_test_source("assert_eq!(1 + 1, 2);");
+        assert!(source.starts_with("fn main() {}"));
+        assert!(source.contains("#[cfg(test)]\nmod kowalski_test {"));
+        assert!(source.contains("#[test]\n    fn run_test() {\nassert_eq!(1 + 1, 2);\n    }"));
+    }
+
+    #[tokio::test]
+    #[ignore]  // Requires Rust to be installed
+    async fn test_rust_test_mode_runs_assertion() {
+        let executor = RustREPL::new()
+            .with_timeout(Duration::from_secs(120))
+            .with_test_mode(true);
+
+        let output = executor
+            .execute("let v = vec![1, 2, 3];\nprintln!(\"sum {}\", v.iter().sum::<i32>());\nassert_eq!(v.len(), 3);")
+            .await
+            .unwrap();
+        assert!(output.contains("sum 6"));
+        assert!(output.contains("test result: ok. 1 passed"));
+
+        let err = executor.execute_test("assert_eq!(1, 2, \"numbers differ\");").await.unwrap_err();
+        match err {
+            RLMError::RuntimeFailed { stderr, .. } => {
+                assert!(stderr.contains("test result: FAILED"));
+                assert!(stderr.contains("numbers differ"));
+            }
+            other => panic!("expected a runtime failure, got {:?}", other),
+        }
+    }
+
+    const RUST_WITH_MAIN: &str = "fn main() {\n    println!(\"explicit main\");\n}";
+    const RUST_BARE_STATEMENTS: &str = "let x = 2 + 3;\nprintln!(\"sum {}\", x);";
+    const RUST_WITH_STRUCT: &str = "#[derive(Debug)]\nstruct Point {\n    x: i32,\n    y: i32,\n}\n\nlet p = Point { x: 1, y: 2 };\nprintln!(\"{:?}\", p);";
+
+    #[test]
+    fn test_rust_detect_needs_wrapping() {
+        assert!(!RustREPL::detect_needs_wrapping(RUST_WITH_MAIN));
+        assert!(!RustREPL::detect_needs_wrapping("pub fn main ( ) {}"));
+        assert!(RustREPL::detect_needs_wrapping(RUST_BARE_STATEMENTS));
+        assert!(RustREPL::detect_needs_wrapping(RUST_WITH_STRUCT));
+        assert!(RustREPL::detect_needs_wrapping("use serde::*;\nlet x = 1;"));
+        assert!(RustREPL::detect_needs_wrapping("fn mainly() {}"));
+    }
+
+    #[test]
+    fn test_rust_prepare_source_hoists_items() {
+        assert_eq!(RustREPL::prepare_source(RUST_WITH_MAIN), RUST_WITH_MAIN);
+
+        let source = RustREPL::prepare_source("use serde::*;\nlet x = 1;\nprintln!(\"{}\", x);");
+        assert_eq!(source, "use serde::*;\nfn main() {\nlet x = 1;\nprintln!(\"{}\", x);\n}");
+
+        let source = RustREPL::prepare_source(RUST_WITH_STRUCT);
+        let main_at = source.find("fn main()").unwrap();
+        assert!(source.find("struct Point").unwrap() < main_at);
+        assert!(source.find("#[derive(Debug)]").unwrap() < main_at);
+        assert!(source.find("let p = Point").unwrap() > main_at);
+    }
+
+    #[tokio::test]
+    #[ignore]  // Requires Rust to be installed
+    async fn test_rust_wrapping_variants_compile() {
+        let executor = RustREPL::new().with_timeout(Duration::from_secs(120));
+
+        let output = executor.execute(RUST_WITH_MAIN).await.unwrap();
+        assert!(output.contains("explicit main"));
+
+        let output = executor.execute(RUST_BARE_STATEMENTS).await.unwrap();
+        assert!(output.contains("sum 5"));
+
+        let output = executor.execute(RUST_WITH_STRUCT).await.unwrap();
+        assert!(output.contains("Point { x: 1, y: 2 }"));
+
+        // Snippets build without dependencies, so use std rather than serde
+        let code = "use std::collections::HashMap;\nlet mut m = HashMap::new();\nm.insert(\"k\", 7);\nprintln!(\"k={}\", m[\"k\"]);";
+        let output = executor.execute(code).await.unwrap();
+        assert!(output.contains("k=7"));
+    }
+
+    #[tokio::test]
+    #[ignore]  // Requires Rust to be installed
+    async fn test_rust_repeated_snippet_compiles_once() {
+        let root = tempfile::TempDir::new().unwrap();
+        let cache = Arc::new(ArtifactCache::new(
+            root.path().join("cache"),
+            crate::artifact_cache::ArtifactCacheConfig::default(),
+        ));
+        let executor = RustREPL::new()
+            .with_timeout(Duration::from_secs(120))
+            .with_artifact_cache(Arc::clone(&cache));
+        let code = r#"println!("cached rust");"#;
+
+        let first = executor.execute(code).await.unwrap();
+        let second = executor.execute(code).await.unwrap();
+
+        assert_eq!(first, second);
+        assert!(second.contains("cached rust"));
+        let stats = cache.stats();
+        assert_eq!(stats.misses, 1, "snippet should be compiled exactly once");
+        assert_eq!(stats.hits, 1);
+        assert_eq!(cache.len(), 1);
+    }
+
     #[tokio::test]
     #[ignore]  // Requires Java to be installed
     async fn test_java_simple() {
@@ -502,6 +2049,58 @@ mod tests {
         assert!(output.contains("hello from java"));
     }
 
+    #[tokio::test]
+    #[ignore]  // Requires kotlinc and Java to be installed
+    async fn test_kotlin_simple() {
+        let executor = KotlinREPL::new();
+        let code = r#"println("hello from kotlin")"#;
+        let output = executor.execute(code).await.unwrap();
+        assert!(output.contains("hello from kotlin"));
+    }
+
+    #[tokio::test]
+    #[ignore]  // Requires the .NET SDK to be installed
+    async fn test_csharp_simple() {
+        let executor = CSharpREPL::new();
+        let output = executor.execute(r#"Console.WriteLine("hello from csharp");"#).await.unwrap();
+        assert!(output.contains("hello from csharp"));
+
+        // The second call reuses the restored project
+        let err = executor.execute("int x = ;").await.unwrap_err();
+        assert!(matches!(err, RLMError::CompilationFailed { .. }));
+    }
+
+    #[test]
+    fn test_csharp_temp_project_removed_on_drop() {
+        let executor = CSharpREPL::new();
+        let project_dir = executor.project_dir().unwrap();
+        assert!(project_dir.is_dir());
+        assert_eq!(executor.project_dir().unwrap(), project_dir);
+
+        drop(executor);
+        assert!(!project_dir.exists());
+    }
+
+    #[test]
+    fn test_csharp_prepare_source_hoists_usings() {
+        let code = "var sb = new StringBuilder();\nusing System.Text;\nusing var reader = new StringReader(\"\");\nConsole.WriteLine(sb);";
+        assert_eq!(
+            CSharpREPL::prepare_source(code),
+            "using System.Text;\n\nvar sb = new StringBuilder();\nusing var reader = new StringReader(\"\");\nConsole.WriteLine(sb);"
+        );
+
+        let program = "class P {\n    static void Main() { }\n}\nusing System;";
+        assert_eq!(CSharpREPL::prepare_source(program), program);
+    }
+
+    #[test]
+    fn test_factory_csharp() {
+        for name in ["csharp", "cs", "C#"] {
+            let executor = REPLExecutorFactory::create(name).unwrap();
+            assert_eq!(executor.language(), "csharp");
+        }
+    }
+
     #[tokio::test]
     #[ignore]  // Requires bash to be installed
     async fn test_bash_simple() {
@@ -511,6 +2110,97 @@ mod tests {
         assert!(output.contains("hello from bash"));
     }
 
+    #[tokio::test]
+    #[ignore]  // Requires bash to be installed
+    async fn test_bash_runtime_failure_is_structured() {
+        let executor = BashREPL::new();
+        let err = executor.execute("echo boom >&2\nexit 3").await.unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "REPL execution failed: Bash execution failed:\nboom\n"
+        );
+        match err {
+            RLMError::RuntimeFailed { language, exit_code, stderr } => {
+                assert_eq!(language, "bash");
+                assert_eq!(exit_code, Some(3));
+                assert_eq!(stderr, "boom\n");
+            }
+            other => panic!("expected RuntimeFailed, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    #[ignore]  // Requires Rust to be installed
+    async fn test_rust_compilation_failure_is_structured() {
+        let executor = RustREPL::new().with_timeout(Duration::from_secs(120));
+        let err = executor.execute("let x: u32 = \"not a number\";").await.unwrap_err();
+
+        assert!(err.to_string().starts_with("REPL execution failed: Rust compilation failed:"));
+        assert!(matches!(
+            err,
+            RLMError::CompilationFailed { ref language, ref stderr }
+                if language == "rust" && stderr.contains("mismatched types")
+        ));
+    }
+
+    #[test]
+    fn test_missing_interpreter_is_structured() {
+        let err = spawn_error(
+            "kotlin",
+            "kotlinc",
+            std::io::Error::from(std::io::ErrorKind::NotFound),
+        );
+        assert!(matches!(
+            err,
+            RLMError::InterpreterNotFound { ref language, ref program }
+                if language == "kotlin" && program == "kotlinc"
+        ));
+
+        let err = spawn_error(
+            "kotlin",
+            "kotlinc",
+            std::io::Error::from(std::io::ErrorKind::PermissionDenied),
+        );
+        assert!(matches!(err, RLMError::ExecutionError(_)));
+
+        let err = spawn_error("r", "Rscript", std::io::Error::from(std::io::ErrorKind::NotFound));
+        assert!(err.to_string().contains("install R and make sure `Rscript` is on PATH"));
+    }
+
+    #[cfg(target_os = "linux")]
+    fn process_running(marker: &str) -> bool {
+        std::fs::read_dir("/proc")
+            .unwrap()
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| std::fs::read(entry.path().join("cmdline")).ok())
+            .any(|cmdline| String::from_utf8_lossy(&cmdline).contains(marker))
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    #[ignore]  // Requires bash to be installed
+    async fn test_timeout_kills_process_tree() {
+        let marker = format!("kowalski-sleep-{}", Uuid::new_v4());
+        let executor = BashREPL::new().with_timeout(Duration::from_millis(500));
+        // Background a renamed sleep so it would outlive bash if only the direct child died
+        let code = format!("(exec -a {} sleep 600) &\nsleep 600", marker);
+
+        let result = executor.execute(&code).await;
+        assert!(matches!(result, Err(RLMError::REPLTimeout(500))));
+
+        // Killed processes may take a moment to be reaped by init
+        let mut orphaned = true;
+        for _ in 0..20 {
+            if !process_running(&marker) {
+                orphaned = false;
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        assert!(!orphaned, "sleep process survived the REPL timeout");
+    }
+
     #[tokio::test]
     #[ignore]  // Requires Node to be installed
     async fn test_javascript_simple() {
@@ -520,6 +2210,20 @@ mod tests {
         assert!(output.contains("hello from javascript"));
     }
 
+    #[test]
+    fn test_factory_applies_language_timeouts() {
+        let config = RLMConfig::default();
+        let rust = REPLExecutorFactory::create("rs").unwrap();
+        let python = REPLExecutorFactory::create("python").unwrap();
+        assert_eq!(rust.timeout(), config.repl_timeout_for("rust"));
+        assert_eq!(python.timeout(), config.repl_timeout_for("python"));
+        assert!(rust.timeout() > python.timeout());
+
+        let config = config.with_language_timeout("python", Duration::from_secs(3));
+        let python = REPLExecutorFactory::create_with_config("py", &config).unwrap();
+        assert_eq!(python.timeout(), Duration::from_secs(3));
+    }
+
     #[test]
     fn test_factory_python() {
         let executor = REPLExecutorFactory::create("python").unwrap();
@@ -538,6 +2242,32 @@ mod tests {
         assert_eq!(executor.language(), "java");
     }
 
+    #[test]
+    fn test_kotlin_prepare_source_keeps_declarations_at_file_scope() {
+        let program = "fun main() {\n    println(\"hi\")\n}";
+        assert_eq!(KotlinREPL::prepare_source(program), program);
+
+        assert_eq!(
+            KotlinREPL::prepare_source("println(1)"),
+            "\nfun main() {\nprintln(1)\n}"
+        );
+
+        let code = "import kotlin.math.sqrt\ndata class Point(\n    val x: Double\n)\nfun norm(p: Point) = sqrt(p.x * p.x)\nprintln(norm(Point(3.0)))";
+        assert_eq!(
+            KotlinREPL::prepare_source(code),
+            "import kotlin.math.sqrt\ndata class Point(\n    val x: Double\n)\nfun norm(p: Point) = sqrt(p.x * p.x)\nfun main() {\nprintln(norm(Point(3.0)))\n}"
+        );
+    }
+
+    #[test]
+    fn test_factory_kotlin() {
+        let executor = REPLExecutorFactory::create("kotlin").unwrap();
+        assert_eq!(executor.language(), "kotlin");
+
+        let executor = REPLExecutorFactory::create("kt").unwrap();
+        assert_eq!(executor.language(), "kotlin");
+    }
+
     #[test]
     fn test_factory_bash() {
         let executor = REPLExecutorFactory::create("bash").unwrap();
@@ -550,9 +2280,237 @@ mod tests {
         assert_eq!(executor.language(), "javascript");
     }
 
+    #[tokio::test]
+    #[ignore]  // Requires Lua to be installed
+    async fn test_lua_simple() {
+        let executor = LuaREPL::new();
+        let output = executor.execute("print('hello from lua')").await.unwrap();
+        assert!(output.contains("hello from lua"));
+
+        let err = executor.execute("error('lua boom')").await.unwrap_err();
+        match err {
+            RLMError::RuntimeFailed { language, stderr, .. } => {
+                assert_eq!(language, "lua");
+                assert!(stderr.contains("lua boom"));
+                assert!(stderr.contains("stack traceback"));
+            }
+            other => panic!("expected a runtime failure, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_factory_lua() {
+        let executor = REPLExecutorFactory::create("lua").unwrap();
+        assert_eq!(executor.language(), "lua");
+    }
+
+    #[tokio::test]
+    #[ignore]  // Requires PHP to be installed
+    async fn test_php_simple() {
+        let executor = PhpREPL::new();
+        let output = executor.execute(r#"echo "hi";"#).await.unwrap();
+        assert_eq!(output, "hi");
+
+        let err = executor.execute("echo 'unterminated").await.unwrap_err();
+        assert!(matches!(err, RLMError::CompilationFailed { ref language, .. } if language == "php"));
+
+        let err = executor.execute("throw new Exception('php boom');").await.unwrap_err();
+        match err {
+            RLMError::RuntimeFailed { language, stderr, .. } => {
+                assert_eq!(language, "php");
+                assert!(stderr.contains("php boom"));
+            }
+            other => panic!("expected a runtime failure, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_php_prepare_source_adds_open_tag() {
+        assert_eq!(PhpREPL::prepare_source("echo 1;"), "<?php\necho 1;");
+        assert_eq!(PhpREPL::prepare_source("<?php echo 1;"), "<?php echo 1;");
+    }
+
+    #[test]
+    fn test_factory_php() {
+        let executor = REPLExecutorFactory::create("php").unwrap();
+        assert_eq!(executor.language(), "php");
+        assert_eq!(executor.timeout(), Duration::from_secs(15));
+    }
+
+    #[tokio::test]
+    #[ignore]  // Requires GHC to be installed
+    async fn test_haskell_simple() {
+        let executor = HaskellREPL::new();
+        let output = executor.execute("sum [1..10]").await.unwrap();
+        assert_eq!(output.trim(), "55");
+
+        let output = executor
+            .execute("main :: IO ()\nmain = putStrLn \"hi from haskell\"")
+            .await
+            .unwrap();
+        assert_eq!(output.trim(), "hi from haskell");
+
+        let err = executor.execute("1 + True").await.unwrap_err();
+        assert!(matches!(err, RLMError::CompilationFailed { ref language, .. } if language == "haskell"));
+
+        let err = executor.execute("head ([] :: [Int])").await.unwrap_err();
+        assert!(matches!(err, RLMError::RuntimeFailed { ref language, .. } if language == "haskell"));
+    }
+
+    #[test]
+    fn test_haskell_prepare_source_wraps_expressions() {
+        assert_eq!(HaskellREPL::prepare_source("sum [1..10]"), "main = print (\n    sum [1..10]\n    )");
+
+        let with_main = "main :: IO ()\nmain = print 1";
+        assert_eq!(HaskellREPL::prepare_source(with_main), with_main);
+
+        let source = HaskellREPL::prepare_source(
+            "import Data.List (sort)\n\nsquare :: Int -> Int\nsquare x =\n  x * x\n\nsort (map square [3, 1, 2])",
+        );
+        assert_eq!(
+            source,
+            "import Data.List (sort)\n\nsquare :: Int -> Int\nsquare x =\n  x * x\n\nmain = print (\n    sort (map square [3, 1, 2])\n    )"
+        );
+
+        // IO actions and several statements run as a do block
+        assert_eq!(
+            HaskellREPL::prepare_source("putStrLn \"a\"\nprint (1 == 1)"),
+            "main = do\n    putStrLn \"a\"\n    print (1 == 1)"
+        );
+        assert_eq!(
+            HaskellREPL::prepare_source("filter (\\x -> x == 2) [1, 2, 3]"),
+            "main = print (\n    filter (\\x -> x == 2) [1, 2, 3]\n    )"
+        );
+    }
+
+    #[test]
+    fn test_factory_haskell() {
+        for name in ["haskell", "hs", "Haskell"] {
+            let executor = REPLExecutorFactory::create(name).unwrap();
+            assert_eq!(executor.language(), "haskell");
+            assert_eq!(executor.timeout(), Duration::from_secs(30));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_haskell_missing_interpreter() {
+        let executor = HaskellREPL::new().with_interpreter("kowalski-missing-runghc");
+        let err = executor.execute("1").await.unwrap_err();
+        assert!(matches!(err, RLMError::InterpreterNotFound { .. }));
+    }
+
+    #[tokio::test]
+    #[ignore]  // Requires R to be installed
+    async fn test_rscript_simple() {
+        let executor = RscriptREPL::new();
+        let output = executor.execute("print(mean(c(1,2,3)))").await.unwrap();
+        assert_eq!(output.trim(), "[1] 2");
+
+        let output = executor.execute("x <- log(-1)\nprint(1)").await.unwrap();
+        assert!(output.starts_with("[1] 1\nWarnings:\n"));
+        assert!(output.contains("NaNs produced"));
+    }
+
+    #[test]
+    fn test_rscript_format_output_separates_warnings() {
+        assert_eq!(RscriptREPL::format_output("", ""), "(no output)");
+        assert_eq!(RscriptREPL::format_output("[1] 2\n", ""), "[1] 2\n");
+        assert_eq!(
+            RscriptREPL::format_output("[1] NaN\n", "Warning message:\nNaNs produced\n"),
+            "[1] NaN\nWarnings:\nWarning message:\nNaNs produced"
+        );
+        assert_eq!(
+            RscriptREPL::format_output("", "Warning message:\nNaNs produced\n"),
+            "Warnings:\nWarning message:\nNaNs produced"
+        );
+    }
+
+    #[test]
+    fn test_factory_r() {
+        for name in ["r", "R", "rscript"] {
+            let executor = REPLExecutorFactory::create(name).unwrap();
+            assert_eq!(executor.language(), "r");
+        }
+    }
+
+    #[test]
+    fn test_registered_languages_can_be_created_and_warmed() {
+        for language in REPLExecutorFactory::registered_languages() {
+            let executor = REPLExecutorFactory::create(language).unwrap();
+            assert_eq!(executor.language(), *language);
+            assert!(REPLExecutorFactory::hello_world(language).is_some());
+        }
+        assert!(REPLExecutorFactory::hello_world("cobol").is_none());
+    }
+
+    #[test]
+    fn test_canonical_language_resolves_aliases() {
+        assert_eq!(REPLExecutorFactory::canonical_language("PY"), Some("python"));
+        assert_eq!(REPLExecutorFactory::canonical_language("sh"), Some("bash"));
+        assert_eq!(REPLExecutorFactory::canonical_language("c++"), Some("cpp"));
+        assert_eq!(REPLExecutorFactory::canonical_language("cobol"), None);
+    }
+
     #[test]
     fn test_factory_unsupported() {
-        let result = REPLExecutorFactory::create("cpp");
+        let result = REPLExecutorFactory::create("cobol");
         assert!(result.is_err());
     }
+
+    #[cfg(feature = "docker")]
+    #[test]
+    fn test_factory_docker() {
+        let executor = REPLExecutorFactory::create("c").unwrap();
+        assert_eq!(executor.language(), "c");
+        let executor = REPLExecutorFactory::create("c++").unwrap();
+        assert_eq!(executor.language(), "cpp");
+    }
+
+    #[cfg(feature = "docker")]
+    #[test]
+    fn test_docker_run_args() {
+        let executor = DockerREPL::new("c", "gcc:latest").with_memory_limit(256);
+        let args = executor.run_args(Path::new("/tmp/snippet"), "kowalski-test").unwrap();
+
+        assert_eq!(&args[..6], ["run", "--rm", "--name", "kowalski-test", "-v", "/tmp/snippet:/code"]);
+        assert_eq!(&args[6..9], ["--memory", "256m", "gcc:latest"]);
+        assert_eq!(args[11], "gcc /code/main.c -o /code/out || exit 97; exec /code/out");
+
+        let unsupported = DockerREPL::new("fortran", "gcc:latest");
+        assert!(unsupported.run_args(Path::new("/tmp"), "x").is_err());
+    }
+
+    #[cfg(feature = "docker")]
+    #[tokio::test]
+    #[ignore]  // Requires Docker to be installed
+    async fn test_docker_c_compiles_and_runs() {
+        let executor = DockerREPL::new("c", "gcc:latest").with_memory_limit(256);
+        let output = executor
+            .execute("#include <stdio.h>\nint main(void) { printf(\"hello from c\\n\"); return 0; }")
+            .await
+            .unwrap();
+        assert_eq!(output, "hello from c\n");
+
+        let executor = DockerREPL::new("cpp", "gcc:latest");
+        let output = executor
+            .execute("#include <iostream>\nint main() { std::cout << 6 * 7 << std::endl; }")
+            .await
+            .unwrap();
+        assert_eq!(output.trim(), "42");
+    }
+
+    #[cfg(feature = "docker")]
+    #[tokio::test]
+    #[ignore]  // Requires Docker to be installed
+    async fn test_docker_c_syntax_error() {
+        let executor = DockerREPL::new("c", "gcc:latest");
+        let err = executor.execute("int main(void) { return 0 }").await.unwrap_err();
+        match err {
+            RLMError::CompilationFailed { language, stderr } => {
+                assert_eq!(language, "c");
+                assert!(stderr.contains("error"));
+            }
+            other => panic!("expected a compilation failure, got {:?}", other),
+        }
+    }
 }
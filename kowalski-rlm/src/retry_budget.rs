@@ -0,0 +1,80 @@
+//! Retry budget shared across an RLM run
+//!
+//! Every retry site in a run draws from the same [`RetryBudget`], so the
+//! total number of retries stays bounded no matter where failures happen.
+//! Once the budget is spent, further failures are returned immediately.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Default number of retries allowed per RLM run
+pub const DEFAULT_RETRY_BUDGET: usize = 10;
+
+/// Shared, thread-safe count of the retries left in a run
+///
+/// Clones share the same counter.
+#[derive(Clone, Debug)]
+pub struct RetryBudget {
+    total: usize,
+    remaining: Arc<AtomicUsize>,
+}
+
+impl RetryBudget {
+    /// Create a budget allowing `total` retries
+    pub fn new(total: usize) -> Self {
+        Self {
+            total,
+            remaining: Arc::new(AtomicUsize::new(total)),
+        }
+    }
+
+    /// Take one retry from the budget
+    ///
+    /// Returns `false`, without retrying, once the budget is exhausted.
+    pub fn try_consume(&self) -> bool {
+        self.remaining
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |remaining| {
+                remaining.checked_sub(1)
+            })
+            .is_ok()
+    }
+
+    /// Retries left
+    pub fn remaining(&self) -> usize {
+        self.remaining.load(Ordering::Acquire)
+    }
+
+    /// Retries the budget started with
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// Returns true if no retries are left
+    pub fn is_exhausted(&self) -> bool {
+        self.remaining() == 0
+    }
+}
+
+impl Default for RetryBudget {
+    fn default() -> Self {
+        Self::new(DEFAULT_RETRY_BUDGET)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clones_share_budget() {
+        let budget = RetryBudget::new(2);
+        let shared = budget.clone();
+
+        assert!(budget.try_consume());
+        assert!(shared.try_consume());
+        assert!(!budget.try_consume());
+        assert!(shared.is_exhausted());
+        assert_eq!(budget.remaining(), 0);
+        assert_eq!(budget.total(), 2);
+    }
+}
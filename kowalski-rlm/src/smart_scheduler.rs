@@ -7,18 +7,29 @@
 //!
 //! - **SmartScheduler**: Cost-aware agent scheduler
 //! - **SchedulerConfig**: Scheduling configuration
+//! - **AgentPool**: Agents available to the scheduler
+//! - **AgentAssignmentStrategy**: How tasks are assigned to candidate agents
 //! - **ScheduledTask**: Task in the priority queue
 //! - **AgentStatus**: Agent status tracking
+//! - **AgentUtilizationReport**: Per-agent load diagnostics
 
 use crate::error::{RLMError, RLMResult};
+use kowalski_federation::ModelRegistry;
 use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
-use std::collections::{BinaryHeap, VecDeque};
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering as AtomicOrdering};
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::time::Duration;
+use tokio::sync::{Notify, RwLock};
 
 /// Configuration for smart scheduling
-#[derive(Clone, Debug, Serialize, Deserialize)]
+///
+/// Fields missing from serialized configurations take their default values.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct SchedulerConfig {
     /// Maximum concurrent agents
     pub max_concurrent: usize,
@@ -30,6 +41,50 @@ pub struct SchedulerConfig {
     pub latency_weight: f64,
     /// Load balance weight (0.0-1.0)
     pub load_weight: f64,
+    /// Strategy used to assign tasks to candidate agents
+    #[serde(default)]
+    pub assignment_strategy: AgentAssignmentStrategy,
+    /// Score added to agents listed in a task's `preferred_agents`
+    #[serde(default = "default_affinity_bonus")]
+    pub affinity_bonus: f64,
+}
+
+/// Partial [`SchedulerConfig`], as read from the environment
+///
+/// Each field that is set replaces the corresponding config field in
+/// [`SchedulerConfig::overlay`]; unset fields leave it unchanged.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SchedulerConfigOverrides {
+    /// Overrides `max_concurrent`
+    pub max_concurrent: Option<usize>,
+    /// Overrides `queue_size`
+    pub queue_size: Option<usize>,
+    /// Overrides `cost_weight`
+    pub cost_weight: Option<f64>,
+    /// Overrides `latency_weight`
+    pub latency_weight: Option<f64>,
+    /// Overrides `load_weight`
+    pub load_weight: Option<f64>,
+    /// Overrides `affinity_bonus`
+    pub affinity_bonus: Option<f64>,
+}
+
+fn default_affinity_bonus() -> f64 {
+    0.1
+}
+
+/// Strategy for assigning a task to one of the candidate agents
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum AgentAssignmentStrategy {
+    /// Always pick the highest-scored agent
+    #[default]
+    BestFit,
+    /// Spread tasks across agents in proportion to their weights.
+    ///
+    /// Weights are aligned with agent registration order; agents without an
+    /// explicit weight (or all agents, if the list is empty) are weighted by
+    /// their score.
+    WeightedRoundRobin(Vec<f64>),
 }
 
 impl Default for SchedulerConfig {
@@ -40,11 +95,92 @@ impl Default for SchedulerConfig {
             cost_weight: 0.4,
             latency_weight: 0.35,
             load_weight: 0.25,
+            assignment_strategy: AgentAssignmentStrategy::BestFit,
+            affinity_bonus: default_affinity_bonus(),
         }
     }
 }
 
 impl SchedulerConfig {
+    /// Parse a configuration from TOML and validate it
+    ///
+    /// ```
+    /// use kowalski_rlm::smart_scheduler::SchedulerConfig;
+    ///
+    /// let config = SchedulerConfig::from_toml_str(
+    ///     "max_concurrent = 4\ncost_weight = 0.5\nlatency_weight = 0.25\nload_weight = 0.25",
+    /// )
+    /// .unwrap();
+    /// assert_eq!(config.max_concurrent, 4);
+    /// assert_eq!(config.queue_size, 100);
+    /// ```
+    pub fn from_toml_str(s: &str) -> Result<Self, String> {
+        let config: Self =
+            toml::from_str(s).map_err(|e| format!("Invalid scheduler config: {}", e))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Read a configuration from a TOML file and validate it
+    pub fn from_toml_file(path: &Path) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        Self::from_toml_str(&contents)
+    }
+
+    /// Read configuration overrides from `<prefix>_*` environment variables
+    ///
+    /// `MAX_CONCURRENT`, `QUEUE_SIZE`, `COST_WEIGHT`, `LATENCY_WEIGHT`,
+    /// `LOAD_WEIGHT` and `AFFINITY_BONUS` are read. Missing or unparsable
+    /// variables are left unset; unparsable ones are logged. The overrides are
+    /// not validated, since they are meant to be [`overlay`](Self::overlay)ed
+    /// onto a file config.
+    pub fn from_env_prefix(prefix: &str) -> SchedulerConfigOverrides {
+        fn var<T: FromStr>(prefix: &str, name: &str) -> Option<T> {
+            let key = format!("{}_{}", prefix, name);
+            let value = std::env::var(&key).ok()?;
+            let parsed = value.trim().parse().ok();
+            if parsed.is_none() {
+                log::warn!("Ignoring {}={:?}: not a valid value", key, value);
+            }
+            parsed
+        }
+
+        SchedulerConfigOverrides {
+            max_concurrent: var(prefix, "MAX_CONCURRENT"),
+            queue_size: var(prefix, "QUEUE_SIZE"),
+            cost_weight: var(prefix, "COST_WEIGHT"),
+            latency_weight: var(prefix, "LATENCY_WEIGHT"),
+            load_weight: var(prefix, "LOAD_WEIGHT"),
+            affinity_bonus: var(prefix, "AFFINITY_BONUS"),
+        }
+    }
+
+    /// Apply the overrides that are set
+    ///
+    /// Meant for layering [`from_env_prefix`](Self::from_env_prefix) over a
+    /// file configuration:
+    ///
+    /// ```no_run
+    /// use kowalski_rlm::smart_scheduler::SchedulerConfig;
+    /// use std::path::Path;
+    ///
+    /// let config = SchedulerConfig::from_toml_file(Path::new("scheduler.toml"))
+    ///     .unwrap_or_default()
+    ///     .overlay(SchedulerConfig::from_env_prefix("KOWALSKI_SCHEDULER"));
+    /// ```
+    pub fn overlay(self, overrides: SchedulerConfigOverrides) -> Self {
+        Self {
+            max_concurrent: overrides.max_concurrent.unwrap_or(self.max_concurrent),
+            queue_size: overrides.queue_size.unwrap_or(self.queue_size),
+            cost_weight: overrides.cost_weight.unwrap_or(self.cost_weight),
+            latency_weight: overrides.latency_weight.unwrap_or(self.latency_weight),
+            load_weight: overrides.load_weight.unwrap_or(self.load_weight),
+            affinity_bonus: overrides.affinity_bonus.unwrap_or(self.affinity_bonus),
+            ..self
+        }
+    }
+
     /// Validate the scheduler configuration
     pub fn validate(&self) -> Result<(), String> {
         if self.max_concurrent == 0 {
@@ -73,6 +209,16 @@ impl SchedulerConfig {
                 weight_sum
             ));
         }
+
+        if !self.affinity_bonus.is_finite() || self.affinity_bonus < 0.0 {
+            return Err("affinity_bonus must be finite and >= 0.0".to_string());
+        }
+
+        if let AgentAssignmentStrategy::WeightedRoundRobin(weights) = &self.assignment_strategy {
+            if weights.iter().any(|w| !w.is_finite() || *w < 0.0) {
+                return Err("round-robin weights must be finite and >= 0.0".to_string());
+            }
+        }
         
         Ok(())
     }
@@ -91,6 +237,12 @@ pub struct ScheduledTask {
     pub latency_ms: u64,
     /// Required capabilities
     pub required_capabilities: Vec<String>,
+    /// Agents this task should preferably run on, e.g. ones with warm state
+    ///
+    /// A soft preference: qualifying preferred agents get the configured
+    /// `affinity_bonus`, other agents stay eligible.
+    #[serde(default)]
+    pub preferred_agents: Vec<String>,
 }
 
 /// Agent availability status
@@ -105,11 +257,108 @@ pub struct AgentStatus {
     /// Available capabilities
     pub capabilities: Vec<String>,
     /// Cost per operation
+    ///
+    /// [`AgentStatus::with_model_cost`] derives it from a model's prices.
     pub cost_per_op: f64,
     /// Is currently available
     pub available: bool,
 }
 
+impl AgentStatus {
+    /// Set `cost_per_op` to the price of one call to `model`
+    ///
+    /// A call is priced at `tokens_in` prompt and `tokens_out` response
+    /// tokens, using the model's spec in `registry`. A model missing from the
+    /// registry leaves `cost_per_op` unchanged.
+    pub fn with_model_cost(
+        mut self,
+        registry: &ModelRegistry,
+        model: &str,
+        tokens_in: usize,
+        tokens_out: usize,
+    ) -> Self {
+        if let Some(cost) = registry.estimate_cost(model, tokens_in, tokens_out) {
+            self.cost_per_op = cost;
+        }
+        self
+    }
+}
+
+/// Agents a [`SmartScheduler`] can assign tasks to
+///
+/// Agents keep their registration order, which
+/// [`AgentAssignmentStrategy::WeightedRoundRobin`] weights are aligned with.
+#[derive(Clone, Debug, Default)]
+pub struct AgentPool {
+    agents: Vec<AgentStatus>,
+}
+
+impl AgentPool {
+    /// Create an empty pool
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an agent, replacing any agent with the same ID in place
+    pub fn add(&mut self, agent: AgentStatus) {
+        match self.agents.iter_mut().find(|a| a.id == agent.id) {
+            Some(existing) => *existing = agent,
+            None => self.agents.push(agent),
+        }
+    }
+
+    /// Remove an agent, returning true if it was in the pool
+    pub fn remove(&mut self, id: &str) -> bool {
+        let before = self.agents.len();
+        self.agents.retain(|a| a.id != id);
+        self.agents.len() != before
+    }
+
+    /// Get an agent by ID
+    pub fn get(&self, id: &str) -> Option<&AgentStatus> {
+        self.agents.iter().find(|a| a.id == id)
+    }
+
+    /// Replace an agent's status, returning false if the agent is unknown
+    pub fn update(&mut self, id: &str, status: AgentStatus) -> bool {
+        match self.agents.iter_mut().find(|a| a.id == id) {
+            Some(agent) => {
+                *agent = status;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Agents currently marked available
+    pub fn available(&self) -> Vec<&AgentStatus> {
+        self.agents.iter().filter(|a| a.available).collect()
+    }
+
+    /// Agents offering `cap`, available or not
+    pub fn with_capability(&self, cap: &str) -> Vec<&AgentStatus> {
+        self.agents
+            .iter()
+            .filter(|a| a.capabilities.iter().any(|c| c == cap))
+            .collect()
+    }
+
+    /// All agents, in registration order
+    pub fn iter(&self) -> impl Iterator<Item = &AgentStatus> {
+        self.agents.iter()
+    }
+
+    /// Number of agents
+    pub fn len(&self) -> usize {
+        self.agents.len()
+    }
+
+    /// Returns true if the pool has no agents
+    pub fn is_empty(&self) -> bool {
+        self.agents.is_empty()
+    }
+}
+
 /// Scheduling statistics
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct SchedulingStats {
@@ -127,6 +376,21 @@ pub struct SchedulingStats {
     pub total_cost: f64,
 }
 
+/// Load-balancing diagnostics for a single agent
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AgentUtilizationReport {
+    /// Agent ID
+    pub agent_id: String,
+    /// Tasks assigned to the agent by `select_agent_for_task`
+    pub tasks_received: u64,
+    /// Assigned tasks whose completion has not been recorded yet
+    pub tasks_in_progress: u64,
+    /// Estimated time to drain the in-progress tasks, from the agent's average latency
+    pub estimated_completion_ms: u64,
+    /// In-progress tasks as a percentage of `max_concurrent`
+    pub utilization_percent: f32,
+}
+
 /// Task scoring for priority queue
 #[derive(Clone, Debug)]
 struct ScoredTask {
@@ -150,12 +414,11 @@ impl PartialOrd for ScoredTask {
 
 impl Ord for ScoredTask {
     fn cmp(&self, other: &Self) -> Ordering {
-        // Reverse ordering for max-heap
-        other
-            .score
-            .partial_cmp(&self.score)
+        // Max-heap: the highest score pops first, ties go to the lowest ID
+        self.score
+            .partial_cmp(&other.score)
             .unwrap_or(Ordering::Equal)
-            .then_with(|| self.task.id.cmp(&other.task.id))
+            .then_with(|| other.task.id.cmp(&self.task.id))
     }
 }
 
@@ -163,10 +426,21 @@ impl Ord for ScoredTask {
 pub struct SmartScheduler {
     config: SchedulerConfig,
     task_queue: Arc<RwLock<BinaryHeap<ScoredTask>>>,
-    agent_pool: Arc<RwLock<Vec<AgentStatus>>>,
+    agent_pool: Arc<RwLock<AgentPool>>,
     stats: Arc<RwLock<SchedulingStats>>,
     wait_times: Arc<RwLock<VecDeque<u64>>>,
     execution_times: Arc<RwLock<VecDeque<u64>>>,
+    /// Smooth weighted round-robin cursor: current weight per agent ID
+    round_robin_weights: Arc<RwLock<HashMap<String, f64>>>,
+    assignment_counts: Arc<RwLock<HashMap<String, u64>>>,
+    /// Completed tasks per agent ID, recorded by `record_agent_task_completion`
+    completion_counts: Arc<RwLock<HashMap<String, u64>>>,
+    /// Set by `initiate_drain`; rejects new submissions
+    draining: AtomicBool,
+    /// Tasks given an agent by `select_agent_for_task` and not yet completed
+    in_flight: AtomicUsize,
+    /// Woken whenever the queue or the in-flight count shrinks
+    drain_progress: Notify,
 }
 
 impl SmartScheduler {
@@ -175,10 +449,16 @@ impl SmartScheduler {
         Self {
             config,
             task_queue: Arc::new(RwLock::new(BinaryHeap::new())),
-            agent_pool: Arc::new(RwLock::new(Vec::new())),
+            agent_pool: Arc::new(RwLock::new(AgentPool::new())),
             stats: Arc::new(RwLock::new(SchedulingStats::default())),
             wait_times: Arc::new(RwLock::new(VecDeque::new())),
             execution_times: Arc::new(RwLock::new(VecDeque::new())),
+            round_robin_weights: Arc::new(RwLock::new(HashMap::new())),
+            assignment_counts: Arc::new(RwLock::new(HashMap::new())),
+            completion_counts: Arc::new(RwLock::new(HashMap::new())),
+            draining: AtomicBool::new(false),
+            in_flight: AtomicUsize::new(0),
+            drain_progress: Notify::new(),
         }
     }
 
@@ -192,12 +472,28 @@ impl SmartScheduler {
             ));
         }
 
-        pool.push(agent);
+        pool.add(agent);
         Ok(())
     }
 
+    /// Shared handle to the agent pool
+    ///
+    /// Changes made through the handle are seen by the scheduler, so agents
+    /// can be inspected or managed independently of task scheduling.
+    pub fn agent_pool(&self) -> Arc<RwLock<AgentPool>> {
+        Arc::clone(&self.agent_pool)
+    }
+
     /// Submit a task for scheduling
+    ///
+    /// Fails once [`initiate_drain`](Self::initiate_drain) has been called.
     pub async fn submit_task(&self, task: ScheduledTask) -> RLMResult<()> {
+        if self.is_draining() {
+            return Err(RLMError::SchedulingFailed(
+                "scheduler is draining".to_string(),
+            ));
+        }
+
         let mut queue = self.task_queue.write().await;
 
         if queue.len() >= self.config.queue_size {
@@ -215,16 +511,100 @@ impl SmartScheduler {
     /// Get the next task to execute
     pub async fn next_task(&self) -> RLMResult<Option<ScheduledTask>> {
         let mut queue = self.task_queue.write().await;
-        Ok(queue.pop().map(|scored| scored.task))
+        let task = queue.pop().map(|scored| scored.task);
+        if task.is_some() {
+            self.drain_progress.notify_waiters();
+        }
+        Ok(task)
+    }
+
+    /// Remove every queued task, in the order `next_task` would return them
+    ///
+    /// Tasks come out highest score first, so a supervisor shutting down
+    /// can persist or cancel them deterministically. The queue is left
+    /// empty and tasks submitted afterwards start a fresh queue.
+    pub async fn drain(&self) -> Vec<ScheduledTask> {
+        let queue = std::mem::take(&mut *self.task_queue.write().await);
+        self.drain_progress.notify_waiters();
+        queue
+            .into_sorted_vec()
+            .into_iter()
+            .rev()
+            .map(|scored| scored.task)
+            .collect()
+    }
+
+    /// Stop accepting tasks so the scheduler can shut down gracefully
+    ///
+    /// Later [`submit_task`](Self::submit_task) calls fail, while queued and
+    /// in-flight tasks carry on; use [`wait_drain`](Self::wait_drain) to
+    /// wait for them. Calling this again has no further effect.
+    pub fn initiate_drain(&self) -> RLMResult<()> {
+        self.draining.store(true, AtomicOrdering::SeqCst);
+        Ok(())
+    }
+
+    /// Returns true once [`initiate_drain`](Self::initiate_drain) has been called
+    pub fn is_draining(&self) -> bool {
+        self.draining.load(AtomicOrdering::SeqCst)
+    }
+
+    /// Number of tasks given an agent and not yet recorded as completed
+    pub fn in_flight_tasks(&self) -> usize {
+        self.in_flight.load(AtomicOrdering::SeqCst)
     }
 
-    /// Select best agent for a task
+    /// Returns true if no task is queued and none is in flight
+    pub async fn is_drained(&self) -> bool {
+        self.pending_tasks().await == 0 && self.in_flight_tasks() == 0
+    }
+
+    /// Wait until the scheduler [is drained](Self::is_drained)
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RLMError::SchedulingFailed`] if tasks are still queued or
+    /// in flight after `timeout`.
+    pub async fn wait_drain(&self, timeout: Duration) -> RLMResult<()> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            // Register for wakeups before checking, so progress made in
+            // between is not missed
+            let progress = self.drain_progress.notified();
+            tokio::pin!(progress);
+            progress.as_mut().enable();
+
+            if self.is_drained().await {
+                return Ok(());
+            }
+            if tokio::time::timeout_at(deadline, progress).await.is_err() {
+                return Err(RLMError::SchedulingFailed(format!(
+                    "drain did not finish within {:?}: {} tasks pending, {} in flight",
+                    timeout,
+                    self.pending_tasks().await,
+                    self.in_flight_tasks()
+                )));
+            }
+        }
+    }
+
+    /// Select an agent for a task using the configured assignment strategy
+    ///
+    /// A selected agent counts the task as in flight until it is recorded
+    /// with [`record_task_completion`](Self::record_task_completion).
+    ///
+    /// Only available agents with every required capability are
+    /// candidates. Among them, agents in the task's `preferred_agents`
+    /// score `affinity_bonus` higher, which also raises their share under
+    /// [`AgentAssignmentStrategy::WeightedRoundRobin`] when weights are
+    /// derived from scores.
     pub async fn select_agent_for_task(&self, task: &ScheduledTask) -> RLMResult<Option<AgentStatus>> {
         let pool = self.agent_pool.read().await;
 
         let mut candidates: Vec<_> = pool
             .iter()
-            .filter(|agent| {
+            .enumerate()
+            .filter(|(_, agent)| {
                 agent.available
                     && task
                         .required_capabilities
@@ -237,22 +617,83 @@ impl SmartScheduler {
             return Ok(None);
         }
 
-        // Sort by combined score
-        candidates.sort_by(|a, b| {
-            let score_a = self.calculate_agent_score(a);
-            let score_b = self.calculate_agent_score(b);
-            score_b.partial_cmp(&score_a).unwrap_or(Ordering::Equal)
-        });
+        let selected = match &self.config.assignment_strategy {
+            AgentAssignmentStrategy::BestFit => {
+                // Sort by combined score
+                candidates.sort_by(|(_, a), (_, b)| {
+                    let score_a = self.task_agent_score(task, a);
+                    let score_b = self.task_agent_score(task, b);
+                    score_b.partial_cmp(&score_a).unwrap_or(Ordering::Equal)
+                });
+                candidates[0].1
+            }
+            AgentAssignmentStrategy::WeightedRoundRobin(weights) => {
+                self.next_weighted_agent(task, &candidates, weights).await
+            }
+        };
 
-        Ok(candidates.first().map(|a| (*a).clone()))
+        *self
+            .assignment_counts
+            .write()
+            .await
+            .entry(selected.id.clone())
+            .or_insert(0) += 1;
+        self.in_flight.fetch_add(1, AtomicOrdering::SeqCst);
+
+        Ok(Some(selected.clone()))
+    }
+
+    /// Pick the next agent with smooth weighted round-robin.
+    ///
+    /// Every candidate's current weight grows by its weight, the largest is
+    /// chosen and then reduced by the total, so each agent is picked in
+    /// proportion to its weight and picks are interleaved rather than bursty.
+    async fn next_weighted_agent<'a>(
+        &self,
+        task: &ScheduledTask,
+        candidates: &[(usize, &'a AgentStatus)],
+        weights: &[f64],
+    ) -> &'a AgentStatus {
+        let weighted: Vec<_> = candidates
+            .iter()
+            .map(|(index, agent)| {
+                let weight = weights
+                    .get(*index)
+                    .copied()
+                    .unwrap_or_else(|| self.task_agent_score(task, agent));
+                (*agent, weight.max(0.0))
+            })
+            .collect();
+        let total: f64 = weighted.iter().map(|(_, weight)| weight).sum();
+
+        let mut current = self.round_robin_weights.write().await;
+        let mut selected = weighted[0].0;
+        let mut best = f64::NEG_INFINITY;
+
+        for (agent, weight) in &weighted {
+            let entry = current.entry(agent.id.clone()).or_insert(0.0);
+            *entry += weight;
+            if *entry > best {
+                best = *entry;
+                selected = agent;
+            }
+        }
+
+        if let Some(entry) = current.get_mut(&selected.id) {
+            *entry -= total;
+        }
+
+        selected
+    }
+
+    /// Get the number of tasks assigned to each agent
+    pub async fn get_assignment_stats(&self) -> HashMap<String, u64> {
+        self.assignment_counts.read().await.clone()
     }
 
     /// Update agent status
     pub async fn update_agent_status(&self, id: &str, status: AgentStatus) -> RLMResult<()> {
-        let mut pool = self.agent_pool.write().await;
-
-        if let Some(pos) = pool.iter().position(|a| a.id == id) {
-            pool[pos] = status;
+        if self.agent_pool.write().await.update(id, status) {
             Ok(())
         } else {
             Err(RLMError::SchedulingFailed(format!("Agent {} not found", id)))
@@ -267,6 +708,12 @@ impl SmartScheduler {
         cost: f64,
         success: bool,
     ) {
+        // Completions recorded without a prior selection leave the count at zero
+        let _ = self
+            .in_flight
+            .fetch_update(AtomicOrdering::SeqCst, AtomicOrdering::SeqCst, |n| n.checked_sub(1));
+        self.drain_progress.notify_waiters();
+
         let mut stats = self.stats.write().await;
         stats.total_tasks += 1;
 
@@ -304,6 +751,79 @@ impl SmartScheduler {
         }
     }
 
+    /// Record completion of a task that was assigned to `agent_id`
+    ///
+    /// Updates the global statistics like
+    /// [`record_task_completion`](Self::record_task_completion) and also
+    /// marks one of the agent's tasks as no longer in progress.
+    pub async fn record_agent_task_completion(
+        &self,
+        agent_id: &str,
+        wait_time_ms: u64,
+        execution_time_ms: u64,
+        cost: f64,
+        success: bool,
+    ) {
+        *self
+            .completion_counts
+            .write()
+            .await
+            .entry(agent_id.to_string())
+            .or_insert(0) += 1;
+        self.record_task_completion(wait_time_ms, execution_time_ms, cost, success)
+            .await;
+    }
+
+    /// Per-agent utilization, in agent registration order
+    pub async fn get_agent_utilization_report(&self) -> Vec<AgentUtilizationReport> {
+        let pool = self.agent_pool.read().await;
+        let received = self.assignment_counts.read().await;
+        let completed = self.completion_counts.read().await;
+
+        pool.iter()
+            .map(|agent| {
+                let tasks_received = received.get(&agent.id).copied().unwrap_or(0);
+                let tasks_in_progress =
+                    tasks_received.saturating_sub(completed.get(&agent.id).copied().unwrap_or(0));
+                AgentUtilizationReport {
+                    agent_id: agent.id.clone(),
+                    tasks_received,
+                    tasks_in_progress,
+                    estimated_completion_ms: tasks_in_progress.saturating_mul(agent.avg_latency_ms),
+                    utilization_percent: tasks_in_progress as f32
+                        / self.config.max_concurrent as f32
+                        * 100.0,
+                }
+            })
+            .collect()
+    }
+
+    /// Agent with the most tasks in progress (ties go to the lowest ID)
+    pub async fn most_loaded_agent(&self) -> Option<String> {
+        self.get_agent_utilization_report()
+            .await
+            .into_iter()
+            .min_by(|a, b| {
+                b.tasks_in_progress
+                    .cmp(&a.tasks_in_progress)
+                    .then_with(|| a.agent_id.cmp(&b.agent_id))
+            })
+            .map(|report| report.agent_id)
+    }
+
+    /// Agent with the fewest tasks in progress (ties go to the lowest ID)
+    pub async fn least_loaded_agent(&self) -> Option<String> {
+        self.get_agent_utilization_report()
+            .await
+            .into_iter()
+            .min_by(|a, b| {
+                a.tasks_in_progress
+                    .cmp(&b.tasks_in_progress)
+                    .then_with(|| a.agent_id.cmp(&b.agent_id))
+            })
+            .map(|report| report.agent_id)
+    }
+
     /// Get current statistics
     pub async fn stats(&self) -> SchedulingStats {
         self.stats.read().await.clone()
@@ -315,6 +835,16 @@ impl SmartScheduler {
         task.priority as f64
     }
 
+    /// Score of `agent` for `task`, including the affinity bonus
+    fn task_agent_score(&self, task: &ScheduledTask, agent: &AgentStatus) -> f64 {
+        let score = self.calculate_agent_score(agent);
+        if task.preferred_agents.contains(&agent.id) {
+            score + self.config.affinity_bonus
+        } else {
+            score
+        }
+    }
+
     /// Calculate score for an agent (higher = better choice)
     fn calculate_agent_score(&self, agent: &AgentStatus) -> f64 {
         // Normalize values to 0-1 range
@@ -356,11 +886,10 @@ impl SmartScheduler {
 
     /// Get available agent count
     pub async fn available_agents(&self) -> usize {
-        let pool = self.agent_pool.read().await;
-        pool.iter().filter(|a| a.available).count()
+        self.agent_pool.read().await.available().len()
     }
 
-    /// Reset statistics
+    /// Reset statistics, including per-agent assignment and completion counts
     pub async fn reset_stats(&self) {
         let mut stats = self.stats.write().await;
         *stats = SchedulingStats::default();
@@ -368,6 +897,8 @@ impl SmartScheduler {
         let mut execs = self.execution_times.write().await;
         waits.clear();
         execs.clear();
+        self.assignment_counts.write().await.clear();
+        self.completion_counts.write().await.clear();
     }
 }
 
@@ -375,6 +906,71 @@ impl SmartScheduler {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_scheduler_config_from_toml() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("scheduler.toml");
+        std::fs::write(
+            &path,
+            "max_concurrent = 4\nqueue_size = 20\ncost_weight = 0.5\nlatency_weight = 0.3\nload_weight = 0.2\n",
+        )
+        .unwrap();
+
+        let config = SchedulerConfig::from_toml_file(&path).unwrap();
+        assert_eq!(config.max_concurrent, 4);
+        assert_eq!(config.queue_size, 20);
+        assert_eq!(config.cost_weight, 0.5);
+        assert_eq!(config.affinity_bonus, 0.1);
+
+        assert!(SchedulerConfig::from_toml_str("queue_size = 0").is_err());
+        assert!(SchedulerConfig::from_toml_str("max_concurrent = \"many\"").is_err());
+        assert!(SchedulerConfig::from_toml_file(&dir.path().join("missing.toml")).is_err());
+    }
+
+    #[test]
+    fn test_scheduler_env_overrides_file() {
+        let prefix = "KOWALSKI_TEST_SCHED_OVERLAY";
+        std::env::set_var(format!("{}_MAX_CONCURRENT", prefix), "32");
+        std::env::set_var(format!("{}_COST_WEIGHT", prefix), "0.6");
+        std::env::set_var(format!("{}_LATENCY_WEIGHT", prefix), "0.2");
+        std::env::set_var(format!("{}_QUEUE_SIZE", prefix), "not a number");
+
+        let env = SchedulerConfig::from_env_prefix(prefix);
+        assert_eq!(env.max_concurrent, Some(32));
+        assert_eq!(env.queue_size, None);
+
+        let file = SchedulerConfig::from_toml_str(
+            "max_concurrent = 4\nqueue_size = 20\ncost_weight = 0.5\nlatency_weight = 0.3\nload_weight = 0.2",
+        )
+        .unwrap();
+        let config = file.overlay(env);
+
+        assert_eq!(config.max_concurrent, 32);
+        assert_eq!(config.cost_weight, 0.6);
+        assert_eq!(config.latency_weight, 0.2);
+        // Not set (or not parsable) in the environment: the file wins
+        assert_eq!(config.queue_size, 20);
+        assert_eq!(config.load_weight, 0.2);
+        assert!(config.validate().is_ok());
+
+        for name in ["MAX_CONCURRENT", "COST_WEIGHT", "LATENCY_WEIGHT", "QUEUE_SIZE"] {
+            std::env::remove_var(format!("{}_{}", prefix, name));
+        }
+    }
+
+    #[test]
+    fn test_scheduler_env_set_to_default_still_overrides_file() {
+        let prefix = "KOWALSKI_TEST_SCHED_DEFAULT_VALUE";
+        let defaults = SchedulerConfig::default();
+        std::env::set_var(format!("{}_MAX_CONCURRENT", prefix), defaults.max_concurrent.to_string());
+
+        let file = SchedulerConfig::from_toml_str("max_concurrent = 4").unwrap();
+        let config = file.overlay(SchedulerConfig::from_env_prefix(prefix));
+
+        assert_eq!(config.max_concurrent, defaults.max_concurrent);
+        std::env::remove_var(format!("{}_MAX_CONCURRENT", prefix));
+    }
+
     #[tokio::test]
     async fn test_scheduler_creation() {
         let config = SchedulerConfig::default();
@@ -414,6 +1010,7 @@ mod tests {
             cost: 0.1,
             latency_ms: 100,
             required_capabilities: vec!["web_search".to_string()],
+            preferred_agents: vec![],
         };
 
         let result = scheduler.submit_task(task).await;
@@ -444,6 +1041,7 @@ mod tests {
             cost: 0.1,
             latency_ms: 100,
             required_capabilities: vec!["web_search".to_string()],
+            preferred_agents: vec![],
         };
 
         let selected = scheduler.select_agent_for_task(&task).await.unwrap();
@@ -521,6 +1119,327 @@ mod tests {
         assert!(score.is_finite() && !score.is_nan());
     }
 
+    #[test]
+    fn test_model_cost_prefers_cheaper_model() {
+        use kowalski_federation::ModelSpec;
+
+        let registry = ModelRegistry::new();
+        registry.register(ModelSpec::new("cheap", 8_000).with_cost(0.001, 0.002));
+        registry.register(ModelSpec::new("pricey", 8_000).with_cost(0.01, 0.03));
+        let scheduler = SmartScheduler::new(SchedulerConfig::default());
+
+        let cheap = load_only_agent("cheap-agent", 0.5).with_model_cost(&registry, "cheap", 1_000, 500);
+        let pricey = load_only_agent("pricey-agent", 0.5).with_model_cost(&registry, "pricey", 1_000, 500);
+        assert!((cheap.cost_per_op - 0.002).abs() < 1e-9);
+        assert!((pricey.cost_per_op - 0.025).abs() < 1e-9);
+        assert!(scheduler.calculate_agent_score(&cheap) > scheduler.calculate_agent_score(&pricey));
+
+        let unknown = load_only_agent("other", 0.5).with_model_cost(&registry, "unknown", 1_000, 500);
+        assert!((unknown.cost_per_op - 0.1).abs() < 1e-9);
+    }
+
+    fn load_only_agent(id: &str, load: f64) -> AgentStatus {
+        AgentStatus {
+            id: id.to_string(),
+            load,
+            avg_latency_ms: 50,
+            capabilities: vec![],
+            cost_per_op: 0.1,
+            available: true,
+        }
+    }
+
+    fn any_task() -> ScheduledTask {
+        ScheduledTask {
+            id: "task".to_string(),
+            priority: 1,
+            cost: 0.1,
+            latency_ms: 100,
+            required_capabilities: vec![],
+            preferred_agents: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_best_fit_always_picks_top_agent() {
+        let scheduler = SmartScheduler::new(SchedulerConfig::default());
+        scheduler.register_agent(load_only_agent("busy", 0.9)).await.unwrap();
+        scheduler.register_agent(load_only_agent("idle", 0.1)).await.unwrap();
+
+        for _ in 0..10 {
+            let selected = scheduler.select_agent_for_task(&any_task()).await.unwrap();
+            assert_eq!(selected.unwrap().id, "idle");
+        }
+
+        let stats = scheduler.get_assignment_stats().await;
+        assert_eq!(stats.get("idle"), Some(&10));
+        assert_eq!(stats.get("busy"), None);
+    }
+
+    #[tokio::test]
+    async fn test_weighted_round_robin_follows_score_ratio() {
+        // Score is 1 - load, so these agents score 0.75 and 0.25 (3:1)
+        let config = SchedulerConfig {
+            cost_weight: 0.0,
+            latency_weight: 0.0,
+            load_weight: 1.0,
+            assignment_strategy: AgentAssignmentStrategy::WeightedRoundRobin(vec![]),
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
+
+        let scheduler = SmartScheduler::new(config);
+        scheduler.register_agent(load_only_agent("strong", 0.25)).await.unwrap();
+        scheduler.register_agent(load_only_agent("weak", 0.75)).await.unwrap();
+
+        for _ in 0..100 {
+            scheduler.select_agent_for_task(&any_task()).await.unwrap();
+        }
+
+        let stats = scheduler.get_assignment_stats().await;
+        let strong = *stats.get("strong").unwrap() as i64;
+        let weak = *stats.get("weak").unwrap() as i64;
+        assert_eq!(strong + weak, 100);
+        assert!((strong - 75).abs() <= 10, "strong agent got {} tasks", strong);
+    }
+
+    #[tokio::test]
+    async fn test_weighted_round_robin_explicit_weights() {
+        let config = SchedulerConfig {
+            assignment_strategy: AgentAssignmentStrategy::WeightedRoundRobin(vec![1.0, 1.0]),
+            ..Default::default()
+        };
+        let scheduler = SmartScheduler::new(config);
+        scheduler.register_agent(load_only_agent("a", 0.0)).await.unwrap();
+        scheduler.register_agent(load_only_agent("b", 0.9)).await.unwrap();
+
+        for _ in 0..10 {
+            scheduler.select_agent_for_task(&any_task()).await.unwrap();
+        }
+
+        let stats = scheduler.get_assignment_stats().await;
+        assert_eq!(stats.get("a"), Some(&5));
+        assert_eq!(stats.get("b"), Some(&5));
+    }
+
+    #[tokio::test]
+    async fn test_preferred_agent_wins_within_affinity_bonus() {
+        let scheduler = SmartScheduler::new(SchedulerConfig::default());
+        let agent = |id: &str, latency_ms: u64, capabilities: &[&str]| AgentStatus {
+            avg_latency_ms: latency_ms,
+            capabilities: capabilities.iter().map(|c| c.to_string()).collect(),
+            ..load_only_agent(id, 0.2)
+        };
+        scheduler.register_agent(agent("fast", 50, &["gpu"])).await.unwrap();
+        scheduler.register_agent(agent("warm", 60, &["gpu"])).await.unwrap();
+        scheduler.register_agent(agent("unqualified", 10, &[])).await.unwrap();
+
+        let task = ScheduledTask {
+            required_capabilities: vec!["gpu".to_string()],
+            ..any_task()
+        };
+        let selected = scheduler.select_agent_for_task(&task).await.unwrap().unwrap();
+        assert_eq!(selected.id, "fast");
+
+        // The warm agent is slightly slower but preferred
+        let task = ScheduledTask {
+            preferred_agents: vec!["warm".to_string()],
+            ..task
+        };
+        let selected = scheduler.select_agent_for_task(&task).await.unwrap().unwrap();
+        assert_eq!(selected.id, "warm");
+
+        // A preferred agent lacking a required capability is still skipped
+        let task = ScheduledTask {
+            preferred_agents: vec!["unqualified".to_string()],
+            ..task
+        };
+        let selected = scheduler.select_agent_for_task(&task).await.unwrap().unwrap();
+        assert_eq!(selected.id, "fast");
+    }
+
+    #[test]
+    fn test_affinity_bonus_validation() {
+        let config = SchedulerConfig {
+            affinity_bonus: -0.1,
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_round_robin_weight_validation() {
+        let config = SchedulerConfig {
+            assignment_strategy: AgentAssignmentStrategy::WeightedRoundRobin(vec![1.0, -1.0]),
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_utilization_report_reflects_imbalance() {
+        // Explicit weights send three tasks to "hot" for every one sent to "cold"
+        let config = SchedulerConfig {
+            max_concurrent: 4,
+            assignment_strategy: AgentAssignmentStrategy::WeightedRoundRobin(vec![3.0, 1.0]),
+            ..Default::default()
+        };
+        let scheduler = SmartScheduler::new(config);
+        scheduler.register_agent(load_only_agent("hot", 0.5)).await.unwrap();
+        scheduler.register_agent(load_only_agent("cold", 0.5)).await.unwrap();
+        assert_eq!(scheduler.most_loaded_agent().await, Some("cold".to_string()));
+
+        for _ in 0..4 {
+            scheduler.select_agent_for_task(&any_task()).await.unwrap();
+        }
+        scheduler.record_agent_task_completion("cold", 10, 20, 0.1, true).await;
+
+        let report = scheduler.get_agent_utilization_report().await;
+        assert_eq!(
+            report[0],
+            AgentUtilizationReport {
+                agent_id: "hot".to_string(),
+                tasks_received: 3,
+                tasks_in_progress: 3,
+                estimated_completion_ms: 150,
+                utilization_percent: 75.0,
+            }
+        );
+        assert_eq!(report[1].tasks_received, 1);
+        assert_eq!(report[1].tasks_in_progress, 0);
+        assert_eq!(report[1].utilization_percent, 0.0);
+
+        assert_eq!(scheduler.most_loaded_agent().await, Some("hot".to_string()));
+        assert_eq!(scheduler.least_loaded_agent().await, Some("cold".to_string()));
+        assert_eq!(scheduler.stats().await.completed_tasks, 1);
+    }
+
+    fn task_with_priority(id: &str, priority: i32) -> ScheduledTask {
+        ScheduledTask {
+            id: id.to_string(),
+            priority,
+            ..any_task()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_drain_returns_tasks_in_scheduling_order() {
+        let tasks = [("low", 1), ("urgent", 9), ("mid-b", 5), ("mid-a", 5), ("high", 7)];
+        let scheduler = SmartScheduler::new(SchedulerConfig::default());
+        let reference = SmartScheduler::new(SchedulerConfig::default());
+        for (id, priority) in tasks {
+            scheduler.submit_task(task_with_priority(id, priority)).await.unwrap();
+            reference.submit_task(task_with_priority(id, priority)).await.unwrap();
+        }
+
+        let mut scheduled = Vec::new();
+        while let Some(task) = reference.next_task().await.unwrap() {
+            scheduled.push(task.id);
+        }
+
+        let drained: Vec<String> = scheduler.drain().await.into_iter().map(|t| t.id).collect();
+        assert_eq!(drained, scheduled);
+        assert_eq!(drained, vec!["urgent", "high", "mid-a", "mid-b", "low"]);
+        assert_eq!(scheduler.pending_tasks().await, 0);
+        assert!(scheduler.drain().await.is_empty());
+
+        // Tasks submitted after a drain start a fresh queue
+        scheduler.submit_task(task_with_priority("late", 1)).await.unwrap();
+        assert_eq!(scheduler.pending_tasks().await, 1);
+        assert_eq!(scheduler.next_task().await.unwrap().unwrap().id, "late");
+        assert!(scheduler.next_task().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_drain_rejects_new_tasks_and_waits_for_in_flight() {
+        let scheduler = Arc::new(SmartScheduler::new(SchedulerConfig::default()));
+        scheduler.register_agent(load_only_agent("agent-1", 0.1)).await.unwrap();
+        for i in 0..5 {
+            scheduler.submit_task(task_with_priority(&format!("task-{}", i), i)).await.unwrap();
+        }
+
+        // Start two tasks before draining
+        for _ in 0..2 {
+            let task = scheduler.next_task().await.unwrap().unwrap();
+            assert!(scheduler.select_agent_for_task(&task).await.unwrap().is_some());
+        }
+        assert_eq!(scheduler.in_flight_tasks(), 2);
+
+        scheduler.initiate_drain().unwrap();
+        assert!(scheduler.is_draining());
+        let rejected = scheduler.submit_task(task_with_priority("late", 9)).await;
+        assert!(matches!(rejected, Err(RLMError::SchedulingFailed(msg)) if msg == "scheduler is draining"));
+        assert_eq!(scheduler.pending_tasks().await, 3);
+        assert!(!scheduler.is_drained().await);
+        assert!(scheduler.wait_drain(Duration::from_millis(20)).await.is_err());
+
+        let worker = Arc::clone(&scheduler);
+        let completion = tokio::spawn(async move {
+            for _ in 0..2 {
+                worker.record_task_completion(0, 10, 0.1, true).await;
+            }
+            while let Some(task) = worker.next_task().await.unwrap() {
+                worker.select_agent_for_task(&task).await.unwrap();
+                tokio::task::yield_now().await;
+                worker.record_task_completion(0, 10, 0.1, true).await;
+            }
+        });
+
+        scheduler.wait_drain(Duration::from_secs(5)).await.unwrap();
+        completion.await.unwrap();
+        assert!(scheduler.is_drained().await);
+        assert_eq!(scheduler.stats().await.completed_tasks, 5);
+    }
+
+    #[test]
+    fn test_agent_pool_operations() {
+        let mut pool = AgentPool::default();
+        assert!(pool.is_empty());
+
+        let mut searcher = load_only_agent("searcher", 0.2);
+        searcher.capabilities = vec!["web_search".to_string()];
+        pool.add(searcher);
+        pool.add(load_only_agent("coder", 0.4));
+        assert_eq!(pool.len(), 2);
+        assert_eq!(pool.get("coder").unwrap().load, 0.4);
+
+        let ids = |agents: Vec<&AgentStatus>| agents.iter().map(|a| a.id.clone()).collect::<Vec<_>>();
+        assert_eq!(ids(pool.with_capability("web_search")), vec!["searcher"]);
+
+        let mut offline = load_only_agent("coder", 0.4);
+        offline.available = false;
+        assert!(pool.update("coder", offline));
+        assert!(!pool.update("missing", load_only_agent("missing", 0.0)));
+        assert_eq!(ids(pool.available()), vec!["searcher"]);
+
+        assert!(pool.remove("searcher"));
+        assert!(!pool.remove("searcher"));
+        assert!(pool.get("searcher").is_none());
+        assert_eq!(pool.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_scheduler_sees_agent_pool_changes() {
+        let scheduler = SmartScheduler::new(SchedulerConfig::default());
+        scheduler.register_agent(load_only_agent("a", 0.5)).await.unwrap();
+
+        let pool = scheduler.agent_pool();
+        pool.write().await.add(load_only_agent("b", 0.1));
+        assert_eq!(scheduler.available_agents().await, 2);
+        let selected = scheduler.select_agent_for_task(&any_task()).await.unwrap();
+        assert_eq!(selected.unwrap().id, "b");
+
+        pool.write().await.remove("b");
+        let selected = scheduler.select_agent_for_task(&any_task()).await.unwrap();
+        assert_eq!(selected.unwrap().id, "a");
+
+        scheduler.update_agent_status("a", AgentStatus { available: false, ..load_only_agent("a", 0.5) })
+            .await
+            .unwrap();
+        assert!(!pool.read().await.get("a").unwrap().available);
+        assert_eq!(scheduler.available_agents().await, 0);
+    }
+
     #[tokio::test]
     async fn test_reset_stats() {
         let config = SchedulerConfig::default();
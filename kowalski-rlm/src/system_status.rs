@@ -0,0 +1,134 @@
+//! Combined status of an RLM deployment
+//!
+//! [`collect_system_status`] gathers the scheduler's statistics, the device
+//! cluster's health and the executor's run counts into one
+//! [`SystemStatus`] snapshot, for dashboards and status endpoints.
+
+use crate::device_health::{DeviceClusterStatus, HealthMonitor};
+use crate::error::{RLMError, RLMResult};
+use crate::executor::{ExecutorMetrics, RLMExecutor};
+use crate::smart_scheduler::{SchedulingStats, SmartScheduler};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Point-in-time status of the scheduler, device cluster and executor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SystemStatus {
+    /// When the snapshot was taken
+    pub collected_at: DateTime<Utc>,
+    /// Agents registered with the scheduler
+    pub registered_agents: usize,
+    /// Registered agents currently available
+    pub available_agents: usize,
+    /// Tasks waiting in the scheduler's queue
+    pub pending_tasks: usize,
+    /// Task totals recorded by the scheduler
+    pub scheduling: SchedulingStats,
+    /// Health of the monitored devices
+    pub devices: DeviceClusterStatus,
+    /// Runs performed by the executor
+    pub executor: ExecutorMetrics,
+}
+
+impl SystemStatus {
+    /// Returns true if every monitored device is healthy and no run has failed
+    pub fn is_healthy(&self) -> bool {
+        self.devices.unhealthy_devices == 0 && self.executor.runs_failed == 0
+    }
+
+    /// Serialize the status as pretty-printed JSON
+    pub fn to_json(&self) -> RLMResult<String> {
+        serde_json::to_string_pretty(self).map_err(|e| RLMError::wrap(e, "Failed to serialize system status"))
+    }
+}
+
+/// Take a [`SystemStatus`] snapshot of `scheduler`, `monitor` and `executor`
+pub async fn collect_system_status(
+    scheduler: &SmartScheduler,
+    monitor: &HealthMonitor,
+    executor: &RLMExecutor,
+) -> SystemStatus {
+    SystemStatus {
+        collected_at: Utc::now(),
+        registered_agents: scheduler.agent_pool().read().await.len(),
+        available_agents: scheduler.available_agents().await,
+        pending_tasks: scheduler.pending_tasks().await,
+        scheduling: scheduler.stats().await,
+        devices: monitor.get_status().await,
+        executor: executor.metrics(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RLMConfig;
+    use crate::smart_scheduler::{AgentStatus, ScheduledTask, SchedulerConfig};
+    use std::time::Duration;
+
+    fn agent(id: &str, available: bool) -> AgentStatus {
+        AgentStatus {
+            id: id.to_string(),
+            load: 0.2,
+            avg_latency_ms: 50,
+            capabilities: vec![],
+            cost_per_op: 0.1,
+            available,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_status_combines_all_sources() {
+        let scheduler = SmartScheduler::new(SchedulerConfig::default());
+        scheduler.register_agent(agent("agent-1", true)).await.unwrap();
+        scheduler.register_agent(agent("agent-2", false)).await.unwrap();
+        scheduler
+            .submit_task(ScheduledTask {
+                id: "queued".to_string(),
+                priority: 1,
+                cost: 0.1,
+                latency_ms: 100,
+                required_capabilities: vec![],
+                preferred_agents: vec![],
+            })
+            .await
+            .unwrap();
+        scheduler.record_task_completion(10, 100, 0.5, true).await;
+        scheduler.record_task_completion(20, 200, 0.5, false).await;
+
+        let monitor = HealthMonitor::new(Duration::from_secs(60), 1);
+        for (id, addr) in [("device-1", "10.0.0.1:8080"), ("device-2", "10.0.0.2:8080")] {
+            monitor.register_device(id.to_string(), addr.parse().unwrap()).await;
+        }
+        monitor.mark_failure("device-2").await;
+
+        let executor = RLMExecutor::new(RLMConfig::default().with_max_iterations(2)).unwrap();
+        executor.execute("Status check", "status").await.unwrap();
+        assert!(executor.execute("", "rejected").await.is_err());
+
+        let status = collect_system_status(&scheduler, &monitor, &executor).await;
+
+        assert_eq!(status.registered_agents, 2);
+        assert_eq!(status.available_agents, 1);
+        assert_eq!(status.pending_tasks, 1);
+        assert_eq!(status.scheduling.total_tasks, 2);
+        assert_eq!(status.scheduling.completed_tasks, 1);
+        assert_eq!(status.scheduling.failed_tasks, 1);
+        assert_eq!(status.devices.total_devices, 2);
+        assert_eq!(status.devices.unhealthy_devices, 1);
+        assert_eq!(
+            status.executor,
+            ExecutorMetrics {
+                runs_started: 1,
+                runs_completed: 1,
+                total_iterations: 2,
+                ..Default::default()
+            }
+        );
+        assert!(!status.is_healthy());
+
+        let json = status.to_json().unwrap();
+        assert!(json.contains("\"registered_agents\": 2"));
+        assert!(json.contains("\"unhealthy_devices\": 1"));
+    }
+}
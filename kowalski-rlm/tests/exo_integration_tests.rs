@@ -84,3 +84,526 @@ async fn test_exo_remote_repl_request() {
     assert_eq!(response.stdout.trim(), "hello");
     assert_eq!(response.exit_code, 0);
 }
+
+#[tokio::test]
+async fn test_exo_execute_parallel_distributes_across_devices() {
+    let server = MockServer::start();
+
+    let python_device = |id: &str| ExoDeviceInfo {
+        id: id.to_string(),
+        address: "127.0.0.1:9999".to_string(),
+        capabilities: kowalski_rlm::DeviceCapabilities {
+            runtimes: vec!["python".to_string()],
+            ..Default::default()
+        },
+    };
+    let _state_mock = server.mock(|when, then| {
+        when.method(GET).path("/state");
+        then.status(200).json_body_obj(&ExoClusterState {
+            devices: vec![python_device("device-1"), python_device("device-2")],
+        });
+    });
+
+    let device_mock = |id: &'static str| {
+        server.mock(|when, then| {
+            when.method(POST)
+                .path("/api/repl/execute")
+                .json_body_partial(format!(r#"{{"device_id": "{}"}}"#, id));
+            then.status(200)
+                .delay(std::time::Duration::from_millis(300))
+                .json_body(json!({ "stdout": id, "stderr": "", "exit_code": 0 }));
+        })
+    };
+    let device_1 = device_mock("device-1");
+    let device_2 = device_mock("device-2");
+
+    let manager = ExoClusterManager::new(server.url(""))
+        .await
+        .expect("Failed to init exo cluster manager");
+
+    let blocks = vec![
+        ("python".to_string(), "print(0)".to_string()),
+        ("python".to_string(), "print(1)".to_string()),
+        ("rust".to_string(), "println!(\"2\");".to_string()),
+        ("python".to_string(), "print(3)".to_string()),
+        ("python".to_string(), "print(4)".to_string()),
+    ];
+
+    let started = std::time::Instant::now();
+    let results = manager.execute_parallel(blocks).await;
+    let elapsed = started.elapsed();
+
+    assert_eq!(results.len(), 5);
+    assert_eq!(results[0].as_ref().unwrap(), "device-1");
+    assert_eq!(results[1].as_ref().unwrap(), "device-2");
+    assert!(matches!(
+        results[2],
+        Err(kowalski_rlm::RLMError::NoDevicesAvailable(_))
+    ));
+    assert_eq!(results[3].as_ref().unwrap(), "device-1");
+    assert_eq!(results[4].as_ref().unwrap(), "device-2");
+
+    device_1.assert_hits(2);
+    device_2.assert_hits(2);
+    // Four 300ms calls run concurrently rather than back to back
+    assert!(elapsed < std::time::Duration::from_millis(1000), "took {:?}", elapsed);
+    assert_eq!(manager.in_flight("device-1"), 0);
+    assert_eq!(manager.in_flight("device-2"), 0);
+}
+
+#[tokio::test]
+async fn test_remote_repl_fails_over_to_healthy_device() {
+    use kowalski_rlm::repl_executor::REPLExecutor;
+    use kowalski_rlm::{DeviceCapabilities, HealthMonitor, RemoteREPLExecutor};
+    use std::sync::Arc;
+
+    let server = MockServer::start();
+
+    let capabilities = DeviceCapabilities {
+        runtimes: vec!["python".to_string()],
+        ..Default::default()
+    };
+    let python_device = |id: &str| ExoDeviceInfo {
+        id: id.to_string(),
+        address: "127.0.0.1:9999".to_string(),
+        capabilities: capabilities.clone(),
+    };
+    let _state_mock = server.mock(|when, then| {
+        when.method(GET).path("/state");
+        then.status(200).json_body_obj(&ExoClusterState {
+            devices: vec![python_device("device-1"), python_device("device-2")],
+        });
+    });
+    let failing = server.mock(|when, then| {
+        when.method(POST)
+            .path("/api/repl/execute")
+            .json_body_partial(r#"{"device_id": "device-1"}"#);
+        then.status(503).body("device unavailable");
+    });
+    let healthy = server.mock(|when, then| {
+        when.method(POST)
+            .path("/api/repl/execute")
+            .json_body_partial(r#"{"device_id": "device-2"}"#);
+        then.status(200)
+            .json_body(json!({ "stdout": "42\n", "stderr": "", "exit_code": 0 }));
+    });
+
+    let monitor = Arc::new(HealthMonitor::new(std::time::Duration::from_secs(60), 1));
+    for id in ["device-1", "device-2"] {
+        monitor
+            .register_device_with_capabilities(
+                id.to_string(),
+                "127.0.0.1:9999".parse().unwrap(),
+                capabilities.clone(),
+            )
+            .await;
+    }
+
+    let manager = ExoClusterManager::new(server.url(""))
+        .await
+        .expect("Failed to init exo cluster manager")
+        .with_health_monitor(Arc::clone(&monitor));
+    let executor = RemoteREPLExecutor::new(Arc::new(manager), "device-1", "python");
+
+    let output = executor.execute("print(42)").await.expect("failover should succeed");
+
+    assert_eq!(output, "42\n");
+    failing.assert_hits(1);
+    healthy.assert_hits(1);
+    assert!(!monitor.is_device_healthy("device-1").await);
+    assert!(monitor.is_device_healthy("device-2").await);
+}
+
+#[tokio::test]
+async fn test_exo_capability_discovery_updates_health_monitor() {
+    use kowalski_rlm::{DeviceCapabilities, HealthMonitor};
+    use std::sync::Arc;
+
+    let server = MockServer::start();
+    let device_address = server.address().to_string();
+
+    let _state_mock = server.mock(|when, then| {
+        when.method(GET).path("/state");
+        then.status(200).json_body_obj(&ExoClusterState {
+            devices: vec![
+                ExoDeviceInfo {
+                    id: "gpu-box".to_string(),
+                    address: device_address.clone(),
+                    capabilities: DeviceCapabilities::default(),
+                },
+                ExoDeviceInfo {
+                    id: "offline-box".to_string(),
+                    address: "127.0.0.1:9".to_string(),
+                    capabilities: DeviceCapabilities {
+                        runtimes: vec!["rust".to_string()],
+                        ..Default::default()
+                    },
+                },
+            ],
+        });
+    });
+    let capabilities_mock = server.mock(|when, then| {
+        when.method(GET).path("/capabilities");
+        then.status(200).json_body(json!({
+            "runtimes": ["python", "node"],
+            "models": ["llama3.2"],
+            "gpu_memory_mb": 24576,
+            "system_memory_mb": 65536
+        }));
+    });
+
+    let manager = ExoClusterManager::new(server.url(""))
+        .await
+        .expect("Failed to init exo cluster manager");
+
+    let monitor = Arc::new(HealthMonitor::new(std::time::Duration::from_secs(60), 3));
+    for device in manager.list_devices().await.unwrap() {
+        monitor
+            .register_device_with_capabilities(
+                device.id.clone(),
+                device.address.parse().unwrap(),
+                DeviceCapabilities::default(),
+            )
+            .await;
+    }
+    let manager = manager.with_health_monitor(Arc::clone(&monitor));
+
+    assert_eq!(manager.refresh_capabilities().await, 1);
+    capabilities_mock.assert_hits(2);
+
+    let recorded = monitor.list_all_devices().await;
+    let gpu_box = recorded.iter().find(|d| d.device_id == "gpu-box").unwrap();
+    assert_eq!(gpu_box.capabilities.runtimes, vec!["python", "node"]);
+    assert_eq!(gpu_box.capabilities.models, vec!["llama3.2"]);
+    assert_eq!(gpu_box.capabilities.gpu_memory_mb, Some(24576));
+    assert_eq!(gpu_box.capabilities.system_memory_mb, Some(65536));
+
+    // Discovery failed for this device, so the capabilities from the cluster are kept
+    let devices = manager.list_devices().await.unwrap();
+    let offline_box = devices.iter().find(|d| d.id == "offline-box").unwrap();
+    assert_eq!(offline_box.capabilities.runtimes, vec!["rust"]);
+}
+
+/// Cluster with two python devices registered in a health monitor
+async fn python_cluster(
+    server: &MockServer,
+    monitor: &std::sync::Arc<kowalski_rlm::HealthMonitor>,
+) -> std::sync::Arc<ExoClusterManager> {
+    let capabilities = kowalski_rlm::DeviceCapabilities {
+        runtimes: vec!["python".to_string()],
+        ..Default::default()
+    };
+    let devices: Vec<ExoDeviceInfo> = ["fast-device", "slow-device"]
+        .iter()
+        .map(|id| ExoDeviceInfo {
+            id: id.to_string(),
+            address: "127.0.0.1:9999".to_string(),
+            capabilities: capabilities.clone(),
+        })
+        .collect();
+    for device in &devices {
+        monitor
+            .register_device_with_capabilities(
+                device.id.clone(),
+                device.address.parse().unwrap(),
+                capabilities.clone(),
+            )
+            .await;
+    }
+    server.mock(|when, then| {
+        when.method(GET).path("/state");
+        then.status(200).json_body_obj(&ExoClusterState { devices });
+    });
+
+    let manager = ExoClusterManager::new(server.url(""))
+        .await
+        .expect("Failed to init exo cluster manager")
+        .with_health_monitor(std::sync::Arc::clone(monitor));
+    std::sync::Arc::new(manager)
+}
+
+#[tokio::test]
+async fn test_executor_runs_code_on_fastest_device() {
+    use kowalski_rlm::{HealthMonitor, RLMConfig, RLMExecutor};
+    use std::sync::Arc;
+
+    let server = MockServer::start();
+    let monitor = Arc::new(HealthMonitor::new(std::time::Duration::from_secs(60), 1));
+    let cluster = python_cluster(&server, &monitor).await;
+    monitor.mark_success("slow-device", 200).await;
+    monitor.mark_success("fast-device", 10).await;
+
+    let device_mock = |id: &'static str| {
+        server.mock(|when, then| {
+            when.method(POST)
+                .path("/api/repl/execute")
+                .json_body_partial(format!(r#"{{"device_id": "{}"}}"#, id));
+            then.status(200)
+                .json_body(json!({ "stdout": format!("ran on {}", id), "stderr": "", "exit_code": 0 }));
+        })
+    };
+    let fast = device_mock("fast-device");
+    let slow = device_mock("slow-device");
+
+    assert_eq!(cluster.select_device("python").await.unwrap().id, "fast-device");
+
+    let executor = RLMExecutor::new(RLMConfig::default().with_max_iterations(1))
+        .unwrap()
+        .with_exo_cluster(cluster);
+    let answer = executor
+        .execute("Run:\n```python\nprint(1)\n```\n", "fastest-device")
+        .await
+        .unwrap();
+
+    assert!(answer.contains("ran on fast-device"), "{}", answer);
+    fast.assert_hits(1);
+    slow.assert_hits(0);
+}
+
+#[tokio::test]
+async fn test_executor_errors_when_capable_devices_unhealthy() {
+    use kowalski_rlm::execution_trace::TraceEvent;
+    use kowalski_rlm::{HealthMonitor, RLMConfig, RLMError, RLMExecutor};
+    use std::sync::Arc;
+
+    let server = MockServer::start();
+    let monitor = Arc::new(HealthMonitor::new(std::time::Duration::from_secs(60), 1));
+    let cluster = python_cluster(&server, &monitor).await;
+    monitor.mark_failure("fast-device").await;
+    monitor.mark_failure("slow-device").await;
+
+    assert!(matches!(
+        cluster.select_device("python").await,
+        Err(RLMError::NoDevicesAvailable(msg)) if msg.contains("unhealthy")
+    ));
+    assert!(matches!(
+        cluster.select_device("rust").await,
+        Err(RLMError::NoDevicesAvailable(msg)) if msg.contains("No device supports rust")
+    ));
+
+    let executor = RLMExecutor::new(RLMConfig::default().with_max_iterations(1))
+        .unwrap()
+        .with_exo_cluster(cluster);
+    let (_, trace) = executor
+        .execute_traced("Run:\n```python\nprint(1)\n```\n", "unhealthy-devices")
+        .await
+        .unwrap();
+
+    assert!(trace.events().iter().any(|event| matches!(
+        event,
+        TraceEvent::Error { msg } if msg.contains("unhealthy")
+    )));
+}
+
+#[tokio::test]
+async fn test_remote_repl_streams_output_chunks() {
+    use kowalski_rlm::{REPLOutputChunk, REPLOutputStream, RemoteREPLExecutor};
+    use std::sync::Arc;
+
+    let server = MockServer::start();
+    let _state_mock = server.mock(|when, then| {
+        when.method(GET).path("/state");
+        then.status(200).json_body_obj(&ExoClusterState { devices: vec![] });
+    });
+    let stream_mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/api/repl/execute")
+            .json_body_partial(r#"{"device_id": "device-1", "stream": true}"#);
+        then.status(200)
+            .header("content-type", "application/x-ndjson")
+            .body(concat!(
+                r#"{"type":"output","stream":"stdout","data":"step 1\n"}"#, "\n",
+                r#"{"type":"output","stream":"stderr","data":"warning\n"}"#, "\n",
+                r#"{"type":"output","stream":"stdout","data":"step 2\n"}"#, "\n",
+                r#"{"type":"exit","exit_code":0}"#, "\n",
+            ));
+    });
+
+    let manager = ExoClusterManager::new(server.url(""))
+        .await
+        .expect("Failed to init exo cluster manager");
+    let executor = RemoteREPLExecutor::new(Arc::new(manager), "device-1", "python");
+
+    let mut chunks: Vec<REPLOutputChunk> = Vec::new();
+    let output = executor
+        .execute_streaming("slow_job()", |chunk| chunks.push(chunk.clone()))
+        .await
+        .expect("Streaming execution failed");
+
+    stream_mock.assert();
+    assert_eq!(output, "step 1\nstep 2\n");
+    assert_eq!(
+        chunks.iter().map(|c| (c.stream, c.data.as_str())).collect::<Vec<_>>(),
+        vec![
+            (REPLOutputStream::Stdout, "step 1\n"),
+            (REPLOutputStream::Stderr, "warning\n"),
+            (REPLOutputStream::Stdout, "step 2\n"),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_shared_retry_budget_stops_failover() {
+    use kowalski_rlm::repl_executor::REPLExecutor;
+    use kowalski_rlm::{RLMConfig, RLMContext, RemoteREPLExecutor};
+    use std::sync::Arc;
+
+    let server = MockServer::start();
+    let devices = ["device-1", "device-2", "device-3"]
+        .iter()
+        .map(|id| ExoDeviceInfo {
+            id: id.to_string(),
+            address: "127.0.0.1:9999".to_string(),
+            capabilities: kowalski_rlm::DeviceCapabilities {
+                runtimes: vec!["python".to_string()],
+                ..Default::default()
+            },
+        })
+        .collect();
+    let _state_mock = server.mock(|when, then| {
+        when.method(GET).path("/state");
+        then.status(200).json_body_obj(&ExoClusterState { devices });
+    });
+    let failing = |id: &'static str| {
+        server.mock(|when, then| {
+            when.method(POST)
+                .path("/api/repl/execute")
+                .json_body_partial(format!(r#"{{"device_id": "{}"}}"#, id));
+            then.status(503).body("device unavailable");
+        })
+    };
+    let device_1 = failing("device-1");
+    let device_2 = failing("device-2");
+    let device_3 = failing("device-3");
+
+    let cluster = Arc::new(
+        ExoClusterManager::new(server.url(""))
+            .await
+            .expect("Failed to init exo cluster manager"),
+    );
+    let context = RLMContext::new("budgeted", Arc::new(RLMConfig::default().with_retry_budget(1)));
+    let executor = |device: &str| {
+        RemoteREPLExecutor::new(Arc::clone(&cluster), device, "python")
+            .with_max_failovers(5)
+            .with_retry_budget(context.retry_budget().clone())
+    };
+
+    // The only retry in the budget is spent failing over from device-1 to device-2
+    assert!(executor("device-1").execute("print(1)").await.is_err());
+    device_1.assert_hits(1);
+    device_2.assert_hits(1);
+    device_3.assert_hits(0);
+    assert_eq!(context.stats().retry_budget_remaining, 0);
+
+    // With the budget exhausted, the next failure surfaces without failing over
+    assert!(executor("device-3").execute("print(1)").await.is_err());
+    device_3.assert_hits(1);
+    device_1.assert_hits(1);
+    device_2.assert_hits(1);
+}
+
+/// Local executor that echoes the code back and counts its runs
+struct EchoREPL(std::sync::Arc<std::sync::atomic::AtomicUsize>);
+
+#[async_trait::async_trait]
+impl kowalski_rlm::repl_executor::REPLExecutor for EchoREPL {
+    async fn execute(&self, code: &str) -> kowalski_rlm::RLMResult<String> {
+        self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        Ok(format!("local: {}", code))
+    }
+
+    fn language(&self) -> &str {
+        "python"
+    }
+
+    fn timeout(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(1)
+    }
+}
+
+#[tokio::test]
+async fn test_remote_repl_falls_back_to_local_executor() {
+    use kowalski_rlm::repl_executor::REPLExecutor;
+    use kowalski_rlm::RemoteREPLExecutor;
+    use std::sync::Arc;
+
+    let server = MockServer::start();
+    let _state_mock = server.mock(|when, then| {
+        when.method(GET).path("/state");
+        then.status(200).json_body_obj(&ExoClusterState { devices: vec![] });
+    });
+    let timed_out = server.mock(|when, then| {
+        when.method(POST)
+            .path("/api/repl/execute")
+            .json_body_partial(r#"{"device_id": "unreachable"}"#);
+        then.status(504).body("upstream request timed out");
+    });
+    let healthy = server.mock(|when, then| {
+        when.method(POST)
+            .path("/api/repl/execute")
+            .json_body_partial(r#"{"device_id": "healthy"}"#);
+        then.status(200)
+            .json_body(json!({ "stdout": "remote\n", "stderr": "", "exit_code": 0 }));
+    });
+
+    let cluster = Arc::new(
+        ExoClusterManager::new(server.url(""))
+            .await
+            .expect("Failed to init exo cluster manager"),
+    );
+    let executor = |device: &str| {
+        RemoteREPLExecutor::new(Arc::clone(&cluster), device, "python")
+            .with_max_failovers(0)
+            .with_local_fallback(Box::new(EchoREPL(Default::default())))
+    };
+
+    let remote = executor("healthy");
+    assert_eq!(remote.execute("print(1)").await.unwrap(), "remote\n");
+    assert!(!remote.last_execution_was_local());
+    healthy.assert_hits(1);
+
+    let fallback = executor("unreachable");
+    assert_eq!(fallback.execute("print(1)").await.unwrap(), "local: print(1)");
+    assert!(fallback.last_execution_was_local());
+    timed_out.assert_hits(1);
+
+    // Without a fallback the remote error surfaces
+    let no_fallback = RemoteREPLExecutor::new(Arc::clone(&cluster), "unreachable", "python")
+        .with_max_failovers(0);
+    assert!(no_fallback.execute("print(1)").await.is_err());
+    assert!(!no_fallback.last_execution_was_local());
+}
+
+#[tokio::test]
+async fn test_remote_code_errors_do_not_fall_back() {
+    use kowalski_rlm::repl_executor::REPLExecutor;
+    use kowalski_rlm::{RLMError, RemoteREPLExecutor};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    let server = MockServer::start();
+    let _state_mock = server.mock(|when, then| {
+        when.method(GET).path("/state");
+        then.status(200).json_body_obj(&ExoClusterState { devices: vec![] });
+    });
+    let _repl_mock = server.mock(|when, then| {
+        when.method(POST).path("/api/repl/execute");
+        then.status(200).json_body(json!({
+            "stdout": "",
+            "stderr": "NameError: name 'x' is not defined",
+            "exit_code": 1
+        }));
+    });
+
+    let manager = ExoClusterManager::new(server.url(""))
+        .await
+        .expect("Failed to init exo cluster manager");
+    let local_runs = Arc::new(AtomicUsize::new(0));
+    let executor = RemoteREPLExecutor::new(Arc::new(manager), "device-1", "python")
+        .with_local_fallback(Box::new(EchoREPL(Arc::clone(&local_runs))));
+
+    let err = executor.execute("print(x)").await.unwrap_err();
+    assert!(matches!(err, RLMError::REPLError(_)));
+    assert!(!executor.last_execution_was_local());
+    assert_eq!(local_runs.load(Ordering::SeqCst), 0);
+}
@@ -215,3 +215,34 @@ async fn test_concurrent_execution() {
         assert!(execution.is_ok());
     }
 }
+
+#[tokio::test]
+async fn test_foldable_shrinks_content_below_threshold() {
+    use kowalski_rlm::context_fold::{ContextFoldConfig, ContextFolder, Foldable};
+    use kowalski_rlm::core::AnswerBuffer;
+
+    let max_tokens = 150;
+    let folder = ContextFolder::new(ContextFoldConfig::new(max_tokens));
+    let lines: Vec<String> = (0..50)
+        .map(|i| format!("step {} produced some intermediate output", i))
+        .collect();
+
+    let mut context = RLMContext::new("fold-task", Arc::new(RLMConfig::default()));
+    context.append_answer(lines.join("\n"));
+    assert!(context.token_count() > max_tokens);
+    context.fold(&folder).await.expect("Context folding failed");
+    assert!(context.token_count() <= max_tokens);
+    assert_eq!(context.metadata.folds, 1);
+
+    let mut results = lines.clone();
+    results.fold(&folder).await.expect("Vec folding failed");
+    assert_eq!(results.len(), 1);
+    assert!(results.token_count() <= max_tokens);
+
+    let mut buffer = AnswerBuffer::new();
+    buffer.append(&lines.join("\n")).await;
+    buffer.finalize().await;
+    buffer.fold(&folder).await.expect("Buffer folding failed");
+    assert!(buffer.token_count() <= max_tokens);
+    assert!(buffer.is_ready().await);
+}
@@ -134,6 +134,7 @@ mod smart_scheduling_tests {
             cost: 0.1,
             latency_ms: 100,
             required_capabilities: vec!["analysis".to_string()],
+            preferred_agents: vec![],
         };
 
         let result = scheduler.submit_task(task).await;
@@ -164,6 +165,7 @@ mod smart_scheduling_tests {
             cost: 0.1,
             latency_ms: 50,
             required_capabilities: vec!["web_search".to_string()],
+            preferred_agents: vec![],
         };
 
         let selected = scheduler.select_agent_for_task(&task).await.unwrap();
@@ -194,6 +196,7 @@ mod smart_scheduling_tests {
             cost: 0.1,
             latency_ms: 50,
             required_capabilities: vec!["special".to_string()],
+            preferred_agents: vec![],
         };
 
         let selected = scheduler.select_agent_for_task(&task).await.unwrap();
@@ -224,6 +227,7 @@ mod smart_scheduling_tests {
             cost: 0.1,
             latency_ms: 50,
             required_capabilities: vec!["web_search".to_string()],
+            preferred_agents: vec![],
         };
 
         let selected = scheduler.select_agent_for_task(&task).await.unwrap();
@@ -337,6 +341,7 @@ mod concurrent_operation_tests {
                     cost: 0.1,
                     latency_ms: 100,
                     required_capabilities: vec!["test".to_string()],
+                    preferred_agents: vec![],
                 };
                 scheduler_clone.submit_task(task).await
             });
@@ -382,6 +387,7 @@ mod concurrent_operation_tests {
                     cost: 0.1,
                     latency_ms: 100,
                     required_capabilities: vec!["test".to_string()],
+                    preferred_agents: vec![],
                 };
                 scheduler_clone.submit_task(task).await
             });
@@ -416,6 +422,7 @@ mod error_handling_tests {
                 cost: 0.1,
                 latency_ms: 100,
                 required_capabilities: vec![],
+                preferred_agents: vec![],
             };
             let result = scheduler.submit_task(task).await;
             assert!(result.is_ok());
@@ -428,6 +435,7 @@ mod error_handling_tests {
             cost: 0.1,
             latency_ms: 100,
             required_capabilities: vec![],
+            preferred_agents: vec![],
         };
         let result = scheduler.submit_task(task).await;
         assert!(result.is_err());